@@ -59,6 +59,201 @@ fn create_string_table(c: &mut Criterion) {
     });
 }
 
+fn table_pairs(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua
+        .load("local t = {} for i = 1, 100000 do t['k' .. i] = i end return t")
+        .eval::<LuaTable>()
+        .unwrap();
+
+    c.bench_function("iterate [table pairs] 100k", |b| {
+        b.iter(|| {
+            let mut sum = 0;
+            for pair in table.clone().pairs::<LuaString, i64>() {
+                sum += pair.unwrap().1;
+            }
+            sum
+        });
+    });
+}
+
+fn table_for_each(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua
+        .load("local t = {} for i = 1, 100000 do t['k' .. i] = i end return t")
+        .eval::<LuaTable>()
+        .unwrap();
+
+    c.bench_function("iterate [table for_each] 100k", |b| {
+        b.iter(|| {
+            let mut sum = 0;
+            table
+                .for_each(|_: LuaString, v: i64| {
+                    sum += v;
+                    Ok(())
+                })
+                .unwrap();
+            sum
+        });
+    });
+}
+
+fn table_concat(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua
+        .load("local t = {} for i = 1, 10000 do t[i] = 'item' .. i end return t")
+        .eval::<LuaTable>()
+        .unwrap();
+
+    c.bench_function("concat [table concat] 10k", |b| {
+        b.iter(|| table.concat(",", None).unwrap());
+    });
+
+    c.bench_function("concat [table manual] 10k", |b| {
+        b.iter(|| {
+            let mut s = String::new();
+            for (i, v) in table.clone().sequence_values::<String>().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                s.push_str(&v.unwrap());
+            }
+            lua.create_string(&s).unwrap()
+        });
+    });
+}
+
+fn table_extend(c: &mut Criterion) {
+    let lua = Lua::new();
+
+    c.bench_function("append [table extend] 100k", |b| {
+        b.iter_batched(
+            || lua.create_table().unwrap(),
+            |table| table.extend(0..100_000).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+
+    c.bench_function("append [table extend presized] 100k", |b| {
+        b.iter_batched(
+            || {
+                let table = lua.create_table().unwrap();
+                table.reserve(100_000, 0).unwrap();
+                table
+            },
+            |table| table.extend(0..100_000).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+
+    c.bench_function("append [table raw_push] 100k", |b| {
+        b.iter_batched(
+            || lua.create_table().unwrap(),
+            |table| {
+                for i in 0..100_000 {
+                    table.raw_push(i).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn table_to_vec(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua.create_sequence_from(0..100_000).unwrap();
+
+    c.bench_function("collect [table to_vec] 100k", |b| {
+        b.iter(|| table.to_vec::<i64>().unwrap());
+    });
+
+    c.bench_function("collect [table sequence_values] 100k", |b| {
+        b.iter(|| {
+            (table.clone().sequence_values::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        });
+    });
+}
+
+fn table_sequence_bytes(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua
+        .create_sequence_from((0..50_000).map(|i| format!("value {}", i)))
+        .unwrap();
+
+    c.bench_function("visit [table sequence_values_bytes] 50k", |b| {
+        b.iter(|| {
+            let mut total = 0;
+            (table.sequence_values_bytes(|_, bytes| {
+                total += bytes.len();
+                Ok(())
+            }))
+            .unwrap();
+            total
+        });
+    });
+
+    c.bench_function("visit [table collect Vec<String>] 50k", |b| {
+        b.iter(|| {
+            let strings = table.to_vec::<LuaString>().unwrap();
+            strings.iter().map(|s| s.as_bytes().len()).sum::<usize>()
+        });
+    });
+}
+
+fn table_raw_get_multi(c: &mut Criterion) {
+    let lua = Lua::new();
+    let table = lua
+        .load("{ x = 1, y = 2, z = 3, w = 4 }")
+        .eval::<LuaTable>()
+        .unwrap();
+
+    c.bench_function("get [table raw_get_multi] 4", |b| {
+        b.iter(|| table.raw_get_multi::<f64, 4>(["x", "y", "z", "w"]).unwrap());
+    });
+
+    c.bench_function("get [table raw_get] 4", |b| {
+        b.iter(|| {
+            [
+                table.raw_get::<_, f64>("x").unwrap(),
+                table.raw_get::<_, f64>("y").unwrap(),
+                table.raw_get::<_, f64>("z").unwrap(),
+                table.raw_get::<_, f64>("w").unwrap(),
+            ]
+        });
+    });
+}
+
+fn string_dispatch(c: &mut Criterion) {
+    let lua = Lua::new();
+    let commands = (0..32)
+        .map(|i| format!("command_{}", i))
+        .collect::<Vec<_>>();
+    let interned = (commands.iter())
+        .map(|c| lua.intern(c).unwrap())
+        .collect::<Vec<_>>();
+    let input = (commands.iter().rev())
+        .map(|c| lua.create_string(c).unwrap())
+        .collect::<Vec<_>>();
+
+    c.bench_function("dispatch [string bytes] 32", |b| {
+        b.iter(|| {
+            (input.iter())
+                .map(|s| commands.iter().position(|c| s == c).unwrap())
+                .sum::<usize>()
+        });
+    });
+
+    c.bench_function("dispatch [string interned] 32", |b| {
+        b.iter(|| {
+            (input.iter())
+                .map(|s| interned.iter().position(|i| s.eq_interned(i)).unwrap())
+                .sum::<usize>()
+        });
+    });
+}
+
 fn create_function(c: &mut Criterion) {
     let lua = Lua::new();
 
@@ -96,6 +291,27 @@ fn call_lua_function(c: &mut Criterion) {
     });
 }
 
+fn call_lua_function_no_args(c: &mut Criterion) {
+    let lua = Lua::new();
+    let function = lua.load("function() end").eval::<LuaFunction>().unwrap();
+
+    c.bench_function("call Lua function [empty] 100", |b| {
+        b.iter(|| {
+            for _ in 0..100 {
+                function.call::<_, ()>(()).unwrap();
+            }
+        });
+    });
+
+    c.bench_function("call Lua function [empty call0] 100", |b| {
+        b.iter(|| {
+            for _ in 0..100 {
+                function.call0().unwrap();
+            }
+        });
+    });
+}
+
 fn call_sum_callback(c: &mut Criterion) {
     let lua = Lua::new();
     let callback = lua
@@ -263,6 +479,123 @@ fn call_userdata_method(c: &mut Criterion) {
     });
 }
 
+fn call_userdata_method_loop(c: &mut Criterion) {
+    struct UserData(i64);
+    impl LuaUserData for UserData {
+        fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("method", |_, this, ()| Ok(this.0));
+        }
+    }
+
+    // Field getters force `__index` lookup through the generated closure
+    struct UserDataWithFields(i64);
+    impl LuaUserData for UserDataWithFields {
+        fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("field", |_, this| Ok(this.0));
+        }
+
+        fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("method", |_, this, ()| Ok(this.0));
+        }
+    }
+
+    let lua = Lua::new();
+    lua.globals().set("userdata", UserData(10)).unwrap();
+    lua.globals()
+        .set("userdata_with_fields", UserDataWithFields(10))
+        .unwrap();
+
+    for (name, global) in [
+        ("call [userdata method] loop 1000", "userdata"),
+        (
+            "call [userdata method with fields] loop 1000",
+            "userdata_with_fields",
+        ),
+    ] {
+        c.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    collect_gc_twice(&lua);
+                    lua.load(format!(
+                        "function() local ud = {global} for i = 1,1000 do ud:method() end end"
+                    ))
+                    .eval::<LuaFunction>()
+                    .unwrap()
+                },
+                |function| {
+                    function.call::<_, ()>(()).unwrap();
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+}
+
+fn call_copy_userdata_method_loop(c: &mut Criterion) {
+    #[derive(Clone, Copy)]
+    struct UserData(i64);
+    impl LuaUserData for UserData {
+        fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("method", |_, this, ()| Ok(this.0));
+            methods.add_method_mut("method_mut", |_, this, ()| {
+                this.0 += 1;
+                Ok(this.0)
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    lua.globals()
+        .set("userdata", lua.create_userdata(UserData(10)).unwrap())
+        .unwrap();
+    lua.globals()
+        .set(
+            "copy_userdata",
+            lua.create_copy_userdata(UserData(10)).unwrap(),
+        )
+        .unwrap();
+
+    for (name, global, method) in [
+        (
+            "call [refcell userdata method] loop 1000",
+            "userdata",
+            "method",
+        ),
+        (
+            "call [copy userdata method] loop 1000",
+            "copy_userdata",
+            "method",
+        ),
+        (
+            "call [refcell userdata method_mut] loop 1000",
+            "userdata",
+            "method_mut",
+        ),
+        (
+            "call [copy userdata method_mut] loop 1000",
+            "copy_userdata",
+            "method_mut",
+        ),
+    ] {
+        c.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    collect_gc_twice(&lua);
+                    lua.load(format!(
+                        "function() local ud = {global} for i = 1,1000 do ud:{method}() end end"
+                    ))
+                    .eval::<LuaFunction>()
+                    .unwrap()
+                },
+                |function| {
+                    function.call::<_, ()>(()).unwrap();
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+}
+
 fn call_async_userdata_method(c: &mut Criterion) {
     #[derive(Clone, Copy)]
     struct UserData(i64);
@@ -303,8 +636,17 @@ criterion_group! {
         create_table,
         create_array,
         create_string_table,
+        table_pairs,
+        table_for_each,
+        table_concat,
+        table_extend,
+        table_to_vec,
+        table_raw_get_multi,
+        table_sequence_bytes,
+        string_dispatch,
         create_function,
         call_lua_function,
+        call_lua_function_no_args,
         call_sum_callback,
         call_async_sum_callback,
         call_concat_callback,
@@ -312,6 +654,8 @@ criterion_group! {
         create_userdata,
         call_userdata_index,
         call_userdata_method,
+        call_userdata_method_loop,
+        call_copy_userdata_method_loop,
         call_async_userdata_method,
 }
 
@@ -0,0 +1,50 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::time::Duration;
+
+use mlua::prelude::*;
+
+fn serialize_table(c: &mut Criterion) {
+    let lua = Lua::new();
+    let array = lua
+        .load("local t = {} for i = 1, 1000000 do t[i] = i end return t")
+        .eval::<LuaTable>()
+        .unwrap();
+    let map = lua
+        .load("local t = {} for i = 1, 1000000 do t['key' .. i] = i end return t")
+        .eval::<LuaTable>()
+        .unwrap();
+
+    c.bench_function("serialize [array json] 1M", |b| {
+        b.iter(|| serde_json::to_string(&array).unwrap());
+    });
+
+    c.bench_function("serialize [array json via Value] 1M", |b| {
+        b.iter(|| {
+            let value: serde_json::Value = lua.from_value(LuaValue::Table(array.clone())).unwrap();
+            serde_json::to_string(&value).unwrap()
+        });
+    });
+
+    c.bench_function("serialize [map json] 1M", |b| {
+        b.iter(|| serde_json::to_string(&map).unwrap());
+    });
+
+    c.bench_function("serialize [map json via Value] 1M", |b| {
+        b.iter(|| {
+            let value: serde_json::Value = lua.from_value(LuaValue::Table(map.clone())).unwrap();
+            serde_json::to_string(&value).unwrap()
+        });
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(10))
+        .noise_threshold(0.02);
+    targets =
+        serialize_table,
+}
+
+criterion_main!(benches);
@@ -1,7 +1,9 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::quote;
-use syn::{parse_macro_input, AttributeArgs, Error, ItemFn, Lit, Meta, NestedMeta, Result};
+use syn::{
+    parse_macro_input, AttributeArgs, DeriveInput, Error, ItemFn, Lit, Meta, NestedMeta, Result,
+};
 
 #[cfg(feature = "macros")]
 use {
@@ -73,6 +75,15 @@ pub fn lua_module(attr: TokenStream, item: TokenStream) -> TokenStream {
     wrapped.into()
 }
 
+#[proc_macro_derive(LuaEnum, attributes(lua))]
+pub fn lua_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match lua_enum::derive(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 #[cfg(feature = "macros")]
 fn to_ident(tt: &TokenTree) -> TokenStream2 {
     let s: TokenStream = tt.clone().into();
@@ -150,5 +161,6 @@ pub fn chunk(input: TokenStream) -> TokenStream {
 
 #[cfg(feature = "macros")]
 mod chunk;
+mod lua_enum;
 #[cfg(feature = "macros")]
 mod token;
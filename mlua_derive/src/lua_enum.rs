@@ -0,0 +1,176 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, ExprLit, ExprUnary, Fields, Lit, Meta, NestedMeta,
+    Result, UnOp,
+};
+
+enum VariantValue {
+    Integer(i128),
+    String(String),
+}
+
+#[derive(Default)]
+struct VariantArgs {
+    name: Option<String>,
+    value: Option<VariantValue>,
+}
+
+impl VariantArgs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut ret = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("lua")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new_spanned(meta, "expected `#[lua(...)]`")),
+            };
+            for arg in list.nested {
+                match arg {
+                    NestedMeta::Meta(Meta::NameValue(meta)) => {
+                        if meta.path.is_ident("value") {
+                            ret.value = Some(match meta.lit {
+                                Lit::Int(val) => VariantValue::Integer(val.base10_parse()?),
+                                Lit::Str(val) => VariantValue::String(val.value()),
+                                lit => {
+                                    return Err(Error::new_spanned(
+                                        lit,
+                                        "expected integer or string literal",
+                                    ))
+                                }
+                            });
+                        } else if meta.path.is_ident("name") {
+                            match meta.lit {
+                                Lit::Str(val) => ret.name = Some(val.value()),
+                                lit => {
+                                    return Err(Error::new_spanned(lit, "expected string literal"))
+                                }
+                            }
+                        } else {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "expected `value` or `name`",
+                            ));
+                        }
+                    }
+                    arg => return Err(Error::new_spanned(arg, "invalid argument")),
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+// Parses an explicit enum discriminant (integer literal, optionally negated)
+fn parse_discriminant(expr: &Expr) -> Result<i128> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(val), ..
+        }) => val.base10_parse(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_discriminant(expr).map(|val| -val),
+        expr => Err(Error::new_spanned(
+            expr,
+            "unsupported discriminant, use `#[lua(value = ...)]` instead",
+        )),
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "`LuaEnum` can be derived only for enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`LuaEnum` cannot be derived for generic enums",
+        ));
+    }
+
+    let mut variants = Vec::new();
+    let mut to_index = Vec::new();
+    let mut from_index = Vec::new();
+    let mut next_value = 0i128;
+    for (index, variant) in data.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "`LuaEnum` supports only variants without fields",
+            ));
+        }
+
+        let args = VariantArgs::parse(&variant.attrs)?;
+        let var_ident = &variant.ident;
+        let name = args.name.unwrap_or_else(|| var_ident.to_string());
+        let value = match args.value {
+            Some(VariantValue::String(s)) => quote!(::mlua::EnumValue::String(#s)),
+            Some(VariantValue::Integer(i)) => {
+                next_value = i + 1;
+                let i = Literal::i128_unsuffixed(i);
+                quote!(::mlua::EnumValue::Integer(#i))
+            }
+            None => {
+                let i = match &variant.discriminant {
+                    Some((_, expr)) => parse_discriminant(expr)?,
+                    None => next_value,
+                };
+                next_value = i + 1;
+                let i = Literal::i128_unsuffixed(i);
+                quote!(::mlua::EnumValue::Integer(#i))
+            }
+        };
+
+        variants.push(quote!((#name, #value)));
+        to_index.push(quote!(#ident::#var_ident => #index));
+        from_index.push(quote!(#index => ::std::option::Option::Some(#ident::#var_ident)));
+    }
+
+    let enum_name = ident.to_string();
+    Ok(quote! {
+        impl ::mlua::EnumLua for #ident {
+            fn enum_name() -> &'static str {
+                #enum_name
+            }
+
+            fn variants() -> &'static [(&'static str, ::mlua::EnumValue)] {
+                &[#(#variants),*]
+            }
+
+            fn variant_index(&self) -> usize {
+                match *self {
+                    #(#to_index,)*
+                }
+            }
+
+            fn from_variant_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#from_index,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        impl<'lua> ::mlua::FromLua<'lua> for #ident {
+            fn from_lua(value: ::mlua::Value<'lua>, lua: &'lua ::mlua::Lua) -> ::mlua::Result<Self> {
+                <Self as ::mlua::EnumLua>::from_lua_enum(value, lua)
+            }
+        }
+
+        impl<'lua> ::mlua::IntoLua<'lua> for #ident {
+            fn into_lua(self, lua: &'lua ::mlua::Lua) -> ::mlua::Result<::mlua::Value<'lua>> {
+                <Self as ::mlua::EnumLua>::to_lua_enum(&self, lua)
+            }
+        }
+    })
+}
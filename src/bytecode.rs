@@ -0,0 +1,417 @@
+//! Inspection of precompiled Lua chunks (bytecode).
+//!
+//! Bytecode produced by [`Function::dump`] (or Luau [`Compiler`]) is tied to the Lua version
+//! and to the configuration of the host that produced it (byte order, sizes of C types and Lua
+//! numbers). [`inspect`] parses the header of a precompiled chunk without loading it.
+//!
+//! [`Chunk`] validates the header of binary chunks against the running Lua before loading them
+//! and returns [`Error::BytecodeMismatch`] describing both formats if they are not compatible.
+//!
+//! [`Function::dump`]: crate::Function::dump
+//! [`Compiler`]: crate::Compiler
+//! [`Chunk`]: crate::Chunk
+
+use std::fmt;
+use std::string::String as StdString;
+
+use crate::error::{Error, Result};
+
+#[cfg(any(
+    feature = "lua54",
+    feature = "lua53",
+    feature = "lua52",
+    feature = "lua51"
+))]
+use {
+    crate::types::{Integer, Number},
+    std::mem,
+    std::os::raw::c_int,
+};
+
+#[cfg(feature = "luau")]
+use crate::ffi;
+
+const LUA_SIGNATURE: &[u8] = b"\x1bLua";
+const LUAJIT_SIGNATURE: &[u8] = b"\x1bLJ";
+
+// Lua 5.2+ header data to catch conversion errors
+const LUAC_DATA: &[u8] = b"\x19\x93\r\n\x1a\n";
+// Lua 5.3+ integer to detect byte order
+const LUAC_INT: i64 = 0x5678;
+
+// LuaJIT header flags
+const LUAJIT_F_BE: u32 = 0x01;
+
+// Bytecode versions of the running LuaJIT: 2.1 produces v2 and 2.0 produces v1.
+// The version of a system LuaJIT is not known at build time, so both are accepted.
+#[cfg(feature = "luajit")]
+const LUAJIT_VERSIONS: &[u8] = if cfg!(feature = "vendored") {
+    &[2]
+} else {
+    &[1, 2]
+};
+
+/// Family of the bytecode format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BytecodeKind {
+    /// Lua 5.1 - 5.4
+    Lua,
+    /// LuaJIT
+    LuaJIT,
+    /// Roblox Luau
+    Luau,
+}
+
+/// Byte order of numbers in the bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+#[cfg(not(feature = "luau"))]
+impl Endianness {
+    const fn native() -> Self {
+        if cfg!(target_endian = "big") {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        }
+    }
+}
+
+/// Information from the header of a precompiled chunk, returned by [`inspect`].
+///
+/// Fields that are not present in the header of the given format are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BytecodeInfo {
+    /// Family of the bytecode format.
+    pub kind: BytecodeKind,
+    /// Bytecode version: `0x51`-`0x54` for Lua 5.x, `1` or `2` for LuaJIT, `3` and later for Luau.
+    pub version: u8,
+    /// Format number of Lua 5.x bytecode (`0` is the official format).
+    pub format: Option<u8>,
+    /// Header flags of LuaJIT bytecode.
+    pub flags: Option<u32>,
+    /// Version of type information of Luau bytecode (bytecode version 4 and later).
+    pub types_version: Option<u8>,
+    /// Byte order of numbers. Luau bytecode is always little-endian.
+    pub endianness: Endianness,
+    /// Size of C `int` (Lua 5.1 - 5.3).
+    pub int_size: Option<u8>,
+    /// Size of C `size_t` (Lua 5.1 - 5.3).
+    pub size_t_size: Option<u8>,
+    /// Size of VM instruction (Lua 5.x).
+    pub instruction_size: Option<u8>,
+    /// Size of `lua_Integer` (Lua 5.3 and 5.4).
+    pub integer_size: Option<u8>,
+    /// Size of `lua_Number` (Lua 5.x).
+    pub number_size: Option<u8>,
+}
+
+impl BytecodeInfo {
+    const fn new(kind: BytecodeKind, version: u8) -> Self {
+        BytecodeInfo {
+            kind,
+            version,
+            format: None,
+            flags: None,
+            types_version: None,
+            endianness: Endianness::Little,
+            int_size: None,
+            size_t_size: None,
+            instruction_size: None,
+            integer_size: None,
+            number_size: None,
+        }
+    }
+}
+
+impl fmt::Display for BytecodeInfo {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            BytecodeKind::Lua => {
+                let (major, minor) = (self.version >> 4, self.version & 0xf);
+                write!(fmt, "Lua {}.{} bytecode", major, minor)?
+            }
+            BytecodeKind::LuaJIT => write!(fmt, "LuaJIT bytecode v{}", self.version)?,
+            BytecodeKind::Luau => write!(fmt, "Luau bytecode v{}", self.version)?,
+        }
+
+        let endianness = match self.endianness {
+            Endianness::Little => "little-endian",
+            Endianness::Big => "big-endian",
+        };
+        let mut details = Vec::new();
+        if let Some(format) = self.format {
+            details.push(format!("format {}", format));
+        }
+        if let Some(flags) = self.flags {
+            details.push(format!("flags {:#x}", flags));
+        }
+        if let Some(types_version) = self.types_version {
+            details.push(format!("types v{}", types_version));
+        }
+        details.push(endianness.to_string());
+        let sizes = [
+            ("int", self.int_size),
+            ("size_t", self.size_t_size),
+            ("Instruction", self.instruction_size),
+            ("lua_Integer", self.integer_size),
+            ("lua_Number", self.number_size),
+        ];
+        for (name, size) in sizes {
+            if let Some(size) = size {
+                details.push(format!("{} {} bytes", name, size));
+            }
+        }
+        write!(fmt, " ({})", details.join(", "))
+    }
+}
+
+/// Parses the header of a precompiled chunk.
+///
+/// Recognizes bytecode of all Lua versions supported by mlua, regardless of the version mlua
+/// is built with. Returns an error if the data is not a precompiled chunk or the header is
+/// truncated or corrupted. Only the header is checked, the rest of the chunk is not validated.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Lua, Result};
+/// # #[cfg(not(feature = "luau"))]
+/// # fn main() -> Result<()> {
+/// use mlua::bytecode::{self, BytecodeKind};
+///
+/// let lua = Lua::new();
+/// let func = lua.load("return 1 + 1").into_function()?;
+/// let info = bytecode::inspect(&func.dump(true))?;
+/// assert_ne!(info.kind, BytecodeKind::Luau);
+/// # Ok(())
+/// # }
+/// # #[cfg(feature = "luau")]
+/// # fn main() {}
+/// ```
+pub fn inspect(data: &[u8]) -> Result<BytecodeInfo> {
+    parse_header(data)
+        .map_err(|err| Error::RuntimeError(format!("invalid bytecode header: {}", err)))
+}
+
+fn parse_header(data: &[u8]) -> std::result::Result<BytecodeInfo, StdString> {
+    if data.starts_with(LUA_SIGNATURE) {
+        parse_lua_header(data)
+    } else if data.starts_with(LUAJIT_SIGNATURE) {
+        parse_luajit_header(data)
+    } else {
+        match data.first() {
+            Some(0) => Err("Luau bytecode contains a compilation error".into()),
+            Some(&version) if version < b'\n' => {
+                let mut info = BytecodeInfo::new(BytecodeKind::Luau, version);
+                if version >= 4 {
+                    info.types_version = Some(*data.get(1).ok_or("truncated header")?);
+                }
+                Ok(info)
+            }
+            _ => Err("unknown signature".into()),
+        }
+    }
+}
+
+fn parse_lua_header(data: &[u8]) -> std::result::Result<BytecodeInfo, StdString> {
+    let byte = |i: usize| data.get(i).copied().ok_or("truncated header");
+    let check_data = |i: usize| match data.get(i..i + LUAC_DATA.len()) {
+        Some(d) if d == LUAC_DATA => Ok(()),
+        Some(_) => Err("corrupted header"),
+        None => Err("truncated header"),
+    };
+
+    let version = byte(4)?;
+    let mut info = BytecodeInfo::new(BytecodeKind::Lua, version);
+    info.format = Some(byte(5)?);
+    match version {
+        0x51 | 0x52 => {
+            info.endianness = match byte(6)? {
+                0 => Endianness::Big,
+                1 => Endianness::Little,
+                _ => return Err("corrupted header".into()),
+            };
+            info.int_size = Some(byte(7)?);
+            info.size_t_size = Some(byte(8)?);
+            info.instruction_size = Some(byte(9)?);
+            info.number_size = Some(byte(10)?);
+            byte(11)?; // integral flag
+            if version == 0x52 {
+                check_data(12)?;
+            }
+        }
+        0x53 | 0x54 => {
+            check_data(6)?;
+            let mut pos = 12;
+            if version == 0x53 {
+                info.int_size = Some(byte(pos)?);
+                info.size_t_size = Some(byte(pos + 1)?);
+                pos += 2;
+            }
+            info.instruction_size = Some(byte(pos)?);
+            let integer_size = byte(pos + 1)?;
+            info.integer_size = Some(integer_size);
+            info.number_size = Some(byte(pos + 2)?);
+            pos += 3;
+
+            // Byte order is detected using the sample integer
+            let size = integer_size as usize;
+            let int_data = data.get(pos..pos + size).ok_or("truncated header")?;
+            let le_data = LUAC_INT.to_le_bytes();
+            let be_data = LUAC_INT.to_be_bytes();
+            info.endianness = if size <= 8 && int_data == &le_data[..size] {
+                Endianness::Little
+            } else if size <= 8 && int_data == &be_data[8 - size..] {
+                Endianness::Big
+            } else {
+                return Err("corrupted header".into());
+            };
+        }
+        _ => return Err(format!("unsupported Lua version {:#x}", version)),
+    }
+    Ok(info)
+}
+
+fn parse_luajit_header(data: &[u8]) -> std::result::Result<BytecodeInfo, StdString> {
+    let version = *data.get(3).ok_or("truncated header")?;
+    let mut info = BytecodeInfo::new(BytecodeKind::LuaJIT, version);
+
+    // Flags are encoded as ULEB128
+    let mut flags = 0u32;
+    for (pos, shift) in (4..).zip((0..35).step_by(7)) {
+        let b = *data.get(pos).ok_or("truncated header")?;
+        flags |= ((b & 0x7f) as u32) << shift;
+        if b & 0x80 == 0 {
+            break;
+        }
+    }
+    info.flags = Some(flags);
+    if flags & LUAJIT_F_BE != 0 {
+        info.endianness = Endianness::Big;
+    }
+    Ok(info)
+}
+
+// Returns the bytecode format produced by the running Lua
+#[cfg(any(
+    feature = "lua54",
+    feature = "lua53",
+    feature = "lua52",
+    feature = "lua51",
+    feature = "luajit"
+))]
+fn expected() -> BytecodeInfo {
+    #[cfg(feature = "luajit")]
+    {
+        let version = LUAJIT_VERSIONS[LUAJIT_VERSIONS.len() - 1];
+        let mut info = BytecodeInfo::new(BytecodeKind::LuaJIT, version);
+        info.endianness = Endianness::native();
+        info
+    }
+
+    #[cfg(not(feature = "luajit"))]
+    {
+        let version = if cfg!(feature = "lua54") {
+            0x54
+        } else if cfg!(feature = "lua53") {
+            0x53
+        } else if cfg!(feature = "lua52") {
+            0x52
+        } else {
+            0x51
+        };
+        let mut info = BytecodeInfo::new(BytecodeKind::Lua, version);
+        info.format = Some(0);
+        info.endianness = Endianness::native();
+        if version <= 0x53 {
+            info.int_size = Some(mem::size_of::<c_int>() as u8);
+            info.size_t_size = Some(mem::size_of::<usize>() as u8);
+        }
+        info.instruction_size = Some(4);
+        if version >= 0x53 {
+            info.integer_size = Some(mem::size_of::<Integer>() as u8);
+        }
+        info.number_size = Some(mem::size_of::<Number>() as u8);
+        info
+    }
+}
+
+// Returns description of the supported bytecode format
+fn expected_description() -> StdString {
+    #[cfg(feature = "luau")]
+    {
+        format!(
+            "Luau bytecode v{}-v{}",
+            ffi::LBC_VERSION_MIN,
+            ffi::LBC_VERSION_MAX
+        )
+    }
+
+    #[cfg(feature = "luajit")]
+    {
+        let info = expected();
+        let endianness = match info.endianness {
+            Endianness::Little => "little-endian",
+            Endianness::Big => "big-endian",
+        };
+        match LUAJIT_VERSIONS {
+            [min, .., max] => format!("LuaJIT bytecode v{}-v{} ({})", min, max, endianness),
+            _ => format!("LuaJIT bytecode v{} ({})", info.version, endianness),
+        }
+    }
+
+    #[cfg(not(any(feature = "luau", feature = "luajit")))]
+    {
+        expected().to_string()
+    }
+}
+
+fn is_compatible(info: &BytecodeInfo) -> bool {
+    #[cfg(feature = "luau")]
+    {
+        let versions = ffi::LBC_VERSION_MIN..=ffi::LBC_VERSION_MAX;
+        let types_versions = ffi::LBC_TYPE_VERSION_MIN..=ffi::LBC_TYPE_VERSION_MAX;
+        info.kind == BytecodeKind::Luau
+            && versions.contains(&info.version)
+            && info
+                .types_version
+                .map_or(true, |v| types_versions.contains(&v))
+    }
+
+    #[cfg(feature = "luajit")]
+    {
+        // Other flags are checked by LuaJIT
+        let expected = expected();
+        info.kind == expected.kind
+            && LUAJIT_VERSIONS.contains(&info.version)
+            && info.endianness == expected.endianness
+    }
+
+    #[cfg(not(any(feature = "luau", feature = "luajit")))]
+    {
+        *info == expected()
+    }
+}
+
+// Checks that the precompiled chunk can be loaded by the running Lua
+pub(crate) fn check(data: &[u8]) -> Result<()> {
+    // Luau compiler stores compilation errors as bytecode version 0, they're reported on load
+    #[cfg(feature = "luau")]
+    if data.first() == Some(&0) {
+        return Ok(());
+    }
+
+    let found = match parse_header(data) {
+        Ok(info) if is_compatible(&info) => return Ok(()),
+        Ok(info) => info.to_string(),
+        Err(err) => format!("invalid header ({})", err),
+    };
+    Err(Error::BytecodeMismatch {
+        expected: expected_description(),
+        found,
+    })
+}
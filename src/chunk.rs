@@ -4,13 +4,18 @@ use std::ffi::CString;
 use std::io::Result as IoResult;
 use std::path::{Path, PathBuf};
 use std::string::String as StdString;
+use std::time::{Duration, Instant};
 
+use crate::bytecode;
 use crate::error::{Error, Result};
 use crate::ffi;
 use crate::function::Function;
 use crate::lua::Lua;
 use crate::value::{FromLuaMulti, IntoLua, IntoLuaMulti, Value};
 
+#[cfg(any(feature = "luau", doc))]
+use crate::table::Table;
+
 #[cfg(feature = "async")]
 use {futures_core::future::LocalBoxFuture, futures_util::future};
 
@@ -118,6 +123,43 @@ pub enum ChunkMode {
     Binary,
 }
 
+/// Chunk lifecycle event delivered to the observer set by [`Lua::set_chunk_observer`].
+///
+/// [`Lua::set_chunk_observer`]: crate::Lua::set_chunk_observer
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChunkEvent {
+    /// A chunk was successfully loaded into a function.
+    Loaded {
+        /// Name of the chunk.
+        name: StdString,
+        /// Size of the loaded source code or bytecode.
+        bytes_len: usize,
+        /// Whether the chunk was loaded from text or binary.
+        mode: ChunkMode,
+    },
+    /// A loaded chunk finished executing.
+    Executed {
+        /// Name of the chunk.
+        name: StdString,
+        /// Time spent executing the chunk.
+        duration: Duration,
+        /// `true` if the chunk finished without error.
+        ok: bool,
+    },
+    /// A Lua function was created by loading a chunk.
+    ///
+    /// Emitted only if the observer is set with the `verbose` flag. Only the main chunk function
+    /// is reported: functions defined inside the chunk are created later, when the chunk runs,
+    /// and do not produce this event.
+    FunctionCreated {
+        /// Name of the chunk the function comes from.
+        chunk_name: StdString,
+        /// The line where the function definition starts (`0` for the main chunk function).
+        line: i32,
+    },
+}
+
 /// Luau compiler
 #[cfg(any(feature = "luau", doc))]
 #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
@@ -271,6 +313,23 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
         self
     }
 
+    /// Sets the environment of the loaded chunk and marks it as immutable.
+    ///
+    /// Works like [`set_environment`], additionally setting the `safeenv` attribute on the
+    /// environment table to enable Luau optimizations for global access and builtin calls.
+    /// The environment should not be modified afterwards, see [`Table::set_safe_env`].
+    ///
+    /// Requires `feature = "luau"`
+    ///
+    /// [`set_environment`]: #method.set_environment
+    /// [`Table::set_safe_env`]: crate::Table::set_safe_env
+    #[cfg(any(feature = "luau", doc))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    pub fn set_safe_environment(self, env: Table<'lua>) -> Self {
+        env.set_safe_env(true);
+        self.set_environment(env)
+    }
+
     /// Sets whether the chunk is text or binary (autodetected by default).
     ///
     /// Be aware, Lua does not check the consistency of the code inside binary chunks.
@@ -326,7 +385,8 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
         if self.detect_mode() == ChunkMode::Binary {
             self.call(())
         } else if let Ok(function) = self.to_expression() {
-            function.call(())
+            let name = self.observed_name();
+            Self::observe_call(self.lua, name, || function.call(()))
         } else {
             self.call(())
         }
@@ -349,7 +409,8 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
         if self.detect_mode() == ChunkMode::Binary {
             self.call_async(())
         } else if let Ok(function) = self.to_expression() {
-            function.call_async(())
+            let name = self.observed_name();
+            Self::observe_call_async(self.lua, name, function.call_async(()))
         } else {
             self.call_async(())
         }
@@ -359,7 +420,10 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
     ///
     /// This is equivalent to `into_function` and calling the resulting function.
     pub fn call<A: IntoLuaMulti<'lua>, R: FromLuaMulti<'lua>>(self, args: A) -> Result<R> {
-        self.into_function()?.call(args)
+        let lua = self.lua;
+        let name = self.observed_name();
+        let func = self.into_function()?;
+        Self::observe_call(lua, name, || func.call(args))
     }
 
     /// Load the chunk function and asynchronously call it with the given arguments.
@@ -377,8 +441,10 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
         A: IntoLuaMulti<'lua>,
         R: FromLuaMulti<'lua> + 'fut,
     {
+        let lua = self.lua;
+        let name = self.observed_name();
         match self.into_function() {
-            Ok(func) => func.call_async(args),
+            Ok(func) => Self::observe_call_async(lua, name, func.call_async(args)),
             Err(e) => Box::pin(future::err(e)),
         }
     }
@@ -394,9 +460,21 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
             self.compile();
         }
 
+        let observed_name = self.observed_name();
+        let mode = self.detect_mode();
         let name = Self::convert_name(self.name)?;
-        self.lua
-            .load_chunk(Some(&name), self.env?, self.mode, self.source?.as_ref())
+        let env = self.env?;
+        let source = self.source?;
+        if self.mode != Some(ChunkMode::Text) && Self::is_bytecode(&source) {
+            bytecode::check(&source)?;
+        }
+        let func = self
+            .lua
+            .load_chunk(Some(&name), env, self.mode, source.as_ref())?;
+        if let Some(name) = observed_name {
+            Self::observe_load(self.lua, name, source.len(), mode, &func);
+        }
+        Ok(func)
     }
 
     /// Compiles the chunk and changes mode to binary.
@@ -478,28 +556,115 @@ impl<'lua, 'a> Chunk<'lua, 'a> {
             .unwrap_or(source);
 
         let name = Self::convert_name(self.name.clone())?;
+        let func = self
+            .lua
+            .load_chunk(Some(&name), self.env.clone()?, None, &source)?;
+        if let Some(name) = self.observed_name() {
+            let mode = Self::detect_source_mode(&source);
+            Self::observe_load(self.lua, name, source.len(), mode, &func);
+        }
+        Ok(func)
+    }
+
+    /// Returns the chunk name if the chunk observer is set.
+    fn observed_name(&self) -> Option<StdString> {
         self.lua
-            .load_chunk(Some(&name), self.env.clone()?, None, &source)
+            .chunk_observer_verbosity()
+            .map(|_| self.name.clone())
+    }
+
+    fn observe_load(
+        lua: &Lua,
+        name: StdString,
+        bytes_len: usize,
+        mode: ChunkMode,
+        func: &Function,
+    ) {
+        if lua.chunk_observer_verbosity() == Some(true) {
+            let chunk_name = name.clone();
+            lua.emit_chunk_event(ChunkEvent::Loaded {
+                name,
+                bytes_len,
+                mode,
+            });
+            let line = func.info().line_defined;
+            lua.emit_chunk_event(ChunkEvent::FunctionCreated { chunk_name, line });
+        } else {
+            lua.emit_chunk_event(ChunkEvent::Loaded {
+                name,
+                bytes_len,
+                mode,
+            });
+        }
+    }
+
+    fn observe_call<R>(
+        lua: &Lua,
+        name: Option<StdString>,
+        f: impl FnOnce() -> Result<R>,
+    ) -> Result<R> {
+        let name = match name {
+            Some(name) => name,
+            None => return f(),
+        };
+        let start = Instant::now();
+        let result = f();
+        let (duration, ok) = (start.elapsed(), result.is_ok());
+        lua.emit_chunk_event(ChunkEvent::Executed { name, duration, ok });
+        result
+    }
+
+    #[cfg(feature = "async")]
+    fn observe_call_async<'fut, R: 'fut>(
+        lua: &'fut Lua,
+        name: Option<StdString>,
+        fut: LocalBoxFuture<'fut, Result<R>>,
+    ) -> LocalBoxFuture<'fut, Result<R>> {
+        let name = match name {
+            Some(name) => name,
+            None => return fut,
+        };
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = fut.await;
+            let (duration, ok) = (start.elapsed(), result.is_ok());
+            lua.emit_chunk_event(ChunkEvent::Executed { name, duration, ok });
+            result
+        })
     }
 
     fn detect_mode(&self) -> ChunkMode {
         match (self.mode, &self.source) {
             (Some(mode), _) => mode,
-            (None, Ok(source)) => {
-                #[cfg(not(feature = "luau"))]
-                if source.starts_with(ffi::LUA_SIGNATURE) {
-                    return ChunkMode::Binary;
-                }
-                #[cfg(feature = "luau")]
-                if *source.first().unwrap_or(&u8::MAX) < b'\n' {
-                    return ChunkMode::Binary;
-                }
-                ChunkMode::Text
-            }
+            (None, Ok(source)) => Self::detect_source_mode(source),
             (None, Err(_)) => ChunkMode::Text, // any value is fine
         }
     }
 
+    fn detect_source_mode(source: &[u8]) -> ChunkMode {
+        #[cfg(not(feature = "luau"))]
+        if source.starts_with(ffi::LUA_SIGNATURE) {
+            return ChunkMode::Binary;
+        }
+        #[cfg(feature = "luau")]
+        if *source.first().unwrap_or(&u8::MAX) < b'\n' {
+            return ChunkMode::Binary;
+        }
+        ChunkMode::Text
+    }
+
+    // Returns `true` if the source looks like a precompiled chunk of any Lua version
+    fn is_bytecode(source: &[u8]) -> bool {
+        #[cfg(not(feature = "luau"))]
+        {
+            source.first() == Some(&ffi::LUA_SIGNATURE[0])
+        }
+        #[cfg(feature = "luau")]
+        {
+            Self::detect_source_mode(source) == ChunkMode::Binary
+        }
+    }
+
     fn convert_name(name: String) -> Result<CString> {
         CString::new(name).map_err(|err| Error::RuntimeError(format!("invalid name: {err}")))
     }
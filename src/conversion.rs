@@ -3,6 +3,10 @@ use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
 use std::ffi::{CStr, CString};
 use std::hash::{BuildHasher, Hash};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
 use std::string::String as StdString;
 
 use bstr::{BStr, BString};
@@ -21,6 +25,7 @@ use crate::value::{FromLua, IntoLua, Nil, Value};
 #[cfg(feature = "unstable")]
 use crate::{
     function::{OwnedFunction, WrappedFunction},
+    string::OwnedString,
     table::OwnedTable,
     userdata::OwnedAnyUserData,
 };
@@ -62,6 +67,22 @@ impl<'lua> FromLua<'lua> for String<'lua> {
     }
 }
 
+#[cfg(feature = "unstable")]
+impl<'lua> IntoLua<'lua> for OwnedString {
+    #[inline]
+    fn into_lua(self, lua: &'lua Lua) -> Result<Value<'lua>> {
+        Ok(Value::String(String(lua.adopt_owned_ref(self.0))))
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<'lua> FromLua<'lua> for OwnedString {
+    #[inline]
+    fn from_lua(value: Value<'lua>, lua: &'lua Lua) -> Result<OwnedString> {
+        String::from_lua(value, lua).map(|s| s.into_owned())
+    }
+}
+
 impl<'lua> IntoLua<'lua> for Table<'lua> {
     #[inline]
     fn into_lua(self, _: &'lua Lua) -> Result<Value<'lua>> {
@@ -476,6 +497,44 @@ lua_convert_int!(u128);
 lua_convert_int!(isize);
 lua_convert_int!(usize);
 
+macro_rules! lua_convert_nonzero_int {
+    ($x:ty, $i:ty) => {
+        impl<'lua> IntoLua<'lua> for $x {
+            #[inline]
+            fn into_lua(self, lua: &'lua Lua) -> Result<Value<'lua>> {
+                self.get().into_lua(lua)
+            }
+        }
+
+        impl<'lua> FromLua<'lua> for $x {
+            #[inline]
+            fn from_lua(value: Value<'lua>, lua: &'lua Lua) -> Result<Self> {
+                let ty = value.type_name();
+                <$x>::new(<$i>::from_lua(value, lua)?).ok_or_else(|| {
+                    Error::FromLuaConversionError {
+                        from: ty,
+                        to: stringify!($x),
+                        message: Some("expected non-zero integer".to_string()),
+                    }
+                })
+            }
+        }
+    };
+}
+
+lua_convert_nonzero_int!(NonZeroI8, i8);
+lua_convert_nonzero_int!(NonZeroU8, u8);
+lua_convert_nonzero_int!(NonZeroI16, i16);
+lua_convert_nonzero_int!(NonZeroU16, u16);
+lua_convert_nonzero_int!(NonZeroI32, i32);
+lua_convert_nonzero_int!(NonZeroU32, u32);
+lua_convert_nonzero_int!(NonZeroI64, i64);
+lua_convert_nonzero_int!(NonZeroU64, u64);
+lua_convert_nonzero_int!(NonZeroI128, i128);
+lua_convert_nonzero_int!(NonZeroU128, u128);
+lua_convert_nonzero_int!(NonZeroIsize, isize);
+lua_convert_nonzero_int!(NonZeroUsize, usize);
+
 macro_rules! lua_convert_float {
     ($x:ty) => {
         impl<'lua> IntoLua<'lua> for $x {
@@ -605,6 +664,8 @@ impl<'lua, T: FromLua<'lua>> FromLua<'lua> for Vec<T> {
                 T::from_lua(Value::Number(y as _), _lua)?,
                 T::from_lua(Value::Number(z as _), _lua)?,
             ]),
+            // Tables without metatable are converted in a single pass
+            Value::Table(table) if !table.has_metatable() => table.to_vec(),
             Value::Table(table) => table.sequence_values().collect(),
             _ => Err(Error::FromLuaConversionError {
                 from: value.type_name(),
@@ -630,7 +691,7 @@ impl<'lua, K: Eq + Hash + FromLua<'lua>, V: FromLua<'lua>, S: BuildHasher + Defa
     #[inline]
     fn from_lua(value: Value<'lua>, _: &'lua Lua) -> Result<Self> {
         if let Value::Table(table) = value {
-            table.pairs().collect()
+            table.collect_pairs()
         } else {
             Err(Error::FromLuaConversionError {
                 from: value.type_name(),
@@ -652,7 +713,7 @@ impl<'lua, K: Ord + FromLua<'lua>, V: FromLua<'lua>> FromLua<'lua> for BTreeMap<
     #[inline]
     fn from_lua(value: Value<'lua>, _: &'lua Lua) -> Result<Self> {
         if let Value::Table(table) = value {
-            table.pairs().collect()
+            table.collect_pairs()
         } else {
             Err(Error::FromLuaConversionError {
                 from: value.type_name(),
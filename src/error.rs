@@ -25,7 +25,10 @@ pub enum Error {
     ///
     /// The Lua VM returns this error when a builtin operation is performed on incompatible types.
     /// Among other things, this includes invoking operators on wrong types (such as calling or
-    /// indexing a `nil` value).
+    /// indexing a `nil` value). Errors raised by Lua code using the `error` function are returned
+    /// as this error too, followed by the stack traceback.
+    ///
+    /// Use [`Error::script_source`] to get the location where the error was raised.
     RuntimeError(StdString),
     /// Lua memory error, aka `LUA_ERRMEM`
     ///
@@ -106,7 +109,30 @@ pub enum Error {
     ///
     /// [`AnyUserData`]: crate::AnyUserData
     /// [`UserDataMethods`]: crate::UserDataMethods
+    #[deprecated(note = "mlua returns `UserDataTypeMismatchNamed` instead")]
     UserDataTypeMismatch,
+    /// An [`AnyUserData`] is not the expected type.
+    ///
+    /// Returned when borrowing or taking a userdata value of a wrong type, converting it to a
+    /// `T: UserData`, or calling a userdata method with an unexpected `self` value
+    /// (eg. `obj.method(other_obj)`). Non-userdata values produce a [`FromLuaConversionError`]
+    /// instead.
+    ///
+    /// Type names are the `__name` metafields, which are set to the Rust type name (without module
+    /// path) by default.
+    ///
+    /// [`AnyUserData`]: crate::AnyUserData
+    /// [`FromLuaConversionError`]: #variant.FromLuaConversionError
+    UserDataTypeMismatchNamed {
+        /// Name of the expected userdata type.
+        ///
+        /// It's `mlua userdata` for operations that accept any userdata created by mlua.
+        expected: StdString,
+        /// Name of the actual type of the value.
+        ///
+        /// It's the `__name` metafield of the userdata, or `userdata` if it does not have one.
+        actual: StdString,
+    },
     /// An [`AnyUserData`] borrow failed because it has been destructed.
     ///
     /// This error can happen either due to to being destructed in a previous __gc, or due to being
@@ -132,6 +158,17 @@ pub enum Error {
     /// [`AnyUserData`]: crate::AnyUserData
     /// [`UserData`]: crate::UserData
     UserDataBorrowMutError,
+    /// An [`AnyUserData`] borrow failed because the userdata was created by
+    /// [`Lua::create_copy_userdata`].
+    ///
+    /// Such userdata stores its value without borrow tracking. Use [`AnyUserData::get_copy`] to
+    /// get a copy of the value or [`AnyUserData::take`] to take it out.
+    ///
+    /// [`AnyUserData`]: crate::AnyUserData
+    /// [`Lua::create_copy_userdata`]: crate::Lua::create_copy_userdata
+    /// [`AnyUserData::get_copy`]: crate::AnyUserData::get_copy
+    /// [`AnyUserData::take`]: crate::AnyUserData::take
+    UserDataCopyBorrowError,
     /// A [`MetaMethod`] operation is restricted (typically for `__gc` or `__metatable`).
     ///
     /// [`MetaMethod`]: crate::MetaMethod
@@ -144,10 +181,54 @@ pub enum Error {
         type_name: &'static str,
         message: Option<StdString>,
     },
+    /// The value was not found after following the maximum number of `__index` metamethods.
+    ///
+    /// Returned by [`Table::get_with_depth`].
+    ///
+    /// [`Table::get_with_depth`]: crate::Table::get_with_depth
+    MetaIndexDepthExceeded {
+        /// Maximum allowed number of `__index` hops.
+        max_depth: u32,
+    },
+    /// Precompiled chunk (bytecode) is not compatible with the running Lua.
+    ///
+    /// Returned when loading a binary chunk produced by a different Lua version or
+    /// configuration (eg. byte order or sizes of numbers), or with a corrupted header.
+    /// See [`bytecode::inspect`].
+    ///
+    /// [`bytecode::inspect`]: crate::bytecode::inspect
+    BytecodeMismatch {
+        /// Description of the bytecode format supported by the running Lua.
+        expected: StdString,
+        /// Description of the bytecode format found in the chunk.
+        found: StdString,
+    },
     /// A [`RegistryKey`] produced from a different Lua state was used.
     ///
     /// [`RegistryKey`]: crate::RegistryKey
     MismatchedRegistryKey,
+    /// Too many values were passed as function arguments (or returned as results).
+    ///
+    /// Every value is placed on the Lua stack, which has an engine-specific size limit
+    /// (see [`Lua::max_call_args`]). Large amounts of data should be passed in a table instead.
+    ///
+    /// [`Lua::max_call_args`]: crate::Lua::max_call_args
+    TooManyArguments {
+        /// Number of values.
+        count: usize,
+        /// Maximum allowed number of values.
+        limit: usize,
+    },
+    /// A [`Handle`] refers to an object that is no longer registered in the [`HandleRegistry`].
+    ///
+    /// [`Handle`]: crate::Handle
+    /// [`HandleRegistry`]: crate::HandleRegistry
+    StaleHandle,
+    /// A handle that does not keep the Lua instance alive (eg. [`SendOwnedAnyUserData`]) was used
+    /// after the Lua instance was dropped.
+    ///
+    /// [`SendOwnedAnyUserData`]: crate::SendOwnedAnyUserData
+    LuaDropped,
     /// A Rust callback returned `Err`, raising the contained `Error` as a Lua error.
     CallbackError {
         /// Lua call stack backtrace.
@@ -231,10 +312,15 @@ impl fmt::Display for Error {
                 }
             }
             Error::CoroutineInactive => write!(fmt, "cannot resume inactive coroutine"),
+            #[allow(deprecated)]
             Error::UserDataTypeMismatch => write!(fmt, "userdata is not expected type"),
+            Error::UserDataTypeMismatchNamed { ref expected, ref actual } => {
+                write!(fmt, "userdata is not expected type (expected {}, got {})", expected, actual)
+            }
             Error::UserDataDestructed => write!(fmt, "userdata has been destructed"),
             Error::UserDataBorrowError => write!(fmt, "userdata already mutably borrowed"),
             Error::UserDataBorrowMutError => write!(fmt, "userdata already borrowed"),
+            Error::UserDataCopyBorrowError => write!(fmt, "copy userdata cannot be borrowed"),
             Error::MetaMethodRestricted(ref method) => write!(fmt, "metamethod {} is restricted", method),
             Error::MetaMethodTypeError { ref method, type_name, ref message } => {
                 write!(fmt, "metamethod {} has unsupported type {}", method, type_name)?;
@@ -243,9 +329,20 @@ impl fmt::Display for Error {
                     Some(ref message) => write!(fmt, " ({})", message),
                 }
             }
+            Error::MetaIndexDepthExceeded { max_depth } => {
+                write!(fmt, "__index chain is longer than {} hops", max_depth)
+            }
+            Error::BytecodeMismatch { ref expected, ref found } => {
+                write!(fmt, "bytecode mismatch: expected {}, found {}", expected, found)
+            }
             Error::MismatchedRegistryKey => {
                 write!(fmt, "RegistryKey used from different Lua state")
             }
+            Error::TooManyArguments { count, limit } => {
+                write!(fmt, "too many arguments: {} (limit is {})", count, limit)
+            }
+            Error::StaleHandle => write!(fmt, "handle refers to an unregistered object"),
+            Error::LuaDropped => write!(fmt, "Lua instance is dropped"),
             Error::CallbackError { ref cause, ref traceback } => {
                 writeln!(fmt, "callback error")?;
                 // Trace errors down to the root
@@ -303,6 +400,177 @@ impl Error {
     pub fn external<T: Into<Box<dyn StdError + Send + Sync>>>(err: T) -> Error {
         Error::ExternalError(err.into().into())
     }
+
+    /// Returns structured details of a syntax error.
+    ///
+    /// The details are parsed from the engine error message (the message itself is kept intact).
+    /// Returns `None` if the error is not a [`Error::SyntaxError`] or the message does not
+    /// contain the error location (eg. errors about loading binary chunks).
+    pub fn syntax_error_info(&self) -> Option<SyntaxErrorInfo> {
+        match self {
+            Error::SyntaxError { message, .. } => SyntaxErrorInfo::parse(message),
+            _ => None,
+        }
+    }
+
+    // Adds the table key (or path) to the message of a conversion error
+    pub(crate) fn with_key(self, key: &str) -> Self {
+        match self {
+            Error::FromLuaConversionError { from, to, message } => {
+                let message = match message {
+                    Some(message) => format!("key '{}': {}", key, message),
+                    None => format!("key '{}'", key),
+                };
+                Error::FromLuaConversionError {
+                    from,
+                    to,
+                    message: Some(message),
+                }
+            }
+            err => err,
+        }
+    }
+
+    /// Returns the source location of an error raised by a Lua script.
+    ///
+    /// The location is the `<chunk>:<line>:` prefix that Lua adds to the [`Error::RuntimeError`]
+    /// message (the message itself is kept intact), returned as the chunk name (without
+    /// the `[string "..."]` decoration) and the line number.
+    /// The prefix is accepted only if it matches a Lua function in the stack traceback recorded
+    /// when the error was raised, so a message that merely looks like a location is ignored.
+    ///
+    /// Returns `None` for other errors or if the message has no location prefix, eg. when raised
+    /// using `error(msg, 0)` or from a Rust callback.
+    pub fn script_source(&self) -> Option<(&str, u32)> {
+        match self {
+            Error::RuntimeError(message) => {
+                let (chunk_id, line, _) = script_location(message)?;
+                Some((chunk_name(chunk_id), line))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the message of an [`Error::RuntimeError`] without the source location prefix
+    /// and the stack traceback.
+    ///
+    /// See [`Error::script_source`] for details. Returns `None` for other errors.
+    pub fn script_message(&self) -> Option<&str> {
+        match self {
+            Error::RuntimeError(message) => {
+                let message = match script_location(message) {
+                    Some((_, _, message)) => message,
+                    None => message,
+                };
+                match message.find("\nstack traceback:") {
+                    Some(pos) => Some(&message[..pos]),
+                    None => Some(message),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+// Returns the `<chunkid>:<line>: ` prefix of the error message (and the rest of the message)
+// if it's the location of a Lua function listed in the stack traceback following the message.
+fn script_location(message: &str) -> Option<(&str, u32, &str)> {
+    let traceback = &message[message.rfind("\nstack traceback:")?..];
+    traceback.lines().find_map(|frame| {
+        // `\t<chunkid>:<line>: in <function>`
+        let frame = frame.strip_prefix('\t')?;
+        frame.match_indices(": in ").find_map(|(pos, _)| {
+            let location = &frame[..pos];
+            let (chunk_id, line) = location.rsplit_once(':')?;
+            let line = line.parse().ok()?;
+            let message = message.strip_prefix(location)?.strip_prefix(": ")?;
+            Some((chunk_id, line, message))
+        })
+    })
+}
+
+/// Details of a syntax error.
+///
+/// See [`Error::syntax_error_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SyntaxErrorInfo {
+    /// Name of the chunk where the error was found (without the `[string "..."]` decoration).
+    pub chunk_name: StdString,
+    /// Line number where the error was found.
+    pub line: u32,
+    /// Column number where the error was found, if reported by the engine.
+    pub column: Option<u32>,
+    /// The token near which the error was found, eg. `end` or `<eof>`.
+    pub token: Option<StdString>,
+    /// The error message without the chunk name and location.
+    pub message: StdString,
+}
+
+impl SyntaxErrorInfo {
+    // Parses error message in the `<chunkid>:<line>: <message>` format used by all engines
+    fn parse(error: &str) -> Option<Self> {
+        let (chunk_name, line, message) = split_location(error)?;
+
+        // PUC Lua reports `... near <token>`, Luau reports `..., got <token>`
+        let token = (message.rfind(", got ").map(|pos| &message[pos + 6..]))
+            .or_else(|| message.rfind(" near ").map(|pos| &message[pos + 6..]))
+            .and_then(parse_token);
+
+        Some(SyntaxErrorInfo {
+            chunk_name: chunk_name.to_string(),
+            line,
+            column: None,
+            token,
+            message: message.to_string(),
+        })
+    }
+}
+
+// Splits `<chunkid>:<line>: <message>` error message into the chunk name (without
+// the `[string "..."]` decoration), line number and the message
+fn split_location(error: &str) -> Option<(&str, u32, &str)> {
+    // Chunk name can contain `:<number>:` sequences (eg. default chunk names),
+    // so look for the first one followed by a space.
+    // Chunk ids never contain newlines
+    let first_line = error.lines().next().unwrap_or_default();
+    let mut start = 0;
+    let (chunk_id, line, message) = loop {
+        let pos = start + first_line[start..].find(':')?;
+        let rest = &error[pos + 1..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 && rest[digits..].starts_with(": ") {
+            let line = rest[..digits].parse().ok()?;
+            break (&error[..pos], line, &rest[digits + 2..]);
+        }
+        start = pos + 1;
+    };
+    if chunk_id.is_empty() {
+        return None;
+    }
+
+    Some((chunk_name(chunk_id), line, message))
+}
+
+// Returns the chunk name from the chunk id (`short_src`) without the `[string "..."]` decoration
+pub(crate) fn chunk_name(chunk_id: &str) -> &str {
+    chunk_id
+        .strip_prefix("[string \"")
+        .and_then(|name| name.strip_suffix("\"]"))
+        .unwrap_or(chunk_id)
+}
+
+fn parse_token(s: &str) -> Option<StdString> {
+    let token = if let Some(quoted) = s.strip_prefix('\'') {
+        // Search the closing quote after the first character, the token can be a quote itself
+        let end = quoted.char_indices().nth(1).map(|(i, _)| i)?;
+        &quoted[..end + quoted[end..].find('\'')?]
+    } else if s.starts_with('<') {
+        &s[..s.find('>')? + 1]
+    } else {
+        s.split_whitespace().next()?
+    };
+    Some(token.to_string())
 }
 
 pub trait ExternalError {
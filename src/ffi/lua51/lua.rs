@@ -13,6 +13,9 @@ pub const LUA_SIGNATURE: &[u8] = b"\x1bLJ";
 // Option for multiple returns in 'lua_pcall' and 'lua_call'
 pub const LUA_MULTRET: c_int = -1;
 
+// Max number of Lua stack slots that a C function can use
+pub const LUAI_MAXCSTACK: c_int = 8000;
+
 //
 // Pseudo-indices
 //
@@ -180,5 +180,34 @@ pub unsafe fn luaL_loadbuffer(
 }
 
 //
-// TODO: Generic Buffer Manipulation
+// Generic Buffer Manipulation
 //
+
+// Lua 5.2 uses `BUFSIZ` from the C library, which differs between platforms.
+// Lua sets `size` of the initial buffer on init, so it's safe to reserve more space.
+pub const LUAL_BUFFERSIZE: usize = 8192;
+
+#[repr(C)]
+pub struct luaL_Buffer {
+    pub b: *mut c_char,
+    pub size: usize,
+    pub n: usize,
+    pub L: *mut lua_State,
+    pub initb: [c_char; LUAL_BUFFERSIZE],
+}
+
+extern "C" {
+    pub fn luaL_buffinit(L: *mut lua_State, B: *mut luaL_Buffer);
+    pub fn luaL_prepbuffsize(B: *mut luaL_Buffer, sz: usize) -> *mut c_char;
+    pub fn luaL_addlstring(B: *mut luaL_Buffer, s: *const c_char, l: usize);
+    pub fn luaL_addstring(B: *mut luaL_Buffer, s: *const c_char);
+    pub fn luaL_addvalue(B: *mut luaL_Buffer);
+    pub fn luaL_pushresult(B: *mut luaL_Buffer);
+    pub fn luaL_pushresultsize(B: *mut luaL_Buffer, sz: usize);
+    pub fn luaL_buffinitsize(L: *mut lua_State, B: *mut luaL_Buffer, sz: usize) -> *mut c_char;
+}
+
+#[inline(always)]
+pub unsafe fn luaL_addsize(B: *mut luaL_Buffer, s: usize) {
+    (*B).n += s;
+}
@@ -1,7 +1,7 @@
 //! Contains definitions from `lauxlib.h`.
 
-use std::os::raw::{c_char, c_int, c_void};
-use std::ptr;
+use std::os::raw::{c_char, c_int, c_long, c_void};
+use std::{mem, ptr};
 
 use super::lua::{self, lua_CFunction, lua_Integer, lua_Number, lua_State};
 
@@ -182,5 +182,44 @@ pub unsafe fn luaL_loadbuffer(
 }
 
 //
-// TODO: Generic Buffer Manipulation
+// Generic Buffer Manipulation
 //
+
+pub const LUAL_BUFFERSIZE: usize =
+    16 * mem::size_of::<*mut c_void>() * mem::size_of::<lua_Number>();
+
+#[repr(C)]
+pub struct luaL_Buffer {
+    pub b: *mut c_char,
+    pub size: usize,
+    pub n: usize,
+    pub L: *mut lua_State,
+    pub init: luaL_BufferInit,
+}
+
+// Initial buffer, with maximum alignment
+#[repr(C)]
+pub union luaL_BufferInit {
+    pub n: lua_Number,
+    pub u: f64,
+    pub s: *mut c_void,
+    pub i: lua_Integer,
+    pub l: c_long,
+    pub b: [c_char; LUAL_BUFFERSIZE],
+}
+
+extern "C" {
+    pub fn luaL_buffinit(L: *mut lua_State, B: *mut luaL_Buffer);
+    pub fn luaL_prepbuffsize(B: *mut luaL_Buffer, sz: usize) -> *mut c_char;
+    pub fn luaL_addlstring(B: *mut luaL_Buffer, s: *const c_char, l: usize);
+    pub fn luaL_addstring(B: *mut luaL_Buffer, s: *const c_char);
+    pub fn luaL_addvalue(B: *mut luaL_Buffer);
+    pub fn luaL_pushresult(B: *mut luaL_Buffer);
+    pub fn luaL_pushresultsize(B: *mut luaL_Buffer, sz: usize);
+    pub fn luaL_buffinitsize(L: *mut lua_State, B: *mut luaL_Buffer, sz: usize) -> *mut c_char;
+}
+
+#[inline(always)]
+pub unsafe fn luaL_addsize(B: *mut luaL_Buffer, s: usize) {
+    (*B).n += s;
+}
@@ -3,6 +3,12 @@
 use std::os::raw::{c_char, c_int, c_void};
 use std::slice;
 
+// Supported bytecode versions, from `Bytecode.h`
+pub const LBC_VERSION_MIN: u8 = 3;
+pub const LBC_VERSION_MAX: u8 = 6;
+pub const LBC_TYPE_VERSION_MIN: u8 = 1;
+pub const LBC_TYPE_VERSION_MAX: u8 = 3;
+
 #[repr(C)]
 pub struct lua_CompileOptions {
     pub optimizationLevel: c_int,
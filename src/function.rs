@@ -1,3 +1,4 @@
+use std::hash::{Hash, Hasher};
 use std::mem;
 use std::os::raw::{c_int, c_void};
 use std::ptr;
@@ -5,11 +6,21 @@ use std::slice;
 
 use crate::error::{Error, Result};
 use crate::ffi;
+use crate::table::Table;
 use crate::types::LuaRef;
 use crate::util::{
-    assert_stack, check_stack, error_traceback, pop_error, ptr_to_cstr_bytes, StackGuard,
+    assert_stack, check_call_args, check_stack, error_traceback, error_traceback_full, pop_error,
+    ptr_to_cstr_bytes, push_error_traceback, StackGuard, MAX_CALL_ARGS,
+};
+use crate::value::{FromLuaMulti, IntoLuaMulti, MultiValue, Value};
+
+#[cfg(not(feature = "luau"))]
+use {
+    crate::chunk::ChunkMode,
+    std::any::Any,
+    std::io,
+    std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
 };
-use crate::value::{FromLuaMulti, IntoLuaMulti};
 
 #[cfg(feature = "unstable")]
 use {
@@ -35,12 +46,39 @@ pub struct Function<'lua>(pub(crate) LuaRef<'lua>);
 #[derive(Clone, Debug)]
 pub struct OwnedFunction(pub(crate) crate::types::LuaOwnedRef);
 
+/// Methods of [`OwnedFunction`] mirror the [`Function`] API.
+///
+/// The handle keeps the originating Lua state alive, so it can be stored (eg. in a struct field)
+/// and called later without a `&Lua` reference. With `send` feature the handle is `Send` and
+/// every access to the Lua state (including dropping the handle) takes the Lua lock.
 #[cfg(feature = "unstable")]
 impl OwnedFunction {
     /// Get borrowed handle to the underlying Lua function.
     pub const fn to_ref(&self) -> Function {
         Function(self.0.to_ref())
     }
+
+    /// Calls the function, passing `args` as function arguments. See [`Function::call`].
+    pub fn call<'lua, A: IntoLuaMulti<'lua>, R: FromLuaMulti<'lua>>(
+        &'lua self,
+        args: A,
+    ) -> Result<R> {
+        self.to_ref().call(args)
+    }
+
+    /// Returns a future that, when polled, calls `self`, passing `args` as function arguments,
+    /// and drives the execution. See [`Function::call_async`].
+    ///
+    /// Requires `feature = "async"`
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn call_async<'lua, A, R>(&'lua self, args: A) -> LocalBoxFuture<'lua, Result<R>>
+    where
+        A: IntoLuaMulti<'lua>,
+        R: FromLuaMulti<'lua> + 'lua,
+    {
+        self.to_ref().call_async(args)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -53,6 +91,16 @@ pub struct FunctionInfo {
     pub line_defined: i32,
     #[cfg(not(feature = "luau"))]
     pub last_line_defined: i32,
+    /// Number of upvalues of the function.
+    pub num_upvalues: u8,
+    /// Number of fixed parameters of the function (always `0` for C functions).
+    ///
+    /// Not available in Lua 5.1 and LuaJIT.
+    pub num_params: Option<u8>,
+    /// Whether the function is a variadic function (always `true` for C functions).
+    ///
+    /// Not available in Lua 5.1 and LuaJIT.
+    pub is_vararg: Option<bool>,
 }
 
 /// Luau function coverage snapshot.
@@ -60,9 +108,15 @@ pub struct FunctionInfo {
 #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CoverageInfo {
+    /// Name of the function, if known.
     pub function: Option<std::string::String>,
+    /// Line where the function is defined.
     pub line_defined: i32,
+    /// Nesting depth of the function (`0` for the top-level function).
     pub depth: i32,
+    /// Number of hits per source line, indexed by line number (starting from `0`).
+    ///
+    /// Lines without executable code are marked with `-1`.
     pub hits: Vec<i32>,
 }
 
@@ -108,17 +162,58 @@ impl<'lua> Function<'lua> {
     /// # }
     /// ```
     pub fn call<A: IntoLuaMulti<'lua>, R: FromLuaMulti<'lua>>(&self, args: A) -> Result<R> {
+        self.call_with_handler(args, error_traceback)
+    }
+
+    /// Calls the function like [`call`], making sure that any returned error carries a traceback.
+    ///
+    /// Lua errors (including non-string error values, converted using `__tostring` metamethod
+    /// if present) are returned as [`Error::RuntimeError`] with the message followed by the stack
+    /// traceback, same as [`call`] does.
+    /// In addition, Rust errors that propagate through Lua code without traceback (for example,
+    /// an [`Error`] value caught by `pcall` and rethrown using `error`) are wrapped into
+    /// [`Error::CallbackError`] with the traceback of the place where they were raised.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Error, Function, Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let func: Function = lua.load("function(err) error(err) end").eval()?;
+    ///
+    /// let err = func.call_with_traceback::<_, ()>(Error::external("boom")).unwrap_err();
+    /// assert!(matches!(err, Error::CallbackError { .. }));
+    /// assert!(err.to_string().contains("stack traceback"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`call`]: #method.call
+    pub fn call_with_traceback<A: IntoLuaMulti<'lua>, R: FromLuaMulti<'lua>>(
+        &self,
+        args: A,
+    ) -> Result<R> {
+        self.call_with_handler(args, error_traceback_full)
+    }
+
+    fn call_with_handler<A: IntoLuaMulti<'lua>, R: FromLuaMulti<'lua>>(
+        &self,
+        args: A,
+        handler: ffi::lua_CFunction,
+    ) -> Result<R> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
 
         let mut args = args.into_lua_multi(lua)?;
-        let nargs = args.len() as c_int;
+        let nargs = check_call_args(args.len())?;
 
         let results = unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, nargs + 3)?;
 
-            ffi::lua_pushcfunction(state, error_traceback);
+            ffi::lua_pushcfunction(state, handler);
             let stack_start = ffi::lua_gettop(state);
             lua.push_ref(&self.0);
             for arg in args.drain_all() {
@@ -140,6 +235,112 @@ impl<'lua> Function<'lua> {
         R::from_lua_multi(results, lua)
     }
 
+    /// Calls the function, passing `args` as function arguments, and stores the results in `out`.
+    ///
+    /// Unlike [`call`], this method reuses the caller-provided [`MultiValue`] buffer: it's cleared
+    /// and refilled with the function results, keeping its allocated capacity. Dropping the
+    /// previous contents before the call releases their references, so the freed slots are
+    /// reused for the new results. This makes repeated calls in a tight loop allocation-free in
+    /// the steady state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Function, Lua, MultiValue, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let pos: Function = lua.load("function(i) return i, i * 2 end").eval()?;
+    ///
+    /// let mut out = MultiValue::new();
+    /// for i in 0..10 {
+    ///     pos.call_into(i, &mut out)?;
+    ///     assert_eq!(out.len(), 2);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`call`]: #method.call
+    pub fn call_into<A: IntoLuaMulti<'lua>>(
+        &self,
+        args: A,
+        out: &mut MultiValue<'lua>,
+    ) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+
+        // Release references held by the previous results
+        out.clear();
+
+        let mut args = args.into_lua_multi(lua)?;
+        let nargs = check_call_args(args.len())?;
+
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, nargs + 3)?;
+
+            push_error_traceback(state)?;
+            let stack_start = ffi::lua_gettop(state);
+            lua.push_ref(&self.0);
+            for arg in args.drain_all() {
+                lua.push_value(arg)?;
+            }
+            MultiValue::return_to_pool(args, lua);
+            let ret = ffi::lua_pcall(state, nargs, ffi::LUA_MULTRET, stack_start);
+            if ret != ffi::LUA_OK {
+                return Err(pop_error(state, ret));
+            }
+            let nresults = ffi::lua_gettop(state) - stack_start;
+            out.reserve(nresults as usize);
+            assert_stack(state, 2);
+            for _ in 0..nresults {
+                out.push_front(lua.pop_value());
+            }
+            ffi::lua_pop(state, 1);
+        }
+        Ok(())
+    }
+
+    /// Calls the function without arguments, discarding the results.
+    ///
+    /// Equivalent to `call::<_, ()>(())` (including the error handling), but does not convert
+    /// the arguments and the results, so the call does not allocate in the steady state.
+    /// This is useful for callback-heavy code (eg. event handlers called every frame).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Function, Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let tick: Function = lua.load("function() ticks = (ticks or 0) + 1 end").eval()?;
+    /// for _ in 0..10 {
+    ///     tick.call0()?;
+    /// }
+    /// assert_eq!(lua.globals().get::<_, u32>("ticks")?, 10);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call0(&self) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+
+            push_error_traceback(state)?;
+            let stack_start = ffi::lua_gettop(state);
+            lua.push_ref(&self.0);
+            let ret = ffi::lua_pcall(state, 0, 0, stack_start);
+            if ret != ffi::LUA_OK {
+                return Err(pop_error(state, ret));
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a future that, when polled, calls `self`, passing `args` as function arguments,
     /// and drives the execution.
     ///
@@ -216,45 +417,46 @@ impl<'lua> Function<'lua> {
     /// # }
     /// ```
     pub fn bind<A: IntoLuaMulti<'lua>>(&self, args: A) -> Result<Function<'lua>> {
-        unsafe extern "C" fn args_wrapper_impl(state: *mut ffi::lua_State) -> c_int {
-            let nargs = ffi::lua_gettop(state);
-            let nbinds = ffi::lua_tointeger(state, ffi::lua_upvalueindex(1)) as c_int;
-            ffi::luaL_checkstack(state, nbinds, ptr::null());
-
-            for i in 0..nbinds {
-                ffi::lua_pushvalue(state, ffi::lua_upvalueindex(i + 2));
-            }
-            if nargs > 0 {
-                ffi::lua_rotate(state, 1, nbinds);
-            }
-
-            nargs + nbinds
-        }
-
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
 
         let args = args.into_lua_multi(lua)?;
-        let nargs = args.len() as c_int;
+        let nargs = args.len();
 
         if nargs == 0 {
             return Ok(self.clone());
         }
 
-        if nargs + 1 > ffi::LUA_MAX_UPVALUES {
+        // Binding an already bound function extends the list of bound arguments
+        // instead of nesting another call frame
+        let (func, prev_args, nprev) = match self.bound_parts() {
+            Some((func, prev_args, nprev)) => (func, Some(prev_args), nprev),
+            None => (self.clone(), None, 0),
+        };
+        let nbinds = nprev + nargs;
+        if nbinds > MAX_CALL_ARGS {
             return Err(Error::BindError);
         }
 
+        let bound_args = lua.create_table_with_capacity(nbinds as c_int, 0)?;
+        if let Some(prev_args) = prev_args {
+            for i in 1..=nprev {
+                bound_args.raw_set(i, prev_args.raw_get::<_, Value>(i)?)?;
+            }
+        }
+        for (i, arg) in args.into_iter().enumerate() {
+            bound_args.raw_set(nprev + i + 1, arg)?;
+        }
+
         let args_wrapper = unsafe {
             let _sg = StackGuard::new(state);
-            check_stack(state, nargs + 3)?;
+            check_stack(state, 4)?;
 
-            ffi::lua_pushinteger(state, nargs as ffi::lua_Integer);
-            for arg in args {
-                lua.push_value(arg)?;
-            }
-            protect_lua!(state, nargs + 1, 1, fn(state) {
-                ffi::lua_pushcclosure(state, args_wrapper_impl, ffi::lua_gettop(state));
+            lua.push_ref(&bound_args.0);
+            ffi::lua_pushinteger(state, nbinds as ffi::lua_Integer);
+            protect_lua!(state, 2, 1, fn(state) {
+                ffi::lua_pushcclosure(state, args_wrapper_impl, 2);
             })?;
 
             Function(lua.pop_ref())
@@ -270,7 +472,47 @@ impl<'lua> Function<'lua> {
         )
         .try_cache()
         .set_name("_mlua_bind")
-        .call((self.clone(), args_wrapper))
+        .call((func, args_wrapper))
+    }
+
+    // Returns the original function, the table of bound arguments and their number
+    // if the function was created by `Function::bind`.
+    // Bound functions are recognized by the `args_wrapper_impl` upvalue, that is not accessible
+    // from Lua without the debug library.
+    fn bound_parts(&self) -> Option<(Function<'lua>, Table<'lua>, usize)> {
+        let _guard = self.0.lua.lock();
+        if self.info().num_upvalues != 2 {
+            return None;
+        }
+
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            assert_stack(state, 4);
+
+            lua.push_ref(&self.0);
+            if ffi::lua_getupvalue(state, -1, 2).is_null() {
+                return None;
+            }
+            let args_wrapper = ffi::lua_tocfunction(state, -1).map(|f| f as *const ());
+            if args_wrapper != Some(args_wrapper_impl as *const ()) {
+                return None;
+            }
+            ffi::lua_getupvalue(state, -1, 2);
+            let nprev = ffi::lua_tointeger(state, -1) as usize;
+            ffi::lua_pop(state, 1);
+            ffi::lua_getupvalue(state, -1, 1);
+            ffi::lua_getupvalue(state, -3, 1);
+            if ffi::lua_type(state, -1) != ffi::LUA_TFUNCTION
+                || ffi::lua_type(state, -2) != ffi::LUA_TTABLE
+            {
+                return None;
+            }
+            let func = Function(lua.pop_ref());
+            let prev_args = Table(lua.pop_ref());
+            Some((func, prev_args, nprev))
+        }
     }
 
     /// Returns information about the function.
@@ -279,6 +521,7 @@ impl<'lua> Function<'lua> {
     ///
     /// [`lua_getinfo`]: https://www.lua.org/manual/5.4/manual.html#lua_getinfo
     pub fn info(&self) -> FunctionInfo {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
@@ -288,10 +531,10 @@ impl<'lua> Function<'lua> {
             let mut ar: ffi::lua_Debug = mem::zeroed();
             lua.push_ref(&self.0);
             #[cfg(not(feature = "luau"))]
-            let res = ffi::lua_getinfo(state, cstr!(">Sn"), &mut ar);
+            let res = ffi::lua_getinfo(state, cstr!(">Snu"), &mut ar);
             #[cfg(feature = "luau")]
-            let res = ffi::lua_getinfo(state, -1, cstr!("sn"), &mut ar);
-            mlua_assert!(res != 0, "lua_getinfo failed with `>Sn`");
+            let res = ffi::lua_getinfo(state, -1, cstr!("snua"), &mut ar);
+            mlua_assert!(res != 0, "lua_getinfo failed with `>Snu`");
 
             FunctionInfo {
                 name: ptr_to_cstr_bytes(ar.name).map(|s| s.to_vec()),
@@ -308,6 +551,28 @@ impl<'lua> Function<'lua> {
                 line_defined: ar.linedefined,
                 #[cfg(not(feature = "luau"))]
                 last_line_defined: ar.lastlinedefined,
+                #[cfg(not(feature = "luau"))]
+                num_upvalues: ar.nups as u8,
+                #[cfg(feature = "luau")]
+                num_upvalues: ar.nupvals,
+                #[cfg(any(
+                    feature = "lua54",
+                    feature = "lua53",
+                    feature = "lua52",
+                    feature = "luau"
+                ))]
+                num_params: Some(ar.nparams),
+                #[cfg(any(feature = "lua51", feature = "luajit"))]
+                num_params: None,
+                #[cfg(any(
+                    feature = "lua54",
+                    feature = "lua53",
+                    feature = "lua52",
+                    feature = "luau"
+                ))]
+                is_vararg: Some(ar.isvararg != 0),
+                #[cfg(any(feature = "lua51", feature = "luajit"))]
+                is_vararg: None,
             }
         }
     }
@@ -317,38 +582,190 @@ impl<'lua> Function<'lua> {
     /// If `strip` is true, the binary representation may not include all debug information
     /// about the function, to save space.
     ///
+    /// C functions (see [`is_c_function`]) cannot be dumped, an empty vector is returned for them.
+    ///
     /// For Luau a [Compiler] can be used to compile Lua chunks to bytecode.
     ///
+    /// [`is_c_function`]: #method.is_c_function
     /// [Compiler]: crate::chunk::Compiler
     #[cfg(not(feature = "luau"))]
     #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
     pub fn dump(&self, strip: bool) -> Vec<u8> {
-        unsafe extern "C" fn writer(
+        if self.is_c_function() {
+            return Vec::new();
+        }
+        let mut data = Vec::new();
+        mlua_expect!(self.dump_to(&mut data, strip), "writing to Vec cannot fail");
+        data
+    }
+
+    /// Dumps the function as a binary chunk into the `writer`.
+    ///
+    /// Works like [`dump`], but writes the chunk directly as it's produced, without buffering it
+    /// in memory. Returns the number of bytes written.
+    ///
+    /// If the writer returns an error, dumping stops and [`Error::RuntimeError`] is returned.
+    /// C functions cannot be dumped, an error is returned for them without touching the writer.
+    ///
+    /// [`dump`]: #method.dump
+    #[cfg(not(feature = "luau"))]
+    #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
+    pub fn dump_to<W: io::Write + ?Sized>(&self, writer: &mut W, strip: bool) -> Result<u64> {
+        let _guard = self.0.lua.lock();
+        struct DumpState<'a, W: ?Sized> {
+            writer: &'a mut W,
+            written: u64,
+            error: Option<io::Error>,
+            panic: Option<Box<dyn Any + Send>>,
+        }
+
+        // Errors and panics are stored and reported after returning from `lua_dump`
+        unsafe extern "C" fn write_chunk<W: io::Write + ?Sized>(
             _state: *mut ffi::lua_State,
             buf: *const c_void,
             buf_len: usize,
             data: *mut c_void,
         ) -> c_int {
-            let data = &mut *(data as *mut Vec<u8>);
+            if buf_len == 0 {
+                return 0;
+            }
+            let data = &mut *(data as *mut DumpState<W>);
             let buf = slice::from_raw_parts(buf as *const u8, buf_len);
-            data.extend_from_slice(buf);
-            0
+            match catch_unwind(AssertUnwindSafe(|| data.writer.write_all(buf))) {
+                Ok(Ok(())) => {
+                    data.written += buf_len as u64;
+                    0
+                }
+                Ok(Err(err)) => {
+                    data.error = Some(err);
+                    1
+                }
+                Err(panic) => {
+                    data.panic = Some(panic);
+                    1
+                }
+            }
+        }
+
+        if self.is_c_function() {
+            return Err(Error::RuntimeError("cannot dump a C function".into()));
         }
 
         let lua = self.0.lua;
         let state = lua.state();
-        let mut data: Vec<u8> = Vec::new();
+        let mut data = DumpState {
+            writer,
+            written: 0,
+            error: None,
+            panic: None,
+        };
         unsafe {
             let _sg = StackGuard::new(state);
             assert_stack(state, 1);
 
             lua.push_ref(&self.0);
-            let data_ptr = &mut data as *mut Vec<u8> as *mut c_void;
-            ffi::lua_dump(state, writer, data_ptr, strip as i32);
-            ffi::lua_pop(state, 1);
+            let data_ptr = &mut data as *mut DumpState<W> as *mut c_void;
+            ffi::lua_dump(state, write_chunk::<W>, data_ptr, strip as i32);
         }
 
-        data
+        if let Some(panic) = data.panic {
+            resume_unwind(panic);
+        }
+        if let Some(err) = data.error {
+            let err = format!("error writing function dump: {}", err);
+            return Err(Error::RuntimeError(err));
+        }
+        Ok(data.written)
+    }
+
+    /// Creates an independent copy of the Lua function.
+    ///
+    /// The copy has its own upvalues, initialized with the current values of the original
+    /// function upvalues, so changing a captured variable in one function is not visible in the
+    /// other. This is useful to instantiate "template" closures. The environment table is the
+    /// same, but on Lua 5.2+ it's stored in a separate `_ENV` upvalue (see
+    /// [`deep_clone_with_shared_env`]).
+    ///
+    /// On Lua 5.x and LuaJIT the function is dumped and loaded back (with debug information).
+    /// On Luau the native `lua_clonefunction` is used, which copies immutable captured values
+    /// but shares mutable upvalues with the original function.
+    ///
+    /// Returns an error for C functions (including Rust callbacks).
+    ///
+    /// [`deep_clone_with_shared_env`]: #method.deep_clone_with_shared_env
+    pub fn deep_clone(&self) -> Result<Function<'lua>> {
+        self.deep_clone_impl(false)
+    }
+
+    /// Creates an independent copy of the Lua function that shares the `_ENV` upvalue with the
+    /// original function.
+    ///
+    /// Works like [`deep_clone`], but on Lua 5.2+ the `_ENV` upvalue is joined, so changing the
+    /// environment of one function (eg. using [`set_environment`]) changes the other.
+    /// On other Lua versions this is equivalent to [`deep_clone`].
+    ///
+    /// [`deep_clone`]: #method.deep_clone
+    /// [`set_environment`]: #method.set_environment
+    pub fn deep_clone_with_shared_env(&self) -> Result<Function<'lua>> {
+        self.deep_clone_impl(true)
+    }
+
+    fn deep_clone_impl(&self, share_env: bool) -> Result<Function<'lua>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            if ffi::lua_iscfunction(state, -1) != 0 {
+                let err = "cannot clone a C function".to_string();
+                return Err(Error::RuntimeError(err));
+            }
+
+            #[cfg(feature = "luau")]
+            {
+                let _ = share_env;
+                protect_lua!(state, 1, 2, fn(state) ffi::lua_clonefunction(state, -1))?;
+                // The clone gets the thread globals as environment
+                ffi::lua_getfenv(state, -2);
+                ffi::lua_setfenv(state, -2);
+                Ok(Function(lua.pop_ref()))
+            }
+
+            #[cfg(not(feature = "luau"))]
+            {
+                let data = self.dump(false);
+                let clone = lua.load_chunk(None, Value::Nil, Some(ChunkMode::Binary), &data)?;
+                lua.push_ref(&clone.0);
+
+                // Stack: original, clone
+                #[cfg(any(feature = "lua51", feature = "luajit"))]
+                {
+                    let _ = share_env;
+                    ffi::lua_getfenv(state, -2);
+                    ffi::lua_setfenv(state, -2);
+                }
+                let mut n = 1;
+                loop {
+                    let name = ffi::lua_getupvalue(state, -2, n);
+                    if name.is_null() {
+                        break;
+                    }
+                    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+                    if share_env && ptr_to_cstr_bytes(name) == Some(&b"_ENV"[..]) {
+                        ffi::lua_pop(state, 1);
+                        ffi::lua_upvaluejoin(state, -1, n, -2, n);
+                        n += 1;
+                        continue;
+                    }
+                    ffi::lua_setupvalue(state, -2, n);
+                    n += 1;
+                }
+                Ok(clone)
+            }
+        }
     }
 
     /// Retrieves recorded coverage information about this Lua function including inner calls.
@@ -367,6 +784,7 @@ impl<'lua> Function<'lua> {
     where
         F: FnMut(CoverageInfo),
     {
+        let _guard = self.0.lua.lock();
         use std::ffi::CStr;
         use std::os::raw::c_char;
 
@@ -384,11 +802,13 @@ impl<'lua> Function<'lua> {
                 None
             };
             let rust_callback = &mut *(data as *mut F);
-            rust_callback(CoverageInfo {
-                function,
-                line_defined,
-                depth,
-                hits: slice::from_raw_parts(hits, size).to_vec(),
+            crate::util::abort_on_panic(|| {
+                rust_callback(CoverageInfo {
+                    function,
+                    line_defined,
+                    depth,
+                    hits: slice::from_raw_parts(hits, size).to_vec(),
+                })
             });
         }
 
@@ -404,6 +824,113 @@ impl<'lua> Function<'lua> {
         }
     }
 
+    /// Returns the environment of the function (the table used to resolve global variables).
+    ///
+    /// On Lua 5.1, LuaJIT and Luau this is the function environment (`getfenv`), on Lua 5.2+
+    /// it's the value of the `_ENV` upvalue. Returns `None` for C functions, and for Lua
+    /// functions without `_ENV` upvalue (eg. functions that don't access globals or were
+    /// loaded from stripped bytecode) or if the environment is not a table.
+    pub fn environment(&self) -> Result<Option<Table<'lua>>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            lua.push_ref(&self.0);
+            if ffi::lua_iscfunction(state, -1) != 0 {
+                return Ok(None);
+            }
+
+            #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+            ffi::lua_getfenv(state, -1);
+            #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+            match find_env_upvalue(state, -1) {
+                Some(n) => {
+                    ffi::lua_getupvalue(state, -1, n);
+                }
+                None => return Ok(None),
+            }
+
+            if ffi::lua_type(state, -1) != ffi::LUA_TTABLE {
+                return Ok(None);
+            }
+            Ok(Some(Table(lua.pop_ref())))
+        }
+    }
+
+    /// Sets the environment of the function (the table used to resolve global variables).
+    ///
+    /// This works like [`Chunk::set_environment`], but for already loaded functions:
+    /// on Lua 5.1, LuaJIT and Luau it sets the function environment (`setfenv`), on Lua 5.2+
+    /// it replaces the value of the `_ENV` upvalue.
+    ///
+    /// Returns `false` (and does nothing) for C functions and for Lua functions without
+    /// `_ENV` upvalue.
+    ///
+    /// # Note
+    ///
+    /// On Lua 5.2+ the `_ENV` upvalue can be shared with other functions, eg. functions
+    /// defined inside a chunk share it with the chunk, so they observe the new environment too.
+    ///
+    /// [`Chunk::set_environment`]: crate::Chunk::set_environment
+    pub fn set_environment(&self, env: Table) -> Result<bool> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            lua.push_ref(&self.0);
+            if ffi::lua_iscfunction(state, -1) != 0 {
+                return Ok(false);
+            }
+
+            #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+            {
+                lua.push_ref(&env.0);
+                // Environment can be modified by the function, disable Luau optimizations
+                #[cfg(feature = "luau")]
+                ffi::lua_setsafeenv(state, -1, 0);
+                Ok(ffi::lua_setfenv(state, -2) != 0)
+            }
+            #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+            match find_env_upvalue(state, -1) {
+                Some(n) => {
+                    lua.push_ref(&env.0);
+                    ffi::lua_setupvalue(state, -2, n);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+    }
+
+    /// Converts the function to a generic C pointer.
+    ///
+    /// Different functions will give different pointers.
+    /// There is no way to convert the pointer back to its original value.
+    ///
+    /// Typically this function is used only for hashing and debug information.
+    #[inline]
+    pub fn to_pointer(&self) -> *const c_void {
+        let _guard = self.0.lua.lock();
+        let ref_thread = self.0.lua.ref_thread();
+        unsafe { ffi::lua_topointer(ref_thread, self.0.index) }
+    }
+
+    /// Returns `true` if the function is implemented in C (including Rust callbacks).
+    ///
+    /// C functions cannot be dumped or cloned.
+    #[inline]
+    pub fn is_c_function(&self) -> bool {
+        let _guard = self.0.lua.lock();
+        let ref_thread = self.0.lua.ref_thread();
+        unsafe { ffi::lua_iscfunction(ref_thread, self.0.index) != 0 }
+    }
+
     /// Convert this handle to owned version.
     #[cfg(feature = "unstable")]
     #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
@@ -413,12 +940,71 @@ impl<'lua> Function<'lua> {
     }
 }
 
+// Pushes the bound arguments (stored in the table upvalue) followed by the passed arguments.
+unsafe extern "C" fn args_wrapper_impl(state: *mut ffi::lua_State) -> c_int {
+    let nargs = ffi::lua_gettop(state);
+    let nbinds = ffi::lua_tointeger(state, ffi::lua_upvalueindex(2)) as c_int;
+    ffi::luaL_checkstack(state, nbinds, ptr::null());
+
+    for i in 1..=nbinds {
+        ffi::lua_rawgeti(state, ffi::lua_upvalueindex(1), i as ffi::lua_Integer);
+    }
+    if nargs > 0 {
+        ffi::lua_rotate(state, 1, nbinds);
+    }
+
+    nargs + nbinds
+}
+
+// Returns the index of the `_ENV` upvalue of the function at `idx`.
+// Uses 1 stack space, does not call checkstack.
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+unsafe fn find_env_upvalue(state: *mut ffi::lua_State, idx: c_int) -> Option<c_int> {
+    let idx = ffi::lua_absindex(state, idx);
+    for n in 1.. {
+        let name = ffi::lua_getupvalue(state, idx, n);
+        if name.is_null() {
+            return None;
+        }
+        ffi::lua_pop(state, 1);
+        if ptr_to_cstr_bytes(name) == Some(&b"_ENV"[..]) {
+            return Some(n);
+        }
+    }
+    None
+}
+
 impl<'lua> PartialEq for Function<'lua> {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
+impl<'lua> Eq for Function<'lua> {}
+
+impl<'lua> Hash for Function<'lua> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_pointer().hash(state);
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl PartialEq for OwnedFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_ref() == other.to_ref()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Eq for OwnedFunction {}
+
+#[cfg(feature = "unstable")]
+impl Hash for OwnedFunction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_ref().hash(state);
+    }
+}
+
 #[cfg(feature = "unstable")]
 pub(crate) struct WrappedFunction<'lua>(pub(crate) Callback<'lua, 'static>);
 
@@ -458,8 +1044,19 @@ impl<'lua> Function<'lua> {
         }))
     }
 
+    /// Wraps a Rust async function or closure, returning an opaque type that implements [`IntoLua`] trait.
+    ///
+    /// The function is created when the value is converted to Lua and behaves the same way as
+    /// functions created using [`Lua::create_async_function`]: it should be called from an async
+    /// context (eg. using [`Chunk::exec_async`] or [`AsyncThread`]).
+    ///
+    /// Requires `feature = "async"`
+    ///
+    /// [`Chunk::exec_async`]: crate::Chunk::exec_async
+    /// [`AsyncThread`]: crate::AsyncThread
     #[cfg(feature = "async")]
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    #[inline]
     pub fn wrap_async<F, A, FR, R>(func: F) -> impl IntoLua<'lua>
     where
         F: Fn(&'lua Lua, A) -> FR + MaybeSend + 'static,
@@ -483,6 +1080,8 @@ mod assertions {
 
     static_assertions::assert_not_impl_any!(Function: Send);
 
-    #[cfg(feature = "unstable")]
+    #[cfg(all(feature = "unstable", not(feature = "send")))]
     static_assertions::assert_not_impl_any!(OwnedFunction: Send);
+    #[cfg(all(feature = "unstable", feature = "send"))]
+    static_assertions::assert_impl_all!(OwnedFunction: Send);
 }
@@ -0,0 +1,331 @@
+use std::fmt;
+use std::ops::Deref;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Error, Result};
+use crate::lua::Lua;
+use crate::types::LightUserData;
+use crate::value::{FromLua, IntoLua, Value};
+
+// Lower half of the light userdata pointer value is the slot index, upper half is the generation
+const INDEX_BITS: u32 = usize::BITS / 2;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: usize = usize::MAX >> INDEX_BITS;
+// Generation of a slot that ran out of generations, it does not match any handle
+const RETIRED_GENERATION: usize = GENERATION_MASK + 1;
+
+/// A handle to an object registered in a [`HandleRegistry`].
+///
+/// Handles are passed to Lua as light userdata, so they are cheap to create and are not tracked
+/// by the garbage collector. Each handle carries a generation number, which allows the registry
+/// to detect handles to objects that were already invalidated (stale handles).
+///
+/// [`HandleRegistry`]: crate::HandleRegistry
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: usize,
+}
+
+impl Handle {
+    /// Returns the slot index of this handle.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the generation of this handle.
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn to_light_userdata(self) -> LightUserData {
+        LightUserData((self.generation << INDEX_BITS | self.index) as *mut c_void)
+    }
+
+    fn from_light_userdata(ud: LightUserData) -> Self {
+        let value = ud.0 as usize;
+        Handle {
+            index: value & INDEX_MASK,
+            generation: value >> INDEX_BITS,
+        }
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+impl<'lua> IntoLua<'lua> for Handle {
+    #[inline]
+    fn into_lua(self, _: &'lua Lua) -> Result<Value<'lua>> {
+        Ok(Value::LightUserData(self.to_light_userdata()))
+    }
+}
+
+impl<'lua> FromLua<'lua> for Handle {
+    #[inline]
+    fn from_lua(value: Value<'lua>, _: &'lua Lua) -> Result<Self> {
+        match value {
+            Value::LightUserData(ud) => Ok(Handle::from_light_userdata(ud)),
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Handle",
+                message: None,
+            }),
+        }
+    }
+}
+
+struct Slot<T> {
+    generation: usize,
+    value: Option<T>,
+}
+
+impl<T> Slot<T> {
+    // Takes the value out and makes all handles to this slot stale.
+    // Generations are never wrapped around (and `0` is never used to avoid producing null
+    // pointers), so a slot that used the last generation is retired instead.
+    fn invalidate(&mut self) -> Option<T> {
+        let value = self.value.take()?;
+        self.generation += 1;
+        Some(value)
+    }
+
+    // Returns `true` if the slot can hold a new value
+    fn is_reusable(&self) -> bool {
+        self.generation != RETIRED_GENERATION
+    }
+}
+
+struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Slots<T> {
+    fn get(&self, handle: Handle) -> Option<&T> {
+        let slot = self.slots.get(handle.index)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.value.as_ref()
+    }
+}
+
+/// A registry of objects shared with Lua through validated [`Handle`]s.
+///
+/// Created by [`Lua::create_handle_registry`]. Rust registers objects and receives handles,
+/// which can be passed to Lua. Scripts pass them back to Rust callbacks that extract
+/// [`ValidatedHandle<T>`] (or resolve a plain [`Handle`] using [`resolve`]). A handle that refers
+/// to an invalidated object produces [`Error::StaleHandle`] instead of accessing a wrong object.
+///
+/// The registry is a cheap reference-counted handle to the shared storage, so it can be freely
+/// cloned.
+///
+/// # Example
+///
+/// ```
+/// # use mlua::{Lua, Result, ValidatedHandle};
+/// # fn main() -> Result<()> {
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Entity(u64);
+///
+/// let lua = Lua::new();
+/// let entities = lua.create_handle_registry::<Entity>();
+///
+/// let despawn = lua.create_function(|_, entity: ValidatedHandle<Entity>| Ok(entity.0))?;
+/// lua.globals().set("despawn", despawn)?;
+///
+/// let handle = entities.register(Entity(42));
+/// lua.globals().set("player", handle)?;
+/// assert_eq!(lua.load("despawn(player)").eval::<u64>()?, 42);
+///
+/// entities.invalidate(handle);
+/// assert!(lua.load("despawn(player)").exec().is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::create_handle_registry`]: crate::Lua::create_handle_registry
+/// [`resolve`]: #method.resolve
+pub struct HandleRegistry<T> {
+    slots: Arc<Mutex<Slots<T>>>,
+}
+
+impl<T> Clone for HandleRegistry<T> {
+    fn clone(&self) -> Self {
+        HandleRegistry {
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for HandleRegistry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HandleRegistry")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> HandleRegistry<T> {
+    pub(crate) fn new() -> Self {
+        HandleRegistry {
+            slots: Arc::new(Mutex::new(Slots {
+                slots: Vec::new(),
+                free: Vec::new(),
+                len: 0,
+            })),
+        }
+    }
+
+    /// Registers a new object and returns a handle to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of slots exceeds the number of available slot indices (half of the
+    /// pointer width bits). Slots of invalidated objects are reused, except slots that ran out of
+    /// generations: they are retired, so a new handle never equals an earlier (stale) one.
+    pub fn register(&self, value: T) -> Handle {
+        let mut slots = self.slots.lock().expect("handle registry is poisoned");
+        slots.len += 1;
+        if let Some(index) = slots.free.pop() {
+            let slot = &mut slots.slots[index];
+            slot.value = Some(value);
+            return Handle {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        let index = slots.slots.len();
+        assert!(index <= INDEX_MASK, "too many objects in handle registry");
+        slots.slots.push(Slot {
+            generation: 1,
+            value: Some(value),
+        });
+        Handle {
+            index,
+            generation: 1,
+        }
+    }
+
+    /// Returns `true` if the handle refers to a registered (live) object.
+    pub fn contains(&self, handle: Handle) -> bool {
+        let slots = self.slots.lock().expect("handle registry is poisoned");
+        slots.get(handle).is_some()
+    }
+
+    /// Returns a clone of the object the handle refers to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StaleHandle`] if the object was invalidated.
+    pub fn resolve(&self, handle: Handle) -> Result<T>
+    where
+        T: Clone,
+    {
+        self.with(handle, |value| value.clone())
+    }
+
+    /// Calls the function with a reference to the object the handle refers to.
+    ///
+    /// The registry is locked while the function is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StaleHandle`] if the object was invalidated.
+    pub fn with<R>(&self, handle: Handle, f: impl FnOnce(&T) -> R) -> Result<R> {
+        let slots = self.slots.lock().expect("handle registry is poisoned");
+        slots.get(handle).map(f).ok_or(Error::StaleHandle)
+    }
+
+    /// Invalidates the handle and returns the object it referred to.
+    ///
+    /// All copies of the handle (including those held by Lua) become stale.
+    /// Returns `None` if the handle is already stale.
+    pub fn invalidate(&self, handle: Handle) -> Option<T> {
+        let mut slots = self.slots.lock().expect("handle registry is poisoned");
+        slots.get(handle)?;
+        let slot = &mut slots.slots[handle.index];
+        let value = slot.invalidate();
+        if slot.is_reusable() {
+            slots.free.push(handle.index);
+        }
+        slots.len -= 1;
+        value
+    }
+
+    /// Invalidates all handles in the registry.
+    pub fn clear(&self) {
+        let mut slots = self.slots.lock().expect("handle registry is poisoned");
+        let Slots { slots, free, len } = &mut *slots;
+        for (index, slot) in slots.iter_mut().enumerate() {
+            if slot.invalidate().is_some() && slot.is_reusable() {
+                free.push(index);
+            }
+        }
+        *len = 0;
+    }
+
+    /// Returns the number of registered (live) objects.
+    pub fn len(&self) -> usize {
+        self.slots.lock().expect("handle registry is poisoned").len
+    }
+
+    /// Returns `true` if the registry has no registered objects.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A [`Handle`] that was checked against the [`HandleRegistry<T>`] of the Lua instance.
+///
+/// Implements [`FromLua`], so it can be used as a Rust callback argument. The conversion returns
+/// [`Error::StaleHandle`] if the handle is stale, or if no registry for `T` was created using
+/// [`Lua::create_handle_registry`].
+///
+/// Holds a clone of the object the handle referred to at the time of conversion.
+///
+/// [`HandleRegistry<T>`]: crate::HandleRegistry
+/// [`Lua::create_handle_registry`]: crate::Lua::create_handle_registry
+#[derive(Clone, Debug)]
+pub struct ValidatedHandle<T> {
+    handle: Handle,
+    value: T,
+}
+
+impl<T> ValidatedHandle<T> {
+    /// Returns the underlying handle.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Consumes the `ValidatedHandle`, returning the object.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for ValidatedHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'lua, T: Clone + 'static> FromLua<'lua> for ValidatedHandle<T> {
+    fn from_lua(value: Value<'lua>, lua: &'lua Lua) -> Result<Self> {
+        let handle = Handle::from_lua(value, lua)?;
+        let registry = lua
+            .app_data_ref::<HandleRegistry<T>>()
+            .ok_or(Error::StaleHandle)?;
+        let value = registry.resolve(handle)?;
+        Ok(ValidatedHandle { handle, value })
+    }
+}
@@ -0,0 +1,235 @@
+use std::string::String as StdString;
+
+use crate::error::Result;
+use crate::lua::Lua;
+use crate::table::Table;
+use crate::value::{IntoLua, Value};
+
+/// Description of the host application and its capabilities, visible to Lua scripts.
+///
+/// Installed with [`Lua::install_host_info`] as a read-only global table (`_HOST` by default).
+/// Besides the values provided by the host, the table always contains the mlua version,
+/// the Lua engine kind and version, and the list of enabled mlua features.
+///
+/// # Example
+///
+/// ```
+/// # use mlua::{HostInfo, Lua, Result};
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// lua.install_host_info(
+///     HostInfo::new("my-app", "1.2.0")
+///         .sandbox_profile("untrusted")
+///         .extra("http", true),
+/// )?;
+/// lua.load(r#"
+///     assert(_HOST.name == "my-app")
+///     if _HOST.extra.http then
+///         -- use the http API
+///     end
+/// "#).exec()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::install_host_info`]: crate::Lua::install_host_info
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct HostInfo {
+    /// Name of the host application.
+    pub name: StdString,
+
+    /// Version of the host application.
+    pub version: StdString,
+
+    /// Name of the sandbox profile the scripts are running under (if any).
+    ///
+    /// Default: **None**
+    pub sandbox_profile: Option<StdString>,
+
+    /// Additional host-defined key-values (eg. capability flags).
+    ///
+    /// Available to scripts in the `extra` subtable.
+    pub extra: Vec<(StdString, HostInfoValue)>,
+
+    /// Name of the global variable the table is installed to.
+    ///
+    /// Default: **`_HOST`**
+    pub global_name: StdString,
+}
+
+/// A value stored in [`HostInfo::extra`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum HostInfoValue {
+    /// A boolean value.
+    Boolean(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A floating point value.
+    Number(f64),
+    /// A string value.
+    String(StdString),
+}
+
+impl From<bool> for HostInfoValue {
+    fn from(b: bool) -> Self {
+        HostInfoValue::Boolean(b)
+    }
+}
+
+impl From<i64> for HostInfoValue {
+    fn from(i: i64) -> Self {
+        HostInfoValue::Integer(i)
+    }
+}
+
+impl From<f64> for HostInfoValue {
+    fn from(n: f64) -> Self {
+        HostInfoValue::Number(n)
+    }
+}
+
+impl From<&str> for HostInfoValue {
+    fn from(s: &str) -> Self {
+        HostInfoValue::String(s.to_owned())
+    }
+}
+
+impl From<StdString> for HostInfoValue {
+    fn from(s: StdString) -> Self {
+        HostInfoValue::String(s)
+    }
+}
+
+impl<'lua> IntoLua<'lua> for &HostInfoValue {
+    fn into_lua(self, lua: &'lua Lua) -> Result<Value<'lua>> {
+        match self {
+            HostInfoValue::Boolean(b) => Ok(Value::Boolean(*b)),
+            HostInfoValue::Integer(i) => i.into_lua(lua),
+            HostInfoValue::Number(n) => n.into_lua(lua),
+            HostInfoValue::String(s) => s.as_str().into_lua(lua),
+        }
+    }
+}
+
+impl HostInfo {
+    /// Returns a new instance of `HostInfo` for the host with the given name and version.
+    pub fn new(name: impl Into<StdString>, version: impl Into<StdString>) -> Self {
+        HostInfo {
+            name: name.into(),
+            version: version.into(),
+            sandbox_profile: None,
+            extra: Vec::new(),
+            global_name: "_HOST".to_string(),
+        }
+    }
+
+    /// Sets [`sandbox_profile`] option.
+    ///
+    /// [`sandbox_profile`]: #structfield.sandbox_profile
+    #[must_use]
+    pub fn sandbox_profile(mut self, name: impl Into<StdString>) -> Self {
+        self.sandbox_profile = Some(name.into());
+        self
+    }
+
+    /// Adds a key-value pair to [`extra`] values.
+    ///
+    /// [`extra`]: #structfield.extra
+    #[must_use]
+    pub fn extra(mut self, key: impl Into<StdString>, value: impl Into<HostInfoValue>) -> Self {
+        self.extra.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets [`global_name`] option.
+    ///
+    /// [`global_name`]: #structfield.global_name
+    #[must_use]
+    pub fn global_name(mut self, name: impl Into<StdString>) -> Self {
+        self.global_name = name.into();
+        self
+    }
+
+    /// Returns the mlua version.
+    pub const fn mlua_version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// Returns the kind of the Lua engine mlua is built with (eg. `lua54` or `luau`).
+    pub const fn engine(&self) -> &'static str {
+        #[cfg(feature = "lua54")]
+        return "lua54";
+        #[cfg(feature = "lua53")]
+        return "lua53";
+        #[cfg(feature = "lua52")]
+        return "lua52";
+        #[cfg(feature = "lua51")]
+        return "lua51";
+        #[cfg(feature = "luajit")]
+        return "luajit";
+        #[cfg(feature = "luau")]
+        return "luau";
+    }
+
+    /// Returns the human-readable version of the Lua engine (eg. `Lua 5.4`).
+    pub const fn engine_version(&self) -> &'static str {
+        #[cfg(feature = "lua54")]
+        return "Lua 5.4";
+        #[cfg(feature = "lua53")]
+        return "Lua 5.3";
+        #[cfg(feature = "lua52")]
+        return "Lua 5.2";
+        #[cfg(feature = "lua51")]
+        return "Lua 5.1";
+        #[cfg(feature = "luajit")]
+        return "LuaJIT 2.1";
+        #[cfg(feature = "luau")]
+        return "Luau";
+    }
+
+    /// Returns the list of enabled mlua features.
+    pub fn features(&self) -> Vec<&'static str> {
+        let mut features = Vec::new();
+        if cfg!(feature = "async") {
+            features.push("async");
+        }
+        if cfg!(feature = "send") {
+            features.push("send");
+        }
+        if cfg!(feature = "serialize") {
+            features.push("serialize");
+        }
+        if cfg!(feature = "macros") {
+            features.push("macros");
+        }
+        if cfg!(feature = "unstable") {
+            features.push("unstable");
+        }
+        features
+    }
+
+    // Creates a read-only table with the host info
+    pub(crate) fn create_table<'lua>(&self, lua: &'lua Lua) -> Result<Table<'lua>> {
+        let features = lua.create_table()?;
+        for feature in self.features() {
+            features.raw_set(feature, true)?;
+        }
+
+        let extra = lua.create_table()?;
+        for (key, value) in &self.extra {
+            extra.raw_set(key.as_str(), value)?;
+        }
+
+        let info = lua.create_table()?;
+        info.raw_set("name", self.name.as_str())?;
+        info.raw_set("version", self.version.as_str())?;
+        info.raw_set("mlua_version", self.mlua_version())?;
+        info.raw_set("engine", self.engine())?;
+        info.raw_set("engine_version", self.engine_version())?;
+        info.raw_set("features", features.read_only_proxy()?)?;
+        info.raw_set("sandbox", self.sandbox_profile.as_deref())?;
+        info.raw_set("extra", extra.read_only_proxy()?)?;
+        info.read_only_proxy()
+    }
+}
@@ -86,14 +86,22 @@ mod conversion;
 mod error;
 mod ffi;
 mod function;
+mod handle;
 mod hook;
+mod host_info;
 mod lua;
+mod lua_enum;
+#[cfg(feature = "luajit")]
+mod luajit;
 #[cfg(feature = "luau")]
 mod luau;
 mod multi;
+mod promise;
+mod report;
 mod scope;
 mod stdlib;
 mod string;
+mod string_builder;
 mod table;
 mod thread;
 mod types;
@@ -102,55 +110,86 @@ mod userdata_impl;
 mod util;
 mod value;
 
+pub mod bytecode;
+pub mod math;
 pub mod prelude;
 
 pub use crate::{ffi::lua_CFunction, ffi::lua_State};
 
-pub use crate::chunk::{AsChunk, Chunk, ChunkMode};
-pub use crate::error::{Error, ExternalError, ExternalResult, Result};
+pub use crate::chunk::{AsChunk, Chunk, ChunkEvent, ChunkMode};
+pub use crate::error::{Error, ExternalError, ExternalResult, Result, SyntaxErrorInfo};
 pub use crate::function::{Function, FunctionInfo};
+pub use crate::handle::{Handle, HandleRegistry, ValidatedHandle};
 pub use crate::hook::{Debug, DebugEvent, DebugNames, DebugSource, DebugStack};
+pub use crate::host_info::{HostInfo, HostInfoValue};
 pub use crate::lua::{GCMode, Lua, LuaOptions};
-pub use crate::multi::Variadic;
+pub use crate::lua_enum::{EnumLua, EnumValue};
+pub use crate::math::{ArithOp, NumericModel};
+pub use crate::multi::{Defaulted, Variadic};
+pub use crate::promise::PromiseResolver;
+pub use crate::report::{ConversionIssue, ConversionReport, FromLuaReport};
 pub use crate::scope::Scope;
 pub use crate::stdlib::StdLib;
-pub use crate::string::String;
-pub use crate::table::{Table, TableExt, TablePairs, TableSequence};
-pub use crate::thread::{Thread, ThreadStatus};
+pub use crate::string::{InternedString, String};
+pub use crate::table::{
+    CloneOptions, EqualsOptions, Table, TableExt, TableKeys, TableMetaPairs, TablePairs,
+    TableSequence, TableValues, WeakMode,
+};
+pub use crate::thread::{AnchorId, Thread, ThreadStatus};
 pub use crate::types::{Integer, LightUserData, Number, RegistryKey};
 pub use crate::userdata::{
-    AnyUserData, MetaMethod, UserData, UserDataFields, UserDataMetatable, UserDataMethods,
+    AnyUserData, IndexFallbackPriority, MetaMethod, UserData, UserDataFields, UserDataInfo,
+    UserDataMetatable, UserDataMethods,
 };
 pub use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, MultiValue, Nil, Value};
 
 #[cfg(not(feature = "luau"))]
 pub use crate::hook::HookTriggers;
 
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+pub use crate::lua::OomPolicy;
+
 #[cfg(any(feature = "luau", doc))]
 #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
 pub use crate::{chunk::Compiler, function::CoverageInfo, types::VmState};
 
+#[cfg(feature = "luajit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "luajit")))]
+pub use crate::luajit::FfiPolicy;
+
 #[cfg(feature = "async")]
-pub use crate::thread::AsyncThread;
+pub use crate::thread::{AsyncThread, AsyncThreadStream};
+
+#[cfg(feature = "send")]
+pub use crate::userdata::SendOwnedAnyUserData;
 
 #[cfg(feature = "serialize")]
 #[doc(inline)]
 pub use crate::serde::{
     de::Options as DeserializeOptions, ser::Options as SerializeOptions, LuaSerdeExt,
+    UnsupportedPolicy,
 };
 
 #[cfg(feature = "serialize")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serialize")))]
 pub mod serde;
 
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 #[cfg(any(feature = "mlua_derive"))]
 #[allow(unused_imports)]
 #[macro_use]
 extern crate mlua_derive;
 
 // Unstable features
-#[cfg(all(feature = "unstable", not(feature = "send")))]
-pub use crate::{function::OwnedFunction, table::OwnedTable};
+#[cfg(feature = "unstable")]
+pub use crate::{
+    function::OwnedFunction,
+    string::OwnedString,
+    table::{OwnedTable, OwnedTablePairs},
+};
 
 /// Create a type that implements [`AsChunk`] and can capture Rust variables.
 ///
@@ -208,6 +247,39 @@ pub use crate::{function::OwnedFunction, table::OwnedTable};
 #[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
 pub use mlua_derive::chunk;
 
+/// Derives [`EnumLua`], [`FromLua`] and [`IntoLua`] for an enum with unit variants.
+///
+/// Variants are exposed by their names and numbered from zero (or from the explicit
+/// discriminant). The Lua value can be overridden using `#[lua(value = 3)]` or
+/// `#[lua(value = "red")]` attribute, and the name using `#[lua(name = "...")]` attribute.
+///
+/// ```
+/// use mlua::{Lua, LuaEnum, Result};
+///
+/// #[derive(Debug, PartialEq, LuaEnum)]
+/// enum Color {
+///     RED,
+///     GREEN,
+///     #[lua(value = 10)]
+///     BLUE,
+/// }
+///
+/// fn main() -> Result<()> {
+///     let lua = Lua::new();
+///     lua.globals().set("Color", lua.create_enum_table::<Color>()?)?;
+///     assert_eq!(lua.load("Color.BLUE").eval::<Color>()?, Color::BLUE);
+///     assert_eq!(lua.load("'GREEN'").eval::<Color>()?, Color::GREEN);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`EnumLua`]: crate::EnumLua
+/// [`FromLua`]: crate::FromLua
+/// [`IntoLua`]: crate::IntoLua
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use mlua_derive::LuaEnum;
+
 /// Registers Lua module entrypoint.
 ///
 /// You can register multiple entrypoints as required.
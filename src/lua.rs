@@ -1,6 +1,6 @@
 use std::any::{Any, TypeId};
-use std::cell::{Ref, RefCell, RefMut, UnsafeCell};
-use std::collections::HashMap;
+use std::cell::{Cell, Ref, RefCell, RefMut, UnsafeCell};
+use std::collections::{HashMap, VecDeque};
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::marker::PhantomData;
@@ -9,33 +9,48 @@ use std::ops::Deref;
 use std::os::raw::{c_char, c_int, c_void};
 use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe, Location};
 use std::ptr::NonNull;
+use std::string::String as StdString;
 use std::sync::atomic::{AtomicPtr, Ordering};
 use std::sync::{Arc, Mutex};
-use std::{mem, ptr, str};
+use std::{io, mem, ptr, str};
 
 use rustc_hash::FxHashMap;
 
-use crate::chunk::{AsChunk, Chunk, ChunkMode};
+use crate::chunk::{AsChunk, Chunk, ChunkEvent, ChunkMode};
 use crate::error::{Error, Result};
 use crate::ffi;
 use crate::function::Function;
+use crate::handle::HandleRegistry;
 use crate::hook::Debug;
+use crate::host_info::HostInfo;
+use crate::lua_enum::{self, EnumLua};
+use crate::math::{ArithOp, NumericModel};
+use crate::promise::{Promise, PromiseResolver};
+use crate::report::{ConversionIssue, ConversionReport, FromLuaReport};
 use crate::scope::Scope;
 use crate::stdlib::StdLib;
-use crate::string::String;
-use crate::table::Table;
+use crate::string::{InternedString, String};
+use crate::string_builder::StringBuilder;
+use crate::table::{Table, WeakMode};
 use crate::thread::Thread;
 use crate::types::{
-    Callback, CallbackUpvalue, DestructedUserdata, Integer, LightUserData, LuaRef, MaybeSend,
-    Number, RegistryKey,
+    Callback, CallbackUpvalue, ChunkObserverCallback, DestructedUserdata, ErrorTranslatorCallback,
+    Integer, LightUserData, LuaRef, MaybeSend, Number, RegistryKey,
+};
+use crate::userdata::{
+    AnyUserData, MetaMethod, UserData, UserDataAnyFns, UserDataCell, UserDataInfo,
+};
+use crate::userdata_impl::{
+    collect_userdata_info, StaticUserDataFields, StaticUserDataMethods, UserDataClassProxy,
+    UserDataProxy,
 };
-use crate::userdata::{AnyUserData, MetaMethod, UserData, UserDataCell};
-use crate::userdata_impl::{StaticUserDataFields, StaticUserDataMethods, UserDataProxy};
 use crate::util::{
-    self, assert_stack, callback_error, check_stack, get_destructed_userdata_metatable,
-    get_gc_metatable, get_gc_userdata, get_main_state, get_userdata, init_error_registry,
-    init_gc_metatable, init_userdata_metatable, pop_error, push_gc_userdata, push_string,
-    push_table, rawset_field, safe_pcall, safe_xpcall, StackGuard, WrappedFailure,
+    self, assert_stack, callback_error, check_call_args, check_stack,
+    get_destructed_userdata_metatable, get_gc_metatable, get_gc_userdata, get_main_state,
+    get_userdata, init_error_registry, init_gc_metatable, init_userdata_metatable, pop_error,
+    ptr_to_cstr_bytes, push_gc_userdata, push_string, push_string_from_reader, push_table,
+    push_userdata_with_bytes, rawset_field, rawset_field_default, safe_pcall, safe_xpcall,
+    short_type_name, StackGuard, WrappedFailure,
 };
 use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, MultiValue, Nil, Value};
 
@@ -45,13 +60,21 @@ use crate::util::push_userdata;
 use crate::{types::WarnCallback, userdata::USER_VALUE_MAXSLOT, util::push_userdata_uv};
 
 #[cfg(not(feature = "luau"))]
-use crate::{hook::HookTriggers, types::HookCallback};
+use {
+    crate::hook::HookTriggers,
+    crate::types::{DropOffloaderCallback, HookCallback},
+    crate::util::{take_userdata, userdata_destructor_chained, userdata_destructor_chained_with},
+    std::sync::Condvar,
+};
 
 #[cfg(feature = "luau")]
 use crate::types::InterruptCallback;
 #[cfg(any(feature = "luau", doc))]
 use crate::{chunk::Compiler, types::VmState};
 
+#[cfg(feature = "serialize")]
+use rustc_hash::FxHashSet;
+
 #[cfg(feature = "async")]
 use {
     crate::types::{AsyncCallback, AsyncCallbackUpvalue, AsyncPollUpvalue},
@@ -76,6 +99,9 @@ pub struct LuaInner {
     state: AtomicPtr<ffi::lua_State>,
     main_state: *mut ffi::lua_State,
     extra: Arc<UnsafeCell<ExtraData>>,
+    // Serializes access from handles that can be sent to other threads
+    #[cfg(feature = "send")]
+    lock: crate::types::ReentrantLock,
 }
 
 // Data associated with the Lua.
@@ -84,7 +110,16 @@ pub(crate) struct ExtraData {
     inner: Option<ManuallyDrop<Arc<LuaInner>>>,
 
     registered_userdata: FxHashMap<TypeId, c_int>,
+    // Minimal metatables of serializable types that do not implement `UserData`
+    #[cfg(feature = "serialize")]
+    registered_any_userdata: FxHashMap<TypeId, c_int>,
     registered_userdata_mt: FxHashMap<*const c_void, Option<TypeId>>,
+    registered_userdata_info: FxHashMap<TypeId, UserDataInfo>,
+    // Type ids of userdata with `Cell<T>` storage (see `Lua::create_copy_userdata`)
+    #[cfg(feature = "serialize")]
+    copy_userdata: FxHashSet<TypeId>,
+    // Type-erased accessors of registered userdata values (see `AnyUserData::take_any`)
+    registered_userdata_any: FxHashMap<TypeId, UserDataAnyFns>,
 
     // When Lua instance dropped, setting `None` would prevent collecting `RegistryKey`s
     registry_unref_list: Arc<Mutex<Option<Vec<c_int>>>>,
@@ -117,6 +152,8 @@ pub(crate) struct ExtraData {
     // Index of `Option<Waker>` userdata on the ref thread
     #[cfg(feature = "async")]
     ref_waker_idx: c_int,
+    // Index of the globals table on the ref thread
+    ref_globals_idx: c_int,
 
     #[cfg(not(feature = "luau"))]
     hook_callback: Option<HookCallback>,
@@ -125,16 +162,238 @@ pub(crate) struct ExtraData {
     #[cfg(feature = "luau")]
     interrupt_callback: Option<InterruptCallback>,
 
+    #[cfg(not(feature = "luau"))]
+    drop_offloader: Option<DropOffloaderCallback>,
+    #[cfg(not(feature = "luau"))]
+    offloaded_drops: Arc<OffloadedDrops>,
+
+    // Chunk observer and its verbosity flag
+    chunk_observer: Option<(ChunkObserverCallback, bool)>,
+    // Events waiting to be delivered to the chunk observer
+    chunk_events: VecDeque<ChunkEvent>,
+    chunk_observer_active: bool,
+
+    host_info: Option<HostInfo>,
+
+    // Rewrites error messages seen by scripts
+    error_translator: Option<ErrorTranslatorCallback>,
+
     #[cfg(feature = "luau")]
     sandboxed: bool,
     #[cfg(feature = "luau")]
     compiler: Option<Compiler>,
 }
 
+// Number of userdata values handed to the drop offloader that are not dropped yet
+#[cfg(not(feature = "luau"))]
+#[derive(Default)]
+struct OffloadedDrops {
+    pending: Mutex<usize>,
+    done: Condvar,
+}
+
+#[cfg(not(feature = "luau"))]
+impl OffloadedDrops {
+    fn start(self: &Arc<Self>) -> OffloadedDropGuard {
+        *mlua_expect!(self.pending.lock(), "offloaded drops lock poisoned") += 1;
+        OffloadedDropGuard(Arc::clone(self))
+    }
+
+    // Blocks until all offloaded drops are finished
+    fn wait(&self) {
+        let mut pending = mlua_expect!(self.pending.lock(), "offloaded drops lock poisoned");
+        while *pending > 0 {
+            pending = mlua_expect!(self.done.wait(pending), "offloaded drops lock poisoned");
+        }
+    }
+}
+
+// Marks offloaded drop as finished when dropped (even if the job was discarded without running)
+#[cfg(not(feature = "luau"))]
+struct OffloadedDropGuard(Arc<OffloadedDrops>);
+
+#[cfg(not(feature = "luau"))]
+impl Drop for OffloadedDropGuard {
+    fn drop(&mut self) {
+        let mut pending = mlua_expect!(self.0.pending.lock(), "offloaded drops lock poisoned");
+        *pending -= 1;
+        if *pending == 0 {
+            self.0.done.notify_all();
+        }
+    }
+}
+
 #[derive(Default)]
 struct MemoryInfo {
     used_memory: isize,
     memory_limit: isize,
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    oom_policy: OomPolicy,
+    // Last allocation request rejected due to the memory limit
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    failed_request: Option<(usize, usize)>,
+}
+
+impl MemoryInfo {
+    // Checks that the allocation request fits to the memory limit (if set).
+    //
+    // This function must never call into Lua, as it's called from inside the allocator.
+    #[inline]
+    fn check_limit(&mut self, _ptr: *mut c_void, _nsize: usize, new_used_memory: isize) -> bool {
+        if self.memory_limit <= 0 {
+            return true;
+        }
+
+        #[cfg(not(any(feature = "lua54", feature = "lua53", feature = "lua52")))]
+        return new_used_memory <= self.memory_limit;
+
+        #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+        {
+            // When allocation fails, Lua runs an emergency full GC cycle and then repeats
+            // exactly the same request.
+            let request = (_ptr as usize, _nsize);
+            let is_retry = self.failed_request.take() == Some(request);
+            let fail_retry = is_retry && matches!(self.oom_policy, OomPolicy::Fail);
+            if new_used_memory <= self.memory_limit && !fail_retry {
+                return true;
+            }
+            if !is_retry {
+                self.failed_request = Some(request);
+                return false;
+            }
+
+            match self.oom_policy {
+                OomPolicy::Fail | OomPolicy::CollectAndRetryOnce => false,
+                OomPolicy::Callback(ref mut callback) => {
+                    let used_memory = self.used_memory as usize;
+                    let required_memory = new_used_memory as usize;
+                    let new_limit =
+                        catch_unwind(AssertUnwindSafe(|| callback(used_memory, required_memory)));
+                    match new_limit {
+                        Ok(Some(new_limit)) if new_limit >= required_memory => {
+                            self.memory_limit = new_limit as isize;
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Controls what happens when an allocation inside the Lua state exceeds the memory limit.
+///
+/// Set using [`Lua::on_out_of_memory`].
+///
+/// Requires `feature = "lua54/lua53/lua52"`
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+#[derive(Default)]
+pub enum OomPolicy {
+    /// Fail the allocation immediately, raising `Error::MemoryError`.
+    ///
+    /// Lua does not get a chance to reclaim memory by running an emergency garbage collection.
+    Fail,
+    /// Let Lua run a full (emergency) garbage collection cycle and then retry the allocation once.
+    ///
+    /// This is the default policy that matches how Lua handles allocation failures.
+    #[default]
+    CollectAndRetryOnce,
+    /// Like [`CollectAndRetryOnce`], but if the retried allocation still exceeds the limit,
+    /// calls the function with the used memory and the amount of memory required to satisfy
+    /// the request (in bytes).
+    ///
+    /// If the function returns a new limit that is enough to satisfy the request, the limit is
+    /// raised and the allocation succeeds. Otherwise `Error::MemoryError` is raised.
+    /// The limit can be restored later using [`Lua::set_memory_limit`].
+    ///
+    /// The function is called from inside the Lua allocator, so it must not interact with the Lua
+    /// state. Panics are caught and treated as a refusal to raise the limit.
+    ///
+    /// [`CollectAndRetryOnce`]: #variant.CollectAndRetryOnce
+    #[cfg(not(feature = "send"))]
+    Callback(Box<dyn FnMut(usize, usize) -> Option<usize>>),
+    #[cfg(feature = "send")]
+    Callback(Box<dyn FnMut(usize, usize) -> Option<usize> + Send>),
+}
+
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+impl fmt::Debug for OomPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OomPolicy::Fail => write!(f, "Fail"),
+            OomPolicy::CollectAndRetryOnce => write!(f, "CollectAndRetryOnce"),
+            OomPolicy::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+// A weak reference to a Lua instance
+#[cfg(feature = "send")]
+#[derive(Clone)]
+pub(crate) struct WeakLua(std::sync::Weak<LuaInner>);
+
+#[cfg(feature = "send")]
+unsafe impl Send for WeakLua {}
+
+#[cfg(feature = "send")]
+impl WeakLua {
+    // Returns the Lua instance if it is still alive
+    pub(crate) fn upgrade(&self) -> Option<Lua> {
+        self.0.upgrade().map(Lua)
+    }
+}
+
+// Memory allocated outside of the Lua allocator (eg. by Rust objects owned by Lua), that is
+// accounted against the Lua memory usage and limit. The memory is released on drop.
+pub(crate) struct ExternalMemory {
+    extra: std::sync::Weak<UnsafeCell<ExtraData>>,
+    size: usize,
+}
+
+// `ExternalMemory` is owned by Lua objects and only used from the thread that owns the Lua state
+#[cfg(feature = "send")]
+unsafe impl Send for ExternalMemory {}
+
+impl ExternalMemory {
+    // Accounts `additional` bytes, returning `Error::MemoryError` if the memory limit is exceeded.
+    pub(crate) fn grow(&mut self, additional: usize) -> Result<()> {
+        if let Some(mut mem_info) = self.mem_info() {
+            let mem_info = unsafe { mem_info.as_mut() };
+            let new_used_memory = mem_info.used_memory.saturating_add(additional as isize);
+            if mem_info.memory_limit > 0 && new_used_memory > mem_info.memory_limit {
+                return Err(Error::MemoryError("not enough memory".to_string()));
+            }
+            mem_info.used_memory = new_used_memory;
+        }
+        self.size += additional;
+        Ok(())
+    }
+
+    // Releases `size` bytes
+    pub(crate) fn shrink(&mut self, size: usize) {
+        let size = size.min(self.size);
+        if let Some(mut mem_info) = self.mem_info() {
+            unsafe { mem_info.as_mut().used_memory -= size as isize };
+        }
+        self.size -= size;
+    }
+
+    #[inline]
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    fn mem_info(&self) -> Option<NonNull<MemoryInfo>> {
+        let extra = self.extra.upgrade()?;
+        unsafe { (*extra.get()).mem_info }
+    }
+}
+
+impl Drop for ExternalMemory {
+    fn drop(&mut self) {
+        self.shrink(self.size);
+    }
 }
 
 /// Mode of the Lua garbage collector (GC).
@@ -166,6 +425,10 @@ pub struct LuaOptions {
     /// If enabled, keeps [`pcall`]/[`xpcall`] unmodified.
     /// Panics are still automatically resumed if returned to the Rust side.
     ///
+    /// In both cases panics never unwind through Lua (C) frames. Panics that cannot be
+    /// propagated as Lua errors (eg. in userdata destructors called by the garbage collector)
+    /// abort the process.
+    ///
     /// Default: **true**
     ///
     /// [`pcall`]: https://www.lua.org/manual/5.4/manual.html#pdf-pcall
@@ -254,6 +517,10 @@ impl Drop for LuaInner {
                 ffi::lua_replace(extra.ref_thread, extra.ref_waker_idx);
                 extra.ref_free.push(extra.ref_waker_idx);
             }
+            // Destroy globals slot
+            ffi::lua_pushnil(extra.ref_thread);
+            ffi::lua_replace(extra.ref_thread, extra.ref_globals_idx);
+            extra.ref_free.push(extra.ref_globals_idx);
             #[cfg(feature = "luau")]
             {
                 (*ffi::lua_callbacks(self.state())).userdata = ptr::null_mut();
@@ -406,7 +673,7 @@ impl Lua {
                 mem_diff -= osize as isize;
             }
             let new_used_memory = mem_info.used_memory + mem_diff;
-            if mem_info.memory_limit > 0 && new_used_memory > mem_info.memory_limit {
+            if !mem_info.check_limit(ptr, nsize, new_used_memory) {
                 return ptr::null_mut();
             }
             mem_info.used_memory += mem_diff;
@@ -565,13 +832,28 @@ impl Lua {
             );
             ffi::lua_gettop(ref_thread)
         };
+
+        // Cache the globals table on the ref thread
+        let ref_globals_idx = {
+            #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+            ffi::lua_rawgeti(ref_thread, ffi::LUA_REGISTRYINDEX, ffi::LUA_RIDX_GLOBALS);
+            #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+            ffi::lua_xpush(main_state, ref_thread, ffi::LUA_GLOBALSINDEX);
+            ffi::lua_gettop(ref_thread)
+        };
         let ref_stack_top = ffi::lua_gettop(ref_thread);
 
         // Create ExtraData
         let extra = Arc::new(UnsafeCell::new(ExtraData {
             inner: None,
             registered_userdata: FxHashMap::default(),
+            #[cfg(feature = "serialize")]
+            registered_any_userdata: FxHashMap::default(),
             registered_userdata_mt: FxHashMap::default(),
+            registered_userdata_info: FxHashMap::default(),
+            #[cfg(feature = "serialize")]
+            copy_userdata: FxHashSet::default(),
+            registered_userdata_any: FxHashMap::default(),
             registry_unref_list: Arc::new(Mutex::new(Some(Vec::new()))),
             app_data: RefCell::new(HashMap::new()),
             safe: false,
@@ -589,12 +871,22 @@ impl Lua {
             wrapped_failure_mt_ptr,
             #[cfg(feature = "async")]
             ref_waker_idx,
+            ref_globals_idx,
             #[cfg(not(feature = "luau"))]
             hook_callback: None,
             #[cfg(feature = "lua54")]
             warn_callback: None,
             #[cfg(feature = "luau")]
             interrupt_callback: None,
+            #[cfg(not(feature = "luau"))]
+            drop_offloader: None,
+            #[cfg(not(feature = "luau"))]
+            offloaded_drops: Arc::new(OffloadedDrops::default()),
+            chunk_observer: None,
+            chunk_events: VecDeque::new(),
+            chunk_observer_active: false,
+            host_info: None,
+            error_translator: None,
             #[cfg(feature = "luau")]
             sandboxed: false,
             #[cfg(feature = "luau")]
@@ -639,6 +931,8 @@ impl Lua {
             state: AtomicPtr::new(state),
             main_state,
             extra: Arc::clone(&extra),
+            #[cfg(feature = "send")]
+            lock: Default::default(),
         });
 
         (*extra.get()).inner = Some(ManuallyDrop::new(Arc::clone(&inner)));
@@ -654,6 +948,7 @@ impl Lua {
     ///
     /// [`StdLib`]: crate::StdLib
     pub fn load_from_std_lib(&self, libs: StdLib) -> Result<()> {
+        let _guard = self.lock();
         #[cfg(not(feature = "luau"))]
         let is_safe = unsafe { (*self.extra.get()).safe };
 
@@ -704,6 +999,7 @@ impl Lua {
     where
         T: FromLua<'lua>,
     {
+        let _guard = self.lock();
         let state = self.state();
         let loaded = unsafe {
             let _sg = StackGuard::new(state);
@@ -737,6 +1033,7 @@ impl Lua {
     ///
     /// [`package.loaded`]: https://www.lua.org/manual/5.4/manual.html#pdf-package.loaded
     pub fn unload(&self, modname: &str) -> Result<()> {
+        let _guard = self.lock();
         let state = self.state();
         let loaded = unsafe {
             let _sg = StackGuard::new(state);
@@ -778,6 +1075,18 @@ impl Lua {
         *Box::from_raw(lua as *const Lua as *mut Lua)
     }
 
+    /// Closes the Lua state.
+    ///
+    /// This is the same as dropping the `Lua` instance, except that it also waits for all userdata
+    /// values handed to the drop offloader (see [`Lua::set_drop_offloader`]) to be dropped.
+    pub fn close(self) {
+        #[cfg(not(feature = "luau"))]
+        let offloaded_drops = unsafe { (*self.extra.get()).offloaded_drops.clone() };
+        drop(self);
+        #[cfg(not(feature = "luau"))]
+        offloaded_drops.wait();
+    }
+
     // Executes module entrypoint function, which returns only one Value.
     // The returned value then pushed onto the stack.
     #[doc(hidden)]
@@ -864,6 +1173,7 @@ impl Lua {
     #[cfg(any(feature = "luau", docsrs))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     pub fn sandbox(&self, enabled: bool) -> Result<()> {
+        let _guard = self.lock();
         unsafe {
             if (*self.extra.get()).sandboxed != enabled {
                 let state = self.main_state;
@@ -880,6 +1190,11 @@ impl Lua {
                     }
                 })?;
                 (*self.extra.get()).sandboxed = enabled;
+
+                // Refresh the cached globals table
+                let ref_thread = self.ref_thread();
+                ffi::lua_xpush(state, ref_thread, ffi::LUA_GLOBALSINDEX);
+                ffi::lua_replace(ref_thread, (*self.extra.get()).ref_globals_idx);
             }
             Ok(())
         }
@@ -924,6 +1239,7 @@ impl Lua {
     where
         F: 'static + MaybeSend + Fn(&Lua, Debug) -> Result<()>,
     {
+        let _guard = self.lock();
         unsafe extern "C" fn hook_proc(state: *mut ffi::lua_State, ar: *mut ffi::lua_Debug) {
             let lua = match Lua::try_from_ptr(state) {
                 Some(lua) => lua,
@@ -955,6 +1271,7 @@ impl Lua {
     #[cfg(not(feature = "luau"))]
     #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
     pub fn remove_hook(&self) {
+        let _guard = self.lock();
         unsafe {
             // If main_state is not available, then sethook wasn't called.
             let state = match get_main_state(self.main_state) {
@@ -1014,6 +1331,7 @@ impl Lua {
     where
         F: 'static + MaybeSend + Fn() -> Result<VmState>,
     {
+        let _guard = self.lock();
         unsafe extern "C" fn interrupt_proc(state: *mut ffi::lua_State, gc: c_int) {
             if gc >= 0 {
                 // We don't support GC interrupts since they cannot survive Lua exceptions
@@ -1052,6 +1370,7 @@ impl Lua {
     #[cfg(any(feature = "luau", docsrs))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     pub fn remove_interrupt(&self) {
+        let _guard = self.lock();
         unsafe {
             (*self.extra.get()).interrupt_callback = None;
             (*ffi::lua_callbacks(self.main_state)).interrupt = None;
@@ -1067,6 +1386,7 @@ impl Lua {
     where
         F: 'static + MaybeSend + Fn(&Lua, &CStr, bool) -> Result<()>,
     {
+        let _guard = self.lock();
         unsafe extern "C" fn warn_proc(ud: *mut c_void, msg: *const c_char, tocont: c_int) {
             let extra = ud as *mut ExtraData;
             let lua: &Lua = mem::transmute((*extra).inner.as_ref().unwrap());
@@ -1095,6 +1415,7 @@ impl Lua {
     #[cfg(feature = "lua54")]
     #[cfg_attr(docsrs, doc(cfg(feature = "lua54")))]
     pub fn remove_warning_function(&self) {
+        let _guard = self.lock();
         unsafe {
             (*self.extra.get()).warn_callback = None;
             ffi::lua_setwarnf(self.main_state, None, ptr::null_mut());
@@ -1109,6 +1430,7 @@ impl Lua {
     #[cfg(feature = "lua54")]
     #[cfg_attr(docsrs, doc(cfg(feature = "lua54")))]
     pub fn warning<S: Into<Vec<u8>>>(&self, msg: S, tocont: bool) -> Result<()> {
+        let _guard = self.lock();
         let msg = CString::new(msg).map_err(|err| Error::RuntimeError(err.to_string()))?;
         unsafe { ffi::lua_warning(self.state(), msg.as_ptr(), tocont as c_int) };
         Ok(())
@@ -1122,6 +1444,7 @@ impl Lua {
     ///
     /// [`Debug`]: crate::hook::Debug
     pub fn inspect_stack(&self, level: usize) -> Option<Debug> {
+        let _guard = self.lock();
         unsafe {
             let mut ar: ffi::lua_Debug = mem::zeroed();
             let level = level as c_int;
@@ -1137,8 +1460,26 @@ impl Lua {
         }
     }
 
+    /// Returns the maximum number of values that can be passed as function arguments
+    /// (or returned from a Rust function) in a single call.
+    ///
+    /// The limit depends on the Lua engine stack size: it's about 1M values for Lua 5.2-5.4,
+    /// 100K values for Luau and 8K values for Lua 5.1 and LuaJIT. Exceeding the limit returns
+    /// [`Error::TooManyArguments`] before any value is pushed to the stack.
+    ///
+    /// This is an upper bound: nested calls use the same stack, so the available space
+    /// can be smaller. Large amounts of data should be passed in a table instead
+    /// (eg. created using [`create_sequence_from`]).
+    ///
+    /// [`Error::TooManyArguments`]: crate::Error::TooManyArguments
+    /// [`create_sequence_from`]: #method.create_sequence_from
+    pub const fn max_call_args(&self) -> usize {
+        util::MAX_CALL_ARGS
+    }
+
     /// Returns the amount of memory (in bytes) currently used inside this Lua state.
     pub fn used_memory(&self) -> usize {
+        let _guard = self.lock();
         unsafe {
             match (*self.extra.get()).mem_info.map(|x| x.as_ref()) {
                 Some(mem_info) => mem_info.used_memory as usize,
@@ -1163,6 +1504,7 @@ impl Lua {
     /// Requires `feature = "lua54/lua53/lua52"`
     #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
     pub fn set_memory_limit(&self, memory_limit: usize) -> Result<usize> {
+        let _guard = self.lock();
         unsafe {
             match (*self.extra.get()).mem_info.map(|mut x| x.as_mut()) {
                 Some(mem_info) => {
@@ -1175,6 +1517,35 @@ impl Lua {
         }
     }
 
+    /// Sets the policy to apply when an allocation exceeds the memory limit.
+    ///
+    /// By default Lua runs an emergency garbage collection and then retries the failed allocation
+    /// once (see [`OomPolicy::CollectAndRetryOnce`]). The collection is performed by Lua itself
+    /// outside of the allocator, so the allocator is never re-entered.
+    ///
+    /// Once `Error::MemoryError` is returned, the Lua state remains consistent and can be used
+    /// further: it's safe to run the garbage collector, change the memory limit, drop handles,
+    /// read values and call functions again. Only the operation that triggered the error is
+    /// aborted and any partial results of it are discarded.
+    ///
+    /// Does not work on module mode where Lua state is managed externally.
+    ///
+    /// Requires `feature = "lua54/lua53/lua52"`
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    pub fn on_out_of_memory(&self, policy: OomPolicy) -> Result<()> {
+        let _guard = self.lock();
+        unsafe {
+            match (*self.extra.get()).mem_info.map(|mut x| x.as_mut()) {
+                Some(mem_info) => {
+                    mem_info.oom_policy = policy;
+                    mem_info.failed_request = None;
+                    Ok(())
+                }
+                None => Err(Error::MemoryLimitNotAvailable),
+            }
+        }
+    }
+
     /// Returns true if the garbage collector is currently running automatically.
     ///
     /// Requires `feature = "lua54/lua53/lua52/luau"`
@@ -1185,16 +1556,19 @@ impl Lua {
         feature = "luau"
     ))]
     pub fn gc_is_running(&self) -> bool {
+        let _guard = self.lock();
         unsafe { ffi::lua_gc(self.main_state, ffi::LUA_GCISRUNNING, 0) != 0 }
     }
 
     /// Stop the Lua GC from running
     pub fn gc_stop(&self) {
+        let _guard = self.lock();
         unsafe { ffi::lua_gc(self.main_state, ffi::LUA_GCSTOP, 0) };
     }
 
     /// Restarts the Lua GC if it is not running
     pub fn gc_restart(&self) {
+        let _guard = self.lock();
         unsafe { ffi::lua_gc(self.main_state, ffi::LUA_GCRESTART, 0) };
     }
 
@@ -1203,6 +1577,7 @@ impl Lua {
     /// It may be necessary to call this function twice to collect all currently unreachable
     /// objects. Once to finish the current gc cycle, and once to start and finish the next cycle.
     pub fn gc_collect(&self) -> Result<()> {
+        let _guard = self.lock();
         unsafe {
             check_stack(self.main_state, 2)?;
             protect_lua!(self.main_state, 0, 0, fn(state) ffi::lua_gc(state, ffi::LUA_GCCOLLECT, 0))
@@ -1221,6 +1596,7 @@ impl Lua {
     /// if `kbytes` is 0, then this is the same as calling `gc_step`. Returns true if this step has
     /// finished a collection cycle.
     pub fn gc_step_kbytes(&self, kbytes: c_int) -> Result<bool> {
+        let _guard = self.lock();
         unsafe {
             check_stack(self.main_state, 3)?;
             protect_lua!(self.main_state, 0, 0, |state| {
@@ -1238,6 +1614,7 @@ impl Lua {
     ///
     /// [documentation]: https://www.lua.org/manual/5.4/manual.html#2.5
     pub fn gc_set_pause(&self, pause: c_int) -> c_int {
+        let _guard = self.lock();
         unsafe {
             #[cfg(not(feature = "luau"))]
             return ffi::lua_gc(self.main_state, ffi::LUA_GCSETPAUSE, pause);
@@ -1253,6 +1630,7 @@ impl Lua {
     ///
     /// [documentation]: https://www.lua.org/manual/5.4/manual.html#2.5
     pub fn gc_set_step_multiplier(&self, step_multiplier: c_int) -> c_int {
+        let _guard = self.lock();
         unsafe { ffi::lua_gc(self.main_state, ffi::LUA_GCSETSTEPMUL, step_multiplier) }
     }
 
@@ -1263,6 +1641,7 @@ impl Lua {
     ///
     /// [documentation]: https://www.lua.org/manual/5.4/manual.html#2.5.1
     pub fn gc_inc(&self, pause: c_int, step_multiplier: c_int, step_size: c_int) -> GCMode {
+        let _guard = self.lock();
         let state = self.main_state;
 
         #[cfg(any(
@@ -1316,6 +1695,7 @@ impl Lua {
     #[cfg(any(feature = "lua54"))]
     #[cfg_attr(docsrs, doc(cfg(feature = "lua54")))]
     pub fn gc_gen(&self, minor_multiplier: c_int, major_multiplier: c_int) -> GCMode {
+        let _guard = self.lock();
         let state = self.main_state;
         let prev_mode =
             unsafe { ffi::lua_gc(state, ffi::LUA_GCGEN, minor_multiplier, major_multiplier) };
@@ -1326,6 +1706,109 @@ impl Lua {
         }
     }
 
+    /// Sets a function that will be used to drop userdata values off the Lua thread.
+    ///
+    /// When a userdata value of a type registered using [`Lua::register_offload_drop`] is
+    /// garbage collected, the value is moved into a boxed closure and handed to the `offloader`
+    /// instead of being dropped inline. The offloader can then run the closure on another thread
+    /// (e.g. a thread pool), so that expensive `Drop` implementations do not stall Lua execution.
+    ///
+    /// The offloader is called from inside the Lua garbage collector, so it must be cheap, must not
+    /// panic, and must eventually run (or drop) every closure it receives.
+    ///
+    /// # Ordering
+    ///
+    /// Offloaded values are dropped eventually, in no particular order and not necessarily before
+    /// the Lua state is dropped. Use [`Lua::close`] to wait for all pending offloaded drops.
+    ///
+    /// If no offloader is set, values are dropped inline as usual.
+    #[cfg(not(feature = "luau"))]
+    #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
+    pub fn set_drop_offloader<F>(&self, offloader: F)
+    where
+        F: 'static + MaybeSend + Fn(Box<dyn FnOnce() + Send>),
+    {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).drop_offloader = Some(Box::new(offloader)) };
+    }
+
+    /// Removes the drop offloader previously set by [`Lua::set_drop_offloader`].
+    ///
+    /// Values collected after this call are dropped inline.
+    #[cfg(not(feature = "luau"))]
+    #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
+    pub fn remove_drop_offloader(&self) {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).drop_offloader = None };
+    }
+
+    /// Registers UserData type `T` to be dropped using the drop offloader.
+    ///
+    /// See [`Lua::set_drop_offloader`] for details.
+    ///
+    /// This applies to all values of type `T`, including the ones already created.
+    #[cfg(not(feature = "luau"))]
+    #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
+    pub fn register_offload_drop<T: UserData + Send + 'static>(&self) -> Result<()> {
+        let _guard = self.lock();
+        // Expects the userdata on top of the stack and pops it
+        unsafe fn offload_drop<T: Send + 'static>(state: *mut ffi::lua_State) {
+            let value = take_userdata::<UserDataCell<T>>(state).into_inner();
+            let extra = extra_data(state);
+            util::abort_on_panic(|| {
+                match extra
+                    .as_ref()
+                    .and_then(|extra| extra.drop_offloader.as_ref())
+                {
+                    Some(offloader) => {
+                        let guard = (*extra).offloaded_drops.start();
+                        offloader(Box::new(move || {
+                            drop(value);
+                            drop(guard);
+                        }));
+                    }
+                    None => drop(value),
+                }
+            });
+        }
+
+        unsafe extern "C" fn offload_destructor<T: Send + 'static>(
+            state: *mut ffi::lua_State,
+        ) -> c_int {
+            offload_drop::<T>(state);
+            0
+        }
+
+        // Calls user-defined `__gc` metamethod (the first upvalue) before offloading the drop
+        unsafe extern "C" fn offload_destructor_chained<T: Send + 'static>(
+            state: *mut ffi::lua_State,
+        ) -> c_int {
+            userdata_destructor_chained_with(state, offload_drop::<T>)
+        }
+
+        let state = self.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            self.push_userdata_metatable::<T>()?;
+            // Chained destructor keeps the user-defined `__gc` as upvalue
+            push_string(state, b"__gc", true)?;
+            ffi::lua_rawget(state, -2);
+            if ffi::lua_getupvalue(state, -1, 1).is_null() {
+                ffi::lua_pop(state, 1);
+                ffi::lua_pushcfunction(state, offload_destructor::<T>);
+            } else {
+                ffi::lua_remove(state, -2);
+                protect_lua!(state, 1, 1, |state| {
+                    ffi::lua_pushcclosure(state, offload_destructor_chained::<T>, 1);
+                })?;
+            }
+            rawset_field(state, -2, "__gc")?;
+        }
+        Ok(())
+    }
+
     /// Sets a default Luau compiler (with custom options).
     ///
     /// This compiler will be used by default to load all Lua chunks
@@ -1337,9 +1820,138 @@ impl Lua {
     #[cfg(any(feature = "luau", doc))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     pub fn set_compiler(&self, compiler: Compiler) {
+        let _guard = self.lock();
         unsafe { (*self.extra.get()).compiler = Some(compiler) };
     }
 
+    /// Sets an observer function that will be notified about [`Chunk`] lifecycle events.
+    ///
+    /// The observer receives a [`ChunkEvent`] every time a chunk is loaded or executed through
+    /// the [`Chunk`] API, which makes it possible to track which scripts are live (e.g. to
+    /// implement hot reloading). If `verbose` is `true`, [`ChunkEvent::FunctionCreated`] events
+    /// are emitted as well (for the main chunk function only).
+    ///
+    /// The observer must not re-enter the load in progress. Any events triggered while the
+    /// observer is running are queued and delivered after it returns, in order.
+    ///
+    /// Only one observer can be set at a time; setting a new one replaces the previous.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mlua::{ChunkEvent, Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    /// lua.set_chunk_observer(false, |event| {
+    ///     if let ChunkEvent::Executed { name, duration, ok } = event {
+    ///         println!("chunk `{name}` finished in {duration:?} (ok: {ok})");
+    ///     }
+    /// });
+    /// lua.load("x = 1").set_name("init").exec()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_chunk_observer<F>(&self, verbose: bool, callback: F)
+    where
+        F: 'static + MaybeSend + Fn(ChunkEvent),
+    {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).chunk_observer = Some((Arc::new(callback), verbose)) };
+    }
+
+    /// Removes the chunk observer previously set by [`Lua::set_chunk_observer`].
+    ///
+    /// This function has no effect if an observer was not previously set.
+    pub fn remove_chunk_observer(&self) {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).chunk_observer = None };
+    }
+
+    /// Sets a function that translates error messages seen by Lua scripts.
+    ///
+    /// The translator is consulted every time an [`Error`] (for example returned by a Rust
+    /// callback) is converted to a string on the Lua side, eg. by `tostring(err)` after `pcall`.
+    /// If it returns `Some(message)`, the script sees `message` instead of the default error
+    /// formatting. Returning `None` keeps the default formatting.
+    ///
+    /// This allows to rewrite or localize script-facing messages, while the Rust side still
+    /// receives the original `Error` with full details. Errors returned by callbacks are passed
+    /// to the translator as [`Error::CallbackError`], the original error is in its `cause`.
+    ///
+    /// The translator receives only the error and cannot call back into Lua.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mlua::{Error, Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    /// lua.set_error_translator(|err| match err {
+    ///     Error::CallbackError { cause, .. } => match cause.as_ref() {
+    ///         Error::RuntimeError(msg) if msg == "not found" => Some("introuvable".to_string()),
+    ///         _ => None,
+    ///     },
+    ///     _ => None,
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_error_translator<F>(&self, translator: F)
+    where
+        F: 'static + MaybeSend + Fn(&Error) -> Option<StdString>,
+    {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).error_translator = Some(Arc::new(translator)) };
+    }
+
+    /// Removes the error translator previously set by [`Lua::set_error_translator`].
+    ///
+    /// This function has no effect if a translator was not previously set.
+    pub fn remove_error_translator(&self) {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).error_translator = None };
+    }
+
+    /// Returns the verbosity flag of the current chunk observer, or `None` if no observer is set.
+    #[inline]
+    pub(crate) fn chunk_observer_verbosity(&self) -> Option<bool> {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).chunk_observer.as_ref().map(|(_, v)| *v) }
+    }
+
+    /// Delivers the event to the chunk observer (if any).
+    ///
+    /// If the observer is already running, the event is queued and will be delivered
+    /// by the outer call after the observer returns.
+    pub(crate) fn emit_chunk_event(&self, event: ChunkEvent) {
+        let _guard = self.lock();
+        unsafe {
+            let extra = self.extra.get();
+            if (*extra).chunk_observer.is_none() {
+                return;
+            }
+            (*extra).chunk_events.push_back(event);
+            if (*extra).chunk_observer_active {
+                return;
+            }
+
+            (*extra).chunk_observer_active = true;
+            while let Some(event) = (*extra).chunk_events.pop_front() {
+                let callback = match (*extra).chunk_observer {
+                    Some((ref callback, _)) => callback.clone(),
+                    None => break,
+                };
+                if let Err(err) = catch_unwind(AssertUnwindSafe(|| callback(event))) {
+                    (*extra).chunk_events.clear();
+                    (*extra).chunk_observer_active = false;
+                    resume_unwind(err);
+                }
+            }
+            (*extra).chunk_events.clear();
+            (*extra).chunk_observer_active = false;
+        }
+    }
+
     /// Returns Lua source code as a `Chunk` builder type.
     ///
     /// In order to actually compile or run the resulting code, you must call [`Chunk::exec`] or
@@ -1349,6 +1961,7 @@ impl Lua {
     /// [`Chunk::exec`]: crate::Chunk::exec
     #[track_caller]
     pub fn load<'lua, 'a>(&'lua self, chunk: impl AsChunk<'a>) -> Chunk<'lua, 'a> {
+        let _guard = self.lock();
         let caller = Location::caller();
         Chunk {
             lua: self,
@@ -1368,6 +1981,7 @@ impl Lua {
         mode: Option<ChunkMode>,
         source: &[u8],
     ) -> Result<Function<'lua>> {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
@@ -1405,6 +2019,7 @@ impl Lua {
     /// embedded nulls, so in addition to `&str` and `&String`, you can also pass plain `&[u8]`
     /// here.
     pub fn create_string(&self, s: impl AsRef<[u8]>) -> Result<String> {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             if self.unlikely_memory_error() {
@@ -1419,23 +2034,70 @@ impl Lua {
         }
     }
 
-    /// Creates and returns a new empty table.
-    pub fn create_table(&self) -> Result<Table> {
-        self.create_table_with_capacity(0, 0)
-    }
-
-    /// Creates and returns a new empty table, with the specified capacity.
-    /// `narr` is a hint for how many elements the table will have as a sequence;
-    /// `nrec` is a hint for how many other elements the table will have.
-    /// Lua may use these hints to preallocate memory for the new table.
-    pub fn create_table_with_capacity(&self, narr: c_int, nrec: c_int) -> Result<Table> {
-        let state = self.state();
-        unsafe {
-            if self.unlikely_memory_error() {
-                push_table(self.ref_thread(), narr, nrec, false)?;
-                return Ok(Table(self.pop_ref_thread()));
-            }
-
+    /// Create and return an interned Lua string with the content read from `reader` until EOF.
+    ///
+    /// The content is read in chunks directly into Lua memory (without an intermediate Rust buffer),
+    /// so reading stops with `Error::MemoryError` once the Lua [memory limit] is exceeded.
+    /// IO errors are returned as `Error::RuntimeError`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    ///
+    /// let s = lua.create_string_from_reader(&b"hello world"[..])?;
+    /// assert_eq!(s, "hello world");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [memory limit]: #method.set_memory_limit
+    pub fn create_string_from_reader<R: io::Read>(&self, reader: R) -> Result<String> {
+        let _guard = self.lock();
+        let state = self.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+            push_string_from_reader(state, reader)?;
+            Ok(String(self.pop_ref()))
+        }
+    }
+
+    /// Interns a Lua string and returns a handle to compare Lua strings with it by identity.
+    ///
+    /// The string is kept in the Lua registry until the handle is dropped (and
+    /// [`expire_registry_values`] is called). Useful for hot paths that compare incoming strings
+    /// with a fixed set of names, see [`String::eq_interned`].
+    ///
+    /// [`expire_registry_values`]: #method.expire_registry_values
+    pub fn intern(&self, s: impl AsRef<[u8]>) -> Result<InternedString> {
+        let s = self.create_string(s)?;
+        Ok(InternedString::new(
+            &s,
+            self.create_registry_value(s.clone())?,
+        ))
+    }
+
+    /// Creates and returns a new empty table.
+    pub fn create_table(&self) -> Result<Table> {
+        self.create_table_with_capacity(0, 0)
+    }
+
+    /// Creates and returns a new empty table, with the specified capacity.
+    /// `narr` is a hint for how many elements the table will have as a sequence;
+    /// `nrec` is a hint for how many other elements the table will have.
+    /// Lua may use these hints to preallocate memory for the new table.
+    pub fn create_table_with_capacity(&self, narr: c_int, nrec: c_int) -> Result<Table> {
+        let _guard = self.lock();
+        let state = self.state();
+        unsafe {
+            if self.unlikely_memory_error() {
+                push_table(self.ref_thread(), narr, nrec, false)?;
+                return Ok(Table(self.pop_ref_thread()));
+            }
+
             let _sg = StackGuard::new(state);
             check_stack(state, 3)?;
             push_table(state, narr, nrec, true)?;
@@ -1450,6 +2112,7 @@ impl Lua {
         V: IntoLua<'lua>,
         I: IntoIterator<Item = (K, V)>,
     {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
@@ -1473,12 +2136,28 @@ impl Lua {
         }
     }
 
+    /// Creates a table with weak references to its keys, values or both.
+    ///
+    /// The table gets a metatable with the `__mode` field set according to `mode`.
+    /// Entries of a weak table are removed by the garbage collector once the weakly
+    /// referenced keys (or values) are not referenced elsewhere.
+    ///
+    /// All supported Lua versions (including Luau) handle the `__mode` field the same way.
+    pub fn create_weak_table(&self, mode: WeakMode) -> Result<Table> {
+        let table = self.create_table()?;
+        let metatable = self.create_table_with_capacity(0, 1)?;
+        metatable.raw_set("__mode", mode.as_str())?;
+        table.set_metatable(Some(metatable));
+        Ok(table)
+    }
+
     /// Creates a table from an iterator of values, using `1..` as the keys.
     pub fn create_sequence_from<'lua, T, I>(&'lua self, iter: I) -> Result<Table<'lua>>
     where
         T: IntoLua<'lua>,
         I: IntoIterator<Item = T>,
     {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
@@ -1503,6 +2182,21 @@ impl Lua {
         }
     }
 
+    /// Creates a read-only table of constants for the enum `E`.
+    ///
+    /// The table maps the variant names to their Lua values (integers or strings). Values of
+    /// the table are accepted back by [`EnumLua::from_lua_enum`] (and by the `FromLua`
+    /// implementation generated by `#[derive(LuaEnum)]`), as well as the variant names.
+    ///
+    /// In Luau the table is made readonly. For other Lua versions scripts get a proxy table
+    /// that raises an error on any attempt to modify it. In both cases the returned table is
+    /// frozen (see [`Table::freeze`]).
+    ///
+    /// [`EnumLua::from_lua_enum`]: crate::EnumLua::from_lua_enum
+    pub fn create_enum_table<E: EnumLua>(&self) -> Result<Table> {
+        lua_enum::create_enum_table::<E>(self)
+    }
+
     /// Wraps a Rust function or closure, creating a callable Lua function handle to it.
     ///
     /// The function's return value is always a `Result`: If the function returns `Err`, the error
@@ -1584,12 +2278,56 @@ impl Lua {
     /// # Safety
     /// This function is unsafe because provides a way to execute unsafe C function.
     pub unsafe fn create_c_function(&self, func: ffi::lua_CFunction) -> Result<Function> {
+        let _guard = self.lock();
         let state = self.state();
         check_stack(state, 1)?;
         ffi::lua_pushcfunction(state, func);
         Ok(Function(self.pop_ref()))
     }
 
+    /// Wraps a C function with the given upvalues, creating a callable Lua function handle to it.
+    ///
+    /// The upvalues are pushed by mlua, so any values convertible to Lua can be used. Inside the
+    /// function they are accessible using `lua_upvalueindex(1)`, `lua_upvalueindex(2)` and so on.
+    ///
+    /// Returns an error if the number of upvalues exceeds the engine limit
+    /// (255 on Lua 5.2+, 200 on Luau, 60 on Lua 5.1 and LuaJIT).
+    ///
+    /// # Safety
+    /// This function is unsafe because provides a way to execute unsafe C function.
+    pub unsafe fn create_c_closure<'lua, A>(
+        &'lua self,
+        func: ffi::lua_CFunction,
+        upvalues: A,
+    ) -> Result<Function<'lua>>
+    where
+        A: IntoLuaMulti<'lua>,
+    {
+        let _guard = self.lock();
+        let state = self.state();
+        let upvalues = upvalues.into_lua_multi(self)?;
+        let nupvalues = upvalues.len();
+        if nupvalues > ffi::LUA_MAX_UPVALUES as usize {
+            return Err(Error::RuntimeError(format!(
+                "too many upvalues for a C function ({} > {})",
+                nupvalues,
+                ffi::LUA_MAX_UPVALUES
+            )));
+        }
+        let nupvalues = nupvalues as c_int;
+
+        let _sg = StackGuard::new(state);
+        check_stack(state, nupvalues + 3)?;
+
+        for value in upvalues {
+            self.push_value(value)?;
+        }
+        protect_lua!(state, nupvalues, 1, |state| {
+            ffi::lua_pushcclosure(state, func, nupvalues)
+        })?;
+        Ok(Function(self.pop_ref()))
+    }
+
     /// Wraps a Rust async function or closure, creating a callable Lua function handle to it.
     ///
     /// While executing the function Rust will poll Future and if the result is not ready, call
@@ -1652,6 +2390,7 @@ impl Lua {
     ///
     /// Equivalent to `coroutine.create`.
     pub fn create_thread<'lua>(&'lua self, func: Function<'lua>) -> Result<Thread<'lua>> {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
@@ -1675,6 +2414,7 @@ impl Lua {
         &'lua self,
         func: Function<'lua>,
     ) -> Result<Thread<'lua>> {
+        let _guard = self.lock();
         #[cfg(any(
             feature = "lua54",
             all(feature = "luajit", feature = "vendored"),
@@ -1711,6 +2451,7 @@ impl Lua {
         feature = "luau",
     ))]
     pub(crate) unsafe fn recycle_thread(&self, thread: &mut Thread) -> bool {
+        let _guard = self.lock();
         let extra = &mut *self.extra.get();
         if extra.thread_pool.len() < extra.thread_pool.capacity() {
             let thread_state = ffi::lua_tothread(extra.ref_thread, thread.0.index);
@@ -1743,6 +2484,61 @@ impl Lua {
         unsafe { self.make_userdata(UserDataCell::new(data)) }
     }
 
+    /// Creates a promise that is resolved later from Rust.
+    ///
+    /// Returns the resolver and the promise userdata to pass to Lua. Scripts can check the
+    /// promise state using `promise:is_ready()` and get the value using `promise:get()`, which
+    /// raises an error if the promise is not resolved yet or was rejected. With the `async`
+    /// feature enabled, `promise:await()` suspends the calling coroutine until the promise is
+    /// resolved and returns the value.
+    ///
+    /// The resolver can be used from any context (including other threads if `T` is `Send`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let (resolver, promise) = lua.create_promise::<i32>()?;
+    /// lua.globals().set("promise", promise)?;
+    /// assert!(!lua.load("promise:is_ready()").eval::<bool>()?);
+    ///
+    /// resolver.resolve(42);
+    /// assert_eq!(lua.load("promise:get()").eval::<i32>()?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_promise<T>(&self) -> Result<(PromiseResolver<T>, AnyUserData)>
+    where
+        T: for<'lua> IntoLua<'lua> + MaybeSend + 'static,
+    {
+        let (resolver, promise) = Promise::new();
+        Ok((resolver, self.create_userdata(promise)?))
+    }
+
+    /// Create a Lua userdata object from a custom userdata type with `len` inline bytes.
+    ///
+    /// The value and the zero-initialized bytes are stored in a single Lua allocation, so the
+    /// bytes are accounted in [`used_memory`] and freed by the garbage collector together with
+    /// the userdata. The value can be borrowed as usual with [`AnyUserData::borrow`], and the
+    /// bytes are accessible with [`AnyUserData::bytes`] and [`AnyUserData::bytes_mut`].
+    ///
+    /// [`used_memory`]: #method.used_memory
+    /// [`AnyUserData::borrow`]: crate::AnyUserData::borrow
+    /// [`AnyUserData::bytes`]: crate::AnyUserData::bytes
+    /// [`AnyUserData::bytes_mut`]: crate::AnyUserData::bytes_mut
+    pub fn create_userdata_with_bytes<T>(&self, header: T, len: usize) -> Result<AnyUserData>
+    where
+        T: 'static + MaybeSend + UserData,
+    {
+        unsafe {
+            self.make_userdata_with_metatable(UserDataCell::new(header), len, || {
+                self.push_userdata_metatable::<T>()
+            })
+        }
+    }
+
     /// Create a Lua userdata object from a custom serializable userdata type.
     ///
     /// Requires `feature = "serialize"`
@@ -1756,6 +2552,95 @@ impl Lua {
         unsafe { self.make_userdata(UserDataCell::new_ser(data)) }
     }
 
+    /// Create a Lua userdata object from a serializable type that does not implement [`UserData`].
+    ///
+    /// This is useful for foreign types (eg. `chrono::DateTime`) that need to be passed through
+    /// Lua and serialized back. The userdata uses the metatable of `T` if it was already
+    /// registered, otherwise a minimal metatable without methods.
+    ///
+    /// The value is serialized by [`LuaSerdeExt::to_value`] and can be borrowed using
+    /// [`AnyUserData::borrow`].
+    ///
+    /// Requires `feature = "serialize"`
+    ///
+    /// [`LuaSerdeExt::to_value`]: crate::LuaSerdeExt::to_value
+    /// [`AnyUserData::borrow`]: crate::AnyUserData::borrow
+    #[cfg(feature = "serialize")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serialize")))]
+    pub fn create_ser_any_userdata<T>(&self, data: T) -> Result<AnyUserData>
+    where
+        T: 'static + MaybeSend + Serialize,
+    {
+        unsafe {
+            self.make_userdata_with_metatable(UserDataCell::new_ser(data), 0, || {
+                self.push_any_userdata_metatable::<T>()
+            })
+        }
+    }
+
+    /// Create a Lua userdata object from a custom `Copy` userdata type.
+    ///
+    /// Unlike [`create_userdata`], the value is stored inline in a [`Cell`] instead of a `RefCell`,
+    /// so method calls do not need to track the borrow state. Methods registered using
+    /// [`add_method`] receive a copy of the value, and methods registered using [`add_method_mut`]
+    /// write the whole (modified) value back after returning successfully. If such a method
+    /// returns an error, the changes it made are discarded.
+    ///
+    /// The value cannot be borrowed using [`AnyUserData::borrow`] or [`AnyUserData::borrow_mut`]
+    /// (they return [`Error::UserDataCopyBorrowError`]), use [`AnyUserData::get_copy`] or
+    /// [`AnyUserData::take`] instead.
+    ///
+    /// Userdata created by this method uses a separate metatable from the instances of `T` created
+    /// by [`create_userdata`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// #[derive(Clone, Copy)]
+    /// struct Vec2(f32, f32);
+    ///
+    /// impl UserData for Vec2 {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_method("len", |_, this, ()| Ok(this.0.hypot(this.1)));
+    ///         methods.add_method_mut("scale", |_, this, k: f32| {
+    ///             this.0 *= k;
+    ///             this.1 *= k;
+    ///             Ok(())
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// let v = lua.create_copy_userdata(Vec2(3.0, 4.0))?;
+    /// lua.globals().set("v", v.clone())?;
+    /// lua.load("v:scale(2)").exec()?;
+    /// assert_eq!(v.get_copy::<Vec2>()?.0, 6.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`create_userdata`]: #method.create_userdata
+    /// [`add_method`]: crate::UserDataMethods::add_method
+    /// [`add_method_mut`]: crate::UserDataMethods::add_method_mut
+    /// [`AnyUserData::borrow`]: crate::AnyUserData::borrow
+    /// [`AnyUserData::borrow_mut`]: crate::AnyUserData::borrow_mut
+    /// [`Error::UserDataCopyBorrowError`]: crate::Error::UserDataCopyBorrowError
+    /// [`AnyUserData::get_copy`]: crate::AnyUserData::get_copy
+    /// [`AnyUserData::take`]: crate::AnyUserData::take
+    #[inline]
+    pub fn create_copy_userdata<T>(&self, data: T) -> Result<AnyUserData>
+    where
+        T: 'static + MaybeSend + UserData + Copy,
+    {
+        unsafe {
+            self.make_userdata_with_metatable(Cell::new(data), 0, || {
+                self.push_userdata_metatable_with::<T, Cell<T>>(TypeId::of::<Cell<T>>())
+            })
+        }
+    }
+
     /// Create a Lua userdata "proxy" object from a custom userdata type.
     ///
     /// Proxy object is an empty userdata object that has `T` metatable attached.
@@ -1796,23 +2681,295 @@ impl Lua {
         unsafe { self.make_userdata(UserDataCell::new(UserDataProxy::<T>(PhantomData))) }
     }
 
+    /// Creates a class-like proxy object for a UserData type `T`.
+    ///
+    /// Unlike [`create_proxy`], the returned object exposes only functions that do not take `self`
+    /// (registered using [`add_function`], [`add_static_function`] and similar methods) and the
+    /// `__call` metamethod (registered using [`add_meta_function`]). Instance methods and fields
+    /// are not reachable through the proxy. This is useful to publish constructors and static helpers of a type
+    /// as a Lua global.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{AnyUserData, Lua, MetaMethod, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// struct Point(f64, f64);
+    ///
+    /// impl UserData for Point {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_function("new", |_, (x, y)| Ok(Point(x, y)));
+    ///         methods.add_meta_function(MetaMethod::Call, |_, (_, x, y): (AnyUserData, _, _)| {
+    ///             Ok(Point(x, y))
+    ///         });
+    ///         methods.add_method("sum", |_, this, ()| Ok(this.0 + this.1));
+    ///     }
+    /// }
+    ///
+    /// lua.globals().set("Point", lua.create_userdata_proxy::<Point>()?)?;
+    ///
+    /// lua.load(r#"
+    ///     assert(Point.new(1, 2):sum() == 3)
+    ///     assert(Point(3, 4):sum() == 7)
+    /// "#).exec()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`create_proxy`]: #method.create_proxy
+    /// [`add_function`]: crate::UserDataMethods::add_function
+    /// [`add_static_function`]: crate::UserDataMethods::add_static_function
+    /// [`add_meta_function`]: crate::UserDataMethods::add_meta_function
+    #[inline]
+    pub fn create_userdata_proxy<T>(&self) -> Result<AnyUserData>
+    where
+        T: 'static + UserData,
+    {
+        unsafe { self.make_userdata(UserDataCell::new(UserDataClassProxy::<T>(PhantomData))) }
+    }
+
+    /// Returns description of methods and fields registered by a UserData type `T`.
+    ///
+    /// The description is captured once per type, when the type metatable is created (the
+    /// metatable is created by this method if needed). It can be used for example to implement
+    /// autocompletion in an interactive console.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, UserData, UserDataFields, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// struct Player(String);
+    ///
+    /// impl UserData for Player {
+    ///     fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+    ///         fields.add_field_method_get("name", |_, this| Ok(this.0.clone()));
+    ///     }
+    ///
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_method("greet", |_, this, ()| Ok(format!("Hi, {}!", this.0)));
+    ///         methods.add_function("new", |_, name| Ok(Player(name)));
+    ///     }
+    /// }
+    ///
+    /// let info = lua.userdata_info::<Player>()?;
+    /// assert_eq!(info.methods, ["greet"]);
+    /// assert_eq!(info.functions, ["new"]);
+    /// assert_eq!(info.field_getters, ["name"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn userdata_info<T: UserData + 'static>(&self) -> Result<UserDataInfo> {
+        let _guard = self.lock();
+        let state = self.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 1)?;
+
+            self.push_userdata_metatable::<T>()?;
+            Ok(self
+                .registered_userdata_info(TypeId::of::<T>())
+                .unwrap_or_default())
+        }
+    }
+
+    /// Returns a new table with static functions of a UserData type `T`.
+    ///
+    /// The table contains only functions registered using [`add_static_function`], which are
+    /// not reachable through instances of `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// struct Counter(u32);
+    ///
+    /// impl UserData for Counter {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_static_function("new", |_, n| Ok(Counter(n)));
+    ///         methods.add_method("get", |_, this, ()| Ok(this.0));
+    ///     }
+    /// }
+    ///
+    /// lua.globals().set("Counter", lua.userdata_statics::<Counter>()?)?;
+    ///
+    /// lua.load(r#"
+    ///     local c = Counter.new(5)
+    ///     assert(c:get() == 5)
+    ///     assert(not pcall(function() return c.new end))
+    /// "#).exec()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`add_static_function`]: crate::UserDataMethods::add_static_function
+    pub fn userdata_statics<T>(&self) -> Result<Table>
+    where
+        T: 'static + UserData,
+    {
+        let mut methods = StaticUserDataMethods::<T>::default();
+        T::add_methods(&mut methods);
+
+        let statics =
+            self.create_table_with_capacity(0, methods.static_functions.len() as c_int)?;
+        for (name, callback) in methods.static_functions {
+            statics.raw_set(name, self.create_callback(callback)?)?;
+        }
+        Ok(statics)
+    }
+
+    /// Installs the `mlua.bind(obj, name)` helper function into the global environment.
+    ///
+    /// The helper returns a function that calls method `name` of userdata `obj` with `obj` bound
+    /// as `self`, so scripts do not need to write `function(...) return obj:name(...) end`
+    /// wrappers. The method is resolved when binding, so binding a missing method raises
+    /// an error immediately.
+    ///
+    /// If the global `mlua` table already exists, the helper is added to it.
+    ///
+    /// See [`AnyUserData::bind_method`] for more details.
+    pub fn install_bind_helper(&self) -> Result<()> {
+        let globals = self.globals();
+        let mlua = match globals.raw_get::<_, Option<Table>>("mlua")? {
+            Some(mlua) => mlua,
+            None => {
+                let mlua = self.create_table()?;
+                globals.raw_set("mlua", mlua.clone())?;
+                mlua
+            }
+        };
+        let bind = self.create_function(|_, (ud, name): (AnyUserData, String)| {
+            ud.bind_method(name.to_str()?)
+        })?;
+        mlua.raw_set("bind", bind)
+    }
+
     /// Returns a handle to the global environment.
+    ///
+    /// The globals table is cached internally, so this is a cheap operation that does not
+    /// touch the Lua stack.
     pub fn globals(&self) -> Table {
-        let state = self.state();
+        let _guard = self.lock();
         unsafe {
+            let extra = &mut *self.extra.get();
+            ffi::lua_pushvalue(extra.ref_thread, extra.ref_globals_idx);
+            Table(LuaRef::new(self, ref_stack_pop(extra)))
+        }
+    }
+
+    /// Replaces the global environment table.
+    ///
+    /// Chunks loaded after this call and [`Lua::globals`] use the new table. Functions that were
+    /// already loaded (and coroutines in Lua 5.1/LuaJIT/Luau) keep the environment they captured.
+    ///
+    /// In Luau returns an error if the state is [sandboxed].
+    ///
+    /// [sandboxed]: #method.sandbox
+    pub fn set_globals(&self, globals: Table) -> Result<()> {
+        let _guard = self.lock();
+        #[cfg(feature = "luau")]
+        if unsafe { (*self.extra.get()).sandboxed } {
+            return Err(Error::RuntimeError(
+                "cannot replace globals of a sandboxed Lua state".to_string(),
+            ));
+        }
+
+        assert!(
+            Arc::ptr_eq(&globals.0.lua.0, &self.0),
+            "Lua instance passed Value created from a different main Lua state"
+        );
+
+        let state = self.main_state;
+        unsafe {
+            let extra = &mut *self.extra.get();
             let _sg = StackGuard::new(state);
-            assert_stack(state, 1);
+            check_stack(state, 1)?;
+
+            ffi::lua_xpush(extra.ref_thread, state, globals.0.index);
             #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
-            ffi::lua_rawgeti(state, ffi::LUA_REGISTRYINDEX, ffi::LUA_RIDX_GLOBALS);
+            ffi::lua_rawseti(state, ffi::LUA_REGISTRYINDEX, ffi::LUA_RIDX_GLOBALS);
             #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
-            ffi::lua_pushvalue(state, ffi::LUA_GLOBALSINDEX);
-            Table(self.pop_ref())
+            ffi::lua_replace(state, ffi::LUA_GLOBALSINDEX);
+
+            // The ref thread keeps the unsandboxed environment in Luau
+            #[cfg(feature = "luau")]
+            {
+                ffi::lua_pushvalue(extra.ref_thread, globals.0.index);
+                ffi::lua_replace(extra.ref_thread, ffi::LUA_GLOBALSINDEX);
+            }
+
+            ffi::lua_pushvalue(extra.ref_thread, globals.0.index);
+            ffi::lua_replace(extra.ref_thread, extra.ref_globals_idx);
         }
+        Ok(())
+    }
+
+    /// Installs a read-only table describing the host application into the global environment.
+    ///
+    /// The table is installed under [`HostInfo::global_name`] (`_HOST` by default) and contains
+    /// the host name and version, mlua version, engine kind and version, enabled features,
+    /// sandbox profile name and any extra host-defined values. See [`HostInfo`] for details.
+    ///
+    /// In Luau the table (and its subtables) are made readonly. For other Lua versions scripts
+    /// get a proxy table that raises an error on any attempt to modify it. In both cases the
+    /// tables are frozen (see [`Table::freeze`]).
+    ///
+    /// Installing a new host info replaces the previous one.
+    pub fn install_host_info(&self, info: HostInfo) -> Result<()> {
+        let _guard = self.lock();
+        let table = info.create_table(self)?;
+        self.globals().raw_set(info.global_name.as_str(), table)?;
+        unsafe { (*self.extra.get()).host_info = Some(info) };
+        Ok(())
+    }
+
+    /// Installs a string builder library into the global environment under the `strbuf` name.
+    ///
+    /// `strbuf.new([capacity])` creates a mutable byte buffer that can be used to build large
+    /// strings incrementally, without creating intermediate strings that need to be collected.
+    /// The buffer has the following methods:
+    ///
+    /// * `buf:put(...)` appends strings, numbers or other buffers and returns the buffer
+    /// * `buf:putf(fmt, ...)` appends a string formatted according to the `string.format` rules
+    /// * `buf:tostring()` (or `tostring(buf)`) returns the buffer content as a string
+    /// * `buf:reset()` clears the buffer, keeping the allocated memory for reuse
+    ///
+    /// The length operator `#buf` returns the buffer size in bytes and the `..` operator
+    /// concatenates the buffer content with other strings.
+    ///
+    /// Memory used by the buffers is accounted against the Lua [memory limit].
+    ///
+    /// On LuaJIT the native [`string.buffer`] library is used if available.
+    ///
+    /// [memory limit]: #method.set_memory_limit
+    /// [`string.buffer`]: https://luajit.org/ext_buffer.html
+    pub fn install_string_builder(&self) -> Result<()> {
+        let strbuf = StringBuilder::create_library(self)?;
+        self.globals().raw_set("strbuf", strbuf)
+    }
+
+    // Returns a new `ExternalMemory` to account memory allocated outside of the Lua allocator
+    pub(crate) fn external_memory(&self) -> ExternalMemory {
+        ExternalMemory {
+            extra: Arc::downgrade(&self.extra),
+            size: 0,
+        }
+    }
+
+    /// Returns the host info previously installed by [`Lua::install_host_info`].
+    pub fn host_info(&self) -> Option<HostInfo> {
+        let _guard = self.lock();
+        unsafe { (*self.extra.get()).host_info.clone() }
     }
 
     /// Returns a handle to the active `Thread`. For calls to `Lua` this will be the main Lua thread,
     /// for parameters given to a callback, this will be whatever Lua thread called the callback.
     pub fn current_thread(&self) -> Thread {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
@@ -1878,6 +3035,7 @@ impl Lua {
     /// To succeed, the value must be a string (in which case this is a no-op), an integer, or a
     /// number.
     pub fn coerce_string<'lua>(&'lua self, v: Value<'lua>) -> Result<Option<String<'lua>>> {
+        let _guard = self.lock();
         Ok(match v {
             Value::String(s) => Some(s),
             v => unsafe {
@@ -1909,6 +3067,7 @@ impl Lua {
     /// representation as an integer, or a string that can be converted to an integer. Refer to the
     /// Lua manual for details.
     pub fn coerce_integer(&self, v: Value) -> Result<Option<Integer>> {
+        let _guard = self.lock();
         Ok(match v {
             Value::Integer(i) => Some(i),
             v => unsafe {
@@ -1934,6 +3093,7 @@ impl Lua {
     /// To succeed, the value must be a number or a string that can be converted to a number. Refer
     /// to the Lua manual for details.
     pub fn coerce_number(&self, v: Value) -> Result<Option<Number>> {
+        let _guard = self.lock();
         Ok(match v {
             Value::Number(n) => Some(n),
             v => unsafe {
@@ -1953,6 +3113,178 @@ impl Lua {
         })
     }
 
+    /// Returns the numeric model of the Lua version mlua is built with.
+    ///
+    /// The model is derived from the enabled Lua version. For LuaJIT the dual-number mode is
+    /// detected using the `jit.arch` value (if the `jit` module is loaded) or the target
+    /// architecture otherwise. The rounding of integer division is checked in the running Lua.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    /// // 2^53 + 1 cannot be represented as a float
+    /// let value = lua.load("9007199254740993").eval::<i64>()?;
+    /// if lua.numeric_model().has_integer_subtype() {
+    ///     assert_eq!(value, 9007199254740993);
+    /// } else {
+    ///     assert_eq!(value, 9007199254740992);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn numeric_model(&self) -> NumericModel {
+        #[cfg(any(feature = "lua54", feature = "lua53"))]
+        let integer_width = Some(Integer::BITS);
+        #[cfg(feature = "luajit")]
+        let integer_width = if self.luajit_dual_number() {
+            Some(32)
+        } else {
+            None
+        };
+        #[cfg(not(any(feature = "lua54", feature = "lua53", feature = "luajit")))]
+        let integer_width = None;
+
+        NumericModel {
+            integer_width,
+            float_width: (mem::size_of::<Number>() * 8) as u32,
+            floor_division: self.floor_division(),
+        }
+    }
+
+    // Checks if integer division floors the result: `-7 // 2` is `-4` when flooring and `-3`
+    // when truncating. Without the `//` operator the modulo is checked (`1` vs `-1`).
+    fn floor_division(&self) -> bool {
+        let (op, floored) = match ArithOp::IDiv.is_supported() {
+            true => (ArithOp::IDiv, -4.0),
+            false => (ArithOp::Mod, 1.0),
+        };
+        self.arithmetic(op, Value::Integer(-7), Value::Integer(2))
+            .and_then(|res| self.unpack::<Number>(res))
+            .map(|res| res == floored)
+            .unwrap_or(true)
+    }
+
+    // Checks if LuaJIT is built in the dual-number mode (by default on ARM, ARM64, PPC and MIPS64)
+    #[cfg(feature = "luajit")]
+    fn luajit_dual_number(&self) -> bool {
+        const DUAL_NUMBER_ARCHS: &[&str] =
+            &["arm", "arm64", "arm64be", "ppc", "mips64", "mips64el"];
+
+        let arch = (|| -> Result<Option<StdString>> {
+            let loaded = self.named_registry_value::<Option<Table>>("_LOADED")?;
+            let jit = match loaded {
+                Some(loaded) => loaded.raw_get::<_, Option<Table>>("jit")?,
+                None => None,
+            };
+            match jit {
+                Some(jit) => jit.raw_get("arch"),
+                None => Ok(None),
+            }
+        })();
+        match arch {
+            Ok(Some(arch)) => DUAL_NUMBER_ARCHS.contains(&arch.as_str()),
+            _ => cfg!(any(
+                target_arch = "arm",
+                target_arch = "aarch64",
+                target_arch = "powerpc",
+                target_arch = "mips64"
+            )),
+        }
+    }
+
+    /// Performs an arithmetic operation on the values inside the Lua VM.
+    ///
+    /// The operation follows the Lua semantics, including string coercion and metamethods
+    /// (eg. `__add`). For unary operations ([`ArithOp::Unm`] and [`ArithOp::BNot`]) `b` is ignored.
+    ///
+    /// Returns an error if the operation is not supported by the Lua version (see
+    /// [`ArithOp::is_supported`]) or if it raised a Lua error.
+    ///
+    /// [`ArithOp::Unm`]: crate::ArithOp::Unm
+    /// [`ArithOp::BNot`]: crate::ArithOp::BNot
+    /// [`ArithOp::is_supported`]: crate::ArithOp::is_supported
+    pub fn arithmetic<'lua>(
+        &'lua self,
+        op: ArithOp,
+        a: Value<'lua>,
+        b: Value<'lua>,
+    ) -> Result<Value<'lua>> {
+        let _guard = self.lock();
+        if !op.is_supported() {
+            return Err(Error::RuntimeError(format!(
+                "operator '{op}' is not supported by this Lua version"
+            )));
+        }
+
+        #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+        unsafe {
+            let state = self.state();
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            let lua_op = match op {
+                ArithOp::Add => ffi::LUA_OPADD,
+                ArithOp::Sub => ffi::LUA_OPSUB,
+                ArithOp::Mul => ffi::LUA_OPMUL,
+                ArithOp::Div => ffi::LUA_OPDIV,
+                ArithOp::Mod => ffi::LUA_OPMOD,
+                ArithOp::Pow => ffi::LUA_OPPOW,
+                ArithOp::Unm => ffi::LUA_OPUNM,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::IDiv => ffi::LUA_OPIDIV,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::BAnd => ffi::LUA_OPBAND,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::BOr => ffi::LUA_OPBOR,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::BXor => ffi::LUA_OPBXOR,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::Shl => ffi::LUA_OPSHL,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::Shr => ffi::LUA_OPSHR,
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                ArithOp::BNot => ffi::LUA_OPBNOT,
+                #[cfg(feature = "lua52")]
+                _ => unreachable!(),
+            };
+
+            self.push_value(a)?;
+            if op.is_unary() {
+                protect_lua!(state, 1, 1, |state| ffi::lua_arith(state, lua_op))?;
+            } else {
+                self.push_value(b)?;
+                protect_lua!(state, 2, 1, |state| ffi::lua_arith(state, lua_op))?;
+            }
+            Ok(self.pop_value())
+        }
+
+        // There is no `lua_arith` in Lua 5.1 and Luau, so use compiled functions (one per operator)
+        #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+        {
+            let key = format!("__mlua_arith_{op:?}");
+            let func = match self.named_registry_value::<Option<Function>>(&key)? {
+                Some(func) => func,
+                None => {
+                    let source = if op.is_unary() {
+                        format!("local a = ...; return {op}a")
+                    } else {
+                        format!("local a, b = ...; return a {op} b")
+                    };
+                    let func = self
+                        .load(&source)
+                        .set_name("=__mlua_arith")
+                        .into_function()?;
+                    self.set_named_registry_value(&key, func.clone())?;
+                    func
+                }
+            };
+            func.call((a, b))
+        }
+    }
+
     /// Converts a value that implements `IntoLua` into a `Value` instance.
     pub fn pack<'lua, T: IntoLua<'lua>>(&'lua self, t: T) -> Result<Value<'lua>> {
         t.into_lua(self)
@@ -1963,6 +3295,51 @@ impl Lua {
         T::from_lua(value, self)
     }
 
+    /// Converts a `Value` instance into a value that implements [`FromLuaReport`], reporting all
+    /// found problems instead of the first error.
+    ///
+    /// Container types (`Option`, `Vec`, `HashMap` and `BTreeMap`) convert all their elements and
+    /// report every element that cannot be converted, with the path to it (eg. `servers[2].port`).
+    /// Other types report a single problem. Custom types can provide their own diagnostics by
+    /// implementing [`FromLuaReport`], for example using [`LuaSerdeExt::from_value_report`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    /// let value = lua.load("{ a = {1, 2}, b = {3, 'x', 'y'} }").eval()?;
+    /// let report = lua.convert_report::<HashMap<String, Vec<u32>>>(value);
+    /// let mut paths: Vec<_> = report.issues().iter().map(|i| i.path.as_str()).collect();
+    /// paths.sort();
+    /// assert_eq!(paths, ["b[2]", "b[3]"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`FromLuaReport`]: crate::FromLuaReport
+    /// [`LuaSerdeExt::from_value_report`]: crate::LuaSerdeExt::from_value_report
+    pub fn convert_report<'lua, T: FromLuaReport<'lua>>(
+        &'lua self,
+        value: Value<'lua>,
+    ) -> ConversionReport<T> {
+        let mut issues = Vec::new();
+        let found = value.type_name();
+        match T::from_lua_report(value, self, "", &mut issues) {
+            Some(value) if issues.is_empty() => ConversionReport::Value(value),
+            _ => {
+                if issues.is_empty() {
+                    let expected = short_type_name::<T>();
+                    let message = format!("cannot convert {} to {}", found, expected);
+                    issues.push(ConversionIssue::new("", expected, found, message));
+                }
+                ConversionReport::Issues(issues)
+            }
+        }
+    }
+
     /// Converts a value that implements `IntoLuaMulti` into a `MultiValue` instance.
     pub fn pack_multi<'lua, T: IntoLuaMulti<'lua>>(&'lua self, t: T) -> Result<MultiValue<'lua>> {
         t.into_lua_multi(self)
@@ -1984,6 +3361,7 @@ impl Lua {
     where
         T: IntoLua<'lua>,
     {
+        let _guard = self.lock();
         let state = self.state();
         let t = t.into_lua(self)?;
         unsafe {
@@ -2005,6 +3383,7 @@ impl Lua {
     where
         T: FromLua<'lua>,
     {
+        let _guard = self.lock();
         let state = self.state();
         let value = unsafe {
             let _sg = StackGuard::new(state);
@@ -2039,36 +3418,129 @@ impl Lua {
     ///
     /// [`RegistryKey`]: crate::RegistryKey
     pub fn create_registry_value<'lua, T: IntoLua<'lua>>(&'lua self, t: T) -> Result<RegistryKey> {
+        let _guard = self.lock();
         let t = t.into_lua(self)?;
-        if t == Value::Nil {
-            // Special case to skip calling `luaL_ref` and use `LUA_REFNIL` instead
-            let unref_list = unsafe { (*self.extra.get()).registry_unref_list.clone() };
-            return Ok(RegistryKey::new(ffi::LUA_REFNIL, unref_list));
-        }
-
         let state = self.state();
         unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, 4)?;
+            self.anchor_registry_value(t)
+        }
+    }
 
-            let unref_list = (*self.extra.get()).registry_unref_list.clone();
-            self.push_value(t)?;
+    /// Places multiple values in the Lua registry, returning a key for each value.
+    ///
+    /// Works like calling [`create_registry_value`] for every value, but reserves the registry
+    /// slots upfront (see [`reserve_registry_slots`]): for the iterator size hint first and then
+    /// for every chunk of the remaining values. The returned keys are no different from the keys
+    /// created individually.
+    ///
+    /// [`create_registry_value`]: #method.create_registry_value
+    /// [`reserve_registry_slots`]: #method.reserve_registry_slots
+    pub fn create_registry_values<'lua, T, I>(&'lua self, values: I) -> Result<Vec<RegistryKey>>
+    where
+        T: IntoLua<'lua>,
+        I: IntoIterator<Item = T>,
+    {
+        // Values are converted in chunks of bounded size to not exhaust the reference stack
+        const CHUNK_SIZE: usize = 256;
 
-            // Try to reuse previously allocated slot
-            let unref_list2 = unref_list.clone();
-            let mut unref_list2 = mlua_expect!(unref_list2.lock(), "unref list poisoned");
-            if let Some(registry_id) = unref_list2.as_mut().and_then(|x| x.pop()) {
-                // It must be safe to replace the value without triggering memory error
-                ffi::lua_rawseti(state, ffi::LUA_REGISTRYINDEX, registry_id as Integer);
-                return Ok(RegistryKey::new(registry_id, unref_list));
+        let _guard = self.lock();
+        let mut values = values.into_iter();
+        let lower_bound = values.size_hint().0;
+        self.reserve_registry_slots(lower_bound)?;
+
+        let mut keys = Vec::with_capacity(lower_bound);
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        let state = self.state();
+        loop {
+            for value in values.by_ref().take(CHUNK_SIZE) {
+                chunk.push(value.into_lua(self)?);
+            }
+            if chunk.is_empty() {
+                return Ok(keys);
+            }
+            // Nil values do not use registry slots
+            self.reserve_registry_slots(chunk.iter().filter(|v| **v != Value::Nil).count())?;
+            unsafe {
+                let _sg = StackGuard::new(state);
+                check_stack(state, 4)?;
+                for value in chunk.drain(..) {
+                    keys.push(self.anchor_registry_value(value)?);
+                }
             }
+        }
+    }
 
-            // Allocate a new RegistryKey
-            let registry_id = protect_lua!(state, 1, 0, |state| {
-                ffi::luaL_ref(state, ffi::LUA_REGISTRYINDEX)
-            })?;
-            Ok(RegistryKey::new(registry_id, unref_list))
+    /// Reserves free slots in the Lua registry for at least `additional` values.
+    ///
+    /// Subsequent calls to [`create_registry_value`] use the reserved slots without allocating
+    /// registry space. The slots are released by [`expire_registry_values`] if not used.
+    ///
+    /// [`create_registry_value`]: #method.create_registry_value
+    /// [`expire_registry_values`]: #method.expire_registry_values
+    pub fn reserve_registry_slots(&self, additional: usize) -> Result<()> {
+        let _guard = self.lock();
+        let unref_list = unsafe { &(*self.extra.get()).registry_unref_list };
+        let free = match &*mlua_expect!(unref_list.lock(), "unref list poisoned") {
+            Some(list) => list.len(),
+            None => return Ok(()),
+        };
+        if free >= additional {
+            return Ok(());
+        }
+
+        let ids = vec![Cell::new(ffi::LUA_REFNIL); additional - free];
+        let count = Cell::new(0);
+        let state = self.state();
+        let res = unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+            protect_lua!(state, 0, 0, |state| {
+                for id in &ids {
+                    ffi::lua_pushboolean(state, 0);
+                    id.set(ffi::luaL_ref(state, ffi::LUA_REGISTRYINDEX));
+                    count.set(count.get() + 1);
+                }
+            })
+        };
+
+        // Keep the allocated slots even if the allocation failed in the middle
+        let mut unref_list = mlua_expect!(unref_list.lock(), "unref list poisoned");
+        if let Some(list) = unref_list.as_mut() {
+            list.extend(ids[..count.get()].iter().rev().map(Cell::get));
         }
+        res
+    }
+
+    // Places the value to the registry, reusing a previously allocated slot if possible.
+    // Uses 3 stack spaces, does not call checkstack.
+    unsafe fn anchor_registry_value(&self, value: Value) -> Result<RegistryKey> {
+        let _guard = self.lock();
+        let unref_list = (*self.extra.get()).registry_unref_list.clone();
+        if value == Value::Nil {
+            // Special case to skip calling `luaL_ref` and use `LUA_REFNIL` instead
+            return Ok(RegistryKey::new(ffi::LUA_REFNIL, unref_list));
+        }
+
+        let state = self.state();
+        self.push_value(value)?;
+
+        // Try to reuse previously allocated slot
+        let unref_list2 = unref_list.clone();
+        let mut unref_list2 = mlua_expect!(unref_list2.lock(), "unref list poisoned");
+        if let Some(registry_id) = unref_list2.as_mut().and_then(|x| x.pop()) {
+            // It must be safe to replace the value without triggering memory error
+            ffi::lua_rawseti(state, ffi::LUA_REGISTRYINDEX, registry_id as Integer);
+            return Ok(RegistryKey::new(registry_id, unref_list));
+        }
+        drop(unref_list2);
+
+        // Allocate a new RegistryKey
+        let registry_id = protect_lua!(state, 1, 0, |state| {
+            ffi::luaL_ref(state, ffi::LUA_REGISTRYINDEX)
+        })?;
+        Ok(RegistryKey::new(registry_id, unref_list))
     }
 
     /// Get a value from the Lua registry by its `RegistryKey`
@@ -2078,6 +3550,7 @@ impl Lua {
     ///
     /// [`create_registry_value`]: #method.create_registry_value
     pub fn registry_value<'lua, T: FromLua<'lua>>(&'lua self, key: &RegistryKey) -> Result<T> {
+        let _guard = self.lock();
         if !self.owns_registry_value(key) {
             return Err(Error::MismatchedRegistryKey);
         }
@@ -2107,6 +3580,7 @@ impl Lua {
     /// [`create_registry_value`]: #method.create_registry_value
     /// [`expire_registry_values`]: #method.expire_registry_values
     pub fn remove_registry_value(&self, key: RegistryKey) -> Result<()> {
+        let _guard = self.lock();
         if !self.owns_registry_value(&key) {
             return Err(Error::MismatchedRegistryKey);
         }
@@ -2127,6 +3601,7 @@ impl Lua {
         key: &RegistryKey,
         t: T,
     ) -> Result<()> {
+        let _guard = self.lock();
         if !self.owns_registry_value(key) {
             return Err(Error::MismatchedRegistryKey);
         }
@@ -2167,6 +3642,7 @@ impl Lua {
     /// `Error::MismatchedRegistryKey` if passed a `RegistryKey` that was not created with a
     /// matching `Lua` state.
     pub fn owns_registry_value(&self, key: &RegistryKey) -> bool {
+        let _guard = self.lock();
         let registry_unref_list = unsafe { &(*self.extra.get()).registry_unref_list };
         Arc::ptr_eq(&key.unref_list, registry_unref_list)
     }
@@ -2176,7 +3652,12 @@ impl Lua {
     /// Unlike normal handle values, `RegistryKey`s do not automatically remove themselves on Drop,
     /// but you can call this method to remove any unreachable registry values not manually removed
     /// by `Lua::remove_registry_value`.
+    ///
+    /// Unused slots reserved by [`reserve_registry_slots`] are released as well.
+    ///
+    /// [`reserve_registry_slots`]: #method.reserve_registry_slots
     pub fn expire_registry_values(&self) {
+        let _guard = self.lock();
         let state = self.state();
         unsafe {
             let mut unref_list = mlua_expect!(
@@ -2218,6 +3699,7 @@ impl Lua {
     /// ```
     #[track_caller]
     pub fn set_app_data<T: 'static + MaybeSend>(&self, data: T) {
+        let _guard = self.lock();
         let extra = unsafe { &*self.extra.get() };
         extra
             .app_data
@@ -2229,6 +3711,7 @@ impl Lua {
     /// Gets a reference to an application data object stored by [`Lua::set_app_data()`] of type `T`.
     #[track_caller]
     pub fn app_data_ref<T: 'static>(&self) -> Option<Ref<T>> {
+        let _guard = self.lock();
         let extra = unsafe { &*self.extra.get() };
         let app_data = extra
             .app_data
@@ -2243,6 +3726,7 @@ impl Lua {
     /// Gets a mutable reference to an application data object stored by [`Lua::set_app_data()`] of type `T`.
     #[track_caller]
     pub fn app_data_mut<T: 'static>(&self) -> Option<RefMut<T>> {
+        let _guard = self.lock();
         let extra = unsafe { &*self.extra.get() };
         let mut app_data = extra
             .app_data
@@ -2255,6 +3739,7 @@ impl Lua {
     /// Removes an application data of type `T`.
     #[track_caller]
     pub fn remove_app_data<T: 'static>(&self) -> Option<T> {
+        let _guard = self.lock();
         let extra = unsafe { &*self.extra.get() };
         extra
             .app_data
@@ -2264,6 +3749,22 @@ impl Lua {
             .and_then(|data| data.downcast().ok().map(|data: Box<T>| *data))
     }
 
+    /// Returns the handle registry for objects of type `T`.
+    ///
+    /// The registry is created on first call and stored in the application data (see
+    /// [`Lua::set_app_data()`]), so subsequent calls return the same registry.
+    /// See [`HandleRegistry`] for details.
+    ///
+    /// [`HandleRegistry`]: crate::HandleRegistry
+    pub fn create_handle_registry<T: MaybeSend + 'static>(&self) -> HandleRegistry<T> {
+        if let Some(registry) = self.app_data_ref::<HandleRegistry<T>>() {
+            return registry.clone();
+        }
+        let registry = HandleRegistry::new();
+        self.set_app_data(registry.clone());
+        registry
+    }
+
     // Uses 2 stack spaces, does not call checkstack
     pub(crate) unsafe fn push_value(&self, value: Value) -> Result<()> {
         let state = self.state();
@@ -2452,6 +3953,7 @@ impl Lua {
     }
 
     pub(crate) fn clone_ref(&self, lref: &LuaRef) -> LuaRef {
+        let _guard = self.lock();
         unsafe {
             ffi::lua_pushvalue(self.ref_thread(), lref.index);
             let index = ref_stack_pop(&mut *self.extra.get());
@@ -2460,6 +3962,7 @@ impl Lua {
     }
 
     pub(crate) fn drop_ref(&self, lref: &LuaRef) {
+        let _guard = self.lock();
         unsafe {
             let ref_thread = self.ref_thread();
             ffi::lua_pushnil(ref_thread);
@@ -2468,6 +3971,12 @@ impl Lua {
         }
     }
 
+    // Returns a weak reference to this Lua instance, that does not keep it alive
+    #[cfg(feature = "send")]
+    pub(crate) fn downgrade(&self) -> WeakLua {
+        WeakLua(Arc::downgrade(&self.0))
+    }
+
     #[cfg(feature = "unstable")]
     pub(crate) fn make_owned_ref(&self, lref: LuaRef) -> crate::types::LuaOwnedRef {
         assert!(lref.drop, "Cannot turn non-drop reference into owned");
@@ -2491,28 +4000,45 @@ impl Lua {
     }
 
     unsafe fn push_userdata_metatable<T: UserData + 'static>(&self) -> Result<()> {
+        self.push_userdata_metatable_with::<T, UserDataCell<T>>(TypeId::of::<T>())
+    }
+
+    // Pushes metatable for userdata `T` stored as `S` and registered under the `type_id` key
+    unsafe fn push_userdata_metatable_with<T: UserData + 'static, S: 'static>(
+        &self,
+        type_id: TypeId,
+    ) -> Result<()> {
+        let _guard = self.lock();
         let state = self.state();
 
-        let type_id = TypeId::of::<T>();
         if let Some(&table_id) = (*self.extra.get()).registered_userdata.get(&type_id) {
             ffi::lua_rawgeti(state, ffi::LUA_REGISTRYINDEX, table_id as Integer);
             return Ok(());
         }
 
         let _sg = StackGuard::new_extra(state, 1);
-        check_stack(state, 13)?;
+        check_stack(state, 14)?;
 
         let mut fields = StaticUserDataFields::default();
         let mut methods = StaticUserDataMethods::default();
         T::add_fields(&mut fields);
         T::add_methods(&mut methods);
+        let info = collect_userdata_info(&fields, &methods);
 
         // Prepare metatable, add meta methods first and then meta fields
         let metatable_nrec = methods.meta_methods.len() + fields.meta_fields.len();
         #[cfg(feature = "async")]
         let metatable_nrec = metatable_nrec + methods.async_meta_methods.len();
         push_table(state, 0, metatable_nrec as c_int, true)?;
+        #[cfg(not(feature = "luau"))]
+        let mut gc_callback = None;
         for (k, m) in methods.meta_methods {
+            // User-defined `__gc` is allowed only in unsafe mode
+            #[cfg(not(feature = "luau"))]
+            if k == "__gc" && !(*self.extra.get()).safe {
+                gc_callback = Some(self.create_callback(m)?);
+                continue;
+            }
             self.push_value(Value::Function(self.create_callback(m)?))?;
             rawset_field(state, -2, MetaMethod::validate(&k)?)?;
         }
@@ -2525,10 +4051,18 @@ impl Lua {
             self.push_value(f(self)?)?;
             rawset_field(state, -2, MetaMethod::validate(&k)?)?;
         }
+        // `__name` is used by `tostring` and in error messages, use the Rust type name by default.
+        // Luau `typeof` returns `__type` metafield, it also defaults to the Rust type name.
+        let type_name = short_type_name::<T>();
+        rawset_field_default(state, "__name", &type_name)?;
+        #[cfg(feature = "luau")]
+        rawset_field_default(state, "__type", &type_name)?;
         let metatable_index = ffi::lua_absindex(state, -1);
 
         let mut extra_tables_count = 0;
 
+        // Static functions are not added to the instance lookup tables: for instances they don't
+        // exist, so lookup falls through to the user `__index` (if any) or fails as unknown field.
         let mut field_getters_index = None;
         let field_getters_nrec = fields.field_getters.len();
         if field_getters_nrec > 0 {
@@ -2572,17 +4106,99 @@ impl Lua {
             extra_tables_count += 1;
         }
 
-        init_userdata_metatable::<UserDataCell<T>>(
+        init_userdata_metatable::<S>(
             state,
             metatable_index,
             field_getters_index,
             field_setters_index,
             methods_index,
+            methods.index_priority,
         )?;
 
         // Pop extra tables to get metatable on top of the stack
         ffi::lua_pop(state, extra_tables_count);
 
+        // Chain user-defined `__gc` with the destructor
+        #[cfg(not(feature = "luau"))]
+        if let Some(gc_callback) = gc_callback {
+            self.push_ref(&gc_callback.0);
+            protect_lua!(state, 1, 1, |state| {
+                ffi::lua_pushcclosure(state, userdata_destructor_chained::<S>, 1);
+            })?;
+            rawset_field(state, -2, "__gc")?;
+        }
+
+        let any_fns = if TypeId::of::<S>() == TypeId::of::<Cell<T>>() {
+            #[cfg(feature = "serialize")]
+            (*self.extra.get()).copy_userdata.insert(type_id);
+            UserDataAnyFns::new_copy::<T>()
+        } else {
+            UserDataAnyFns::new::<T>()
+        };
+        self.register_userdata_type(type_id, info, any_fns)
+    }
+
+    // Pushes minimal metatable (without methods) for userdata `T` stored as `UserDataCell<T>`.
+    // Uses the metatable registered for `T` if any.
+    //
+    // The minimal metatable is kept apart from `registered_userdata`, so registering `T` as
+    // `UserData` later still builds the full metatable.
+    #[cfg(feature = "serialize")]
+    unsafe fn push_any_userdata_metatable<T: 'static>(&self) -> Result<()> {
+        let _guard = self.lock();
+        let state = self.state();
+        let type_id = TypeId::of::<T>();
+
+        let extra = &*self.extra.get();
+        let table_id = (extra.registered_userdata.get(&type_id))
+            .or_else(|| extra.registered_any_userdata.get(&type_id));
+        if let Some(&table_id) = table_id {
+            ffi::lua_rawgeti(state, ffi::LUA_REGISTRYINDEX, table_id as Integer);
+            return Ok(());
+        }
+
+        let _sg = StackGuard::new_extra(state, 1);
+        check_stack(state, 6)?;
+
+        push_table(state, 0, 3, true)?;
+        let type_name = short_type_name::<T>();
+        rawset_field_default(state, "__name", &type_name)?;
+        #[cfg(feature = "luau")]
+        rawset_field_default(state, "__type", &type_name)?;
+        let metatable_index = ffi::lua_absindex(state, -1);
+        init_userdata_metatable::<UserDataCell<T>>(
+            state,
+            metatable_index,
+            None,
+            None,
+            None,
+            Default::default(),
+        )?;
+
+        let mt_ptr = ffi::lua_topointer(state, -1);
+        ffi::lua_pushvalue(state, -1);
+        let id = protect_lua!(state, 1, 0, |state| {
+            ffi::luaL_ref(state, ffi::LUA_REGISTRYINDEX)
+        })?;
+
+        let extra = &mut *self.extra.get();
+        extra.registered_any_userdata.insert(type_id, id);
+        extra.registered_userdata_mt.insert(mt_ptr, Some(type_id));
+        (extra.registered_userdata_info.entry(type_id)).or_default();
+        (extra.registered_userdata_any.entry(type_id)).or_insert_with(UserDataAnyFns::new::<T>);
+
+        Ok(())
+    }
+
+    // Registers metatable on top of the stack as the metatable of userdata type `type_id`
+    unsafe fn register_userdata_type(
+        &self,
+        type_id: TypeId,
+        info: UserDataInfo,
+        any_fns: UserDataAnyFns,
+    ) -> Result<()> {
+        let _guard = self.lock();
+        let state = self.state();
         let mt_ptr = ffi::lua_topointer(state, -1);
         ffi::lua_pushvalue(state, -1);
         let id = protect_lua!(state, 1, 0, |state| {
@@ -2590,9 +4206,15 @@ impl Lua {
         })?;
 
         (*self.extra.get()).registered_userdata.insert(type_id, id);
+        (*self.extra.get())
+            .registered_userdata_info
+            .insert(type_id, info);
         (*self.extra.get())
             .registered_userdata_mt
             .insert(mt_ptr, Some(type_id));
+        (*self.extra.get())
+            .registered_userdata_any
+            .insert(type_id, any_fns);
 
         Ok(())
     }
@@ -2603,13 +4225,47 @@ impl Lua {
         ptr: *const c_void,
         type_id: Option<TypeId>,
     ) {
+        let _guard = self.lock();
         (*self.extra.get())
             .registered_userdata_mt
             .insert(ptr, type_id);
     }
 
+    #[inline]
+    pub(crate) unsafe fn registered_userdata_info(&self, type_id: TypeId) -> Option<UserDataInfo> {
+        let _guard = self.lock();
+        (*self.extra.get())
+            .registered_userdata_info
+            .get(&type_id)
+            .cloned()
+    }
+
+    #[inline]
+    pub(crate) unsafe fn registered_userdata_any_fns(
+        &self,
+        type_id: TypeId,
+    ) -> Option<UserDataAnyFns> {
+        let _guard = self.lock();
+        (*self.extra.get())
+            .registered_userdata_any
+            .get(&type_id)
+            .copied()
+    }
+
+    // Returns true if the userdata registered under `type_id` uses `Cell<T>` storage
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub(crate) unsafe fn is_copy_userdata(&self, type_id: Option<TypeId>) -> bool {
+        let _guard = self.lock();
+        match type_id {
+            Some(type_id) => (*self.extra.get()).copy_userdata.contains(&type_id),
+            None => false,
+        }
+    }
+
     #[inline]
     pub(crate) unsafe fn deregister_userdata_metatable(&self, ptr: *const c_void) {
+        let _guard = self.lock();
         (*self.extra.get()).registered_userdata_mt.remove(&ptr);
     }
 
@@ -2617,21 +4273,41 @@ impl Lua {
     // and not destructed UserData.
     // Uses 2 stack spaces, does not call checkstack.
     pub(crate) unsafe fn push_userdata_ref(&self, lref: &LuaRef) -> Result<Option<TypeId>> {
+        let _guard = self.lock();
         let state = self.state();
         self.push_ref(lref);
         if ffi::lua_getmetatable(state, -1) == 0 {
             ffi::lua_pop(state, 1);
-            return Err(Error::UserDataTypeMismatch);
+            return Err(Error::UserDataTypeMismatchNamed {
+                expected: "mlua userdata".to_string(),
+                actual: "userdata".to_string(),
+            });
         }
         let mt_ptr = ffi::lua_topointer(state, -1);
-        ffi::lua_pop(state, 1);
 
         match (*self.extra.get()).registered_userdata_mt.get(&mt_ptr) {
             Some(&type_id) if type_id == Some(TypeId::of::<DestructedUserdata>()) => {
+                ffi::lua_pop(state, 1);
                 Err(Error::UserDataDestructed)
             }
-            Some(&type_id) => Ok(type_id),
-            None => Err(Error::UserDataTypeMismatch),
+            Some(&type_id) => {
+                ffi::lua_pop(state, 1);
+                Ok(type_id)
+            }
+            None => {
+                // Report the `__name` field of the foreign metatable
+                ffi::lua_replace(state, -2);
+                ffi::lua_pushstring(state, cstr!("__name"));
+                let actual = match ffi::lua_rawget(state, -2) {
+                    ffi::LUA_TSTRING => ptr_to_cstr_bytes(ffi::lua_tostring(state, -1))
+                        .map(|name| StdString::from_utf8_lossy(name).into_owned()),
+                    _ => None,
+                };
+                Err(Error::UserDataTypeMismatchNamed {
+                    expected: "mlua userdata".to_string(),
+                    actual: actual.unwrap_or_else(|| "userdata".to_string()),
+                })
+            }
         }
     }
 
@@ -2647,6 +4323,7 @@ impl Lua {
         &'lua self,
         func: Callback<'lua, 'static>,
     ) -> Result<Function<'lua>> {
+        let _guard = self.lock();
         unsafe extern "C" fn call_callback(state: *mut ffi::lua_State) -> c_int {
             let extra = match ffi::lua_type(state, ffi::lua_upvalueindex(1)) {
                 ffi::LUA_TUSERDATA => {
@@ -2677,7 +4354,7 @@ impl Lua {
 
                 let func = &*(*upvalue).data;
                 let mut results = func(lua, args)?;
-                let nresults = results.len() as c_int;
+                let nresults = check_call_args(results.len())?;
 
                 check_stack(state, nresults)?;
                 for r in results.drain_all() {
@@ -2715,6 +4392,7 @@ impl Lua {
         &'lua self,
         func: AsyncCallback<'lua, 'static>,
     ) -> Result<Function<'lua>> {
+        let _guard = self.lock();
         #[cfg(any(
             feature = "lua54",
             feature = "lua53",
@@ -2886,6 +4564,7 @@ impl Lua {
     #[cfg(feature = "async")]
     #[inline]
     pub(crate) unsafe fn waker(&self) -> Option<Waker> {
+        let _guard = self.lock();
         let extra = &*self.extra.get();
         (*get_userdata::<Option<Waker>>(extra.ref_thread, extra.ref_waker_idx)).clone()
     }
@@ -2893,6 +4572,7 @@ impl Lua {
     #[cfg(feature = "async")]
     #[inline]
     pub(crate) unsafe fn set_waker(&self, waker: Option<Waker>) -> Option<Waker> {
+        let _guard = self.lock();
         let extra = &*self.extra.get();
         let waker_slot = &mut *get_userdata::<Option<Waker>>(extra.ref_thread, extra.ref_waker_idx);
         match waker {
@@ -2905,18 +4585,33 @@ impl Lua {
     where
         T: UserData + 'static,
     {
+        self.make_userdata_with_metatable(data, 0, || self.push_userdata_metatable::<T>())
+    }
+
+    // Creates userdata with `data` followed by `extra_bytes` zeroed bytes
+    unsafe fn make_userdata_with_metatable<S>(
+        &self,
+        data: S,
+        extra_bytes: usize,
+        push_metatable: impl FnOnce() -> Result<()>,
+    ) -> Result<AnyUserData> {
+        let _guard = self.lock();
         let state = self.state();
         let _sg = StackGuard::new(state);
         check_stack(state, 3)?;
 
         // We push metatable first to ensure having correct metatable with `__gc` method
         ffi::lua_pushnil(state);
-        self.push_userdata_metatable::<T>()?;
+        push_metatable()?;
         let protect = !self.unlikely_memory_error();
-        #[cfg(not(feature = "lua54"))]
-        push_userdata(state, data, protect)?;
-        #[cfg(feature = "lua54")]
-        push_userdata_uv(state, data, USER_VALUE_MAXSLOT as c_int, protect)?;
+        if extra_bytes > 0 {
+            push_userdata_with_bytes(state, data, extra_bytes, protect)?;
+        } else {
+            #[cfg(not(feature = "lua54"))]
+            push_userdata(state, data, protect)?;
+            #[cfg(feature = "lua54")]
+            push_userdata_uv(state, data, USER_VALUE_MAXSLOT as c_int, protect)?;
+        }
         ffi::lua_replace(state, -3);
         ffi::lua_setmetatable(state, -2);
 
@@ -2981,6 +4676,16 @@ impl Lua {
 }
 
 impl LuaInner {
+    // Acquires the lock that serializes access to the state from handles sent to other threads.
+    // Without the `send` feature the state never leaves its thread and the guard is a no-op.
+    #[inline(always)]
+    pub(crate) fn lock(&self) -> crate::types::ReentrantLockGuard<'_> {
+        #[cfg(feature = "send")]
+        return self.lock.lock();
+        #[cfg(not(feature = "send"))]
+        crate::types::ReentrantLockGuard(std::marker::PhantomData)
+    }
+
     #[inline(always)]
     pub(crate) fn state(&self) -> *mut ffi::lua_State {
         self.state.load(Ordering::Relaxed)
@@ -2993,12 +4698,14 @@ impl LuaInner {
 
     #[inline]
     pub(crate) fn new_multivalue_from_pool(&self) -> MultiValue {
+        let _guard = self.lock();
         let extra = unsafe { &mut *self.extra.get() };
         extra.multivalue_pool.pop().unwrap_or_default()
     }
 
     #[inline]
     pub(crate) fn return_multivalue_to_pool(&self, mut multivalue: MultiValue) {
+        let _guard = self.lock();
         let extra = unsafe { &mut *self.extra.get() };
         if extra.multivalue_pool.len() < MULTIVALUE_POOL_SIZE {
             multivalue.clear();
@@ -3041,6 +4748,17 @@ unsafe fn extra_data(state: *mut ffi::lua_State) -> *mut ExtraData {
     (*extra_ptr).get()
 }
 
+// Returns the script-facing message for the error, if the error translator is set and provides one.
+// Uses 1 stack space, does not call checkstack.
+pub(crate) unsafe fn translate_error(state: *mut ffi::lua_State, err: &Error) -> Option<StdString> {
+    let extra = extra_data(state);
+    if extra.is_null() {
+        return None;
+    }
+    let translator = (*extra).error_translator.clone()?;
+    translator(err)
+}
+
 // Creates required entries in the metatable cache (see `util::METATABLE_CACHE`)
 pub(crate) fn init_metatable_cache(cache: &mut FxHashMap<TypeId, u8>) {
     cache.insert(TypeId::of::<Arc<UnsafeCell<ExtraData>>>(), 0);
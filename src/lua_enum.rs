@@ -0,0 +1,201 @@
+use std::fmt;
+use std::string::String as StdString;
+
+use crate::error::{Error, Result};
+use crate::lua::Lua;
+use crate::table::Table;
+use crate::types::{Integer, Number};
+use crate::value::{IntoLua, Value};
+
+/// Lua representation of an enum variant, used by [`EnumLua`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnumValue {
+    /// The variant is represented by an integer.
+    Integer(Integer),
+    /// The variant is represented by a string.
+    String(&'static str),
+}
+
+impl EnumValue {
+    fn as_str(&self) -> Option<&'static str> {
+        match self {
+            EnumValue::String(s) => Some(s),
+            EnumValue::Integer(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for EnumValue {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnumValue::Integer(i) => write!(fmt, "{}", i),
+            EnumValue::String(s) => write!(fmt, "{:?}", s),
+        }
+    }
+}
+
+impl<'lua> IntoLua<'lua> for EnumValue {
+    #[inline]
+    fn into_lua(self, lua: &'lua Lua) -> Result<Value<'lua>> {
+        match self {
+            EnumValue::Integer(i) => Ok(Value::Integer(i)),
+            EnumValue::String(s) => s.into_lua(lua),
+        }
+    }
+}
+
+/// Trait for Rust enums that are exposed to Lua as a set of named constants.
+///
+/// Every variant has a name and a Lua value (integer or string). [`Lua::create_enum_table`]
+/// creates a read-only table mapping the names to the values, and [`EnumLua::from_lua_enum`]
+/// accepts either the value or the name back.
+///
+/// The trait is usually implemented using `#[derive(LuaEnum)]` (requires `macros` feature),
+/// which also implements [`FromLua`] and [`IntoLua`] for the enum. Variants are numbered
+/// from zero (or from the explicit discriminant) and the value can be overridden using
+/// the `#[lua(value = 3)]` or `#[lua(value = "red")]` attribute.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{EnumLua, EnumValue, Lua, Result};
+/// #[derive(Debug, PartialEq)]
+/// enum Color {
+///     Red,
+///     Green,
+/// }
+///
+/// impl EnumLua for Color {
+///     fn enum_name() -> &'static str {
+///         "Color"
+///     }
+///
+///     fn variants() -> &'static [(&'static str, EnumValue)] {
+///         &[("RED", EnumValue::Integer(0)), ("GREEN", EnumValue::Integer(1))]
+///     }
+///
+///     fn variant_index(&self) -> usize {
+///         match self {
+///             Color::Red => 0,
+///             Color::Green => 1,
+///         }
+///     }
+///
+///     fn from_variant_index(index: usize) -> Option<Self> {
+///         match index {
+///             0 => Some(Color::Red),
+///             1 => Some(Color::Green),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// # fn main() -> Result<()> {
+/// # let lua = Lua::new();
+/// lua.globals().set("Color", lua.create_enum_table::<Color>()?)?;
+/// let green = lua.load("Color.GREEN").eval()?;
+/// assert_eq!(Color::from_lua_enum(green, &lua)?, Color::Green);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::create_enum_table`]: crate::Lua::create_enum_table
+/// [`FromLua`]: crate::FromLua
+/// [`IntoLua`]: crate::IntoLua
+pub trait EnumLua: Sized {
+    /// Returns the enum name, used in error messages.
+    fn enum_name() -> &'static str;
+
+    /// Returns the list of variant names and their Lua values.
+    fn variants() -> &'static [(&'static str, EnumValue)];
+
+    /// Returns the index of `self` in the [`EnumLua::variants`] list.
+    fn variant_index(&self) -> usize;
+
+    /// Returns the variant at `index` in the [`EnumLua::variants`] list.
+    fn from_variant_index(index: usize) -> Option<Self>;
+
+    /// Returns the Lua value of the variant.
+    fn enum_value(&self) -> EnumValue {
+        Self::variants()[self.variant_index()].1
+    }
+
+    /// Converts the variant to its Lua value.
+    fn to_lua_enum<'lua>(&self, lua: &'lua Lua) -> Result<Value<'lua>> {
+        self.enum_value().into_lua(lua)
+    }
+
+    /// Converts a Lua value to the enum variant.
+    ///
+    /// Accepts the variant value (integer or string) or the variant name. Otherwise returns
+    /// an error listing all valid options.
+    fn from_lua_enum(value: Value, _lua: &Lua) -> Result<Self> {
+        let variants = Self::variants();
+        let index = match &value {
+            Value::Integer(i) => find_value(variants, EnumValue::Integer(*i)),
+            Value::Number(n) => match number_to_integer(*n) {
+                Some(i) => find_value(variants, EnumValue::Integer(i)),
+                None => None,
+            },
+            Value::String(s) => match s.to_str() {
+                Ok(s) => (variants.iter().position(|(name, _)| *name == s))
+                    .or_else(|| (variants.iter()).position(|(_, v)| v.as_str() == Some(s))),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+
+        match index.and_then(Self::from_variant_index) {
+            Some(variant) => Ok(variant),
+            None => {
+                let options = variants
+                    .iter()
+                    .map(|(name, value)| format!("{} ({})", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(Error::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: Self::enum_name(),
+                    message: Some(format!(
+                        "invalid value {}, expected one of: {}",
+                        describe_value(&value),
+                        options
+                    )),
+                })
+            }
+        }
+    }
+}
+
+fn find_value(variants: &[(&str, EnumValue)], value: EnumValue) -> Option<usize> {
+    variants.iter().position(|(_, v)| *v == value)
+}
+
+fn number_to_integer(n: Number) -> Option<Integer> {
+    let i = n as Integer;
+    if i as Number == n {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+fn describe_value(value: &Value) -> StdString {
+    match value {
+        Value::Integer(i) => i.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("{:?}", s.to_string_lossy()),
+        value => format!("<{}>", value.type_name()),
+    }
+}
+
+// Creates a read-only table mapping the enum variant names to their values
+pub(crate) fn create_enum_table<'lua, E: EnumLua>(lua: &'lua Lua) -> Result<Table<'lua>> {
+    let variants = E::variants();
+    let table = lua.create_table_with_capacity(0, variants.len() as _)?;
+    for (name, value) in variants {
+        table.raw_set(*name, *value)?;
+    }
+
+    table.read_only_proxy()
+}
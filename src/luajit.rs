@@ -0,0 +1,170 @@
+use std::fmt;
+use std::string::String as StdString;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::lua::Lua;
+use crate::table::Table;
+use crate::value::Value;
+
+type SymbolFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Restrictions for the LuaJIT [`ffi`](http://luajit.org/ext_ffi.html) library.
+///
+/// Installed using [`Lua::configure_jit_ffi`]. By default all libraries and symbols are allowed.
+///
+/// # Note
+///
+/// The policy is a hardening measure to prevent accidental use of unexpected native code,
+/// it's **not** a security boundary. The `ffi` library allows to cast arbitrary pointers
+/// and call them, so a determined script can bypass the checks.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{FfiPolicy, Lua, LuaOptions, Result, StdLib};
+/// # fn main() -> Result<()> {
+/// let libs = StdLib::ALL_SAFE | StdLib::FFI;
+/// let lua = unsafe { Lua::unsafe_new_with(libs, LuaOptions::new()) };
+/// lua.configure_jit_ffi(
+///     FfiPolicy::new()
+///         .allow_library("libblessed.so")
+///         .symbol_filter(|name| name.starts_with("blessed_")),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::configure_jit_ffi`]: crate::Lua::configure_jit_ffi
+#[derive(Clone, Default)]
+pub struct FfiPolicy {
+    allowed_libraries: Option<Vec<StdString>>,
+    symbol_filter: Option<SymbolFilter>,
+}
+
+impl FfiPolicy {
+    /// Returns a new policy that allows everything.
+    pub const fn new() -> Self {
+        FfiPolicy {
+            allowed_libraries: None,
+            symbol_filter: None,
+        }
+    }
+
+    /// Allows loading the library using `ffi.load`.
+    ///
+    /// Once any library is allowed, loading of other libraries is denied. The name must match
+    /// the `ffi.load` argument exactly.
+    #[must_use]
+    pub fn allow_library(mut self, name: impl Into<StdString>) -> Self {
+        (self.allowed_libraries.get_or_insert_with(Vec::new)).push(name.into());
+        self
+    }
+
+    /// Denies loading of all libraries using `ffi.load` (unless allowed by [`allow_library`]).
+    ///
+    /// [`allow_library`]: #method.allow_library
+    #[must_use]
+    pub fn deny_libraries(mut self) -> Self {
+        self.allowed_libraries.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Sets a callback to approve access to symbols of the default C library namespace (`ffi.C`).
+    ///
+    /// The callback receives the symbol name and returns `true` if access is allowed.
+    /// Symbols of the libraries loaded using `ffi.load` are not checked.
+    #[must_use]
+    pub fn symbol_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.symbol_filter = Some(Arc::new(filter));
+        self
+    }
+}
+
+impl fmt::Debug for FfiPolicy {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("FfiPolicy")
+            .field("allowed_libraries", &self.allowed_libraries)
+            .field("symbol_filter", &self.symbol_filter.is_some())
+            .finish()
+    }
+}
+
+impl Lua {
+    /// Restricts the LuaJIT `ffi` library according to the `policy`.
+    ///
+    /// Replaces `ffi.load` and `ffi.C` entries of the loaded `ffi` module with checking shims,
+    /// that raise an error naming the denied library or symbol. Should be called right after
+    /// creating the Lua state, before running any untrusted code (the code can keep references
+    /// to the original functions).
+    ///
+    /// Returns an error if the `ffi` library is not loaded. See [`FfiPolicy`] for details.
+    ///
+    /// Requires `feature = "luajit"`
+    #[cfg_attr(docsrs, doc(cfg(feature = "luajit")))]
+    pub fn configure_jit_ffi(&self, policy: FfiPolicy) -> Result<()> {
+        let loaded = self.named_registry_value::<Option<Table>>("_LOADED")?;
+        let ffi = match loaded.map(|t| t.raw_get::<_, Option<Table>>("ffi")) {
+            Some(ffi) => ffi?,
+            None => None,
+        };
+        let ffi = ffi.ok_or_else(|| Error::RuntimeError("ffi library is not loaded".into()))?;
+
+        let allowed_libraries = match policy.allowed_libraries {
+            Some(libs) => {
+                Value::Table(self.create_table_from(libs.into_iter().map(|l| (l, true)))?)
+            }
+            None => Value::Nil,
+        };
+        let symbol_filter = match policy.symbol_filter {
+            Some(filter) => {
+                let func = self.create_function(move |_, name: StdString| Ok(filter(&name)))?;
+                Value::Function(func)
+            }
+            None => Value::Nil,
+        };
+
+        // Errors are raised from Lua to report them without the callback error wrapper
+        self.load(
+            r#"
+            local ffi, allowed_libraries, symbol_filter = ...
+            local error, tostring, setmetatable = error, tostring, setmetatable
+
+            if allowed_libraries then
+                local load = ffi.load
+                ffi.load = function(name, ...)
+                    if not allowed_libraries[name] then
+                        error("ffi library '" .. tostring(name) .. "' is not allowed", 2)
+                    end
+                    return load(name, ...)
+                end
+            end
+
+            if symbol_filter then
+                local C = ffi.C
+                local function check(name)
+                    if not symbol_filter(tostring(name)) then
+                        error("ffi symbol '" .. tostring(name) .. "' is not allowed", 3)
+                    end
+                end
+                ffi.C = setmetatable({}, {
+                    __index = function(_, name)
+                        check(name)
+                        return C[name]
+                    end,
+                    __newindex = function(_, name, value)
+                        check(name)
+                        C[name] = value
+                    end,
+                    __metatable = false,
+                })
+            end
+            "#,
+        )
+        .set_name("_mlua_jit_ffi")
+        .call((ffi, allowed_libraries, symbol_filter))
+    }
+}
@@ -95,6 +95,226 @@ macro_rules! require_module_feature {
     };
 }
 
+/// Implements [`FromLua`] and [`IntoLua`] for a type using its conversions from/to another type.
+///
+/// The generated [`FromLua`] implementation converts a Lua value to `$repr` first and then
+/// to `$ty` using [`TryFrom<$repr>`], reporting the `TryFrom` error message on failure.
+/// The [`IntoLua`] implementation converts `$ty` to `$repr` using [`Into<$repr>`].
+///
+/// This is useful for domain newtypes:
+///
+/// ```
+/// use mlua::{Lua, Result};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct UserId(u64);
+///
+/// impl TryFrom<u64> for UserId {
+///     type Error = String;
+///
+///     fn try_from(id: u64) -> std::result::Result<Self, Self::Error> {
+///         if id == 0 {
+///             return Err("user id cannot be zero".to_string());
+///         }
+///         Ok(UserId(id))
+///     }
+/// }
+///
+/// impl From<UserId> for u64 {
+///     fn from(id: UserId) -> u64 {
+///         id.0
+///     }
+/// }
+///
+/// mlua::impl_from_lua_via_try_from!(UserId, u64);
+///
+/// fn main() -> Result<()> {
+///     let lua = Lua::new();
+///     assert_eq!(lua.load("42").eval::<UserId>()?, UserId(42));
+///     assert!(lua.load("0").eval::<UserId>().is_err());
+///     Ok(())
+/// }
+/// ```
+///
+/// [`FromLua`]: crate::FromLua
+/// [`IntoLua`]: crate::IntoLua
+/// [`TryFrom<$repr>`]: std::convert::TryFrom
+/// [`Into<$repr>`]: std::convert::Into
+#[macro_export]
+macro_rules! impl_from_lua_via_try_from {
+    ($ty:ty, $repr:ty) => {
+        impl<'lua> $crate::FromLua<'lua> for $ty {
+            fn from_lua(
+                value: $crate::Value<'lua>,
+                lua: &'lua $crate::Lua,
+            ) -> $crate::Result<Self> {
+                let ty = value.type_name();
+                let repr = <$repr as $crate::FromLua>::from_lua(value, lua)?;
+                <$ty as ::std::convert::TryFrom<$repr>>::try_from(repr).map_err(|err| {
+                    $crate::Error::FromLuaConversionError {
+                        from: ty,
+                        to: stringify!($ty),
+                        message: Some(err.to_string()),
+                    }
+                })
+            }
+        }
+
+        impl<'lua> $crate::IntoLua<'lua> for $ty {
+            fn into_lua(self, lua: &'lua $crate::Lua) -> $crate::Result<$crate::Value<'lua>> {
+                let repr: $repr = ::std::convert::Into::into(self);
+                $crate::IntoLua::into_lua(repr, lua)
+            }
+        }
+    };
+}
+
+/// Creates a Lua table from a literal, returning [`Result<Table>`].
+///
+/// The syntax follows Lua table constructors:
+///
+/// - `{ key = value, [key_expr] = value, ... }` creates a table with the given fields.
+/// - `[value, value, ...]` creates a sequence.
+///
+/// Values are Rust expressions implementing [`IntoLua`], or nested `{ ... }` / `[ ... ]`
+/// literals (wrap an expression in parentheses to use a Rust block or array as a value).
+/// Keys and values are evaluated exactly once, in order. The `?` operator in expressions
+/// propagates the error to the macro result, skipping the remaining entries.
+///
+/// The table is created using a single call to [`Lua::create_table_from`]
+/// (or [`Lua::create_sequence_from`] for sequences).
+///
+/// # Examples
+///
+/// ```
+/// use mlua::{Lua, Result, Table};
+///
+/// fn main() -> Result<()> {
+///     let lua = Lua::new();
+///     let on_hit = lua.create_function(|_, damage: u32| Ok(damage * 2))?;
+///     let slot = 3;
+///
+///     let t: Table = mlua::table!(&lua, {
+///         name = "x",
+///         pos = [1, 2, 3],
+///         stats = { hp = 100, [format!("slot{}", slot)] = true },
+///         on_hit = on_hit,
+///     })?;
+///
+///     lua.globals().set("t", t)?;
+///     lua.load(r#"
+///         assert(t.name == "x" and #t.pos == 3 and t.stats.slot3)
+///         assert(t.on_hit(5) == 10)
+///     "#).exec()
+/// }
+/// ```
+///
+/// [`Result<Table>`]: crate::Table
+/// [`IntoLua`]: crate::IntoLua
+/// [`Lua::create_table_from`]: crate::Lua::create_table_from
+/// [`Lua::create_sequence_from`]: crate::Lua::create_sequence_from
+#[macro_export]
+macro_rules! table {
+    ($lua:expr, { $($body:tt)* }) => {{
+        let lua: &$crate::Lua = $lua;
+        $crate::__table_literal!(lua, { $($body)* })
+    }};
+    ($lua:expr, [ $($body:tt)* ]) => {{
+        let lua: &$crate::Lua = $lua;
+        $crate::__table_literal!(lua, [ $($body)* ])
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __table_literal {
+    ($lua:ident, { $($body:tt)* }) => {
+        (|| {
+            #[allow(unused_mut)]
+            let mut entries = ::std::vec::Vec::<($crate::Value, $crate::Value)>::new();
+            $crate::__table_fields!($lua, entries, $($body)*);
+            $lua.create_table_from(entries)
+        })()
+    };
+    ($lua:ident, [ $($body:tt)* ]) => {
+        (|| {
+            #[allow(unused_mut)]
+            let mut values = ::std::vec::Vec::<$crate::Value>::new();
+            $crate::__table_values!($lua, values, $($body)*);
+            $lua.create_sequence_from(values)
+        })()
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __table_fields {
+    ($lua:ident, $entries:ident $(,)?) => {};
+
+    ($lua:ident, $entries:ident, $key:ident = { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(
+            @push $lua, $entries, stringify!($key),
+            $crate::__table_literal!($lua, { $($inner)* })?
+        );
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+    ($lua:ident, $entries:ident, $key:ident = [ $($inner:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(
+            @push $lua, $entries, stringify!($key),
+            $crate::__table_literal!($lua, [ $($inner)* ])?
+        );
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+    ($lua:ident, $entries:ident, $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(@push $lua, $entries, stringify!($key), $value);
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+
+    ($lua:ident, $entries:ident, [$key:expr] = { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(
+            @push $lua, $entries, $key,
+            $crate::__table_literal!($lua, { $($inner)* })?
+        );
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+    ($lua:ident, $entries:ident, [$key:expr] = [ $($inner:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(
+            @push $lua, $entries, $key,
+            $crate::__table_literal!($lua, [ $($inner)* ])?
+        );
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+    ($lua:ident, $entries:ident, [$key:expr] = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__table_fields!(@push $lua, $entries, $key, $value);
+        $crate::__table_fields!($lua, $entries $(, $($rest)*)?);
+    };
+
+    (@push $lua:ident, $entries:ident, $key:expr, $value:expr) => {
+        let key = $crate::IntoLua::into_lua($key, $lua)?;
+        let value = $crate::IntoLua::into_lua($value, $lua)?;
+        $entries.push((key, value));
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __table_values {
+    ($lua:ident, $values:ident $(,)?) => {};
+
+    ($lua:ident, $values:ident, { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $values.push($crate::Value::Table($crate::__table_literal!($lua, { $($inner)* })?));
+        $crate::__table_values!($lua, $values $(, $($rest)*)?);
+    };
+    ($lua:ident, $values:ident, [ $($inner:tt)* ] $(, $($rest:tt)*)?) => {
+        $values.push($crate::Value::Table($crate::__table_literal!($lua, [ $($inner)* ])?));
+        $crate::__table_values!($lua, $values $(, $($rest)*)?);
+    };
+    ($lua:ident, $values:ident, $value:expr $(, $($rest:tt)*)?) => {
+        $values.push($crate::IntoLua::into_lua($value, $lua)?);
+        $crate::__table_values!($lua, $values $(, $($rest)*)?);
+    };
+}
+
 macro_rules! protect_lua {
     ($state:expr, $nargs:expr, $nresults:expr, $f:expr) => {
         crate::util::protect_lua_closure($state, $nargs, $nresults, $f)
@@ -0,0 +1,242 @@
+//! Lua arithmetic helpers.
+//!
+//! Functions in this module replicate semantics of the division and modulo operators of the Lua
+//! version mlua is built with. They can be used to reproduce (eg. predict or validate) results of
+//! Lua scripts in Rust code.
+//!
+//! Lua 5.3 and 5.4 have a separate integer subtype with floor division and modulo that raise
+//! an error on division by zero. Other Lua versions (and Luau) represent all numbers as floats,
+//! so integer operations are performed on floats and converted back.
+//!
+//! To perform any arithmetic operation (including metamethods) inside the Lua VM,
+//! use [`Lua::arithmetic`].
+//!
+//! [`Lua::arithmetic`]: crate::Lua::arithmetic
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::types::{Integer, Number};
+
+/// Arithmetic operations that can be performed by [`Lua::arithmetic`].
+///
+/// Some operations are not supported by all Lua versions. Performing an unsupported operation
+/// returns an error.
+///
+/// [`Lua::arithmetic`]: crate::Lua::arithmetic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArithOp {
+    /// Addition (`+`).
+    Add,
+    /// Subtraction (`-`).
+    Sub,
+    /// Multiplication (`*`).
+    Mul,
+    /// Float division (`/`).
+    Div,
+    /// Modulo (`%`).
+    Mod,
+    /// Exponentiation (`^`).
+    Pow,
+    /// Floor division (`//`).
+    ///
+    /// Requires Lua 5.3, 5.4 or Luau.
+    IDiv,
+    /// Bitwise AND (`&`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    BAnd,
+    /// Bitwise OR (`|`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    BOr,
+    /// Bitwise exclusive OR (`~`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    BXor,
+    /// Left shift (`<<`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    Shl,
+    /// Right shift (`>>`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    Shr,
+    /// Unary minus (`-`).
+    Unm,
+    /// Bitwise NOT (`~`).
+    ///
+    /// Requires Lua 5.3 or 5.4.
+    BNot,
+}
+
+impl ArithOp {
+    /// Returns the Lua operator of this operation.
+    pub const fn operator(self) -> &'static str {
+        match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub | ArithOp::Unm => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+            ArithOp::Mod => "%",
+            ArithOp::Pow => "^",
+            ArithOp::IDiv => "//",
+            ArithOp::BAnd => "&",
+            ArithOp::BOr => "|",
+            ArithOp::BXor | ArithOp::BNot => "~",
+            ArithOp::Shl => "<<",
+            ArithOp::Shr => ">>",
+        }
+    }
+
+    /// Returns `true` if the operation takes a single operand.
+    pub const fn is_unary(self) -> bool {
+        matches!(self, ArithOp::Unm | ArithOp::BNot)
+    }
+
+    /// Returns `true` if the operation is supported by the Lua version mlua is built with.
+    pub const fn is_supported(self) -> bool {
+        match self {
+            ArithOp::IDiv => cfg!(any(feature = "lua54", feature = "lua53", feature = "luau")),
+            ArithOp::BAnd
+            | ArithOp::BOr
+            | ArithOp::BXor
+            | ArithOp::Shl
+            | ArithOp::Shr
+            | ArithOp::BNot => cfg!(any(feature = "lua54", feature = "lua53")),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for ArithOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.operator())
+    }
+}
+
+/// Description of the numeric model of the Lua version mlua is built with.
+///
+/// Returned by [`Lua::numeric_model`].
+///
+/// [`Lua::numeric_model`]: crate::Lua::numeric_model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct NumericModel {
+    /// Width (in bits) of the integer number representation, or `None` if all numbers are floats.
+    ///
+    /// Lua 5.3 and 5.4 have a separate 64-bit integer subtype. LuaJIT built in the dual-number
+    /// mode (eg. on ARM, PPC or MIPS64) stores integral numbers as 32-bit integers, but they behave like
+    /// floats in Lua code. Other Lua versions (and Luau) represent all numbers as floats.
+    pub integer_width: Option<u32>,
+    /// Width (in bits) of the float number representation.
+    pub float_width: u32,
+    /// `true` if integer division rounds the result towards minus infinity (floors),
+    /// `false` if it rounds towards zero (truncates).
+    ///
+    /// Checked in the running Lua using the `//` operator, or the `%` operator (which is
+    /// consistent with `math.floor(a / b)`) in versions without `//`.
+    pub floor_division: bool,
+}
+
+impl NumericModel {
+    /// Returns `true` if integers are distinct from floats in Lua code (`math.type` returns
+    /// `"integer"` for them).
+    pub const fn has_integer_subtype(&self) -> bool {
+        matches!(self.integer_width, Some(64))
+    }
+}
+
+/// Performs integer floor division (`a // b`).
+///
+/// In Lua 5.3 and 5.4 the result is rounded towards minus infinity and division by zero returns
+/// the "attempt to divide by zero" error. In other Lua versions the operands are converted to
+/// floats and the result is `floor(a / b)`. Division by zero returns the same error, as the result
+/// cannot be represented as an integer.
+pub fn idiv(a: Integer, b: Integer) -> Result<Integer> {
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    {
+        match b {
+            0 => Err(Error::RuntimeError("attempt to divide by zero".to_string())),
+            // Avoid overflow with `Integer::MIN // -1`
+            -1 => Ok(a.wrapping_neg()),
+            _ => {
+                let q = a / b;
+                // Round towards minus infinity if the result is negative and not exact
+                if a % b != 0 && (a ^ b) < 0 {
+                    Ok(q - 1)
+                } else {
+                    Ok(q)
+                }
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+    {
+        let q = fdiv(a as Number, b as Number);
+        if !q.is_finite() {
+            return Err(Error::RuntimeError("attempt to divide by zero".to_string()));
+        }
+        Ok(q as Integer)
+    }
+}
+
+/// Performs integer modulo (`a % b`).
+///
+/// The result has the same sign as the divisor. In Lua 5.3 and 5.4 modulo by zero returns
+/// the "attempt to perform 'n%0'" error. In other Lua versions the operands are converted to
+/// floats and modulo by zero returns the same error, as the result cannot be represented as
+/// an integer.
+pub fn imod(a: Integer, b: Integer) -> Result<Integer> {
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    {
+        match b {
+            0 => Err(Error::RuntimeError("attempt to perform 'n%0'".to_string())),
+            // Avoid overflow with `Integer::MIN % -1`
+            -1 => Ok(0),
+            _ => {
+                let m = a % b;
+                if m != 0 && (m ^ b) < 0 {
+                    Ok(m + b)
+                } else {
+                    Ok(m)
+                }
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+    {
+        let m = fmod(a as Number, b as Number);
+        if m.is_nan() {
+            return Err(Error::RuntimeError("attempt to perform 'n%0'".to_string()));
+        }
+        Ok(m as Integer)
+    }
+}
+
+/// Performs float floor division (`a // b`), which is `floor(a / b)`.
+pub fn fdiv(a: Number, b: Number) -> Number {
+    (a / b).floor()
+}
+
+/// Performs float modulo (`a % b`).
+///
+/// The result has the same sign as the divisor. Modulo by zero returns `NaN`.
+pub fn fmod(a: Number, b: Number) -> Number {
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    {
+        let m = a % b;
+        if (m > 0.0 && b < 0.0) || (m < 0.0 && b > 0.0) {
+            m + b
+        } else {
+            m
+        }
+    }
+
+    #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+    {
+        a - (a / b).floor() * b
+    }
+}
@@ -2,9 +2,9 @@ use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
 use std::result::Result as StdResult;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::lua::Lua;
-use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, MultiValue, Nil};
+use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, MultiValue, Nil, Value};
 
 /// Result is convertible to `MultiValue` following the common Lua idiom of returning the result
 /// on success, or in the case of an error, returning `nil` and an error message.
@@ -35,12 +35,36 @@ impl<'lua, T: IntoLua<'lua>> IntoLuaMulti<'lua> for T {
 impl<'lua, T: FromLua<'lua>> FromLuaMulti<'lua> for T {
     #[inline]
     fn from_lua_multi(mut values: MultiValue<'lua>, lua: &'lua Lua) -> Result<Self> {
-        let res = T::from_lua(values.pop_front().unwrap_or(Nil), lua);
+        let res = from_lua_nth(values.pop_front(), 1, lua);
         MultiValue::return_to_pool(values, lua);
         res
     }
 }
 
+// Converts `n`-th value of a multi value, reporting the position if the value is missing
+#[inline]
+fn from_lua_nth<'lua, T: FromLua<'lua>>(
+    value: Option<Value<'lua>>,
+    n: usize,
+    lua: &'lua Lua,
+) -> Result<T> {
+    match value {
+        Some(value) => T::from_lua(value, lua),
+        None => T::from_lua(Nil, lua).map_err(|err| missing_value_error(err, n)),
+    }
+}
+
+fn missing_value_error(err: Error, n: usize) -> Error {
+    match err {
+        Error::FromLuaConversionError { to, .. } => Error::FromLuaConversionError {
+            from: "nil",
+            to,
+            message: Some(format!("missing value #{} (expected {})", n, to)),
+        },
+        err => err,
+    }
+}
+
 impl<'lua> IntoLuaMulti<'lua> for MultiValue<'lua> {
     #[inline]
     fn into_lua_multi(self, _: &'lua Lua) -> Result<MultiValue<'lua>> {
@@ -148,6 +172,62 @@ impl<'lua, T: FromLua<'lua>> FromLuaMulti<'lua> for Variadic<T> {
     }
 }
 
+/// Wraps a value that is replaced by `T::default()` if missing or `nil`.
+///
+/// Useful to convert optional arguments or return values without unwrapping `Option<T>`.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Defaulted, Lua, Result};
+/// # fn main() -> Result<()> {
+/// # let lua = Lua::new();
+/// let (a, Defaulted(b)) = lua.load("return 1").eval::<(i32, Defaulted<String>)>()?;
+/// assert_eq!((a, b.as_str()), (1, ""));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Defaulted<T>(pub T);
+
+impl<T> Defaulted<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Defaulted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Defaulted<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'lua, T: IntoLua<'lua>> IntoLua<'lua> for Defaulted<T> {
+    #[inline]
+    fn into_lua(self, lua: &'lua Lua) -> Result<Value<'lua>> {
+        self.0.into_lua(lua)
+    }
+}
+
+impl<'lua, T: FromLua<'lua> + Default> FromLua<'lua> for Defaulted<T> {
+    #[inline]
+    fn from_lua(value: Value<'lua>, lua: &'lua Lua) -> Result<Self> {
+        match value {
+            Nil => Ok(Defaulted(T::default())),
+            value => T::from_lua(value, lua).map(Defaulted),
+        }
+    }
+}
+
 macro_rules! impl_tuple {
     () => (
         impl<'lua> IntoLuaMulti<'lua> for () {
@@ -191,9 +271,16 @@ macro_rules! impl_tuple {
             #[allow(non_snake_case)]
             #[inline]
             fn from_lua_multi(mut values: MultiValue<'lua>, lua: &'lua Lua) -> Result<Self> {
-                $(let $name = values.pop_front().unwrap_or(Nil);)*
-                let $last = FromLuaMulti::from_lua_multi(values, lua)?;
-                Ok(($(FromLua::from_lua($name, lua)?,)* $last,))
+                let mut n = 0;
+                $(
+                    n += 1;
+                    let $name = from_lua_nth(values.pop_front(), n, lua)?;
+                )*
+                let missing = values.is_empty();
+                let $last = FromLuaMulti::from_lua_multi(values, lua).map_err(|err| {
+                    if missing { missing_value_error(err, n + 1) } else { err }
+                })?;
+                Ok(($($name,)* $last,))
             }
         }
     );
@@ -2,34 +2,56 @@
 
 #[doc(no_inline)]
 pub use crate::{
-    AnyUserData as LuaAnyUserData, Chunk as LuaChunk, Error as LuaError,
-    ExternalError as LuaExternalError, ExternalResult as LuaExternalResult, FromLua, FromLuaMulti,
+    AnchorId as LuaAnchorId, AnyUserData as LuaAnyUserData, ArithOp as LuaArithOp,
+    Chunk as LuaChunk, ChunkEvent as LuaChunkEvent, CloneOptions as LuaCloneOptions,
+    ConversionIssue as LuaConversionIssue, ConversionReport as LuaConversionReport,
+    Defaulted as LuaDefaulted, EnumLua, EnumValue as LuaEnumValue,
+    EqualsOptions as LuaEqualsOptions, Error as LuaError, ExternalError as LuaExternalError,
+    ExternalResult as LuaExternalResult, FromLua, FromLuaMulti, FromLuaReport,
     Function as LuaFunction, FunctionInfo as LuaFunctionInfo, GCMode as LuaGCMode,
-    Integer as LuaInteger, IntoLua, IntoLuaMulti, LightUserData as LuaLightUserData, Lua,
-    LuaOptions, MetaMethod as LuaMetaMethod, MultiValue as LuaMultiValue, Nil as LuaNil,
-    Number as LuaNumber, RegistryKey as LuaRegistryKey, Result as LuaResult, StdLib as LuaStdLib,
-    String as LuaString, Table as LuaTable, TableExt as LuaTableExt, TablePairs as LuaTablePairs,
-    TableSequence as LuaTableSequence, Thread as LuaThread, ThreadStatus as LuaThreadStatus,
-    UserData as LuaUserData, UserDataFields as LuaUserDataFields,
-    UserDataMetatable as LuaUserDataMetatable, UserDataMethods as LuaUserDataMethods,
-    Value as LuaValue,
+    Handle as LuaHandle, HandleRegistry as LuaHandleRegistry, HostInfo as LuaHostInfo,
+    HostInfoValue as LuaHostInfoValue, IndexFallbackPriority as LuaIndexFallbackPriority,
+    Integer as LuaInteger, InternedString as LuaInternedString, IntoLua, IntoLuaMulti,
+    LightUserData as LuaLightUserData, Lua, LuaOptions, MetaMethod as LuaMetaMethod,
+    MultiValue as LuaMultiValue, Nil as LuaNil, Number as LuaNumber,
+    NumericModel as LuaNumericModel, PromiseResolver as LuaPromiseResolver,
+    RegistryKey as LuaRegistryKey, Result as LuaResult, StdLib as LuaStdLib, String as LuaString,
+    SyntaxErrorInfo as LuaSyntaxErrorInfo, Table as LuaTable, TableExt as LuaTableExt,
+    TableKeys as LuaTableKeys, TableMetaPairs as LuaTableMetaPairs, TablePairs as LuaTablePairs,
+    TableSequence as LuaTableSequence, TableValues as LuaTableValues, Thread as LuaThread,
+    ThreadStatus as LuaThreadStatus, UserData as LuaUserData, UserDataFields as LuaUserDataFields,
+    UserDataInfo as LuaUserDataInfo, UserDataMetatable as LuaUserDataMetatable,
+    UserDataMethods as LuaUserDataMethods, ValidatedHandle as LuaValidatedHandle,
+    Value as LuaValue, WeakMode as LuaWeakMode,
 };
 
 #[cfg(not(feature = "luau"))]
 #[doc(no_inline)]
 pub use crate::HookTriggers as LuaHookTriggers;
 
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+#[doc(no_inline)]
+pub use crate::OomPolicy as LuaOomPolicy;
+
 #[cfg(feature = "luau")]
 #[doc(no_inline)]
 pub use crate::{CoverageInfo as LuaCoverageInfo, VmState as LuaVmState};
 
+#[cfg(feature = "luajit")]
+#[doc(no_inline)]
+pub use crate::FfiPolicy as LuaFfiPolicy;
+
 #[cfg(feature = "async")]
 #[doc(no_inline)]
-pub use crate::AsyncThread as LuaAsyncThread;
+pub use crate::{AsyncThread as LuaAsyncThread, AsyncThreadStream as LuaAsyncThreadStream};
+
+#[cfg(feature = "send")]
+#[doc(no_inline)]
+pub use crate::SendOwnedAnyUserData as LuaSendOwnedAnyUserData;
 
 #[cfg(feature = "serialize")]
 #[doc(no_inline)]
 pub use crate::{
     DeserializeOptions as LuaDeserializeOptions, LuaSerdeExt,
-    SerializeOptions as LuaSerializeOptions,
+    SerializeOptions as LuaSerializeOptions, UnsupportedPolicy as LuaUnsupportedPolicy,
 };
@@ -0,0 +1,163 @@
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+use crate::error::{Error, Result};
+use crate::lua::Lua;
+use crate::types::RegistryKey;
+use crate::userdata::{UserData, UserDataMethods};
+use crate::value::{IntoLua, Value};
+
+#[cfg(feature = "async")]
+use {futures_util::future, std::task::Poll};
+
+enum PromiseState<T> {
+    // Wakers of the coroutines awaiting the promise
+    Pending(Vec<Waker>),
+    // Resolved (or rejected) but the value is not converted to Lua yet
+    Resolved(Result<T>),
+    // The value converted to Lua and stored in the registry
+    Converted(Result<RegistryKey>),
+}
+
+type SharedState<T> = Arc<Mutex<PromiseState<T>>>;
+
+/// Handle to resolve a promise created by [`Lua::create_promise`].
+///
+/// The resolver can be moved to another thread (if `T` is `Send`) and resolved from there,
+/// the Lua side observes the result on the next access to the promise.
+///
+/// Dropping the resolver without resolving the promise rejects it.
+///
+/// [`Lua::create_promise`]: crate::Lua::create_promise
+pub struct PromiseResolver<T> {
+    state: SharedState<T>,
+}
+
+impl<T> PromiseResolver<T> {
+    /// Resolves the promise with `value`.
+    pub fn resolve(self, value: T) {
+        self.settle(Ok(value));
+    }
+
+    /// Rejects the promise with `error`.
+    ///
+    /// The error is raised when the promise value is requested from Lua.
+    pub fn reject(self, error: Error) {
+        self.settle(Err(error));
+    }
+
+    /// Returns `true` if the promise is not resolved yet.
+    pub fn is_pending(&self) -> bool {
+        let state = mlua_expect!(self.state.lock(), "promise state poisoned");
+        matches!(*state, PromiseState::Pending(_))
+    }
+
+    fn settle(&self, result: Result<T>) {
+        let mut state = mlua_expect!(self.state.lock(), "promise state poisoned");
+        if let PromiseState::Pending(wakers) = &mut *state {
+            let wakers = mem::take(wakers);
+            *state = PromiseState::Resolved(result);
+            drop(state);
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for PromiseResolver<T> {
+    fn drop(&mut self) {
+        let reason = "promise resolver dropped without resolving the promise";
+        self.settle(Err(Error::RuntimeError(reason.to_string())));
+    }
+}
+
+impl<T> std::fmt::Debug for PromiseResolver<T> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("PromiseResolver")
+            .field("pending", &self.is_pending())
+            .finish()
+    }
+}
+
+// Lua side of the promise
+pub(crate) struct Promise<T>(SharedState<T>);
+
+impl<T> Clone for Promise<T> {
+    fn clone(&self) -> Self {
+        Promise(self.0.clone())
+    }
+}
+
+impl<T> Promise<T> {
+    pub(crate) fn new() -> (PromiseResolver<T>, Self) {
+        let state = Arc::new(Mutex::new(PromiseState::Pending(Vec::new())));
+        let resolver = PromiseResolver {
+            state: state.clone(),
+        };
+        (resolver, Promise(state))
+    }
+
+    fn is_ready(&self) -> bool {
+        let state = mlua_expect!(self.0.lock(), "promise state poisoned");
+        !matches!(*state, PromiseState::Pending(_))
+    }
+
+    // Returns the promise value or `None` if the promise is not resolved yet.
+    // The value is converted to Lua on first access and cached in the registry.
+    fn value<'lua>(&self, lua: &'lua Lua) -> Result<Option<Value<'lua>>>
+    where
+        T: IntoLua<'lua>,
+    {
+        let mut state = mlua_expect!(self.0.lock(), "promise state poisoned");
+        if let PromiseState::Resolved(_) = *state {
+            let result = match mem::replace(&mut *state, PromiseState::Pending(Vec::new())) {
+                PromiseState::Resolved(Ok(value)) => value
+                    .into_lua(lua)
+                    .and_then(|value| lua.create_registry_value(value)),
+                PromiseState::Resolved(Err(err)) => Err(err),
+                _ => unreachable!(),
+            };
+            *state = PromiseState::Converted(result);
+        }
+        match &*state {
+            PromiseState::Pending(_) => Ok(None),
+            PromiseState::Converted(Ok(key)) => lua.registry_value(key).map(Some),
+            PromiseState::Converted(Err(err)) => Err(err.clone()),
+            PromiseState::Resolved(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T> UserData for Promise<T>
+where
+    T: for<'lua> IntoLua<'lua> + 'static,
+{
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("is_ready", |_, this, ()| Ok(this.is_ready()));
+
+        methods.add_method("get", |lua, this, ()| {
+            this.value(lua)?
+                .ok_or_else(|| Error::RuntimeError("promise is not resolved yet".to_string()))
+        });
+
+        #[cfg(feature = "async")]
+        methods.add_async_method("await", |lua, this, ()| async move {
+            future::poll_fn(|cx| {
+                let mut state = mlua_expect!(this.0.lock(), "promise state poisoned");
+                match &mut *state {
+                    PromiseState::Pending(wakers) => {
+                        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                            wakers.push(cx.waker().clone());
+                        }
+                        Poll::Pending
+                    }
+                    _ => Poll::Ready(()),
+                }
+            })
+            .await;
+            this.value(lua).map(|value| value.unwrap_or(Value::Nil))
+        });
+    }
+}
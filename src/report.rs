@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::string::String as StdString;
+
+use bstr::BString;
+
+use crate::error::Error;
+use crate::function::Function;
+use crate::lua::Lua;
+use crate::string::String;
+use crate::table::Table;
+use crate::thread::Thread;
+use crate::types::LightUserData;
+use crate::userdata::AnyUserData;
+use crate::util::short_type_name;
+use crate::value::{FromLua, Nil, Value};
+
+/// A single problem found while converting a Lua value.
+///
+/// See [`Lua::convert_report`] for details.
+///
+/// [`Lua::convert_report`]: crate::Lua::convert_report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionIssue {
+    /// Path from the root value to the offending value, eg. `server.ports[2]`.
+    ///
+    /// Empty for the root value.
+    pub path: StdString,
+    /// Description of the expected value.
+    pub expected: StdString,
+    /// Description of the found value (usually the Lua type name).
+    pub found: StdString,
+    /// Human readable description of the problem.
+    pub message: StdString,
+}
+
+impl ConversionIssue {
+    /// Creates a new issue.
+    pub fn new(
+        path: impl Into<StdString>,
+        expected: impl Into<StdString>,
+        found: impl Into<StdString>,
+        message: impl Into<StdString>,
+    ) -> Self {
+        ConversionIssue {
+            path: path.into(),
+            expected: expected.into(),
+            found: found.into(),
+            message: message.into(),
+        }
+    }
+
+    // Creates an issue from a conversion error
+    pub(crate) fn from_error(path: &str, expected: &str, found: &str, err: &Error) -> Self {
+        match err {
+            Error::FromLuaConversionError { from, to, message } => {
+                let message = match message {
+                    Some(message) => message.clone(),
+                    None => format!("expected {}, got {}", to, from),
+                };
+                ConversionIssue::new(path, *to, *from, message)
+            }
+            err => ConversionIssue::new(path, expected, found, err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ConversionIssue {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(fmt, "{}", self.message)
+        } else {
+            write!(fmt, "at {}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Result of a diagnostic conversion of a Lua value.
+///
+/// Contains either the converted value or the list of all problems found.
+/// See [`Lua::convert_report`] for details.
+///
+/// [`Lua::convert_report`]: crate::Lua::convert_report
+#[derive(Debug, Clone)]
+pub enum ConversionReport<T> {
+    /// The value was converted successfully.
+    Value(T),
+    /// The value cannot be converted.
+    Issues(Vec<ConversionIssue>),
+}
+
+impl<T> ConversionReport<T> {
+    /// Returns `true` if the value was converted successfully.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ConversionReport::Value(_))
+    }
+
+    /// Returns the list of found problems (empty if the value was converted successfully).
+    pub fn issues(&self) -> &[ConversionIssue] {
+        match self {
+            ConversionReport::Value(_) => &[],
+            ConversionReport::Issues(issues) => issues,
+        }
+    }
+
+    /// Converts the report into a `Result`.
+    pub fn into_result(self) -> std::result::Result<T, Vec<ConversionIssue>> {
+        match self {
+            ConversionReport::Value(value) => Ok(value),
+            ConversionReport::Issues(issues) => Err(issues),
+        }
+    }
+
+    /// Appends problems of this report to `issues`, prefixing their paths with `path`.
+    ///
+    /// Returns the converted value if there are no problems. Useful to implement
+    /// [`FromLuaReport`] for types that are converted using a nested report
+    /// (eg. [`LuaSerdeExt::from_value_report`]).
+    ///
+    /// [`LuaSerdeExt::from_value_report`]: crate::LuaSerdeExt::from_value_report
+    pub fn report_to(self, path: &str, issues: &mut Vec<ConversionIssue>) -> Option<T> {
+        match self {
+            ConversionReport::Value(value) => Some(value),
+            ConversionReport::Issues(nested) => {
+                issues.extend(nested.into_iter().map(|mut issue| {
+                    issue.path = join_path(path, &issue.path);
+                    issue
+                }));
+                None
+            }
+        }
+    }
+}
+
+/// Diagnostic conversion from a Lua value, that reports all found problems.
+///
+/// The default implementation uses [`FromLua`] and reports a single problem if the conversion
+/// fails. Container types override it to convert all their elements and report problems with
+/// paths to the offending elements.
+///
+/// Custom [`FromLua`] implementations can implement this trait (with an empty `impl` block or
+/// by traversing their fields) to be used by [`Lua::convert_report`].
+///
+/// [`Lua::convert_report`]: crate::Lua::convert_report
+pub trait FromLuaReport<'lua>: FromLua<'lua> {
+    /// Converts `value` located at `path`, appending all found problems to `issues`.
+    ///
+    /// Returns `None` if the value cannot be converted. In this case at least one problem
+    /// must be reported.
+    fn from_lua_report(
+        value: Value<'lua>,
+        lua: &'lua Lua,
+        path: &str,
+        issues: &mut Vec<ConversionIssue>,
+    ) -> Option<Self> {
+        let found = value.type_name();
+        match Self::from_lua(value, lua) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                let expected = short_type_name::<Self>();
+                issues.push(ConversionIssue::from_error(path, &expected, found, &err));
+                None
+            }
+        }
+    }
+}
+
+// Joins two paths, eg. `a` + `b[1]` = `a.b[1]`
+pub(crate) fn join_path(path: &str, suffix: &str) -> StdString {
+    if path.is_empty() || suffix.is_empty() || suffix.starts_with('[') {
+        format!("{}{}", path, suffix)
+    } else {
+        format!("{}.{}", path, suffix)
+    }
+}
+
+// Returns path to the table entry with the key `key`
+pub(crate) fn key_path(path: &str, key: &Value) -> StdString {
+    match key {
+        Value::String(s) => join_path(path, &s.to_string_lossy()),
+        Value::Integer(i) => format!("{}[{}]", path, i),
+        Value::Number(n) => format!("{}[{}]", path, n),
+        key => format!("{}[<{}>]", path, key.type_name()),
+    }
+}
+
+macro_rules! impl_from_lua_report {
+    ($($t:ty),* $(,)?) => {
+        $(impl<'lua> FromLuaReport<'lua> for $t {})*
+    };
+}
+
+impl_from_lua_report!(
+    Value<'lua>,
+    String<'lua>,
+    Table<'lua>,
+    Function<'lua>,
+    Thread<'lua>,
+    AnyUserData<'lua>,
+    Error,
+    LightUserData,
+    bool,
+    StdString,
+    BString,
+    i8,
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+    i64,
+    u64,
+    i128,
+    u128,
+    isize,
+    usize,
+    f32,
+    f64,
+);
+
+impl<'lua, T: FromLuaReport<'lua>> FromLuaReport<'lua> for Option<T> {
+    fn from_lua_report(
+        value: Value<'lua>,
+        lua: &'lua Lua,
+        path: &str,
+        issues: &mut Vec<ConversionIssue>,
+    ) -> Option<Self> {
+        match value {
+            Nil => Some(None),
+            value => T::from_lua_report(value, lua, path, issues).map(Some),
+        }
+    }
+}
+
+impl<'lua, T: FromLuaReport<'lua>> FromLuaReport<'lua> for Vec<T> {
+    fn from_lua_report(
+        value: Value<'lua>,
+        lua: &'lua Lua,
+        path: &str,
+        issues: &mut Vec<ConversionIssue>,
+    ) -> Option<Self> {
+        let table = expect_table(value, "Vec", path, issues)?;
+        let mut vec = Some(Vec::new());
+        for (i, value) in table.sequence_values::<Value>().enumerate() {
+            let path = format!("{}[{}]", path, i + 1);
+            let value = match value {
+                Ok(value) => T::from_lua_report(value, lua, &path, issues),
+                Err(err) => {
+                    issues.push(ConversionIssue::from_error(&path, "value", "value", &err));
+                    None
+                }
+            };
+            match (vec.as_mut(), value) {
+                (Some(vec), Some(value)) => vec.push(value),
+                _ => vec = None,
+            }
+        }
+        vec
+    }
+}
+
+impl<'lua, K, V, S> FromLuaReport<'lua> for HashMap<K, V, S>
+where
+    K: Eq + Hash + FromLuaReport<'lua>,
+    V: FromLuaReport<'lua>,
+    S: BuildHasher + Default,
+{
+    fn from_lua_report(
+        value: Value<'lua>,
+        lua: &'lua Lua,
+        path: &str,
+        issues: &mut Vec<ConversionIssue>,
+    ) -> Option<Self> {
+        let table = expect_table(value, "HashMap", path, issues)?;
+        let mut map = Some(HashMap::default());
+        for_each_entry(table, lua, path, issues, |k, v| {
+            match (map.as_mut(), k, v) {
+                (Some(map), Some(k), Some(v)) => {
+                    map.insert(k, v);
+                }
+                _ => map = None,
+            }
+        });
+        map
+    }
+}
+
+impl<'lua, K, V> FromLuaReport<'lua> for BTreeMap<K, V>
+where
+    K: Ord + FromLuaReport<'lua>,
+    V: FromLuaReport<'lua>,
+{
+    fn from_lua_report(
+        value: Value<'lua>,
+        lua: &'lua Lua,
+        path: &str,
+        issues: &mut Vec<ConversionIssue>,
+    ) -> Option<Self> {
+        let table = expect_table(value, "BTreeMap", path, issues)?;
+        let mut map = Some(BTreeMap::new());
+        for_each_entry(table, lua, path, issues, |k, v| {
+            match (map.as_mut(), k, v) {
+                (Some(map), Some(k), Some(v)) => {
+                    map.insert(k, v);
+                }
+                _ => map = None,
+            }
+        });
+        map
+    }
+}
+
+fn expect_table<'lua>(
+    value: Value<'lua>,
+    to: &str,
+    path: &str,
+    issues: &mut Vec<ConversionIssue>,
+) -> Option<Table<'lua>> {
+    match value {
+        Value::Table(table) => Some(table),
+        value => {
+            let message = format!("expected table, got {}", value.type_name());
+            issues.push(ConversionIssue::new(path, to, value.type_name(), message));
+            None
+        }
+    }
+}
+
+// Converts all entries of the table, calling `f` with the converted key and value
+fn for_each_entry<'lua, K, V>(
+    table: Table<'lua>,
+    lua: &'lua Lua,
+    path: &str,
+    issues: &mut Vec<ConversionIssue>,
+    mut f: impl FnMut(Option<K>, Option<V>),
+) where
+    K: FromLuaReport<'lua>,
+    V: FromLuaReport<'lua>,
+{
+    for pair in table.pairs::<Value, Value>() {
+        match pair {
+            Ok((key, value)) => {
+                let path = key_path(path, &key);
+                let key = K::from_lua_report(key, lua, &path, issues);
+                let value = V::from_lua_report(value, lua, &path, issues);
+                f(key, value);
+            }
+            Err(err) => {
+                issues.push(ConversionIssue::from_error(path, "table", "table", &err));
+                f(None, None);
+            }
+        }
+    }
+}
@@ -14,11 +14,12 @@ use crate::function::Function;
 use crate::lua::Lua;
 use crate::types::{Callback, CallbackUpvalue, LuaRef, MaybeSend};
 use crate::userdata::{
-    AnyUserData, MetaMethod, UserData, UserDataCell, UserDataFields, UserDataMethods,
+    AnyUserData, IndexFallbackPriority, MetaMethod, UserData, UserDataCell, UserDataFields,
+    UserDataMethods,
 };
 use crate::util::{
     assert_stack, check_stack, get_userdata, init_userdata_metatable, push_table, rawset_field,
-    take_userdata, StackGuard,
+    rawset_field_default, short_type_name, take_userdata, StackGuard,
 };
 use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, MultiValue, Value};
 
@@ -184,6 +185,7 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
     where
         T: 'static + UserData,
     {
+        let _guard = self.lua.lock();
         // Safe even though T may not be Send, because the parent Lua cannot be sent to another
         // thread while the Scope is alive (or the returned AnyUserData handle even).
         unsafe {
@@ -247,10 +249,14 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
     /// a single metatable for multiple non-'static types, so there is a higher cost associated with
     /// creating the userdata metatable each time a new userdata is created.
     ///
+    /// Fields registered via [`UserDataFields`] are supported in the same way as methods and
+    /// become inaccessible once the scope ends.
+    ///
     /// [`Scope::create_userdata`]: #method.create_userdata
     /// [`Lua::create_userdata`]: crate::Lua::create_userdata
     /// [`Lua::scope`]:crate::Lua::scope
     /// [`UserDataMethods`]: crate::UserDataMethods
+    /// [`UserDataFields`]: crate::UserDataFields
     pub fn create_nonstatic_userdata<T>(&self, data: T) -> Result<AnyUserData<'lua>>
     where
         T: 'scope + UserData,
@@ -275,19 +281,23 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
             // with a type mismatch, but here without this check would proceed as though you had
             // called the method on the original value (since we otherwise completely ignore the
             // first argument).
-            let check_ud_type = move |lua: &'callback Lua, value| {
-                if let Some(Value::UserData(ud)) = value {
-                    let state = lua.state();
-                    unsafe {
-                        let _sg = StackGuard::new(state);
-                        check_stack(state, 2)?;
-                        lua.push_userdata_ref(&ud.0)?;
-                        if get_userdata(state, -1) as *const _ == ud_ptr {
-                            return Ok(());
-                        }
+            let check_ud_type = move |lua: &'callback Lua, value: Option<Value<'callback>>| {
+                let value = value.ok_or(Error::FromLuaConversionError {
+                    from: "missing argument",
+                    to: "userdata",
+                    message: None,
+                })?;
+                let ud = AnyUserData::from_lua(value, lua)?;
+                let state = lua.state();
+                unsafe {
+                    let _sg = StackGuard::new(state);
+                    check_stack(state, 2)?;
+                    ud.push_typed_ref::<T>()?;
+                    if get_userdata(state, -1) as *const _ == ud_ptr {
+                        return Ok(());
                     }
-                };
-                Err(Error::UserDataTypeMismatch)
+                }
+                Err(ud.type_mismatch(short_type_name::<T>()))
             };
 
             match method {
@@ -337,7 +347,7 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
         let state = lua.state();
         unsafe {
             let _sg = StackGuard::new(state);
-            check_stack(state, 13)?;
+            check_stack(state, 14)?;
 
             #[cfg(not(feature = "luau"))]
             #[allow(clippy::let_and_return)]
@@ -377,6 +387,10 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
                 lua.push_value(f(mem::transmute(lua))?)?;
                 rawset_field(state, -2, MetaMethod::validate(&k)?)?;
             }
+            // `__name` (and `__type` in Luau) metafields default to the Rust type name
+            rawset_field_default(state, "__name", &short_type_name::<T>())?;
+            #[cfg(feature = "luau")]
+            rawset_field_default(state, "__type", &short_type_name::<T>())?;
             let metatable_index = ffi::lua_absindex(state, -1);
 
             let mut field_getters_index = None;
@@ -422,6 +436,7 @@ impl<'lua, 'scope> Scope<'lua, 'scope> {
                 field_getters_index,
                 field_setters_index,
                 methods_index,
+                ud_methods.index_priority,
             )?;
 
             let count = field_getters_index.map(|_| 1).unwrap_or(0)
@@ -612,6 +627,7 @@ enum NonStaticMethod<'lua, T> {
 struct NonStaticUserDataMethods<'lua, T: UserData> {
     methods: Vec<(String, NonStaticMethod<'lua, T>)>,
     meta_methods: Vec<(String, NonStaticMethod<'lua, T>)>,
+    index_priority: IndexFallbackPriority,
 }
 
 impl<'lua, T: UserData> Default for NonStaticUserDataMethods<'lua, T> {
@@ -619,6 +635,7 @@ impl<'lua, T: UserData> Default for NonStaticUserDataMethods<'lua, T> {
         NonStaticUserDataMethods {
             methods: Vec::new(),
             meta_methods: Vec::new(),
+            index_priority: IndexFallbackPriority::default(),
         }
     }
 }
@@ -699,6 +716,15 @@ impl<'lua, T: UserData> UserDataMethods<'lua, T> for NonStaticUserDataMethods<'l
         panic!("asynchronous functions are not supported for non-static userdata")
     }
 
+    fn add_static_function<F, A, R>(&mut self, _name: impl AsRef<str>, _function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        // Static functions are not exposed on instances
+    }
+
     fn add_meta_method<M, A, R>(&mut self, name: impl AsRef<str>, method: M)
     where
         M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
@@ -773,6 +799,10 @@ impl<'lua, T: UserData> UserDataMethods<'lua, T> for NonStaticUserDataMethods<'l
         // Non-static lifetime must be bounded to 'lua lifetime
         panic!("asynchronous meta functions are not supported for non-static userdata")
     }
+
+    fn set_index_fallback_priority(&mut self, priority: IndexFallbackPriority) {
+        self.index_priority = priority;
+    }
 }
 
 struct NonStaticUserDataFields<'lua, T: UserData> {
@@ -815,6 +845,20 @@ impl<'lua, T: UserData> UserDataFields<'lua, T> for NonStaticUserDataFields<'lua
         self.field_setters.push((name.as_ref().into(), method));
     }
 
+    fn add_field_method_get_readonly<M, R>(&mut self, name: impl AsRef<str>, method: M)
+    where
+        M: Fn(&'lua Lua, &T) -> Result<R> + MaybeSend + 'static,
+        R: IntoLua<'lua>,
+    {
+        let name = name.as_ref();
+        self.add_field_method_get(name, method);
+        let message = format!("field '{name}' is read-only on {}", short_type_name::<T>());
+        let func = NonStaticMethod::Function(Box::new(move |_, _| {
+            Err(Error::RuntimeError(message.clone()))
+        }));
+        self.field_setters.push((name.into(), func));
+    }
+
     fn add_field_function_get<F, R>(&mut self, name: impl AsRef<str>, function: F)
     where
         F: Fn(&'lua Lua, AnyUserData<'lua>) -> Result<R> + MaybeSend + 'static,
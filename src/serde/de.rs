@@ -1,7 +1,11 @@
 use std::cell::RefCell;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::error::Error as StdError;
+use std::fmt;
+use std::iter;
 use std::os::raw::c_void;
 use std::rc::Rc;
+use std::result::Result as StdResult;
 use std::string::String as StdString;
 
 use rustc_hash::FxHashSet;
@@ -9,15 +13,22 @@ use serde::de::{self, IntoDeserializer};
 
 use crate::error::{Error, Result};
 use crate::table::{Table, TablePairs, TableSequence};
+use crate::types::Integer;
 use crate::userdata::AnyUserData;
 use crate::value::Value;
 
 /// A struct for deserializing Lua values into Rust values.
 #[derive(Debug)]
-pub struct Deserializer<'lua> {
+pub struct Deserializer<'lua>(ValueDeserializer<'lua, Error>);
+
+// Deserializer of Lua values, generic over the error type.
+// Errors of type `PathError` are annotated with the path to the offending value.
+#[derive(Debug)]
+pub(crate) struct ValueDeserializer<'lua, E> {
     value: Value<'lua>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+    issues: Issues<E>,
 }
 
 /// A struct with options to change default deserializer behavior.
@@ -42,6 +53,12 @@ pub struct Options {
     ///
     /// Default: **true**
     pub deny_recursive_tables: bool,
+
+    /// If true, an attempt to deserialize a struct from a table that has keys other than the
+    /// struct fields will cause an error, as if the struct had `#[serde(deny_unknown_fields)]`.
+    ///
+    /// Default: **false**
+    pub deny_unknown_fields: bool,
 }
 
 impl Default for Options {
@@ -56,6 +73,7 @@ impl Options {
         Options {
             deny_unsupported_types: true,
             deny_recursive_tables: true,
+            deny_unknown_fields: false,
         }
     }
 
@@ -76,6 +94,15 @@ impl Options {
         self.deny_recursive_tables = enabled;
         self
     }
+
+    /// Sets [`deny_unknown_fields`] option.
+    ///
+    /// [`deny_unknown_fields`]: #structfield.deny_unknown_fields
+    #[must_use]
+    pub const fn deny_unknown_fields(mut self, enabled: bool) -> Self {
+        self.deny_unknown_fields = enabled;
+        self
+    }
 }
 
 impl<'lua> Deserializer<'lua> {
@@ -86,31 +113,113 @@ impl<'lua> Deserializer<'lua> {
 
     /// Creates a new Lua Deserializer for the `Value` with custom options.
     pub fn new_with_options(value: Value<'lua>, options: Options) -> Self {
-        Deserializer {
-            value,
-            options,
-            visited: Rc::new(RefCell::new(FxHashSet::default())),
-        }
+        Deserializer(ValueDeserializer::new(value, options, Issues::default()))
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.0.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+// Deserializes a primitive value.
+// When collecting issues, a value of a wrong Lua type is reported instead of visiting it.
+macro_rules! deserialize_primitive {
+    ($($method:ident($check:expr) => $visit:ident($placeholder:expr);)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> StdResult<V::Value, E>
+            where
+                V: de::Visitor<'de>,
+            {
+                let mismatch = match self.value {
+                    Value::UserData(ref ud) if ud.is_serializable() => false,
+                    ref value => self.issues.is_collecting() && !$check(value),
+                };
+                if mismatch {
+                    let unexp = de::Unexpected::Other(self.value.type_name());
+                    let err = de::Error::invalid_type(unexp, &visitor);
+                    return self.issues.recover(err, || visitor.$visit($placeholder));
+                }
+                self.deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
+impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
+    type Error = Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+}
+
+impl<'lua, E> ValueDeserializer<'lua, E> {
+    pub(crate) fn new(value: Value<'lua>, options: Options, issues: Issues<E>) -> Self {
+        let visited = Rc::new(RefCell::new(FxHashSet::default()));
+        Self::from_parts(value, options, visited, issues)
     }
 
     fn from_parts(
         value: Value<'lua>,
         options: Options,
         visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+        issues: Issues<E>,
     ) -> Self {
-        Deserializer {
+        ValueDeserializer {
             value,
             options,
             visited,
+            issues,
         }
     }
 }
 
-impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
-    type Error = Error;
+impl<'lua, 'de, E: DeError> serde::Deserializer<'de> for ValueDeserializer<'lua, E> {
+    type Error = E;
 
     #[inline]
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_any<V>(self, visitor: V) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -153,7 +262,7 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
     }
 
     #[inline]
-    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_option<V>(self, visitor: V) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -170,7 +279,7 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
         name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
-    ) -> Result<V::Value>
+    ) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -213,11 +322,12 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
             value,
             options: self.options,
             visited: self.visited,
+            issues: self.issues,
         })
     }
 
     #[inline]
-    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -229,17 +339,20 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
                     next: 0,
                     options: self.options,
                     visited: self.visited,
+                    issues: self.issues,
                 };
                 visitor.visit_seq(&mut deserializer)
             }
             Value::Table(t) => {
                 let _guard = RecursionGuard::new(&t, &self.visited);
 
-                let len = t.raw_len() as usize;
-                let mut deserializer = SeqDeserializer {
+                let len = t.raw_len();
+                let mut deserializer: SeqDeserializer<E> = SeqDeserializer {
                     seq: t.raw_sequence_values(),
+                    index: 0,
                     options: self.options,
                     visited: self.visited,
+                    issues: self.issues,
                 };
                 let seq = visitor.visit_seq(&mut deserializer)?;
                 if deserializer.seq.count() == 0 {
@@ -254,15 +367,17 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
             Value::UserData(ud) if ud.is_serializable() => {
                 serde_userdata(ud, |value| value.deserialize_seq(visitor))
             }
-            value => Err(de::Error::invalid_type(
-                de::Unexpected::Other(value.type_name()),
-                &"table",
-            )),
+            value => {
+                let err =
+                    de::Error::invalid_type(de::Unexpected::Other(value.type_name()), &"table");
+                let empty = de::value::SeqDeserializer::new(iter::empty::<()>());
+                self.issues.recover(err, || visitor.visit_seq(empty))
+            }
         }
     }
 
     #[inline]
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -275,7 +390,7 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
         _name: &'static str,
         _len: usize,
         visitor: V,
-    ) -> Result<V::Value>
+    ) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -283,7 +398,94 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
     }
 
     #[inline]
-    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_map<V>(self, visitor: V) -> StdResult<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_table(None, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> StdResult<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+    {
+        let fields = Some(fields).filter(|_| self.options.deny_unknown_fields);
+        self.deserialize_table(fields, visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> StdResult<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::UserData(ud) if ud.is_serializable() => {
+                serde_userdata(ud, |value| value.deserialize_newtype_struct(name, visitor))
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    // Lua strings are passed as bytes, even if they are valid UTF-8
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> StdResult<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::String(s) => visitor.visit_bytes(s.as_bytes()),
+            Value::UserData(ud) if ud.is_serializable() => {
+                serde_userdata(ud, |value| value.deserialize_bytes(visitor))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> StdResult<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    deserialize_primitive! {
+        deserialize_bool(is_boolean) => visit_bool(false);
+        deserialize_i8(is_integer::<i8>) => visit_i8(0);
+        deserialize_i16(is_integer::<i16>) => visit_i16(0);
+        deserialize_i32(is_integer::<i32>) => visit_i32(0);
+        deserialize_i64(is_integer::<i64>) => visit_i64(0);
+        deserialize_i128(is_integer::<i128>) => visit_i128(0);
+        deserialize_u8(is_integer::<u8>) => visit_u8(0);
+        deserialize_u16(is_integer::<u16>) => visit_u16(0);
+        deserialize_u32(is_integer::<u32>) => visit_u32(0);
+        deserialize_u64(is_integer::<u64>) => visit_u64(0);
+        deserialize_u128(is_integer::<u128>) => visit_u128(0);
+        deserialize_f32(is_number) => visit_f32(0.0);
+        deserialize_f64(is_number) => visit_f64(0.0);
+        deserialize_char(is_char) => visit_char('\0');
+        deserialize_str(is_string) => visit_str("");
+        deserialize_string(is_string) => visit_str("");
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit unit_struct identifier ignored_any
+    }
+}
+
+impl<'lua, E: DeError> ValueDeserializer<'lua, E> {
+    // Deserializes a map or a struct (with the list of allowed `fields`)
+    fn deserialize_table<'de, V>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
@@ -291,12 +493,15 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
             Value::Table(t) => {
                 let _guard = RecursionGuard::new(&t, &self.visited);
 
-                let mut deserializer = MapDeserializer {
+                let mut deserializer: MapDeserializer<E> = MapDeserializer {
                     pairs: t.pairs(),
                     value: None,
+                    key: None,
+                    fields,
                     options: self.options,
                     visited: self.visited,
                     processed: 0,
+                    issues: self.issues,
                 };
                 let map = visitor.visit_map(&mut deserializer)?;
                 let count = deserializer.pairs.count();
@@ -309,27 +514,141 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
                     ))
                 }
             }
-            Value::UserData(ud) if ud.is_serializable() => {
-                serde_userdata(ud, |value| value.deserialize_map(visitor))
+            Value::UserData(ud) if ud.is_serializable() => serde_userdata(ud, |value| {
+                de::Deserializer::deserialize_map(value, visitor)
+            }),
+            value => {
+                let err =
+                    de::Error::invalid_type(de::Unexpected::Other(value.type_name()), &"table");
+                let empty = de::value::MapDeserializer::new(iter::empty::<((), ())>());
+                self.issues.recover(err, || visitor.visit_map(empty))
             }
-            value => Err(de::Error::invalid_type(
-                de::Unexpected::Other(value.type_name()),
-                &"table",
-            )),
         }
     }
+}
+
+/// Deserializes a table directly, without wrapping it into a [`Value`] first.
+///
+/// Equivalent to using [`Deserializer`] with default [`Options`] for `Value::Table`.
+/// Errors are annotated with the path to the offending key (e.g. `at graphics.shadows: ...`).
+///
+/// # Example
+///
+/// ```
+/// # use mlua::{Lua, Result, Table};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     name: String,
+///     threads: u32,
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// let table: Table = lua.load(r#"{name = "server", threads = 4}"#).eval()?;
+/// let config = Config::deserialize(&table)?;
+/// assert_eq!(config.name, "server");
+/// assert_eq!(config.threads, 4);
+/// # Ok(())
+/// # }
+/// ```
+impl<'a, 'lua, 'de> serde::Deserializer<'de> for &'a Table<'lua> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_any(visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_option(visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_enum(name, variants, visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_seq(visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_tuple(len, visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_tuple_struct(name, len, visitor)
+            .map_err(PathError::into_error)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        table_deserializer(self)
+            .deserialize_map(visitor)
+            .map_err(PathError::into_error)
+    }
 
     #[inline]
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        table_deserializer(self)
+            .deserialize_struct(name, fields, visitor)
+            .map_err(PathError::into_error)
     }
 
     #[inline]
@@ -337,12 +656,9 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::UserData(ud) if ud.is_serializable() => {
-                serde_userdata(ud, |value| value.deserialize_newtype_struct(name, visitor))
-            }
-            _ => visitor.visit_newtype_struct(self),
-        }
+        table_deserializer(self)
+            .deserialize_newtype_struct(name, visitor)
+            .map_err(PathError::into_error)
     }
 
     serde::forward_to_deserialize_any! {
@@ -351,16 +667,27 @@ impl<'lua, 'de> serde::Deserializer<'de> for Deserializer<'lua> {
     }
 }
 
-struct SeqDeserializer<'lua> {
+#[inline]
+fn table_deserializer<'lua>(table: &Table<'lua>) -> ValueDeserializer<'lua, PathError> {
+    ValueDeserializer::new(
+        Value::Table(table.clone()),
+        Options::default(),
+        Issues::default(),
+    )
+}
+
+struct SeqDeserializer<'lua, E> {
     seq: TableSequence<'lua, Value<'lua>>,
+    index: i64,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+    issues: Issues<E>,
 }
 
-impl<'lua, 'de> de::SeqAccess<'de> for SeqDeserializer<'lua> {
-    type Error = Error;
+impl<'lua, 'de, E: DeError> de::SeqAccess<'de> for SeqDeserializer<'lua, E> {
+    type Error = E;
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    fn next_element_seed<T>(&mut self, seed: T) -> StdResult<Option<T::Value>, E>
     where
         T: de::DeserializeSeed<'de>,
     {
@@ -368,12 +695,17 @@ impl<'lua, 'de> de::SeqAccess<'de> for SeqDeserializer<'lua> {
             match self.seq.next() {
                 Some(value) => {
                     let value = value?;
+                    self.index += 1;
                     if check_value_if_skip(&value, self.options, &self.visited)? {
                         continue;
                     }
                     let visited = Rc::clone(&self.visited);
-                    let deserializer = Deserializer::from_parts(value, self.options, visited);
-                    return seed.deserialize(deserializer).map(Some);
+                    let issues = self.issues.clone();
+                    let deserializer =
+                        ValueDeserializer::from_parts(value, self.options, visited, issues);
+                    let (index, mark) = (self.index, self.issues.len());
+                    let result = seed.deserialize(deserializer).map(Some);
+                    return self.issues.annotate(mark, result, || PathKey::Index(index));
                 }
                 None => return Ok(None),
             }
@@ -389,18 +721,19 @@ impl<'lua, 'de> de::SeqAccess<'de> for SeqDeserializer<'lua> {
 }
 
 #[cfg(feature = "luau")]
-struct VecDeserializer {
+struct VecDeserializer<E> {
     vec: [f32; 3],
     next: usize,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+    issues: Issues<E>,
 }
 
 #[cfg(feature = "luau")]
-impl<'de> de::SeqAccess<'de> for VecDeserializer {
-    type Error = Error;
+impl<'de, E: DeError> de::SeqAccess<'de> for VecDeserializer<E> {
+    type Error = E;
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    fn next_element_seed<T>(&mut self, seed: T) -> StdResult<Option<T::Value>, E>
     where
         T: de::DeserializeSeed<'de>,
     {
@@ -408,9 +741,13 @@ impl<'de> de::SeqAccess<'de> for VecDeserializer {
             Some(&n) => {
                 self.next += 1;
                 let visited = Rc::clone(&self.visited);
+                let issues = self.issues.clone();
+                let value = Value::Number(n as _);
                 let deserializer =
-                    Deserializer::from_parts(Value::Number(n as _), self.options, visited);
-                seed.deserialize(deserializer).map(Some)
+                    ValueDeserializer::from_parts(value, self.options, visited, issues);
+                let (index, mark) = (self.next as i64, self.issues.len());
+                let result = seed.deserialize(deserializer).map(Some);
+                self.issues.annotate(mark, result, || PathKey::Index(index))
             }
             None => Ok(None),
         }
@@ -421,18 +758,38 @@ impl<'de> de::SeqAccess<'de> for VecDeserializer {
     }
 }
 
-struct MapDeserializer<'lua> {
+struct MapDeserializer<'lua, E> {
     pairs: TablePairs<'lua, Value<'lua>, Value<'lua>>,
     value: Option<Value<'lua>>,
+    // Key of the current entry, kept only if errors are annotated with the path
+    key: Option<Value<'lua>>,
+    // Allowed keys, if unknown fields are denied
+    fields: Option<&'static [&'static str]>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
     processed: usize,
+    issues: Issues<E>,
 }
 
-impl<'lua, 'de> de::MapAccess<'de> for MapDeserializer<'lua> {
-    type Error = Error;
+impl<'lua, E: DeError> MapDeserializer<'lua, E> {
+    // Checks that the key is one of the allowed fields (if unknown fields are denied).
+    // Returns `true` if the entry must be skipped, because the problem is already reported.
+    fn check_unknown_field(&self, key: &Value) -> StdResult<bool, E> {
+        if let (Some(fields), Value::String(key)) = (self.fields, key) {
+            let key = key.to_string_lossy();
+            if !fields.contains(&&*key) {
+                let err = de::Error::unknown_field(&key, fields);
+                return self.issues.recover(err, || Ok(true));
+            }
+        }
+        Ok(false)
+    }
+}
 
-    fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+impl<'lua, 'de, E: DeError> de::MapAccess<'de> for MapDeserializer<'lua, E> {
+    type Error = E;
+
+    fn next_key_seed<T>(&mut self, seed: T) -> StdResult<Option<T::Value>, E>
     where
         T: de::DeserializeSeed<'de>,
     {
@@ -442,13 +799,18 @@ impl<'lua, 'de> de::MapAccess<'de> for MapDeserializer<'lua> {
                     let (key, value) = item?;
                     if check_value_if_skip(&key, self.options, &self.visited)?
                         || check_value_if_skip(&value, self.options, &self.visited)?
+                        || self.check_unknown_field(&key)?
                     {
                         continue;
                     }
                     self.processed += 1;
                     self.value = Some(value);
+                    if E::KEY_PATH {
+                        self.key = Some(key.clone());
+                    }
                     let visited = Rc::clone(&self.visited);
-                    let key_de = Deserializer::from_parts(key, self.options, visited);
+                    let issues = self.issues.clone();
+                    let key_de = ValueDeserializer::from_parts(key, self.options, visited, issues);
                     return seed.deserialize(key_de).map(Some);
                 }
                 None => return Ok(None),
@@ -456,14 +818,24 @@ impl<'lua, 'de> de::MapAccess<'de> for MapDeserializer<'lua> {
         }
     }
 
-    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value>
+    fn next_value_seed<T>(&mut self, seed: T) -> StdResult<T::Value, E>
     where
         T: de::DeserializeSeed<'de>,
     {
         match self.value.take() {
             Some(value) => {
                 let visited = Rc::clone(&self.visited);
-                seed.deserialize(Deserializer::from_parts(value, self.options, visited))
+                let issues = self.issues.clone();
+                let deserializer =
+                    ValueDeserializer::from_parts(value, self.options, visited, issues);
+                let (key, mark) = (self.key.take(), self.issues.len());
+                let result = seed.deserialize(deserializer);
+                match key {
+                    Some(key) => self
+                        .issues
+                        .annotate(mark, result, || PathKey::from_value(&key)),
+                    None => result,
+                }
             }
             None => Err(de::Error::custom("value is missing")),
         }
@@ -477,41 +849,73 @@ impl<'lua, 'de> de::MapAccess<'de> for MapDeserializer<'lua> {
     }
 }
 
-struct EnumDeserializer<'lua> {
+struct EnumDeserializer<'lua, E> {
     variant: StdString,
     value: Option<Value<'lua>>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+    issues: Issues<E>,
 }
 
-impl<'lua, 'de> de::EnumAccess<'de> for EnumDeserializer<'lua> {
-    type Error = Error;
-    type Variant = VariantDeserializer<'lua>;
+impl<'lua, 'de, E: DeError> de::EnumAccess<'de> for EnumDeserializer<'lua, E> {
+    type Error = E;
+    type Variant = VariantDeserializer<'lua, E>;
 
-    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant)>
+    fn variant_seed<T>(self, seed: T) -> StdResult<(T::Value, Self::Variant), E>
     where
         T: de::DeserializeSeed<'de>,
     {
+        let key = match self.value {
+            Some(_) if E::KEY_PATH => Some(self.variant.clone()),
+            _ => None,
+        };
         let variant = self.variant.into_deserializer();
         let variant_access = VariantDeserializer {
             value: self.value,
+            key,
             options: self.options,
             visited: self.visited,
+            issues: self.issues,
         };
         seed.deserialize(variant).map(|v| (v, variant_access))
     }
 }
 
-struct VariantDeserializer<'lua> {
+struct VariantDeserializer<'lua, E> {
     value: Option<Value<'lua>>,
+    // Name of the variant (a table key), kept only if errors are annotated with the path
+    key: Option<StdString>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
+    issues: Issues<E>,
 }
 
-impl<'lua, 'de> de::VariantAccess<'de> for VariantDeserializer<'lua> {
-    type Error = Error;
+impl<'lua, E: DeError> VariantDeserializer<'lua, E> {
+    // Deserializes the variant value with `f`
+    fn deserialize<T>(
+        self,
+        value: Value<'lua>,
+        f: impl FnOnce(ValueDeserializer<'lua, E>) -> StdResult<T, E>,
+    ) -> StdResult<T, E> {
+        let issues = self.issues.clone();
+        let mark = issues.len();
+        let result = f(ValueDeserializer::from_parts(
+            value,
+            self.options,
+            self.visited,
+            self.issues,
+        ));
+        match self.key {
+            Some(key) => issues.annotate(mark, result, || PathKey::Field(key.clone())),
+            None => result,
+        }
+    }
+}
+
+impl<'lua, 'de, E: DeError> de::VariantAccess<'de> for VariantDeserializer<'lua, E> {
+    type Error = E;
 
-    fn unit_variant(self) -> Result<()> {
+    fn unit_variant(self) -> StdResult<(), E> {
         match self.value {
             Some(_) => Err(de::Error::invalid_type(
                 de::Unexpected::NewtypeVariant,
@@ -521,14 +925,12 @@ impl<'lua, 'de> de::VariantAccess<'de> for VariantDeserializer<'lua> {
         }
     }
 
-    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    fn newtype_variant_seed<T>(mut self, seed: T) -> StdResult<T::Value, E>
     where
         T: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Some(value) => {
-                seed.deserialize(Deserializer::from_parts(value, self.options, self.visited))
-            }
+        match self.value.take() {
+            Some(value) => self.deserialize(value, |de| seed.deserialize(de)),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"newtype variant",
@@ -536,15 +938,14 @@ impl<'lua, 'de> de::VariantAccess<'de> for VariantDeserializer<'lua> {
         }
     }
 
-    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    fn tuple_variant<V>(mut self, _len: usize, visitor: V) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Some(value) => serde::Deserializer::deserialize_seq(
-                Deserializer::from_parts(value, self.options, self.visited),
-                visitor,
-            ),
+        match self.value.take() {
+            Some(value) => self.deserialize(value, |de| {
+                serde::Deserializer::deserialize_seq(de, visitor)
+            }),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"tuple variant",
@@ -552,15 +953,18 @@ impl<'lua, 'de> de::VariantAccess<'de> for VariantDeserializer<'lua> {
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> StdResult<V::Value, E>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Some(value) => serde::Deserializer::deserialize_map(
-                Deserializer::from_parts(value, self.options, self.visited),
-                visitor,
-            ),
+        match self.value.take() {
+            Some(value) => self.deserialize(value, |de| {
+                serde::Deserializer::deserialize_struct(de, "", fields, visitor)
+            }),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"struct variant",
@@ -592,6 +996,267 @@ impl Drop for RecursionGuard {
     }
 }
 
+// Error type of `ValueDeserializer`.
+//
+// To avoid any overhead when errors are not annotated, the path to the offending value is built
+// only when an error is returned: each level adds its key, starting from the innermost one.
+pub(crate) trait DeError: de::Error + From<Error> {
+    // If true, keys of table entries are kept to annotate errors
+    const KEY_PATH: bool;
+
+    fn add_key(&mut self, key: impl FnOnce() -> PathKey);
+
+    fn with_key(mut self, key: impl FnOnce() -> PathKey) -> Self {
+        self.add_key(key);
+        self
+    }
+}
+
+impl DeError for Error {
+    const KEY_PATH: bool = false;
+
+    #[inline]
+    fn add_key(&mut self, _key: impl FnOnce() -> PathKey) {}
+}
+
+// Problems found by a diagnostic report (see `LuaSerdeExt::from_value_report`), when collecting.
+//
+// A value of a wrong type is reported and replaced with a placeholder (e.g. `0` for numbers),
+// to continue deserialization and find further problems in a single pass.
+#[derive(Debug)]
+pub(crate) struct Issues<E>(Option<Rc<RefCell<Vec<E>>>>);
+
+impl<E> Default for Issues<E> {
+    fn default() -> Self {
+        Issues(None)
+    }
+}
+
+impl<E> Clone for Issues<E> {
+    fn clone(&self) -> Self {
+        Issues(self.0.clone())
+    }
+}
+
+impl<E> Issues<E> {
+    pub(crate) fn collect() -> Self {
+        Issues(Some(Rc::new(RefCell::new(Vec::new()))))
+    }
+
+    pub(crate) fn take(&self) -> Vec<E> {
+        (self.0.as_ref())
+            .map(|issues| issues.take())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    fn is_collecting(&self) -> bool {
+        self.0.is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        (self.0.as_ref())
+            .map(|issues| issues.borrow().len())
+            .unwrap_or(0)
+    }
+
+    // Reports `err` and returns the `placeholder` result, if collecting and it succeeds.
+    // Otherwise returns `err`.
+    fn recover<T>(&self, err: E, placeholder: impl FnOnce() -> StdResult<T, E>) -> StdResult<T, E> {
+        if let Some(issues) = &self.0 {
+            if let Ok(value) = placeholder() {
+                issues.borrow_mut().push(err);
+                return Ok(value);
+            }
+        }
+        Err(err)
+    }
+}
+
+impl<E: DeError> Issues<E> {
+    // Adds `key` to the path of the error or the issues found since `mark`
+    fn annotate<T>(
+        &self,
+        mark: usize,
+        result: StdResult<T, E>,
+        key: impl Fn() -> PathKey,
+    ) -> StdResult<T, E> {
+        if let Some(issues) = &self.0 {
+            for issue in &mut issues.borrow_mut()[mark..] {
+                issue.add_key(&key);
+            }
+        }
+        result.map_err(|err| err.with_key(key))
+    }
+}
+
+fn is_boolean(value: &Value) -> bool {
+    matches!(value, Value::Boolean(_))
+}
+
+fn is_integer<T: TryFrom<Integer>>(value: &Value) -> bool {
+    matches!(value, Value::Integer(i) if T::try_from(*i).is_ok())
+}
+
+fn is_number(value: &Value) -> bool {
+    matches!(value, Value::Integer(_) | Value::Number(_))
+}
+
+fn is_char(value: &Value) -> bool {
+    matches!(value, Value::String(s) if matches!(s.to_str(), Ok(s) if s.chars().count() == 1))
+}
+
+fn is_string(value: &Value) -> bool {
+    matches!(value, Value::String(s) if s.to_str().is_ok())
+}
+
+// Path from the root value to the offending value, e.g. `graphics.shadows`.
+// Keys are stored from the innermost one.
+#[derive(Debug, Default)]
+pub(crate) struct KeyPath(Vec<PathKey>);
+
+#[derive(Debug)]
+pub(crate) enum PathKey {
+    Field(StdString),
+    Index(i64),
+    Other(&'static str),
+}
+
+impl PathKey {
+    fn from_value(value: &Value) -> Self {
+        match value {
+            Value::String(s) => PathKey::Field(s.to_string_lossy().into_owned()),
+            #[allow(clippy::useless_conversion)]
+            Value::Integer(i) => PathKey::Index((*i).into()),
+            value => PathKey::Other(value.type_name()),
+        }
+    }
+}
+
+impl fmt::Display for KeyPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, key) in self.0.iter().rev().enumerate() {
+            match key {
+                PathKey::Field(name) if i == 0 => write!(f, "{}", name)?,
+                PathKey::Field(name) => write!(f, ".{}", name)?,
+                PathKey::Index(i) => write!(f, "[{}]", i)?,
+                PathKey::Other(type_name) => write!(f, "[<{}>]", type_name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Deserialization error annotated with the path to the offending value
+#[derive(Debug)]
+pub(crate) struct PathError {
+    pub(crate) path: KeyPath,
+    pub(crate) kind: ErrorKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum ErrorKind {
+    InvalidType {
+        message: StdString,
+        expected: StdString,
+        // Lua type name
+        found: StdString,
+    },
+    MissingField(&'static str),
+    UnknownField {
+        message: StdString,
+        field: StdString,
+    },
+    Message(StdString),
+    // Error returned by Lua (e.g. by a metamethod)
+    Lua(Error),
+}
+
+impl PathError {
+    fn new(kind: ErrorKind) -> Self {
+        let path = KeyPath::default();
+        PathError { path, kind }
+    }
+
+    // Converts to `Error::DeserializeError` with the path prepended to the message
+    pub(crate) fn into_error(self) -> Error {
+        let message = match self.kind {
+            ErrorKind::Lua(err) => return err,
+            _ if self.path.0.is_empty() => self.to_string(),
+            _ => format!("at {}: {}", self.path, self),
+        };
+        Error::DeserializeError(message)
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::InvalidType { ref message, .. } => write!(f, "{}", message),
+            ErrorKind::MissingField(field) => write!(f, "missing field `{}`", field),
+            ErrorKind::UnknownField { ref message, .. } => write!(f, "{}", message),
+            ErrorKind::Message(ref message) => write!(f, "{}", message),
+            ErrorKind::Lua(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl StdError for PathError {}
+
+impl de::Error for PathError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        PathError::new(ErrorKind::Message(msg.to_string()))
+    }
+
+    fn invalid_type(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        let found = match unexp {
+            de::Unexpected::Bool(_) => "boolean",
+            de::Unexpected::Unsigned(_) | de::Unexpected::Signed(_) => "integer",
+            de::Unexpected::Float(_) => "number",
+            de::Unexpected::Char(_) | de::Unexpected::Str(_) | de::Unexpected::Bytes(_) => "string",
+            de::Unexpected::Unit | de::Unexpected::Option => "nil",
+            de::Unexpected::Seq | de::Unexpected::Map => "table",
+            de::Unexpected::Other(name) => name,
+            _ => "value",
+        };
+        PathError::new(ErrorKind::InvalidType {
+            message: format!("invalid type: {}, expected {}", unexp, exp),
+            expected: exp.to_string(),
+            found: found.to_string(),
+        })
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        PathError::new(ErrorKind::MissingField(field))
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        // Use the message of the default implementation
+        let message = de::value::Error::unknown_field(field, expected).to_string();
+        let field = field.to_string();
+        PathError::new(ErrorKind::UnknownField { message, field })
+    }
+}
+
+impl From<Error> for PathError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::DeserializeError(message) => PathError::new(ErrorKind::Message(message)),
+            err => PathError::new(ErrorKind::Lua(err)),
+        }
+    }
+}
+
+impl DeError for PathError {
+    const KEY_PATH: bool = true;
+
+    #[inline]
+    fn add_key(&mut self, key: impl FnOnce() -> PathKey) {
+        self.path.0.push(key());
+    }
+}
+
 // Checks `options` and decides should we emit an error or skip next element
 fn check_value_if_skip(
     value: &Value,
@@ -623,10 +1288,10 @@ fn check_value_if_skip(
     Ok(false) // do not skip
 }
 
-fn serde_userdata<V>(
+fn serde_userdata<V, E: DeError>(
     ud: AnyUserData,
-    f: impl FnOnce(serde_value::Value) -> std::result::Result<V, serde_value::DeserializerError>,
-) -> Result<V> {
+    f: impl FnOnce(serde_value::Value) -> StdResult<V, serde_value::DeserializerError>,
+) -> StdResult<V, E> {
     let value = serde_value::to_value(ud).map_err(|err| Error::SerializeError(err.to_string()))?;
-    f(value).map_err(|err| Error::DeserializeError(err.to_string()))
+    f(value).map_err(de::Error::custom)
 }
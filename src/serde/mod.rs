@@ -1,5 +1,7 @@
 //! (De)Serialization support using serde.
 
+use std::cell::Cell;
+use std::marker::PhantomData;
 use std::os::raw::c_void;
 use std::ptr;
 
@@ -8,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::error::Result;
 use crate::ffi;
 use crate::lua::Lua;
+use crate::report::ConversionReport;
 use crate::table::Table;
 use crate::types::LightUserData;
 use crate::util::check_stack;
@@ -196,6 +199,69 @@ pub trait LuaSerdeExt<'lua> {
         value: Value<'lua>,
         options: de::Options,
     ) -> Result<T>;
+
+    /// Deserializes a [`Value`] into any serde deserializable object, reporting all found
+    /// problems instead of the first error.
+    ///
+    /// Values of a wrong type and (with [`DeserializeOptions::deny_unknown_fields`]) unknown
+    /// struct fields are reported with the path to the offending value, and deserialization
+    /// continues. Problems that cannot be skipped, like a missing struct field, stop it and are
+    /// reported last. See [`Lua::convert_report`] for details.
+    ///
+    /// Requires `feature = "serialize"`
+    ///
+    /// [`Value`]: crate::Value
+    /// [`DeserializeOptions::deny_unknown_fields`]: crate::DeserializeOptions::deny_unknown_fields
+    /// [`Lua::convert_report`]: crate::Lua::convert_report
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mlua::{Lua, Result, LuaSerdeExt};
+    /// use serde::Deserialize;
+    ///
+    /// fn main() -> Result<()> {
+    ///     #[derive(Deserialize)]
+    ///     #[allow(dead_code)]
+    ///     struct Config {
+    ///         name: String,
+    ///         threads: u32,
+    ///     }
+    ///
+    ///     let lua = Lua::new();
+    ///     let val = lua.load(r#"{threads = "four"}"#).eval()?;
+    ///     let report = lua.from_value_report::<Config>(val);
+    ///     let paths: Vec<_> = report.issues().iter().map(|i| i.path.as_str()).collect();
+    ///     assert_eq!(paths, ["threads", "name"]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    fn from_value_report<T: Deserialize<'lua>>(
+        &'lua self,
+        value: Value<'lua>,
+    ) -> ConversionReport<T> {
+        self.from_value_report_with(value, de::Options::default())
+    }
+
+    /// Deserializes a [`Value`] into any serde deserializable object with options, reporting
+    /// all found problems instead of the first error.
+    ///
+    /// See [`from_value_report`] for details.
+    ///
+    /// Requires `feature = "serialize"`
+    ///
+    /// [`Value`]: crate::Value
+    /// [`from_value_report`]: #method.from_value_report
+    #[allow(clippy::wrong_self_convention)]
+    fn from_value_report_with<T: Deserialize<'lua>>(
+        &'lua self,
+        value: Value<'lua>,
+        options: de::Options,
+    ) -> ConversionReport<T> {
+        report::from_value_report(value, options)
+    }
 }
 
 impl<'lua> LuaSerdeExt<'lua> for Lua {
@@ -204,6 +270,7 @@ impl<'lua> LuaSerdeExt<'lua> for Lua {
     }
 
     fn array_metatable(&'lua self) -> Table<'lua> {
+        let _guard = self.lock();
         unsafe {
             push_array_metatable(self.ref_thread());
             Table(self.pop_ref_thread())
@@ -239,6 +306,119 @@ impl<'lua> LuaSerdeExt<'lua> for Lua {
     }
 }
 
+/// Policy for Lua values that cannot be serialized.
+///
+/// Applies to functions, threads, errors, non-null light userdata and userdata that was not
+/// created using [`create_ser_userdata`] (or similar). Set using [`set_unsupported_policy`].
+///
+/// [`create_ser_userdata`]: crate::Lua::create_ser_userdata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsupportedPolicy {
+    /// Fail the serialization with an error.
+    ///
+    /// This is the default policy.
+    #[default]
+    Error,
+    /// Omit the value.
+    ///
+    /// Table entries with unsupported keys or values and unsupported sequence elements are
+    /// skipped. Unsupported value serialized directly becomes a unit (`null`).
+    Skip,
+    /// Serialize a descriptive string instead of the value, eg. `"<userdata MyType 0x...>"`
+    /// or `"<function 0x...>"`.
+    Placeholder,
+}
+
+thread_local! {
+    static UNSUPPORTED_POLICY: Cell<UnsupportedPolicy> = const { Cell::new(UnsupportedPolicy::Error) };
+}
+
+/// Sets the [`UnsupportedPolicy`] for serialization of Lua values on the current thread.
+///
+/// The policy is applied until the returned guard is dropped, then the previous policy is
+/// restored.
+///
+/// # Example
+///
+/// ```
+/// use mlua::{Lua, Result, Value};
+/// use mlua::serde::{set_unsupported_policy, UnsupportedPolicy};
+///
+/// fn main() -> Result<()> {
+///     let lua = Lua::new();
+///     let value: Value = lua.load("{ name = 'test', callback = print }").eval()?;
+///
+///     let _guard = set_unsupported_policy(UnsupportedPolicy::Skip);
+///     assert_eq!(serde_json::to_string(&value).unwrap(), r#"{"name":"test"}"#);
+///
+///     Ok(())
+/// }
+/// ```
+#[must_use = "the policy is reset when the guard is dropped"]
+pub fn set_unsupported_policy(policy: UnsupportedPolicy) -> UnsupportedPolicyGuard {
+    let prev = UNSUPPORTED_POLICY.with(|p| p.replace(policy));
+    UnsupportedPolicyGuard {
+        prev,
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard returned by [`set_unsupported_policy`] that restores the previous policy on drop.
+#[derive(Debug)]
+pub struct UnsupportedPolicyGuard {
+    prev: UnsupportedPolicy,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for UnsupportedPolicyGuard {
+    fn drop(&mut self) {
+        UNSUPPORTED_POLICY.with(|p| p.set(self.prev));
+    }
+}
+
+pub(crate) fn unsupported_policy() -> UnsupportedPolicy {
+    UNSUPPORTED_POLICY.with(|p| p.get())
+}
+
+// Returns `true` if the value should be omitted by the `Skip` policy
+pub(crate) fn skip_unsupported(value: &Value) -> bool {
+    unsupported_policy() == UnsupportedPolicy::Skip && !is_supported(value)
+}
+
+fn is_supported(value: &Value) -> bool {
+    match value {
+        Value::Error(_) | Value::Function(_) | Value::Thread(_) => false,
+        Value::LightUserData(ud) => ud.0.is_null(),
+        Value::UserData(ud) => ud.is_serializable(),
+        _ => true,
+    }
+}
+
+// Serializes a value of unsupported type according to the current policy
+pub(crate) fn serialize_unsupported<S: serde::Serializer>(
+    value: &Value,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match unsupported_policy() {
+        UnsupportedPolicy::Error => {
+            let msg = format!("cannot serialize <{}>", value.type_name());
+            Err(serde::ser::Error::custom(msg))
+        }
+        UnsupportedPolicy::Skip => serializer.serialize_unit(),
+        UnsupportedPolicy::Placeholder => {
+            let placeholder = match value {
+                Value::Error(err) => format!("<error: {}>", err),
+                Value::UserData(ud) => match ud.type_name().ok().flatten() {
+                    Some(name) => format!("<userdata {} {:?}>", name, ud.to_pointer()),
+                    None => format!("<userdata {:?}>", ud.to_pointer()),
+                },
+                _ => format!("<{} {:?}>", value.type_name(), value.to_pointer()),
+            };
+            serializer.serialize_str(&placeholder)
+        }
+    }
+}
+
 // Uses 2 stack spaces and calls checkstack.
 pub(crate) unsafe fn init_metatables(state: *mut ffi::lua_State) -> Result<()> {
     check_stack(state, 2)?;
@@ -262,6 +442,7 @@ pub(crate) unsafe fn push_array_metatable(state: *mut ffi::lua_State) {
 static ARRAY_METATABLE_REGISTRY_KEY: u8 = 0;
 
 pub mod de;
+pub(crate) mod report;
 pub mod ser;
 
 #[doc(inline)]
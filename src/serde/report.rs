@@ -0,0 +1,49 @@
+//! Diagnostic deserialization that collects all problems found in a Lua value.
+
+use serde::Deserialize;
+
+use crate::report::{join_path, ConversionIssue, ConversionReport};
+use crate::value::Value;
+
+use super::de::{ErrorKind, Issues, Options, PathError, ValueDeserializer};
+
+// Deserializes `value` into `T`, collecting all found problems in a single pass.
+//
+// Serde visitors cannot be resumed after an error, so problems that can be predicted from the
+// requested type (values of a wrong type, unknown fields) are reported and replaced with a
+// placeholder. Any other problem stops deserialization and is reported last.
+pub(crate) fn from_value_report<'lua, T: Deserialize<'lua>>(
+    value: Value<'lua>,
+    options: Options,
+) -> ConversionReport<T> {
+    let issues = Issues::collect();
+    let result = T::deserialize(ValueDeserializer::new(value, options, issues.clone()));
+    let mut found = issues.take();
+    match result {
+        Ok(value) if found.is_empty() => return ConversionReport::Value(value),
+        Ok(_) => {}
+        Err(err) => found.push(err),
+    }
+    ConversionReport::Issues(found.into_iter().map(into_issue).collect())
+}
+
+fn into_issue(err: PathError) -> ConversionIssue {
+    let path = err.path.to_string();
+    match err.kind {
+        ErrorKind::InvalidType {
+            expected, found, ..
+        } => {
+            let message = format!("expected {}, got {}", expected, found);
+            ConversionIssue::new(path, expected, found, message)
+        }
+        ErrorKind::MissingField(field) => {
+            let message = format!("missing field `{}`", field);
+            ConversionIssue::new(join_path(&path, field), "value", "nil", message)
+        }
+        ErrorKind::UnknownField { message, field } => {
+            ConversionIssue::new(join_path(&path, &field), "valid value", "value", message)
+        }
+        ErrorKind::Message(message) => ConversionIssue::new(path, "valid value", "value", message),
+        ErrorKind::Lua(err) => ConversionIssue::from_error(&path, "valid value", "value", &err),
+    }
+}
@@ -319,6 +319,7 @@ impl<'lua> ser::SerializeSeq for SerializeVec<'lua> {
     where
         T: Serialize + ?Sized,
     {
+        let _guard = self.table.0.lua.lock();
         let lua = self.table.0.lua;
         let state = lua.state();
         let value = lua.to_value_with(value, self.options)?;
@@ -395,7 +396,7 @@ impl<'lua> ser::SerializeTupleVariant for SerializeTupleVariant<'lua> {
         T: Serialize + ?Sized,
     {
         let lua = self.table.0.lua;
-        let idx = self.table.raw_len() + 1;
+        let idx = self.table.raw_len() as Integer + 1;
         self.table
             .raw_insert(idx, lua.to_value_with(value, self.options)?)
     }
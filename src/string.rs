@@ -1,9 +1,13 @@
 use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
+use std::ops::{Bound, RangeBounds};
 use std::os::raw::c_void;
 use std::string::String as StdString;
 use std::{fmt, slice, str};
 
+use bstr::BStr;
+
 #[cfg(feature = "serialize")]
 use {
     serde::ser::{Serialize, Serializer},
@@ -12,7 +16,11 @@ use {
 
 use crate::error::{Error, Result};
 use crate::ffi;
-use crate::types::LuaRef;
+use crate::types::{LuaRef, RegistryKey};
+
+// Strings up to this length are always interned by Lua (`LUAI_MAXSHORTLEN`)
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+const MAX_SHORT_STRING_LEN: usize = 40;
 
 /// Handle to an internal Lua string.
 ///
@@ -20,6 +28,93 @@ use crate::types::LuaRef;
 #[derive(Clone)]
 pub struct String<'lua>(pub(crate) LuaRef<'lua>);
 
+/// Owned handle to an internal Lua string.
+///
+/// The handle keeps the originating Lua state alive, so the string bytes can be accessed
+/// at any time without copying them out of Lua.
+#[cfg(feature = "unstable")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+#[derive(Clone)]
+pub struct OwnedString(pub(crate) crate::types::LuaOwnedRef);
+
+/// Lua string interned using [`Lua::intern`].
+///
+/// The string is anchored in the Lua registry, so [`String::eq_interned`] can compare Lua strings
+/// with it by identity instead of comparing the bytes.
+///
+/// [`Lua::intern`]: crate::Lua::intern
+#[derive(Debug)]
+pub struct InternedString {
+    key: RegistryKey,
+    addr: usize,
+    len: usize,
+}
+
+impl InternedString {
+    pub(crate) fn new(s: &String, key: RegistryKey) -> Self {
+        let bytes = s.as_bytes();
+        InternedString {
+            key,
+            addr: bytes.as_ptr() as usize,
+            len: bytes.len(),
+        }
+    }
+
+    /// Returns the registry key of the interned string.
+    pub const fn registry_key(&self) -> &RegistryKey {
+        &self.key
+    }
+
+    /// Returns the length of the interned string in bytes.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the interned string is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl OwnedString {
+    /// Get borrowed handle to the underlying Lua string.
+    pub const fn to_ref(&self) -> String {
+        String(self.0.to_ref())
+    }
+
+    /// Get a `&str` slice if the Lua string is valid UTF-8. See [`String::to_str`].
+    #[inline]
+    pub fn to_str(&self) -> Result<&str> {
+        to_str(self.as_bytes())
+    }
+
+    /// Converts this string to a [`Cow<str>`]. See [`String::to_string_lossy`].
+    #[inline]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        StdString::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Returns `true` if the string is valid UTF-8. See [`String::is_utf8`].
+    #[inline]
+    pub fn is_utf8(&self) -> bool {
+        str::from_utf8(self.as_bytes()).is_ok()
+    }
+
+    /// Get the bytes that make up this string. See [`String::as_bytes`].
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let nulled = self.as_bytes_with_nul();
+        &nulled[..nulled.len() - 1]
+    }
+
+    /// Get the bytes that make up this string, including the trailing nul byte.
+    #[inline]
+    pub fn as_bytes_with_nul(&self) -> &[u8] {
+        unsafe { ref_bytes_with_nul(&self.0.to_ref()) }
+    }
+}
+
 impl<'lua> String<'lua> {
     /// Get a `&str` slice if the Lua string is valid UTF-8.
     ///
@@ -41,11 +136,7 @@ impl<'lua> String<'lua> {
     /// ```
     #[inline]
     pub fn to_str(&self) -> Result<&str> {
-        str::from_utf8(self.as_bytes()).map_err(|e| Error::FromLuaConversionError {
-            from: "string",
-            to: "&str",
-            message: Some(e.to_string()),
-        })
+        to_str(self.as_bytes())
     }
 
     /// Converts this string to a [`Cow<str>`].
@@ -71,6 +162,25 @@ impl<'lua> String<'lua> {
         StdString::from_utf8_lossy(self.as_bytes())
     }
 
+    /// Returns `true` if the string is valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    ///
+    /// assert!(lua.create_string("hello")?.is_utf8());
+    /// assert!(!lua.create_string(b"test\xff")?.is_utf8());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn is_utf8(&self) -> bool {
+        str::from_utf8(self.as_bytes()).is_ok()
+    }
+
     /// Get the bytes that make up this string.
     ///
     /// The returned slice will not contain the terminating nul byte, but will contain any nul
@@ -96,20 +206,90 @@ impl<'lua> String<'lua> {
 
     /// Get the bytes that make up this string, including the trailing nul byte.
     pub fn as_bytes_with_nul(&self) -> &[u8] {
-        let ref_thread = self.0.lua.ref_thread();
-        unsafe {
-            mlua_debug_assert!(
-                ffi::lua_type(ref_thread, self.0.index) == ffi::LUA_TSTRING,
-                "string ref is not string type"
-            );
-
-            let mut size = 0;
-            // This will not trigger a 'm' error, because the reference is guaranteed to be of
-            // string type
-            let data = ffi::lua_tolstring(ref_thread, self.0.index, &mut size);
-
-            slice::from_raw_parts(data as *const u8, size + 1)
+        unsafe { ref_bytes_with_nul(&self.0) }
+    }
+
+    /// Returns a new Lua string containing the bytes of this string in the given `range`.
+    ///
+    /// Indices are byte offsets (the string does not have to be valid UTF-8). Similar to Lua
+    /// `string.sub`, out of range bounds are clamped to the string length and an empty string is
+    /// returned if the start is past the end.
+    ///
+    /// The bytes are copied directly from the original Lua string, without intermediate
+    /// allocations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    ///
+    /// let s = lua.create_string("hello, world")?;
+    /// assert_eq!(s.sub(7..)?, "world");
+    /// assert_eq!(s.sub(..5)?, "hello");
+    /// assert_eq!(s.sub(10..100)?, "ld");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sub(&self, range: impl RangeBounds<usize>) -> Result<String<'lua>> {
+        let bytes = self.as_bytes();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => bytes.len(),
+        };
+        let end = end.min(bytes.len());
+        let start = start.min(end);
+
+        if start == 0 && end == bytes.len() {
+            return Ok(self.clone());
         }
+        self.0.lua.create_string(&bytes[start..end])
+    }
+
+    /// Checks whether the string is equal to the `interned` string.
+    ///
+    /// Lua interns short strings, so the strings are compared by identity without reading
+    /// the bytes. Only long strings (longer than 40 bytes in Lua 5.2+) are compared by content.
+    /// Returns `false` if the interned string belongs to a different Lua state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, String};
+    /// # fn main() -> Result<()> {
+    /// let lua = Lua::new();
+    /// let get = lua.intern("get")?;
+    ///
+    /// let s: String = lua.load(r#"("GET"):lower()"#).eval()?;
+    /// assert!(s.eq_interned(&get));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eq_interned(&self, interned: &InternedString) -> bool {
+        let bytes = self.as_bytes();
+        if bytes.len() != interned.len || !self.0.lua.owns_registry_value(&interned.key) {
+            return false;
+        }
+        if bytes.as_ptr() as usize == interned.addr {
+            return true;
+        }
+
+        // Long strings are not interned
+        #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+        if bytes.len() > MAX_SHORT_STRING_LEN {
+            return (self.0.lua.registry_value::<String>(&interned.key))
+                .map(|s| s.as_bytes() == bytes)
+                .unwrap_or(false);
+        }
+
+        false
     }
 
     /// Converts the string to a generic C pointer.
@@ -119,40 +299,116 @@ impl<'lua> String<'lua> {
     /// Typically this function is used only for hashing and debug information.
     #[inline]
     pub fn to_pointer(&self) -> *const c_void {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
         unsafe { ffi::lua_topointer(ref_thread, self.0.index) }
     }
+
+    /// Convert this handle to owned version.
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+    #[inline]
+    pub fn into_owned(self) -> OwnedString {
+        OwnedString(self.0.into_owned())
+    }
 }
 
+fn to_str(bytes: &[u8]) -> Result<&str> {
+    str::from_utf8(bytes).map_err(|e| Error::FromLuaConversionError {
+        from: "string",
+        to: "&str",
+        message: Some(e.to_string()),
+    })
+}
+
+// Returns the string bytes (including the trailing nul byte) of the reference.
+// The bytes are valid while the string is referenced, which can outlive the `LuaRef` handle.
+unsafe fn ref_bytes_with_nul<'a>(lua_ref: &LuaRef) -> &'a [u8] {
+    let _guard = lua_ref.lua.lock();
+    let ref_thread = lua_ref.lua.ref_thread();
+    mlua_debug_assert!(
+        ffi::lua_type(ref_thread, lua_ref.index) == ffi::LUA_TSTRING,
+        "string ref is not string type"
+    );
+
+    let mut size = 0;
+    // This will not trigger a 'm' error, because the reference is guaranteed to be of
+    // string type
+    let data = ffi::lua_tolstring(ref_thread, lua_ref.index, &mut size);
+
+    slice::from_raw_parts(data as *const u8, size + 1)
+}
+
+/// Formats the string as a Rust string literal, or as a byte string literal (eg. `b"abc\xff"`)
+/// if the string is not valid UTF-8.
+///
+/// The precision limits the number of displayed bytes, eg. `format!("{:.16?}", s)` shows at most
+/// 16 bytes followed by `...` if the string is longer.
 impl<'lua> fmt::Debug for String<'lua> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let bytes = self.as_bytes();
+        let mut len = bytes.len().min(f.precision().unwrap_or(usize::MAX));
+
         // Check if the string is valid utf8
         if let Ok(s) = str::from_utf8(bytes) {
-            return s.fmt(f);
-        }
-
-        // Format as bytes
-        write!(f, "b\"")?;
-        for &b in bytes {
-            // https://doc.rust-lang.org/reference/tokens.html#byte-escapes
-            match b {
-                b'\n' => write!(f, "\\n")?,
-                b'\r' => write!(f, "\\r")?,
-                b'\t' => write!(f, "\\t")?,
-                b'\\' | b'"' => write!(f, "\\{}", b as char)?,
-                b'\0' => write!(f, "\\0")?,
-                // ASCII printable
-                0x20..=0x7e => write!(f, "{}", b as char)?,
-                _ => write!(f, "\\x{b:02x}")?,
+            while !s.is_char_boundary(len) {
+                len -= 1;
             }
+            write!(f, "{:?}", &s[..len])?;
+        } else {
+            fmt_bytes(f, &bytes[..len])?;
         }
-        write!(f, "\"")?;
 
+        if len < bytes.len() {
+            write!(f, "...")?;
+        }
         Ok(())
     }
 }
 
+// Formats bytes as a byte string literal
+fn fmt_bytes(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    write!(f, "b\"")?;
+    for &b in bytes {
+        // https://doc.rust-lang.org/reference/tokens.html#byte-escapes
+        match b {
+            b'\n' => write!(f, "\\n")?,
+            b'\r' => write!(f, "\\r")?,
+            b'\t' => write!(f, "\\t")?,
+            b'\\' | b'"' => write!(f, "\\{}", b as char)?,
+            b'\0' => write!(f, "\\0")?,
+            // ASCII printable
+            0x20..=0x7e => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\x{b:02x}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Formats the string as UTF-8, replacing invalid sequences with
+/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+///
+/// [U+FFFD]: std::char::REPLACEMENT_CHARACTER
+impl<'lua> fmt::Display for String<'lua> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(BStr::new(self.as_bytes()), f)
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl fmt::Debug for OwnedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_ref().fmt(f)
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl fmt::Display for OwnedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(BStr::new(self.as_bytes()), f)
+    }
+}
+
 impl<'lua> AsRef<[u8]> for String<'lua> {
     fn as_ref(&self) -> &[u8] {
         self.as_bytes()
@@ -184,12 +440,84 @@ where
 
 impl<'lua> Eq for String<'lua> {}
 
+// Strings are ordered lexicographically by bytes, which matches the Lua `<` operator
+// in the "C" locale.
+impl<'lua, T> PartialOrd<T> for String<'lua>
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_bytes().partial_cmp(other.as_ref())
+    }
+}
+
+impl<'lua> Ord for String<'lua> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+// The hash is computed from the string bytes (consistent with `PartialEq<[u8]>`).
+// The bytes are read on every call, the hash is not cached.
 impl<'lua> Hash for String<'lua> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.as_bytes().hash(state);
     }
 }
 
+#[cfg(feature = "unstable")]
+impl AsRef<[u8]> for OwnedString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Borrow<[u8]> for OwnedString {
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<T> PartialEq<T> for OwnedString
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.as_bytes() == other.as_ref()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Eq for OwnedString {}
+
+#[cfg(feature = "unstable")]
+impl<T> PartialOrd<T> for OwnedString
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_bytes().partial_cmp(other.as_ref())
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Ord for OwnedString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Hash for OwnedString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+// Strings that are not valid UTF-8 are serialized as bytes (`serialize_bytes`), so binary data
+// is not lost. How the bytes are represented depends on the format (eg. JSON uses arrays).
 #[cfg(feature = "serialize")]
 impl<'lua> Serialize for String<'lua> {
     fn serialize<S>(&self, serializer: S) -> StdResult<S::Ok, S::Error>
@@ -208,4 +536,9 @@ mod assertions {
     use super::*;
 
     static_assertions::assert_not_impl_any!(String: Send);
+
+    #[cfg(all(feature = "unstable", not(feature = "send")))]
+    static_assertions::assert_not_impl_any!(OwnedString: Send);
+    #[cfg(all(feature = "unstable", feature = "send"))]
+    static_assertions::assert_impl_all!(OwnedString: Send);
 }
@@ -0,0 +1,162 @@
+use crate::error::{Error, Result};
+use crate::function::Function;
+use crate::lua::{ExternalMemory, Lua};
+use crate::multi::Variadic;
+use crate::string::String;
+use crate::table::Table;
+use crate::userdata::{AnyUserData, MetaMethod, UserData, UserDataMethods};
+use crate::value::{MultiValue, Value};
+
+// Name of the registry value to store `string.format` function
+const STRING_FORMAT_KEY: &str = "__mlua_string_format";
+
+// A mutable byte buffer to build strings incrementally.
+//
+// Installed by `Lua::install_string_builder`. The allocated memory is accounted against the Lua
+// memory limit.
+pub(crate) struct StringBuilder {
+    buf: Vec<u8>,
+    memory: ExternalMemory,
+}
+
+impl StringBuilder {
+    fn new(lua: &Lua, capacity: usize) -> Result<Self> {
+        let mut builder = StringBuilder {
+            buf: Vec::new(),
+            memory: lua.external_memory(),
+        };
+        builder.reserve(capacity)?;
+        Ok(builder)
+    }
+
+    // Reserves capacity for at least `additional` more bytes.
+    // The memory is accounted before allocating.
+    fn reserve(&mut self, additional: usize) -> Result<()> {
+        let required = self.buf.len().saturating_add(additional);
+        if required <= self.buf.capacity() {
+            return Ok(());
+        }
+        let new_capacity = required.max(self.buf.capacity().saturating_mul(2));
+        self.memory.grow(new_capacity - self.memory.size())?;
+        self.buf.reserve_exact(new_capacity - self.buf.len());
+        Ok(())
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<()> {
+        self.reserve(bytes.len())?;
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    // Creates the `strbuf` library table
+    pub(crate) fn create_library<'lua>(lua: &'lua Lua) -> Result<Table<'lua>> {
+        #[cfg(feature = "luajit")]
+        {
+            let buffer: Option<Table> = lua
+                .load(
+                    r#"
+                    local ok, buffer = pcall(require, "string.buffer")
+                    if ok then return buffer end
+                    "#,
+                )
+                .set_name("_mlua_string_buffer")
+                .eval()?;
+            if let Some(buffer) = buffer {
+                let strbuf = lua.create_table()?;
+                strbuf.raw_set("new", buffer.get::<_, Function>("new")?)?;
+                return Ok(strbuf);
+            }
+        }
+
+        // `putf` uses `string.format` function from the standard library
+        let format = lua
+            .globals()
+            .get::<_, Table>("string")
+            .and_then(|string| string.get::<_, Function>("format"));
+        if let Ok(format) = format {
+            lua.set_named_registry_value(STRING_FORMAT_KEY, format)?;
+        }
+
+        let strbuf = lua.create_table()?;
+        let new = lua.create_function(|lua, capacity: Option<usize>| {
+            StringBuilder::new(lua, capacity.unwrap_or_default())
+        })?;
+        strbuf.raw_set("new", new)?;
+        Ok(strbuf)
+    }
+}
+
+impl UserData for StringBuilder {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_function(
+            "put",
+            |lua, (this, args): (AnyUserData, Variadic<Value>)| {
+                for value in args {
+                    match value {
+                        // Appending the buffer to itself
+                        Value::UserData(ref ud) if *ud == this => {
+                            let mut this = this.borrow_mut::<StringBuilder>()?;
+                            let len = this.buf.len();
+                            this.reserve(len)?;
+                            this.buf.extend_from_within(..len);
+                        }
+                        value => with_bytes(lua, value, |bytes| {
+                            this.borrow_mut::<StringBuilder>()?.put(bytes)
+                        })?,
+                    }
+                }
+                Ok(this)
+            },
+        );
+
+        methods.add_function("putf", |lua, (this, args): (AnyUserData, MultiValue)| {
+            let format = lua
+                .named_registry_value::<Option<Function>>(STRING_FORMAT_KEY)?
+                .ok_or_else(|| Error::RuntimeError("string.format is not available".into()))?;
+            let s = format.call::<_, String>(args)?;
+            this.borrow_mut::<StringBuilder>()?.put(s.as_bytes())?;
+            Ok(this)
+        });
+
+        methods.add_method("tostring", |lua, this, ()| lua.create_string(&this.buf));
+
+        methods.add_function("reset", |_, this: AnyUserData| {
+            this.borrow_mut::<StringBuilder>()?.buf.clear();
+            Ok(this)
+        });
+
+        methods.add_meta_method(MetaMethod::ToString, |lua, this, ()| {
+            lua.create_string(&this.buf)
+        });
+
+        methods.add_meta_method(MetaMethod::Len, |_, this, ()| Ok(this.buf.len()));
+
+        methods.add_meta_function(MetaMethod::Concat, |lua, (lhs, rhs): (Value, Value)| {
+            let mut bytes = Vec::new();
+            for value in [lhs, rhs] {
+                with_bytes(lua, value, |b| {
+                    bytes.extend_from_slice(b);
+                    Ok(())
+                })?;
+            }
+            lua.create_string(&bytes)
+        });
+    }
+}
+
+// Calls `f` with the content of a string, number or string builder value
+fn with_bytes<R>(lua: &Lua, value: Value, f: impl FnOnce(&[u8]) -> Result<R>) -> Result<R> {
+    match value {
+        Value::UserData(ud) if ud.is::<StringBuilder>() => f(&ud.borrow::<StringBuilder>()?.buf),
+        Value::String(s) => f(s.as_bytes()),
+        Value::Integer(_) | Value::Number(_) => match lua.coerce_string(value)? {
+            Some(s) => f(s.as_bytes()),
+            None => unreachable!("numbers are always convertible to strings"),
+        },
+        _ => Err(Error::FromLuaConversionError {
+            from: value.type_name(),
+            to: "string",
+            message: Some("expected string, number or string builder".to_string()),
+        }),
+    }
+}
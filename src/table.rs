@@ -1,9 +1,15 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::os::raw::c_void;
+use std::{ptr, slice};
+
+use rustc_hash::{FxHashMap, FxHashSet};
 
 #[cfg(feature = "serialize")]
 use {
-    rustc_hash::FxHashSet,
+    crate::serde::{skip_unsupported, UnsupportedPolicy},
     serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer},
     std::{cell::RefCell, result::Result as StdResult},
 };
@@ -11,10 +17,16 @@ use {
 use crate::error::{Error, Result};
 use crate::ffi;
 use crate::function::Function;
+use crate::lua::Lua;
+use crate::report::key_path;
+use crate::string::String;
 use crate::types::{Integer, LuaRef};
-use crate::util::{assert_stack, check_stack, StackGuard};
+use crate::util::{assert_stack, check_stack, push_string, StackGuard};
 use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, Nil, Value};
 
+#[cfg(not(feature = "luau"))]
+use std::os::raw::c_int;
+
 #[cfg(feature = "async")]
 use {futures_core::future::LocalBoxFuture, futures_util::future};
 
@@ -28,12 +40,94 @@ pub struct Table<'lua>(pub(crate) LuaRef<'lua>);
 #[derive(Clone, Debug)]
 pub struct OwnedTable(pub(crate) crate::types::LuaOwnedRef);
 
+/// Methods of [`OwnedTable`] mirror the [`Table`] API.
+///
+/// The handle keeps the originating Lua state alive, so the methods never observe a dropped
+/// state. Values returned by the methods borrow the handle; use owned types (eg. [`OwnedTable`]
+/// or Rust types) to keep them longer.
 #[cfg(feature = "unstable")]
 impl OwnedTable {
     /// Get borrowed handle to the underlying Lua table.
     pub const fn to_ref(&self) -> Table {
         Table(self.0.to_ref())
     }
+
+    /// Sets a key-value pair in the table. See [`Table::set`].
+    pub fn set<'lua, K: IntoLua<'lua>, V: IntoLua<'lua>>(
+        &'lua self,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        self.to_ref().set(key, value)
+    }
+
+    /// Gets the value associated to `key` from the table. See [`Table::get`].
+    pub fn get<'lua, K: IntoLua<'lua>, V: FromLua<'lua>>(&'lua self, key: K) -> Result<V> {
+        self.to_ref().get(key)
+    }
+
+    /// Checks whether the table contains a non-nil value for `key`. See [`Table::contains_key`].
+    pub fn contains_key<'lua, K: IntoLua<'lua>>(&'lua self, key: K) -> Result<bool> {
+        self.to_ref().contains_key(key)
+    }
+
+    /// Appends a value to the back of the table. See [`Table::push`].
+    pub fn push<'lua, V: IntoLua<'lua>>(&'lua self, value: V) -> Result<()> {
+        self.to_ref().push(value)
+    }
+
+    /// Removes the last element from the table and returns it. See [`Table::pop`].
+    pub fn pop<'lua, V: FromLua<'lua>>(&'lua self) -> Result<V> {
+        self.to_ref().pop()
+    }
+
+    /// Sets a key-value pair without invoking metamethods. See [`Table::raw_set`].
+    pub fn raw_set<'lua, K: IntoLua<'lua>, V: IntoLua<'lua>>(
+        &'lua self,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        self.to_ref().raw_set(key, value)
+    }
+
+    /// Gets the value associated to `key` without invoking metamethods. See [`Table::raw_get`].
+    pub fn raw_get<'lua, K: IntoLua<'lua>, V: FromLua<'lua>>(&'lua self, key: K) -> Result<V> {
+        self.to_ref().raw_get(key)
+    }
+
+    /// Returns the result of the Lua `#` operator. See [`Table::len`].
+    pub fn len(&self) -> Result<Integer> {
+        self.to_ref().len()
+    }
+
+    /// Returns the result of the Lua `#` operator, without invoking the `__len` metamethod.
+    /// See [`Table::raw_len`].
+    pub fn raw_len(&self) -> usize {
+        self.to_ref().raw_len()
+    }
+
+    /// Returns `true` if the table is empty, without invoking metamethods.
+    /// See [`Table::is_empty`].
+    pub fn is_empty(&self) -> bool {
+        self.to_ref().is_empty()
+    }
+
+    /// Returns an iterator over the pairs of the table. See [`Table::pairs`].
+    ///
+    /// Unlike [`Table::pairs`] it does not consume the handle. The iterator holds its own handle
+    /// to the table and yields owned values (eg. Rust types or [`OwnedTable`]), so it does not
+    /// borrow `self`.
+    pub fn pairs<K, V>(&self) -> OwnedTablePairs<K, V>
+    where
+        K: for<'lua> FromLua<'lua>,
+        V: for<'lua> FromLua<'lua>,
+    {
+        OwnedTablePairs {
+            table: self.clone(),
+            key: Some(None),
+            _phantom: PhantomData,
+        }
+    }
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -72,11 +166,15 @@ impl<'lua> Table<'lua> {
     ///
     /// [`raw_set`]: #method.raw_set
     pub fn set<K: IntoLua<'lua>, V: IntoLua<'lua>>(&self, key: K, value: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         // Fast track
         if !self.has_metatable() {
             return self.raw_set(key, value);
         }
 
+        #[cfg(not(feature = "luau"))]
+        self.check_frozen_write()?;
+
         let lua = self.0.lua;
         let key = key.into_lua(lua)?;
         let value = value.into_lua(lua)?;
@@ -118,6 +216,7 @@ impl<'lua> Table<'lua> {
     ///
     /// [`raw_get`]: #method.raw_get
     pub fn get<K: IntoLua<'lua>, V: FromLua<'lua>>(&self, key: K) -> Result<V> {
+        let _guard = self.0.lua.lock();
         // Fast track
         if !self.has_metatable() {
             return self.raw_get(key);
@@ -140,6 +239,115 @@ impl<'lua> Table<'lua> {
         V::from_lua(value, lua)
     }
 
+    /// Gets the value associated to `key` from the table, following at most `max_depth`
+    /// `__index` metamethods.
+    ///
+    /// Works like [`get`], but walks the `__index` chain in Rust and returns
+    /// [`Error::MetaIndexDepthExceeded`] if the value is not found after `max_depth` hops.
+    /// Every followed `__index` counts as one hop, including the last one when it's a function,
+    /// so with `max_depth` of 0 only the table itself is searched (see also [`raw_get`]).
+    ///
+    /// This is useful to bound the time spent reading from tables with untrusted metatables.
+    /// Note that [`get`] never hangs on looping chains, as Lua raises an error after a
+    /// fixed number of hops (100 in Lua 5.1 and Luau, 2000 in Lua 5.2+).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let obj: Table = lua
+    ///     .load("setmetatable({}, { __index = setmetatable({}, { __index = { x = 1 } }) })")
+    ///     .eval()?;
+    /// assert_eq!(obj.get_with_depth::<_, i32>("x", 2)?, 1);
+    /// assert!(obj.get_with_depth::<_, i32>("x", 1).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`get`]: #method.get
+    /// [`raw_get`]: #method.raw_get
+    pub fn get_with_depth<K: IntoLua<'lua>, V: FromLua<'lua>>(
+        &self,
+        key: K,
+        max_depth: u32,
+    ) -> Result<V> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let key = key.into_lua(lua)?;
+
+        let mut table = self.clone();
+        let mut depth = 0;
+        loop {
+            let value = table.raw_get::<_, Value>(key.clone())?;
+            if value != Value::Nil {
+                return V::from_lua(value, lua);
+            }
+            let index = match table.get_metatable() {
+                Some(mt) => mt.raw_get::<_, Value>("__index")?,
+                None => Value::Nil,
+            };
+            if index == Value::Nil {
+                return V::from_lua(Value::Nil, lua);
+            }
+            if depth == max_depth {
+                return Err(Error::MetaIndexDepthExceeded { max_depth });
+            }
+            depth += 1;
+
+            let value = match index {
+                Value::Table(t) => {
+                    table = t;
+                    continue;
+                }
+                Value::Function(f) => f.call::<_, Value>((table, key))?,
+                // Other values (eg. userdata) are indexed as usual
+                index => unsafe {
+                    let state = lua.state();
+                    let _sg = StackGuard::new(state);
+                    check_stack(state, 4)?;
+
+                    lua.push_value(index)?;
+                    lua.push_value(key)?;
+                    protect_lua!(state, 2, 1, fn(state) ffi::lua_gettable(state, -2))?;
+                    lua.pop_value()
+                },
+            };
+            return V::from_lua(value, lua);
+        }
+    }
+
+    /// Gets the value associated to `key` from the table, or `default` if the value is `nil`.
+    ///
+    /// Unlike `get::<_, Option<V>>(key)?.unwrap_or(default)`, the returned conversion error
+    /// includes the key when the value is present but cannot be converted to `V`.
+    /// May invoke the `__index` metamethod, see [`get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let config: Table = lua.load("{ retries = 3, timeout = 'soon' }").eval()?;
+    /// assert_eq!(config.get_or("retries", 1)?, 3);
+    /// assert_eq!(config.get_or("delay", 10)?, 10);
+    /// assert!(config.get_or("timeout", 30).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`get`]: #method.get
+    pub fn get_or<K: IntoLua<'lua>, V: FromLua<'lua>>(&self, key: K, default: V) -> Result<V> {
+        let lua = self.0.lua;
+        let key = key.into_lua(lua)?;
+        match self.get::<_, Value>(key.clone())? {
+            Value::Nil => Ok(default),
+            value => V::from_lua(value, lua).map_err(|err| err.with_key(&key_path("", &key))),
+        }
+    }
+
     /// Checks whether the table contains a non-nil value for `key`.
     pub fn contains_key<K: IntoLua<'lua>>(&self, key: K) -> Result<bool> {
         Ok(self.get::<_, Value>(key)? != Value::Nil)
@@ -147,11 +355,15 @@ impl<'lua> Table<'lua> {
 
     /// Appends a value to the back of the table.
     pub fn push<V: IntoLua<'lua>>(&self, value: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         // Fast track
         if !self.has_metatable() {
             return self.raw_push(value);
         }
 
+        #[cfg(not(feature = "luau"))]
+        self.check_frozen_write()?;
+
         let lua = self.0.lua;
         let state = lua.state();
         let value = value.into_lua(lua)?;
@@ -171,6 +383,7 @@ impl<'lua> Table<'lua> {
 
     /// Removes the last element from the table and returns it.
     pub fn pop<V: FromLua<'lua>>(&self) -> Result<V> {
+        let _guard = self.0.lua.lock();
         // Fast track
         if !self.has_metatable() {
             return self.raw_pop();
@@ -252,6 +465,7 @@ impl<'lua> Table<'lua> {
 
     /// Sets a key-value pair without invoking metamethods.
     pub fn raw_set<K: IntoLua<'lua>, V: IntoLua<'lua>>(&self, key: K, value: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         #[cfg(feature = "luau")]
         self.check_readonly_write()?;
 
@@ -280,6 +494,7 @@ impl<'lua> Table<'lua> {
 
     /// Gets the value associated to `key` without invoking metamethods.
     pub fn raw_get<K: IntoLua<'lua>, V: FromLua<'lua>>(&self, key: K) -> Result<V> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let key = key.into_lua(lua)?;
@@ -297,13 +512,175 @@ impl<'lua> Table<'lua> {
         V::from_lua(value, lua)
     }
 
+    /// Gets the values associated to the string `keys` from the table, without invoking
+    /// metamethods.
+    ///
+    /// Works like calling [`raw_get`] for every key, but the table is pushed to the Lua stack
+    /// only once. This is useful to read small structures (eg. vectors) in hot loops.
+    ///
+    /// The returned conversion error includes the key of the failed value (eg. when the value
+    /// is missing and `V` is not an `Option`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let pos: Table = lua.load("{ x = 1.5, y = 2, z = -1 }").eval()?;
+    /// let [x, y, z] = pos.raw_get_multi::<f32, 3>(["x", "y", "z"])?;
+    /// assert_eq!((x, y, z), (1.5, 2.0, -1.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`raw_get`]: #method.raw_get
+    pub fn raw_get_multi<V: FromLua<'lua>, const N: usize>(
+        &self,
+        keys: [&str; N],
+    ) -> Result<[V; N]> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        let mut values = Vec::with_capacity(N);
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            let protect = !lua.unlikely_memory_error();
+            for key in keys {
+                push_string(state, key.as_bytes(), protect)?;
+                ffi::lua_rawget(state, -2);
+                let value = V::from_lua(lua.pop_value(), lua).map_err(|err| err.with_key(key))?;
+                values.push(value);
+            }
+        }
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Gets the value at the dot-separated `path`, eg. `window.size.width`.
+    ///
+    /// Each segment of the path is a string key. Intermediate tables are accessed without
+    /// invoking metamethods (like [`raw_get`]), and an error naming the failed segment is returned
+    /// if an intermediate value is not a table. Keys containing dots are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let config: Table = lua.load("{ window = { size = { width = 800 } } }").eval()?;
+    /// assert_eq!(config.get_path::<u32>("window.size.width")?, 800);
+    /// assert_eq!(config.get_path::<Option<u32>>("window.size.height")?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`raw_get`]: #method.raw_get
+    pub fn get_path<V: FromLua<'lua>>(&self, path: &str) -> Result<V> {
+        let (table, key) = self.walk_path(path, MissingPath::Error)?.unwrap();
+        table.raw_get(key)
+    }
+
+    /// Gets the value at the dot-separated `path`, or `default` if the value or any intermediate
+    /// table is missing (`nil`).
+    ///
+    /// Works like [`get_path`] combined with [`get_or`]: the returned conversion error includes
+    /// the path when the value is present but cannot be converted to `V`.
+    ///
+    /// [`get_path`]: #method.get_path
+    /// [`get_or`]: #method.get_or
+    pub fn get_path_or<V: FromLua<'lua>>(&self, path: &str, default: V) -> Result<V> {
+        let (table, key) = match self.walk_path(path, MissingPath::Skip)? {
+            Some(found) => found,
+            None => return Ok(default),
+        };
+        match table.raw_get::<_, Value>(key)? {
+            Value::Nil => Ok(default),
+            value => V::from_lua(value, self.0.lua).map_err(|err| err.with_key(path)),
+        }
+    }
+
+    /// Sets the value at the dot-separated `path`, eg. `window.size.width`.
+    ///
+    /// Works like [`get_path`], setting the last segment of the path using [`raw_set`].
+    /// If `create_missing` is `true`, missing (`nil`) intermediate tables are created.
+    ///
+    /// [`get_path`]: #method.get_path
+    /// [`raw_set`]: #method.raw_set
+    pub fn set_path<V: IntoLua<'lua>>(
+        &self,
+        path: &str,
+        value: V,
+        create_missing: bool,
+    ) -> Result<()> {
+        let missing = match create_missing {
+            true => MissingPath::Create,
+            false => MissingPath::Error,
+        };
+        let (table, key) = self.walk_path(path, missing)?.unwrap();
+        table.raw_set(key, value)
+    }
+
+    // Walks the path to the last segment, returning the table that holds it and the key.
+    // Returns `None` if an intermediate table is missing and `missing` is `MissingPath::Skip`.
+    fn walk_path<'a>(
+        &self,
+        path: &'a str,
+        missing: MissingPath,
+    ) -> Result<Option<(Table<'lua>, &'a str)>> {
+        let mut segments = path.split('.');
+        if segments.clone().any(|s| s.is_empty()) {
+            let err = format!("invalid path '{}': empty segment", path);
+            return Err(Error::RuntimeError(err));
+        }
+
+        let mut table = self.clone();
+        let mut key = segments.next().unwrap_or_default();
+        let mut pos = key.len();
+        for next in segments {
+            table = match table.raw_get::<_, Value>(key)? {
+                Value::Table(t) => t,
+                Value::Nil if missing == MissingPath::Create => {
+                    let t = self.0.lua.create_table()?;
+                    table.raw_set(key, t.clone())?;
+                    t
+                }
+                Value::Nil if missing == MissingPath::Skip => return Ok(None),
+                value => {
+                    let found = match value.type_name() {
+                        "nil" => "nil".to_string(),
+                        t if t.starts_with(['a', 'e', 'i', 'o', 'u']) => format!("an {}", t),
+                        t => format!("a {}", t),
+                    };
+                    let err = format!(
+                        "path '{}': '{}' is {}, expected table",
+                        &path[..pos],
+                        key,
+                        found
+                    );
+                    return Err(Error::RuntimeError(err));
+                }
+            };
+            key = next;
+            pos += 1 + next.len();
+        }
+        Ok(Some((table, key)))
+    }
+
     /// Inserts element value at position `idx` to the table, shifting up the elements from `table[idx]`.
     /// The worst case complexity is O(n), where n is the table length.
     pub fn raw_insert<V: IntoLua<'lua>>(&self, idx: Integer, value: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
 
-        let size = self.raw_len();
+        let size = self.raw_len() as Integer;
         if idx < 1 || idx > size + 1 {
             return Err(Error::RuntimeError("index out of bounds".to_string()));
         }
@@ -328,6 +705,7 @@ impl<'lua> Table<'lua> {
 
     /// Appends a value to the back of the table without invoking metamethods.
     pub fn raw_push<V: IntoLua<'lua>>(&self, value: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         #[cfg(feature = "luau")]
         self.check_readonly_write()?;
 
@@ -358,6 +736,7 @@ impl<'lua> Table<'lua> {
 
     /// Removes the last element from the table and returns it, without invoking metamethods.
     pub fn raw_pop<V: FromLua<'lua>>(&self) -> Result<V> {
+        let _guard = self.0.lua.lock();
         #[cfg(feature = "luau")]
         self.check_readonly_write()?;
 
@@ -378,6 +757,86 @@ impl<'lua> Table<'lua> {
         V::from_lua(value, lua)
     }
 
+    /// Appends all values from the iterator to the back of the table without invoking metamethods.
+    ///
+    /// This is faster than calling [`raw_push`] in a loop, as the table is pushed to the Lua stack
+    /// and its length is calculated only once.
+    ///
+    /// If converting a value to Lua fails, the values appended before are kept in the table.
+    ///
+    /// [`raw_push`]: #method.raw_push
+    pub fn extend<V: IntoLua<'lua>>(&self, iter: impl IntoIterator<Item = V>) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        self.check_readonly_write()?;
+
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref(&self.0);
+            let mut len = ffi::lua_rawlen(state, -1) as Integer;
+            for value in iter {
+                let value = value.into_lua(lua)?;
+                ffi::lua_pushvalue(state, -1);
+                lua.push_value(value)?;
+                len += 1;
+                if lua.unlikely_memory_error() {
+                    ffi::lua_rawseti(state, -2, len);
+                    ffi::lua_pop(state, 1);
+                } else {
+                    protect_lua!(state, 2, 0, |state| ffi::lua_rawseti(state, -2, len))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the element at position `idx` and returns it, without invoking metamethods.
+    ///
+    /// The removed element is replaced by the last element of the table. This does not preserve
+    /// ordering, but is O(1) unlike [`raw_remove`].
+    ///
+    /// Returns an error if `idx` is out of the `1..=raw_len()` range.
+    ///
+    /// [`raw_remove`]: #method.raw_remove
+    pub fn swap_remove<V: FromLua<'lua>>(&self, idx: Integer) -> Result<V> {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        self.check_readonly_write()?;
+
+        let lua = self.0.lua;
+        let state = lua.state();
+
+        let size = self.raw_len() as Integer;
+        if idx < 1 || idx > size {
+            return Err(Error::RuntimeError(format!(
+                "index {} out of bounds (table length is {})",
+                idx, size
+            )));
+        }
+
+        let value = unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref(&self.0);
+            ffi::lua_rawgeti(state, -1, idx);
+            ffi::lua_pushvalue(state, -2);
+            protect_lua!(state, 1, 0, |state| {
+                // table[idx] = table[size]; table[size] = nil
+                ffi::lua_rawgeti(state, -1, size);
+                ffi::lua_rawseti(state, -2, idx);
+                ffi::lua_pushnil(state);
+                ffi::lua_rawseti(state, -2, size);
+            })?;
+            lua.pop_value()
+        };
+        V::from_lua(value, lua)
+    }
+
     /// Removes a key from the table.
     ///
     /// If `key` is an integer, mlua shifts down the elements from `table[key+1]`,
@@ -386,12 +845,13 @@ impl<'lua> Table<'lua> {
     ///
     /// For other key types this is equivalent to setting `table[key] = nil`.
     pub fn raw_remove<K: IntoLua<'lua>>(&self, key: K) -> Result<()> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let key = key.into_lua(lua)?;
         match key {
             Value::Integer(idx) => {
-                let size = self.raw_len();
+                let size = self.raw_len() as Integer;
                 if idx < 1 || idx > size {
                     return Err(Error::RuntimeError("index out of bounds".to_string()));
                 }
@@ -414,11 +874,70 @@ impl<'lua> Table<'lua> {
         }
     }
 
+    /// Reserves capacity for at least `additional_array` more sequence elements and
+    /// `additional_hash` more other elements, without invoking metamethods.
+    ///
+    /// None of the supported Lua versions provide an API to resize an existing table, so the
+    /// array part is grown by filling the free slots after the border with a placeholder and
+    /// removing it. Lua does not shrink the array part until the table is rehashed (which happens
+    /// only when a new key is inserted into a full hash part), so appending the reserved number of
+    /// elements does not reallocate the table afterwards.
+    ///
+    /// The hash part cannot be grown this way (removed keys are not reused), so `additional_hash`
+    /// is currently ignored. To preallocate memory for both parts, create the table using
+    /// [`Lua::create_table_with_capacity`] instead.
+    ///
+    /// [`Lua::create_table_with_capacity`]: crate::Lua::create_table_with_capacity
+    pub fn reserve(&self, additional_array: usize, additional_hash: usize) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        self.check_readonly_write()?;
+
+        let _ = additional_hash;
+        if additional_array == 0 {
+            return Ok(());
+        }
+
+        let lua = self.0.lua;
+        let state = lua.state();
+        let start = self.raw_len() as Integer + 1;
+        let end = Integer::try_from(additional_array)
+            .ok()
+            .and_then(|n| (start - 1).checked_add(n))
+            .ok_or_else(|| Error::RuntimeError("capacity overflow".into()))?;
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref(&self.0);
+            protect_lua!(state, 1, 0, |state| {
+                // Unique placeholder that cannot be found in the table
+                ffi::lua_newtable(state);
+                for i in start..=end {
+                    if ffi::lua_rawgeti(state, -2, i) == ffi::LUA_TNIL {
+                        ffi::lua_pushvalue(state, -2);
+                        ffi::lua_rawseti(state, -4, i);
+                    }
+                    ffi::lua_pop(state, 1);
+                }
+                for i in start..=end {
+                    ffi::lua_rawgeti(state, -2, i);
+                    if ffi::lua_rawequal(state, -1, -2) != 0 {
+                        ffi::lua_pushnil(state);
+                        ffi::lua_rawseti(state, -4, i);
+                    }
+                    ffi::lua_pop(state, 1);
+                }
+            })
+        }
+    }
+
     /// Clears the table, removing all keys and values from array and hash parts,
     /// without invoking metamethods.
     ///
     /// This method is useful to clear the table while keeping its capacity.
     pub fn clear(&self) -> Result<()> {
+        let _guard = self.0.lua.lock();
         #[cfg(feature = "luau")]
         self.check_readonly_write()?;
 
@@ -430,29 +949,92 @@ impl<'lua> Table<'lua> {
             #[cfg(not(feature = "luau"))]
             {
                 let state = lua.state();
-                check_stack(state, 4)?;
+                let _sg = StackGuard::new(state);
+                check_stack(state, 6)?;
 
                 lua.push_ref(&self.0);
+                protect_lua!(state, 1, 0, fn(state) {
+                    // Clear array part
+                    for i in 1..=ffi::lua_rawlen(state, -1) {
+                        ffi::lua_pushnil(state);
+                        ffi::lua_rawseti(state, -2, i as Integer);
+                    }
 
-                // Clear array part
-                for i in 1..=ffi::lua_rawlen(state, -1) {
+                    // Clear hash part
                     ffi::lua_pushnil(state);
-                    ffi::lua_rawseti(state, -2, i as Integer);
-                }
+                    while ffi::lua_next(state, -2) != 0 {
+                        ffi::lua_pop(state, 1); // pop value
+                        ffi::lua_pushvalue(state, -1); // copy key
+                        ffi::lua_pushnil(state);
+                        ffi::lua_rawset(state, -4);
+                    }
+                })?;
+            }
+        }
 
-                // Clear hash part
-                // It must be safe as long as we don't use invalid keys
-                ffi::lua_pushnil(state);
-                while ffi::lua_next(state, -2) != 0 {
-                    ffi::lua_pop(state, 1); // pop value
-                    ffi::lua_pushvalue(state, -1); // copy key
-                    ffi::lua_pushnil(state);
-                    ffi::lua_rawset(state, -4);
+        Ok(())
+    }
+
+    /// Concatenates the elements of the sequence `t[i]..sep..t[i+1]..sep..t[j]` into a string.
+    ///
+    /// Mirrors the Lua `table.concat` function (without requiring the `table` library).
+    /// The default range is `1..=#t`. Elements must be strings or numbers, otherwise an error
+    /// is returned. Elements and the length are read without invoking metamethods.
+    ///
+    /// The result is accumulated in a single buffer and returned as a Lua string, so no
+    /// intermediate strings are created and non-UTF8 data is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let t: Table = lua.load("{ 'a', 'b', 3, 'd' }").eval()?;
+    /// assert_eq!(t.concat(", ", None)?, "a, b, 3, d");
+    /// assert_eq!(t.concat("", Some((2, 3)))?, "b3");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn concat(&self, sep: &str, range: Option<(Integer, Integer)>) -> Result<String<'lua>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        let (first, last) = range.unwrap_or((1, self.raw_len() as Integer));
+
+        let mut buf = Vec::new();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            let mut i = first;
+            while i <= last {
+                ffi::lua_rawgeti(state, -1, i);
+                match ffi::lua_type(state, -1) {
+                    ffi::LUA_TSTRING => {}
+                    // Numbers are converted to strings in place, which might allocate
+                    ffi::LUA_TNUMBER => protect_lua!(state, 1, 1, fn(state) {
+                        ffi::lua_tolstring(state, -1, ptr::null_mut());
+                    })?,
+                    _ => {
+                        let msg = format!("invalid value (at index {}) in table for 'concat'", i);
+                        return Err(Error::RuntimeError(msg));
+                    }
+                }
+                let mut len = 0;
+                let data = ffi::lua_tolstring(state, -1, &mut len);
+                buf.extend_from_slice(slice::from_raw_parts(data as *const u8, len));
+                ffi::lua_pop(state, 1);
+                if i == last {
+                    break;
                 }
+                buf.extend_from_slice(sep.as_bytes());
+                i += 1;
             }
         }
 
-        Ok(())
+        lua.create_string(&buf)
     }
 
     /// Returns the result of the Lua `#` operator.
@@ -461,9 +1043,10 @@ impl<'lua> Table<'lua> {
     ///
     /// [`raw_len`]: #method.raw_len
     pub fn len(&self) -> Result<Integer> {
+        let _guard = self.0.lua.lock();
         // Fast track
         if !self.has_metatable() {
-            return Ok(self.raw_len());
+            return Ok(self.raw_len() as Integer);
         }
 
         let lua = self.0.lua;
@@ -478,15 +1061,90 @@ impl<'lua> Table<'lua> {
     }
 
     /// Returns the result of the Lua `#` operator, without invoking the `__len` metamethod.
-    pub fn raw_len(&self) -> Integer {
+    pub fn raw_len(&self) -> usize {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
-        unsafe { ffi::lua_rawlen(ref_thread, self.0.index) as Integer }
+        unsafe { ffi::lua_rawlen(ref_thread, self.0.index) }
     }
 
-    /// Returns a reference to the metatable of this table, or `None` if no metatable is set.
+    /// Returns `true` if the table has no entries, without invoking metamethods.
     ///
-    /// Unlike the `getmetatable` Lua function, this method ignores the `__metatable` field.
-    pub fn get_metatable(&self) -> Option<Table<'lua>> {
+    /// Unlike checking [`raw_len`] for zero, this method also checks the hash part of the table.
+    ///
+    /// [`raw_len`]: #method.raw_len
+    pub fn is_empty(&self) -> bool {
+        let _guard = self.0.lua.lock();
+        // Check array part
+        if self.raw_len() != 0 {
+            return false;
+        }
+
+        // Check hash part
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            assert_stack(state, 4);
+
+            lua.push_ref(&self.0);
+            ffi::lua_pushnil(state);
+            ffi::lua_next(state, -2) == 0
+        }
+    }
+
+    /// Returns the total number of entries (in both array and hash parts) of the table,
+    /// without invoking metamethods.
+    ///
+    /// Unlike [`raw_len`], this method counts all keys, not only the sequence part. The table is
+    /// traversed in one go on the Lua side, so the complexity is O(n).
+    ///
+    /// [`raw_len`]: #method.raw_len
+    pub fn count_entries(&self) -> Result<usize> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            protect_lua!(state, 1, 0, |state| {
+                let mut count = 0;
+                ffi::lua_pushnil(state);
+                while ffi::lua_next(state, -2) != 0 {
+                    ffi::lua_pop(state, 1);
+                    count += 1;
+                }
+                count
+            })
+        }
+    }
+
+    /// Returns the number of entries of the table for which the predicate returns `true`,
+    /// without invoking metamethods.
+    ///
+    /// The complexity is O(n). See [`count_entries`] for details.
+    ///
+    /// [`count_entries`]: #method.count_entries
+    pub fn count_entries_where(
+        &self,
+        mut pred: impl FnMut(&Value<'lua>, &Value<'lua>) -> bool,
+    ) -> Result<usize> {
+        let mut count = 0;
+        self.for_each(|key: Value, value: Value| {
+            if pred(&key, &value) {
+                count += 1;
+            }
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// Returns a reference to the metatable of this table, or `None` if no metatable is set.
+    ///
+    /// Unlike the `getmetatable` Lua function, this method ignores the `__metatable` field.
+    pub fn get_metatable(&self) -> Option<Table<'lua>> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
@@ -507,6 +1165,7 @@ impl<'lua> Table<'lua> {
     /// If `metatable` is `None`, the metatable is removed (if no metatable is set, this does
     /// nothing).
     pub fn set_metatable(&self, metatable: Option<Table<'lua>>) {
+        let _guard = self.0.lua.lock();
         // Workaround to throw readonly error without returning Result
         #[cfg(feature = "luau")]
         if self.is_readonly() {
@@ -529,10 +1188,48 @@ impl<'lua> Table<'lua> {
         }
     }
 
+    /// Sets or removes the metatable of this table, returning the previous metatable.
+    ///
+    /// Unlike [`set_metatable`], this method respects metatable protection (like the
+    /// `setmetatable` Lua function does): if the current metatable has a `__metatable` field,
+    /// an error is returned and the metatable is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let t = lua.create_table()?;
+    /// let prev = t.replace_metatable(Some(lua.create_table()?))?;
+    /// // ...
+    /// t.replace_metatable(prev)?;
+    /// assert!(t.get_metatable().is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`set_metatable`]: #method.set_metatable
+    pub fn replace_metatable(&self, metatable: Option<Table<'lua>>) -> Result<Option<Table<'lua>>> {
+        #[cfg(feature = "luau")]
+        self.check_readonly_write()?;
+
+        let prev = self.get_metatable();
+        if let Some(prev) = &prev {
+            if prev.raw_get::<_, Value>("__metatable")? != Nil {
+                let err = "cannot change a protected metatable".to_string();
+                return Err(Error::RuntimeError(err));
+            }
+        }
+        self.set_metatable(metatable);
+        Ok(prev)
+    }
+
     /// Returns true if the table has metatable attached.
     #[doc(hidden)]
     #[inline]
     pub fn has_metatable(&self) -> bool {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
         unsafe {
             if ffi::lua_getmetatable(ref_thread, self.0.index) != 0 {
@@ -543,12 +1240,184 @@ impl<'lua> Table<'lua> {
         false
     }
 
+    /// Returns the weak mode of the table, or `None` if the table is not weak.
+    ///
+    /// The mode is determined by the `__mode` field of the table metatable.
+    /// See [`Lua::create_weak_table`] for details.
+    ///
+    /// [`Lua::create_weak_table`]: crate::Lua::create_weak_table
+    pub fn weak_mode(&self) -> Option<WeakMode> {
+        let metatable = self.get_metatable()?;
+        let mode = match metatable.raw_get::<_, Value>("__mode") {
+            Ok(Value::String(mode)) => mode,
+            _ => return None,
+        };
+        let mode = mode.as_bytes();
+        match (mode.contains(&b'k'), mode.contains(&b'v')) {
+            (true, true) => Some(WeakMode::Both),
+            (true, false) => Some(WeakMode::Keys),
+            (false, true) => Some(WeakMode::Values),
+            (false, false) => None,
+        }
+    }
+
+    /// Freezes the table, preventing modifications from Lua and Rust.
+    ///
+    /// In Luau this sets the `readonly` attribute on the table. For other Lua versions the table
+    /// gets a metatable (a copy of the existing one, if any) with the `__newindex` metamethod
+    /// raising "attempt to modify a frozen table" error and locked `__metatable` field.
+    /// Lua `getmetatable` returns the original metatable (or its `__metatable` field).
+    /// Existing entries stay in the table, so they are readable and can be iterated as usual.
+    ///
+    /// Note that on non-Luau versions, the `__newindex` metamethod is invoked only for absent
+    /// keys, so Lua code can still modify existing entries or bypass the check using `rawset`.
+    /// [`Table::set`] and [`Table::push`] respect the freeze in all cases, unlike raw methods.
+    ///
+    /// Nested tables are not frozen.
+    pub fn freeze(&self) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        {
+            self.set_readonly(true);
+            Ok(())
+        }
+
+        #[cfg(not(feature = "luau"))]
+        {
+            if self.is_frozen() {
+                return Ok(());
+            }
+
+            let lua = self.0.lua;
+            let state = lua.state();
+            unsafe {
+                let _sg = StackGuard::new(state);
+                check_stack(state, 6)?;
+
+                lua.push_ref(&self.0);
+                protect_lua!(state, 1, 0, fn(state) {
+                    if ffi::lua_getmetatable(state, 1) == 0 {
+                        ffi::lua_pushnil(state);
+                    }
+                    ffi::lua_createtable(state, 0, 2);
+                    if ffi::lua_istable(state, 2) != 0 {
+                        // Copy the existing metatable, it can be shared with other tables
+                        ffi::lua_pushnil(state);
+                        while ffi::lua_next(state, 2) != 0 {
+                            ffi::lua_pushvalue(state, -2);
+                            ffi::lua_insert(state, -2);
+                            ffi::lua_rawset(state, 3);
+                        }
+                        ffi::lua_pushstring(state, cstr!("__metatable"));
+                        if ffi::lua_rawget(state, 2) == ffi::LUA_TNIL {
+                            ffi::lua_pop(state, 1);
+                            ffi::lua_pushvalue(state, 2);
+                        }
+                    } else {
+                        ffi::lua_pushboolean(state, 0);
+                    }
+                    ffi::lua_setfield(state, 3, cstr!("__metatable"));
+
+                    ffi::lua_pushcfunction(state, frozen_table_newindex);
+                    ffi::lua_setfield(state, 3, cstr!("__newindex"));
+                    let marker = &FROZEN_TABLE_MARKER as *const u8 as *mut c_void;
+                    ffi::lua_pushlightuserdata(state, marker);
+                    ffi::lua_pushboolean(state, 1);
+                    ffi::lua_rawset(state, 3);
+
+                    ffi::lua_setmetatable(state, 1);
+                })
+            }
+        }
+    }
+
+    /// Returns `true` if the table is frozen using [`Table::freeze`].
+    ///
+    /// In Luau this returns the `readonly` attribute of the table.
+    pub fn is_frozen(&self) -> bool {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        return self.is_readonly();
+
+        #[cfg(not(feature = "luau"))]
+        {
+            let lua = self.0.lua;
+            let state = lua.state();
+            unsafe {
+                let _sg = StackGuard::new(state);
+                assert_stack(state, 3);
+
+                lua.push_ref(&self.0);
+                if ffi::lua_getmetatable(state, -1) == 0 {
+                    return false;
+                }
+                let marker = &FROZEN_TABLE_MARKER as *const u8 as *const c_void;
+                ffi::lua_rawgetp(state, -1, marker);
+                ffi::lua_toboolean(state, -1) != 0
+            }
+        }
+    }
+
+    #[cfg(not(feature = "luau"))]
+    fn check_frozen_write(&self) -> Result<()> {
+        if self.is_frozen() {
+            let err = "attempt to modify a frozen table".to_string();
+            return Err(Error::RuntimeError(err));
+        }
+        Ok(())
+    }
+
+    // Returns a read-only view of the table.
+    //
+    // In Luau the table itself is frozen. For other Lua versions returns a frozen empty proxy,
+    // that reads entries (and length and `pairs` iteration on Lua 5.2+) from the table, so
+    // any attempt to modify the proxy from Lua raises an error.
+    pub(crate) fn read_only_proxy(&self) -> Result<Table<'lua>> {
+        let _guard = self.0.lua.lock();
+        #[cfg(feature = "luau")]
+        {
+            self.freeze()?;
+            Ok(self.clone())
+        }
+
+        #[cfg(not(feature = "luau"))]
+        {
+            let lua = self.0.lua;
+            let state = lua.state();
+            let proxy = unsafe {
+                let _sg = StackGuard::new(state);
+                check_stack(state, 5)?;
+
+                lua.push_ref(&self.0);
+                protect_lua!(state, 1, 1, fn(state) {
+                    ffi::lua_createtable(state, 0, 0);
+                    ffi::lua_createtable(state, 0, 4);
+                    ffi::lua_pushvalue(state, 1);
+                    ffi::lua_setfield(state, 3, cstr!("__index"));
+                    ffi::lua_pushvalue(state, 1);
+                    ffi::lua_pushcclosure(state, read_only_proxy_len, 1);
+                    ffi::lua_setfield(state, 3, cstr!("__len"));
+                    ffi::lua_pushvalue(state, 1);
+                    ffi::lua_pushcclosure(state, read_only_proxy_pairs, 1);
+                    ffi::lua_setfield(state, 3, cstr!("__pairs"));
+                    ffi::lua_pushboolean(state, 0);
+                    ffi::lua_setfield(state, 3, cstr!("__metatable"));
+                    ffi::lua_setmetatable(state, 2);
+                })?;
+                Table(lua.pop_ref())
+            };
+            proxy.freeze()?;
+            Ok(proxy)
+        }
+    }
+
     /// Sets `readonly` attribute on the table.
     ///
     /// Requires `feature = "luau"`
     #[cfg(any(feature = "luau", doc))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     pub fn set_readonly(&self, enabled: bool) {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
         unsafe {
             ffi::lua_setreadonly(ref_thread, self.0.index, enabled as _);
@@ -559,12 +1428,34 @@ impl<'lua> Table<'lua> {
         }
     }
 
+    /// Sets `safeenv` attribute on the table.
+    ///
+    /// Luau can optimize global access (`GETIMPORT`) and builtin calls (eg. `math.floor`) in
+    /// functions whose environment has this attribute, assuming the environment is not modified.
+    /// Use it after populating a custom environment (see [`Chunk::set_safe_environment`]).
+    ///
+    /// Modifying the table afterwards can produce stale reads: scripts may keep seeing the
+    /// previous values and builtins. Setting the table to [non-readonly] resets the attribute.
+    ///
+    /// Requires `feature = "luau"`
+    ///
+    /// [`Chunk::set_safe_environment`]: crate::Chunk::set_safe_environment
+    /// [non-readonly]: #method.set_readonly
+    #[cfg(any(feature = "luau", doc))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    pub fn set_safe_env(&self, enabled: bool) {
+        let _guard = self.0.lua.lock();
+        let ref_thread = self.0.lua.ref_thread();
+        unsafe { ffi::lua_setsafeenv(ref_thread, self.0.index, enabled as _) };
+    }
+
     /// Returns `readonly` attribute of the table.
     ///
     /// Requires `feature = "luau"`
     #[cfg(any(feature = "luau", doc))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     pub fn is_readonly(&self) -> bool {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
         unsafe { ffi::lua_getreadonly(ref_thread, self.0.index) != 0 }
     }
@@ -575,8 +1466,11 @@ impl<'lua> Table<'lua> {
     /// There is no way to convert the pointer back to its original value.
     ///
     /// Typically this function is used only for hashing and debug information.
+    /// The pointer identifies the table only while it's referenced: once the table is
+    /// garbage collected, the same address can be reused by another object.
     #[inline]
     pub fn to_pointer(&self) -> *const c_void {
+        let _guard = self.0.lua.lock();
         let ref_thread = self.0.lua.ref_thread();
         unsafe { ffi::lua_topointer(ref_thread, self.0.index) }
     }
@@ -592,6 +1486,8 @@ impl<'lua> Table<'lua> {
     /// Consume this table and return an iterator over the pairs of the table.
     ///
     /// This works like the Lua `pairs` function, but does not invoke the `__pairs` metamethod.
+    /// The table is traversed using `lua_next`, so no metamethods (including `__index`) are ever
+    /// called. Use [`pairs_metamethod`] to respect the `__pairs` metamethod.
     ///
     /// The pairs are wrapped in a [`Result`], since they are lazily converted to `K` and `V` types.
     ///
@@ -620,6 +1516,7 @@ impl<'lua> Table<'lua> {
     /// # }
     /// ```
     ///
+    /// [`pairs_metamethod`]: #method.pairs_metamethod
     /// [`Result`]: crate::Result
     /// [Lua manual]: http://www.lua.org/manual/5.4/manual.html#pdf-next
     pub fn pairs<K: FromLua<'lua>, V: FromLua<'lua>>(self) -> TablePairs<'lua, K, V> {
@@ -630,6 +1527,438 @@ impl<'lua> Table<'lua> {
         }
     }
 
+    /// Consume this table and return an iterator over the pairs of the table, starting after
+    /// the key `start_after`.
+    ///
+    /// Works like [`pairs`], but the traversal is seeded with the given key (using `lua_next`),
+    /// so iteration can be resumed where a previous pass stopped. Pass the last key returned
+    /// by the previous pass as a cursor, or `None` to start from the beginning.
+    ///
+    /// Returns an error if the key cannot be converted to a Lua value. If the key is not present
+    /// in the table, the iterator returns an error.
+    ///
+    /// # Note
+    ///
+    /// The traversal order is only preserved while the table is not mutated: assigning to
+    /// a field that was not present in the table between passes invalidates the cursor (as
+    /// described in the [Lua manual]). Modifying or clearing existing fields is allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table, Value};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let t: Table = lua.load("{ a = 1, b = 2, c = 3, d = 4 }").eval()?;
+    ///
+    /// // Process at most two pairs per pass
+    /// let mut cursor = None;
+    /// let mut sum = 0;
+    /// loop {
+    ///     let mut count = 0;
+    ///     for pair in t.clone().pairs_from::<Value, i32>(cursor.take())?.take(2) {
+    ///         let (key, value) = pair?;
+    ///         sum += value;
+    ///         cursor = Some(key);
+    ///         count += 1;
+    ///     }
+    ///     if count < 2 {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(sum, 10);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`pairs`]: #method.pairs
+    /// [Lua manual]: http://www.lua.org/manual/5.4/manual.html#pdf-next
+    pub fn pairs_from<K, V>(self, start_after: Option<K>) -> Result<TablePairs<'lua, K, V>>
+    where
+        K: FromLua<'lua> + IntoLua<'lua>,
+        V: FromLua<'lua>,
+    {
+        let key = match start_after {
+            Some(key) => key.into_lua(self.0.lua)?,
+            None => Nil,
+        };
+        Ok(TablePairs {
+            table: self.0,
+            key: Some(key),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Consume this table and return an iterator over the keys of the table.
+    ///
+    /// Works like [`pairs`], but only the keys are converted to `K` (the values are not converted
+    /// at all, so they can be of any type).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let config: Table = lua.load("{ name = 'app', on_start = function() end }").eval()?;
+    /// for key in config.keys::<String>() {
+    ///     let key = key?;
+    ///     assert!(["name", "on_start"].contains(&key.as_str()));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`pairs`]: #method.pairs
+    pub fn keys<K: FromLua<'lua>>(self) -> TableKeys<'lua, K> {
+        TableKeys {
+            table: self.0,
+            key: Some(Nil),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Consume this table and return an iterator over the values of the table.
+    ///
+    /// Works like [`pairs`], but only the values are converted to `V` (the keys are not converted
+    /// at all, so they can be of any type).
+    ///
+    /// [`pairs`]: #method.pairs
+    pub fn values<V: FromLua<'lua>>(self) -> TableValues<'lua, V> {
+        TableValues {
+            table: self.0,
+            key: Some(Nil),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Consume this table and return an iterator over the pairs of the table, respecting
+    /// the `__pairs` metamethod.
+    ///
+    /// This works exactly like the Lua `pairs` function: if the table has a `__pairs` metamethod
+    /// (`__iter` in Luau), it's called to get the iterator function, otherwise the table is
+    /// traversed like in [`pairs`]. Lua 5.1 and LuaJIT don't have the `__pairs` metamethod, so
+    /// the table is always traversed using raw access.
+    ///
+    /// Iteration stops after the first error.
+    ///
+    /// [`pairs`]: #method.pairs
+    pub fn pairs_metamethod<K: FromLua<'lua>, V: FromLua<'lua>>(
+        self,
+    ) -> TableMetaPairs<'lua, K, V> {
+        TableMetaPairs {
+            lua: self.0.lua,
+            state: MetaPairsState::Init(self),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterates over the pairs of the table, invoking the given closure on each key-value pair.
+    ///
+    /// This works like [`pairs`], but walks the table in place without cloning the table
+    /// reference or keeping the previous key between steps, so it's faster for big tables.
+    /// Keys and values are still converted through [`Value`], so each string, table or other
+    /// reference type visited takes a reference slot until it is dropped.
+    /// The `__pairs` metamethod is not invoked.
+    ///
+    /// Iteration stops on the first error returned by the closure or by the key/value conversion,
+    /// and the error is returned.
+    ///
+    /// # Note
+    ///
+    /// Assigning to a field that does not exist in the table during the traversal is not allowed
+    /// (but clearing existing fields is). Refer to the [Lua manual] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let table: Table = lua.load("{a = 1, b = 2, c = 3}").eval()?;
+    ///
+    /// let mut sum = 0;
+    /// table.for_each(|_: String, v: i64| {
+    ///     sum += v;
+    ///     Ok(())
+    /// })?;
+    /// assert_eq!(sum, 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`pairs`]: #method.pairs
+    /// [Lua manual]: http://www.lua.org/manual/5.4/manual.html#pdf-next
+    pub fn for_each<K, V>(&self, mut f: impl FnMut(K, V) -> Result<()>) -> Result<()>
+    where
+        K: FromLua<'lua>,
+        V: FromLua<'lua>,
+    {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref(&self.0);
+            ffi::lua_pushnil(state);
+            // Stack: table, key
+            while protect_lua!(state, 2, ffi::LUA_MULTRET, |state| ffi::lua_next(state, -2))? != 0 {
+                // Stack: table, key, value
+                ffi::lua_pushvalue(state, -2);
+                let key = K::from_lua(lua.pop_value(), lua)?;
+                let value = V::from_lua(lua.pop_value(), lua)?;
+                f(key, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Iterates over the values in the sequence part of the table, invoking the given closure on
+    /// each value.
+    ///
+    /// The values `t[1]`, `t[2]`, and so on, are visited until a `nil` value is encountered.
+    /// Unlike [`sequence_values`], does not invoke the `__index` metamethod.
+    ///
+    /// Iteration stops on the first error returned by the closure or by the value conversion,
+    /// and the error is returned.
+    ///
+    /// [`sequence_values`]: #method.sequence_values
+    pub fn for_each_value<V>(&self, mut f: impl FnMut(V) -> Result<()>) -> Result<()>
+    where
+        V: FromLua<'lua>,
+    {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            let mut index = 1;
+            while ffi::lua_rawgeti(state, -1, index) != ffi::LUA_TNIL {
+                let value = V::from_lua(lua.pop_value(), lua)?;
+                f(value)?;
+                index += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Converts the sequence part of the table to a `Vec`.
+    ///
+    /// The values `t[1]`, `t[2]`, and so on, are collected until a `nil` value is encountered.
+    /// The table is traversed in a single pass without invoking the `__index` metamethod
+    /// (unlike [`sequence_values`]), converting the values directly from the Lua stack.
+    ///
+    /// The returned conversion error includes the index of the failed value.
+    ///
+    /// [`sequence_values`]: #method.sequence_values
+    pub fn to_vec<V: FromLua<'lua>>(&self) -> Result<Vec<V>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            let mut vec = Vec::with_capacity(ffi::lua_rawlen(state, -1));
+            let mut index = 1;
+            while ffi::lua_rawgeti(state, -1, index) != ffi::LUA_TNIL {
+                let value = V::from_lua(lua.pop_value(), lua)
+                    .map_err(|err| err.with_key(&format!("[{}]", index)))?;
+                vec.push(value);
+                index += 1;
+            }
+            Ok(vec)
+        }
+    }
+
+    /// Converts all pairs of the table to a `HashMap`.
+    ///
+    /// The table is traversed in a single pass without invoking the `__pairs` metamethod,
+    /// converting the keys and values directly from the Lua stack.
+    ///
+    /// The returned conversion error includes the key of the failed pair.
+    pub fn to_hashmap<K, V>(&self) -> Result<HashMap<K, V>>
+    where
+        K: FromLua<'lua> + Eq + Hash,
+        V: FromLua<'lua>,
+    {
+        self.collect_pairs()
+    }
+
+    // Collects all pairs of the table (without invoking metamethods) into a collection
+    pub(crate) fn collect_pairs<K, V, C>(&self) -> Result<C>
+    where
+        K: FromLua<'lua>,
+        V: FromLua<'lua>,
+        C: Default + Extend<(K, V)>,
+    {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            // Renders the key at the given stack index for error messages
+            let key_name = |idx| {
+                ffi::lua_pushvalue(state, idx);
+                key_path("", &lua.pop_value())
+            };
+
+            let mut collection = C::default();
+            lua.push_ref(&self.0);
+            ffi::lua_pushnil(state);
+            // Stack: table, key
+            while protect_lua!(state, 2, ffi::LUA_MULTRET, |state| ffi::lua_next(state, -2))? != 0 {
+                // Stack: table, key, value
+                ffi::lua_pushvalue(state, -2);
+                let key =
+                    K::from_lua(lua.pop_value(), lua).map_err(|err| err.with_key(&key_name(-2)))?;
+                let value =
+                    V::from_lua(lua.pop_value(), lua).map_err(|err| err.with_key(&key_name(-1)))?;
+                collection.extend(Some((key, value)));
+            }
+            Ok(collection)
+        }
+    }
+
+    /// Iterates over the string values in the sequence part of the table, invoking the given
+    /// closure on the index and bytes of each value.
+    ///
+    /// The values `t[1]`, `t[2]`, and so on, are visited until a `nil` value is encountered,
+    /// without invoking the `__index` metamethod. The bytes are borrowed directly from the Lua
+    /// stack, so no [`String`] handles are created.
+    ///
+    /// Values of other types (including numbers) are not converted: an error including
+    /// the index of the value is returned instead. Iteration also stops on the first error
+    /// returned by the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let lines: Table = lua.load("{ 'first', 'second' }").eval()?;
+    /// let mut out = Vec::new();
+    /// lines.sequence_values_bytes(|_, bytes| {
+    ///     out.extend_from_slice(bytes);
+    ///     out.push(b'\n');
+    ///     Ok(())
+    /// })?;
+    /// assert_eq!(out, b"first\nsecond\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`String`]: crate::String
+    pub fn sequence_values_bytes(
+        &self,
+        mut f: impl FnMut(usize, &[u8]) -> Result<()>,
+    ) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref(&self.0);
+            let mut index = 1;
+            loop {
+                match ffi::lua_rawgeti(state, -1, index as Integer) {
+                    ffi::LUA_TNIL => break,
+                    ffi::LUA_TSTRING => {
+                        let mut len = 0;
+                        let data = ffi::lua_tolstring(state, -1, &mut len);
+                        f(index, slice::from_raw_parts(data as *const u8, len))?;
+                        ffi::lua_pop(state, 1);
+                    }
+                    _ => {
+                        let err = Error::FromLuaConversionError {
+                            from: lua.pop_value().type_name(),
+                            to: "string",
+                            message: Some("expected string".to_string()),
+                        };
+                        return Err(err.with_key(&format!("[{}]", index)));
+                    }
+                }
+                index += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns all pairs of the table sorted by keys.
+    ///
+    /// Unlike [`pairs`], the order of the pairs is deterministic. All pairs are collected and
+    /// converted upfront (the table is traversed without invoking the `__pairs` metamethod),
+    /// so the table can be freely accessed while processing the result.
+    /// Sorting takes `O(n log n)` time and `O(n)` memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let t: Table = lua.load("{ b = 2, c = 3, a = 1 }").eval()?;
+    /// let pairs = t.sorted_pairs::<String, i32>()?;
+    /// assert_eq!(pairs, vec![("a".into(), 1), ("b".into(), 2), ("c".into(), 3)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`pairs`]: #method.pairs
+    pub fn sorted_pairs<K, V>(&self) -> Result<Vec<(K, V)>>
+    where
+        K: FromLua<'lua> + Ord,
+        V: FromLua<'lua>,
+    {
+        let mut pairs = self.clone().pairs::<K, V>().collect::<Result<Vec<_>>>()?;
+        pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(pairs)
+    }
+
+    /// Returns all pairs of the table sorted by keys using the `compare` function.
+    ///
+    /// Useful for tables with keys of different types, see [`Value::sort_cmp`] for a suitable
+    /// comparator. Works like [`sorted_pairs`] otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table, Value};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let t: Table = lua.load("{ 'x', 'y', key = 'z' }").eval()?;
+    /// let pairs = t.sorted_pairs_by::<String>(Value::sort_cmp)?;
+    /// assert_eq!(pairs[0], (Value::Integer(1), "x".into()));
+    /// assert_eq!(pairs[2].1, "z");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Value::sort_cmp`]: crate::Value::sort_cmp
+    /// [`sorted_pairs`]: #method.sorted_pairs
+    pub fn sorted_pairs_by<V: FromLua<'lua>>(
+        &self,
+        mut compare: impl FnMut(&Value<'lua>, &Value<'lua>) -> Ordering,
+    ) -> Result<Vec<(Value<'lua>, V)>> {
+        let mut pairs = (self.clone().pairs::<Value, V>()).collect::<Result<Vec<_>>>()?;
+        pairs.sort_by(|(a, _), (b, _)| compare(a, b));
+        Ok(pairs)
+    }
+
     /// Consume this table and return an iterator over all values in the sequence part of the table.
     ///
     /// The iterator will yield all values `t[1]`, `t[2]`, and so on, until a `nil` value is
@@ -660,59 +1989,212 @@ impl<'lua> Table<'lua> {
     ///     }
     /// "#).eval()?;
     ///
-    /// let expected = [4, 5];
-    /// for (&expected, got) in expected.iter().zip(my_table.sequence_values::<u32>()) {
-    ///     assert_eq!(expected, got?);
-    /// }
+    /// let expected = [4, 5];
+    /// for (&expected, got) in expected.iter().zip(my_table.sequence_values::<u32>()) {
+    ///     assert_eq!(expected, got?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`pairs`]: #method.pairs
+    /// [`Result`]: crate::Result
+    /// [Lua manual]: http://www.lua.org/manual/5.4/manual.html#pdf-next
+    pub fn sequence_values<V: FromLua<'lua>>(self) -> TableSequence<'lua, V> {
+        TableSequence {
+            table: self.0,
+            index: Some(1),
+            raw: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Consume this table and return an iterator over all values in the sequence part of the table.
+    ///
+    /// Unlike the `sequence_values`, does not invoke `__index` metamethod when iterating.
+    ///
+    /// [`sequence_values`]: #method.sequence_values
+    pub fn raw_sequence_values<V: FromLua<'lua>>(self) -> TableSequence<'lua, V> {
+        TableSequence {
+            table: self.0,
+            index: Some(1),
+            raw: true,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a structural copy of the table using default [`CloneOptions`].
+    ///
+    /// See [`deep_clone_with_options`] for details.
+    ///
+    /// [`deep_clone_with_options`]: #method.deep_clone_with_options
+    pub fn deep_clone(&self) -> Result<Table<'lua>> {
+        self.deep_clone_with_options(CloneOptions::default())
+    }
+
+    /// Creates a structural copy of the table.
+    ///
+    /// Nested tables (both keys and values) are copied recursively, other values (including
+    /// functions, userdata and threads) are shared with the original table. Cycles are preserved:
+    /// a table referenced multiple times is copied only once.
+    ///
+    /// Metatables are shared unless [`CloneOptions::copy_metatables`] is set. Returns an error
+    /// if nesting of tables exceeds [`CloneOptions::max_depth`].
+    ///
+    /// This method does not invoke metamethods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let config: Table = lua.load("{ server = { port = 8080 } }").eval()?;
+    /// let snapshot = config.deep_clone()?;
+    ///
+    /// config.get::<_, Table>("server")?.set("port", 80)?;
+    /// assert_eq!(snapshot.get::<_, Table>("server")?.get::<_, u16>("port")?, 8080);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deep_clone_with_options(&self, options: CloneOptions) -> Result<Table<'lua>> {
+        let mut seen = FxHashMap::default();
+        self.deep_clone_inner(&options, 0, &mut seen)
+    }
+
+    fn deep_clone_inner(
+        &self,
+        options: &CloneOptions,
+        depth: usize,
+        seen: &mut FxHashMap<*const c_void, Table<'lua>>,
+    ) -> Result<Table<'lua>> {
+        if let Some(copy) = seen.get(&self.to_pointer()) {
+            return Ok(copy.clone());
+        }
+        if depth > options.max_depth {
+            let err = format!("maximum table depth ({}) exceeded", options.max_depth);
+            return Err(Error::RuntimeError(err));
+        }
+
+        let lua = self.0.lua;
+        let copy = lua.create_table()?;
+        seen.insert(self.to_pointer(), copy.clone());
+
+        let mut clone_value = |value: Value<'lua>| match value {
+            Value::Table(t) => t
+                .deep_clone_inner(options, depth + 1, seen)
+                .map(Value::Table),
+            value => Ok(value),
+        };
+
+        for pair in self.clone().pairs::<Value, Value>() {
+            let (key, value) = pair?;
+            let key = clone_value(key)?;
+            let value = clone_value(value)?;
+            copy.raw_set(key, value)?;
+        }
+
+        if let Some(mut metatable) = self.get_metatable() {
+            if options.copy_metatables {
+                metatable = metatable.deep_clone_inner(options, depth + 1, seen)?;
+            }
+            copy.set_metatable(Some(metatable));
+        }
+
+        Ok(copy)
+    }
+
+    /// Checks whether this table is structurally equal to `other`.
+    ///
+    /// Tables are equal if they have the same set of keys and the values are equal, comparing
+    /// nested tables recursively. Keys are compared using raw equality, so tables used as keys
+    /// must be the same table. Numbers are compared by value (`1 == 1.0`) and `NaN` values are
+    /// considered equal to each other (unlike `==` in Lua), so a table is always equal to itself.
+    ///
+    /// Cyclic tables are supported: two tables with the same (possibly cyclic) shape are equal.
+    /// Metamethods are not used to access the tables. See [`EqualsOptions`] for other details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{EqualsOptions, Lua, Result, Table};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let a: Table = lua.load("{ 1, 2, nested = { x = 0/0 } }").eval()?;
+    /// let b: Table = lua.load("{ 1, 2.0, nested = { x = 0/0 } }").eval()?;
+    /// assert!(a.deep_equals(&b, &EqualsOptions::new())?);
     /// # Ok(())
     /// # }
     /// ```
-    ///
-    /// [`pairs`]: #method.pairs
-    /// [`Result`]: crate::Result
-    /// [Lua manual]: http://www.lua.org/manual/5.4/manual.html#pdf-next
-    pub fn sequence_values<V: FromLua<'lua>>(self) -> TableSequence<'lua, V> {
-        TableSequence {
-            table: self.0,
-            index: Some(1),
-            len: None,
-            raw: false,
-            _phantom: PhantomData,
-        }
+    pub fn deep_equals(&self, other: &Table<'lua>, options: &EqualsOptions) -> Result<bool> {
+        let mut visited = FxHashSet::default();
+        self.deep_equals_inner(other, options, 0, &mut visited)
     }
 
-    /// Consume this table and return an iterator over all values in the sequence part of the table.
-    ///
-    /// Unlike the `sequence_values`, does not invoke `__index` metamethod when iterating.
-    ///
-    /// [`sequence_values`]: #method.sequence_values
-    pub fn raw_sequence_values<V: FromLua<'lua>>(self) -> TableSequence<'lua, V> {
-        TableSequence {
-            table: self.0,
-            index: Some(1),
-            len: None,
-            raw: true,
-            _phantom: PhantomData,
+    fn deep_equals_inner(
+        &self,
+        other: &Table<'lua>,
+        options: &EqualsOptions,
+        depth: usize,
+        visited: &mut FxHashSet<(*const c_void, *const c_void)>,
+    ) -> Result<bool> {
+        // Pairs that are already being compared are assumed to be equal
+        if self == other || !visited.insert((self.to_pointer(), other.to_pointer())) {
+            return Ok(true);
+        }
+        if depth > options.max_depth {
+            let err = format!("maximum table depth ({}) exceeded", options.max_depth);
+            return Err(Error::RuntimeError(err));
         }
-    }
 
-    #[cfg(any(feature = "serialize"))]
-    pub(crate) fn raw_sequence_values_by_len<V: FromLua<'lua>>(
-        self,
-        len: Option<Integer>,
-    ) -> TableSequence<'lua, V> {
-        let len = len.unwrap_or_else(|| self.raw_len());
-        TableSequence {
-            table: self.0,
-            index: Some(1),
-            len: Some(len),
-            raw: true,
-            _phantom: PhantomData,
+        let mut values_equal = |a: Value<'lua>, b: Value<'lua>| -> Result<bool> {
+            match (a, b) {
+                (Value::Table(a), Value::Table(b)) => {
+                    a.deep_equals_inner(&b, options, depth + 1, visited)
+                }
+                (Value::Number(a), Value::Number(b)) if a.is_nan() && b.is_nan() => Ok(true),
+                (Value::UserData(a), Value::UserData(b)) if options.userdata_eq => a.equals(&b),
+                (a, b) => Ok(a == b),
+            }
+        };
+
+        let mut count = 0;
+        for pair in self.clone().pairs::<Value, Value>() {
+            let (key, value) = pair?;
+            let other_value = other.raw_get::<_, Value>(key)?;
+            if !values_equal(value, other_value)? {
+                return Ok(false);
+            }
+            count += 1;
+        }
+        // All keys of `self` are present in `other`, it remains to check that there are no more
+        let mut other_count = 0;
+        for pair in other.clone().pairs::<Value, Value>() {
+            pair?;
+            other_count += 1;
+            if other_count > count {
+                return Ok(false);
+            }
         }
+
+        if options.compare_metatables {
+            match (self.get_metatable(), other.get_metatable()) {
+                (None, None) => {}
+                (Some(mt), Some(other_mt)) => {
+                    if !values_equal(Value::Table(mt), Value::Table(other_mt))? {
+                        return Ok(false);
+                    }
+                }
+                _ => return Ok(false),
+            }
+        }
+
+        Ok(true)
     }
 
     #[cfg(feature = "serialize")]
     pub(crate) fn is_array(&self) -> bool {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
@@ -739,12 +2221,213 @@ impl<'lua> Table<'lua> {
     }
 }
 
+#[cfg(not(feature = "luau"))]
+static FROZEN_TABLE_MARKER: u8 = 0;
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C" fn frozen_table_newindex(state: *mut ffi::lua_State) -> c_int {
+    ffi::luaL_error(state, cstr!("attempt to modify a frozen table"))
+}
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C" fn read_only_proxy_len(state: *mut ffi::lua_State) -> c_int {
+    let len = ffi::lua_rawlen(state, ffi::lua_upvalueindex(1));
+    ffi::lua_pushinteger(state, len as ffi::lua_Integer);
+    1
+}
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C" fn read_only_proxy_pairs(state: *mut ffi::lua_State) -> c_int {
+    ffi::lua_pushcfunction(state, read_only_proxy_next);
+    ffi::lua_pushvalue(state, ffi::lua_upvalueindex(1));
+    ffi::lua_pushnil(state);
+    3
+}
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C" fn read_only_proxy_next(state: *mut ffi::lua_State) -> c_int {
+    ffi::luaL_checktype(state, 1, ffi::LUA_TTABLE);
+    ffi::lua_settop(state, 2);
+    if ffi::lua_next(state, 1) != 0 {
+        2
+    } else {
+        ffi::lua_pushnil(state);
+        1
+    }
+}
+
+/// Weak mode of a table, see [`Lua::create_weak_table`].
+///
+/// [`Lua::create_weak_table`]: crate::Lua::create_weak_table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeakMode {
+    /// The table has weak keys (`__mode = "k"`).
+    Keys,
+    /// The table has weak values (`__mode = "v"`).
+    Values,
+    /// The table has both weak keys and values (`__mode = "kv"`).
+    Both,
+}
+
+impl WeakMode {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            WeakMode::Keys => "k",
+            WeakMode::Values => "v",
+            WeakMode::Both => "kv",
+        }
+    }
+}
+
+/// Options for [`Table::deep_equals`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct EqualsOptions {
+    /// If true, userdata values are compared using their `__eq` metamethod (if any).
+    /// Otherwise userdata are equal only if they are the same object.
+    ///
+    /// Default: **true**
+    pub userdata_eq: bool,
+
+    /// If true, metatables must be structurally equal too (or both absent).
+    /// Otherwise metatables are ignored.
+    ///
+    /// Default: **false**
+    pub compare_metatables: bool,
+
+    /// Maximum nesting depth of compared tables. The root table has depth 0.
+    ///
+    /// Default: **64**
+    pub max_depth: usize,
+}
+
+impl Default for EqualsOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EqualsOptions {
+    /// Returns a new instance of [`EqualsOptions`] with default parameters.
+    pub const fn new() -> Self {
+        EqualsOptions {
+            userdata_eq: true,
+            compare_metatables: false,
+            max_depth: 64,
+        }
+    }
+
+    /// Sets [`userdata_eq`] option.
+    ///
+    /// [`userdata_eq`]: #structfield.userdata_eq
+    #[must_use]
+    pub const fn userdata_eq(mut self, enabled: bool) -> Self {
+        self.userdata_eq = enabled;
+        self
+    }
+
+    /// Sets [`compare_metatables`] option.
+    ///
+    /// [`compare_metatables`]: #structfield.compare_metatables
+    #[must_use]
+    pub const fn compare_metatables(mut self, enabled: bool) -> Self {
+        self.compare_metatables = enabled;
+        self
+    }
+
+    /// Sets [`max_depth`] option.
+    ///
+    /// [`max_depth`]: #structfield.max_depth
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+/// Options for [`Table::deep_clone_with_options`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct CloneOptions {
+    /// If true, metatables are copied recursively like other tables.
+    /// Otherwise the copy shares the metatable with the original table.
+    ///
+    /// Default: **false**
+    pub copy_metatables: bool,
+
+    /// Maximum nesting depth of copied tables. The root table has depth 0.
+    ///
+    /// Default: **64**
+    pub max_depth: usize,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CloneOptions {
+    /// Returns a new instance of [`CloneOptions`] with default parameters.
+    pub const fn new() -> Self {
+        CloneOptions {
+            copy_metatables: false,
+            max_depth: 64,
+        }
+    }
+
+    /// Sets [`copy_metatables`] option.
+    ///
+    /// [`copy_metatables`]: #structfield.copy_metatables
+    #[must_use]
+    pub const fn copy_metatables(mut self, enabled: bool) -> Self {
+        self.copy_metatables = enabled;
+        self
+    }
+
+    /// Sets [`max_depth`] option.
+    ///
+    /// [`max_depth`]: #structfield.max_depth
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
 impl<'lua> PartialEq for Table<'lua> {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
+impl<'lua> Eq for Table<'lua> {}
+
+/// Tables are hashed by identity (consistent with `PartialEq`), so tables with the same
+/// contents have different hashes.
+impl<'lua> Hash for Table<'lua> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_pointer().hash(state);
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl PartialEq for OwnedTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_ref() == other.to_ref()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Eq for OwnedTable {}
+
+#[cfg(feature = "unstable")]
+impl Hash for OwnedTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_ref().hash(state);
+    }
+}
+
 impl<'lua> AsRef<Table<'lua>> for Table<'lua> {
     #[inline]
     fn as_ref(&self) -> &Self {
@@ -913,6 +2596,7 @@ impl<'lua> Serialize for Table<'lua> {
     where
         S: Serializer,
     {
+        let _guard = self.0.lua.lock();
         thread_local! {
             static VISITED: RefCell<FxHashSet<*const c_void>> = RefCell::new(FxHashSet::default());
         }
@@ -927,22 +2611,52 @@ impl<'lua> Serialize for Table<'lua> {
                 visited.insert(ptr);
             }
 
-            let len = self.raw_len() as usize;
-            if len > 0 || self.is_array() {
-                let mut seq = serializer.serialize_seq(Some(len))?;
-                for v in self.clone().raw_sequence_values_by_len::<Value>(None) {
-                    let v = v.map_err(serde::ser::Error::custom)?;
-                    seq.serialize_element(&v)?;
+            let len = self.raw_len() as Integer;
+            let is_array = len > 0 || self.is_array();
+
+            // The table is traversed in place, serializer is called outside of protected calls
+            let lua = self.0.lua;
+            let state = lua.state();
+            unsafe {
+                let _sg = StackGuard::new(state);
+                check_stack(state, 4).map_err(ser::Error::custom)?;
+
+                lua.push_ref(&self.0);
+                if is_array {
+                    // Skipped elements make the length unknown in advance
+                    let len_hint = match crate::serde::unsupported_policy() {
+                        UnsupportedPolicy::Skip => None,
+                        _ => Some(len as usize),
+                    };
+                    let mut seq = serializer.serialize_seq(len_hint)?;
+                    let mut index = 1;
+                    while ffi::lua_rawgeti(state, -1, index) != ffi::LUA_TNIL || index <= len {
+                        let v = lua.pop_value();
+                        if !skip_unsupported(&v) {
+                            seq.serialize_element(&v)?;
+                        }
+                        index += 1;
+                    }
+                    return seq.end();
                 }
-                return seq.end();
-            }
 
-            let mut map = serializer.serialize_map(None)?;
-            for kv in self.clone().pairs::<Value, Value>() {
-                let (k, v) = kv.map_err(serde::ser::Error::custom)?;
-                map.serialize_entry(&k, &v)?;
+                let mut map = serializer.serialize_map(None)?;
+                ffi::lua_pushnil(state);
+                // Stack: table, key
+                while protect_lua!(state, 2, ffi::LUA_MULTRET, |state| ffi::lua_next(state, -2))
+                    .map_err(ser::Error::custom)?
+                    != 0
+                {
+                    // Stack: table, key, value
+                    ffi::lua_pushvalue(state, -2);
+                    let k = lua.pop_value();
+                    let v = lua.pop_value();
+                    if !skip_unsupported(&k) && !skip_unsupported(&v) {
+                        map.serialize_entry(&k, &v)?;
+                    }
+                }
+                map.end()
             }
-            map.end()
         });
         VISITED.with(|visited| {
             visited.borrow_mut().remove(&ptr);
@@ -951,6 +2665,14 @@ impl<'lua> Serialize for Table<'lua> {
     }
 }
 
+// What to do with missing (`nil`) intermediate tables when walking a path
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MissingPath {
+    Error,
+    Create,
+    Skip,
+}
+
 /// An iterator over the pairs of a Lua table.
 ///
 /// This struct is created by the [`Table::pairs`] method.
@@ -972,29 +2694,17 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(prev_key) = self.key.take() {
             let lua = self.table.lua;
-            let state = lua.state();
-
-            let res = (|| unsafe {
-                let _sg = StackGuard::new(state);
-                check_stack(state, 5)?;
-
-                lua.push_ref(&self.table);
-                lua.push_value(prev_key)?;
 
-                let next = protect_lua!(state, 2, ffi::LUA_MULTRET, |state| {
-                    ffi::lua_next(state, -2)
-                })?;
-                if next != 0 {
-                    let value = lua.pop_value();
-                    let key = lua.pop_value();
-                    Ok(Some((
-                        key.clone(),
-                        K::from_lua(key, lua)?,
-                        V::from_lua(value, lua)?,
-                    )))
-                } else {
-                    Ok(None)
-                }
+            let res = (|| {
+                let (key, value) = match raw_next(&self.table, prev_key, true)? {
+                    Some(pair) => pair,
+                    None => return Ok(None),
+                };
+                Ok(Some((
+                    key.clone(),
+                    K::from_lua(key, lua)?,
+                    V::from_lua(value, lua)?,
+                )))
             })();
 
             match res {
@@ -1011,6 +2721,269 @@ where
     }
 }
 
+/// An iterator over the pairs of an owned Lua table.
+///
+/// This struct is created by the [`OwnedTable::pairs`] method.
+#[cfg(feature = "unstable")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+pub struct OwnedTablePairs<K, V> {
+    table: OwnedTable,
+    // Previous key kept on the reference thread, `Some(None)` before the first step
+    key: Option<Option<crate::types::LuaOwnedRef>>,
+    _phantom: PhantomData<(K, V)>,
+}
+
+#[cfg(feature = "unstable")]
+impl<K, V> Iterator for OwnedTablePairs<K, V>
+where
+    K: for<'lua> FromLua<'lua>,
+    V: for<'lua> FromLua<'lua>,
+{
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev_key = self.key.take()?;
+        let table = self.table.to_ref();
+        let lua = table.0.lua;
+
+        let res = (|| unsafe {
+            let _guard = lua.lock();
+            let state = lua.state();
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref(&table.0);
+            match prev_key {
+                Some(ref prev_key) => lua.push_ref(&prev_key.to_ref()),
+                None => ffi::lua_pushnil(state),
+            }
+            let next = protect_lua!(state, 2, ffi::LUA_MULTRET, |state| {
+                ffi::lua_next(state, -2)
+            })?;
+            if next == 0 {
+                return Ok(None);
+            }
+            // Stack: key, value
+            ffi::lua_pushvalue(state, -2);
+            let key = lua.make_owned_ref(lua.pop_ref());
+            let value = lua.pop_value();
+            let ret_key = K::from_lua(lua.pop_value(), lua)?;
+            Ok(Some((key, ret_key, V::from_lua(value, lua)?)))
+        })();
+
+        match res {
+            Ok(Some((key, ret_key, value))) => {
+                self.key = Some(Some(key));
+                Some(Ok((ret_key, value)))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// An iterator over the keys of a Lua table.
+///
+/// This struct is created by the [`Table::keys`] method.
+///
+/// [`Table::keys`]: crate::Table::keys
+pub struct TableKeys<'lua, K> {
+    table: LuaRef<'lua>,
+    key: Option<Value<'lua>>,
+    _phantom: PhantomData<K>,
+}
+
+impl<'lua, K> Iterator for TableKeys<'lua, K>
+where
+    K: FromLua<'lua>,
+{
+    type Item = Result<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev_key = self.key.take()?;
+        match raw_next(&self.table, prev_key, false) {
+            Ok(Some((key, _))) => {
+                self.key = Some(key.clone());
+                Some(K::from_lua(key, self.table.lua))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// An iterator over the values of a Lua table.
+///
+/// This struct is created by the [`Table::values`] method.
+///
+/// [`Table::values`]: crate::Table::values
+pub struct TableValues<'lua, V> {
+    table: LuaRef<'lua>,
+    key: Option<Value<'lua>>,
+    _phantom: PhantomData<V>,
+}
+
+impl<'lua, V> Iterator for TableValues<'lua, V>
+where
+    V: FromLua<'lua>,
+{
+    type Item = Result<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev_key = self.key.take()?;
+        match raw_next(&self.table, prev_key, true) {
+            Ok(Some((key, value))) => {
+                self.key = Some(key);
+                Some(V::from_lua(value, self.table.lua))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+// Returns the pair following `prev_key` in the raw traversal order of the table.
+// If `with_value` is false, the value is not retrieved and `Nil` is returned instead.
+fn raw_next<'lua>(
+    table: &LuaRef<'lua>,
+    prev_key: Value<'lua>,
+    with_value: bool,
+) -> Result<Option<(Value<'lua>, Value<'lua>)>> {
+    let _guard = table.lua.lock();
+    let lua = table.lua;
+    let state = lua.state();
+    unsafe {
+        let _sg = StackGuard::new(state);
+        check_stack(state, 5)?;
+
+        lua.push_ref(table);
+        lua.push_value(prev_key)?;
+
+        let next = protect_lua!(state, 2, ffi::LUA_MULTRET, |state| {
+            ffi::lua_next(state, -2)
+        })?;
+        if next == 0 {
+            return Ok(None);
+        }
+        let value = match with_value {
+            true => lua.pop_value(),
+            false => {
+                ffi::lua_pop(state, 1);
+                Nil
+            }
+        };
+        Ok(Some((lua.pop_value(), value)))
+    }
+}
+
+/// An iterator over the pairs of a Lua table, respecting the `__pairs` metamethod.
+///
+/// This struct is created by the [`Table::pairs_metamethod`] method.
+///
+/// [`Table::pairs_metamethod`]: crate::Table::pairs_metamethod
+pub struct TableMetaPairs<'lua, K, V> {
+    lua: &'lua Lua,
+    state: MetaPairsState<'lua>,
+    _phantom: PhantomData<(K, V)>,
+}
+
+enum MetaPairsState<'lua> {
+    Init(Table<'lua>),
+    Raw(TablePairs<'lua, Value<'lua>, Value<'lua>>),
+    Iter {
+        func: Function<'lua>,
+        state: Value<'lua>,
+        control: Value<'lua>,
+    },
+    Done,
+}
+
+impl<'lua, K, V> TableMetaPairs<'lua, K, V> {
+    fn next_pair(&mut self) -> Result<Option<(Value<'lua>, Value<'lua>)>> {
+        loop {
+            match &mut self.state {
+                MetaPairsState::Init(table) => {
+                    let table = table.clone();
+                    self.state = match Self::pairs_metamethod(&table)? {
+                        Some(func) => {
+                            let (func, state, control) = func.call(table)?;
+                            MetaPairsState::Iter {
+                                func,
+                                state,
+                                control,
+                            }
+                        }
+                        None => MetaPairsState::Raw(table.pairs()),
+                    };
+                }
+                MetaPairsState::Raw(pairs) => return pairs.next().transpose(),
+                MetaPairsState::Iter {
+                    func,
+                    state,
+                    control,
+                } => {
+                    let (key, value): (Value, Value) =
+                        func.call((state.clone(), control.clone()))?;
+                    if key == Nil {
+                        return Ok(None);
+                    }
+                    *control = key.clone();
+                    return Ok(Some((key, value)));
+                }
+                MetaPairsState::Done => return Ok(None),
+            }
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn pairs_metamethod(table: &Table<'lua>) -> Result<Option<Function<'lua>>> {
+        #[cfg(any(
+            feature = "lua54",
+            feature = "lua53",
+            feature = "lua52",
+            feature = "luajit52",
+            feature = "luau"
+        ))]
+        if let Some(mt) = table.get_metatable() {
+            #[cfg(not(feature = "luau"))]
+            let name = "__pairs";
+            #[cfg(feature = "luau")]
+            let name = "__iter";
+            return mt.raw_get(name);
+        }
+        Ok(None)
+    }
+}
+
+impl<'lua, K, V> Iterator for TableMetaPairs<'lua, K, V>
+where
+    K: FromLua<'lua>,
+    V: FromLua<'lua>,
+{
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_pair().and_then(|pair| match pair {
+            Some((key, value)) => {
+                let lua = self.lua;
+                Ok(Some((K::from_lua(key, lua)?, V::from_lua(value, lua)?)))
+            }
+            None => Ok(None),
+        });
+        match res {
+            Ok(Some(pair)) => Some(Ok(pair)),
+            Ok(None) => {
+                self.state = MetaPairsState::Done;
+                None
+            }
+            Err(err) => {
+                self.state = MetaPairsState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
 /// An iterator over the sequence part of a Lua table.
 ///
 /// This struct is created by the [`Table::sequence_values`] method.
@@ -1019,7 +2992,6 @@ where
 pub struct TableSequence<'lua, V> {
     table: LuaRef<'lua>,
     index: Option<Integer>,
-    len: Option<Integer>,
     raw: bool,
     _phantom: PhantomData<V>,
 }
@@ -1031,6 +3003,7 @@ where
     type Item = Result<V>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let _guard = self.table.lua.lock();
         if let Some(index) = self.index.take() {
             let lua = self.table.lua;
             let state = lua.state();
@@ -1046,7 +3019,7 @@ where
                     protect_lua!(state, 1, 1, |state| ffi::lua_geti(state, -1, index))?
                 };
                 match res {
-                    ffi::LUA_TNIL if index > self.len.unwrap_or(0) => Ok(None),
+                    ffi::LUA_TNIL => Ok(None),
                     _ => Ok(Some((index, lua.pop_value()))),
                 }
             })();
@@ -1071,6 +3044,8 @@ mod assertions {
 
     static_assertions::assert_not_impl_any!(Table: Send);
 
-    #[cfg(feature = "unstable")]
+    #[cfg(all(feature = "unstable", not(feature = "send")))]
     static_assertions::assert_not_impl_any!(OwnedTable: Send);
+    #[cfg(all(feature = "unstable", feature = "send"))]
+    static_assertions::assert_impl_all!(OwnedTable: Send);
 }
@@ -0,0 +1,314 @@
+//! Helpers for testing code built on top of mlua against every supported Lua engine.
+//!
+//! The module formalizes patterns used in mlua's own test suite, so that library authors can
+//! run the same tests with every engine feature without duplicating the engine-specific logic:
+//!
+//! - [`make_lua`] and [`make_lua_with_bytecode`] construct a [`Lua`] instance suitable for tests.
+//! - [`skip_if!`] skips a test for the given engines.
+//! - [`assert_lua_eq!`] evaluates a Lua expression and compares it with the expected value,
+//!   reporting both values (in Lua notation) and the engine name on failure.
+//!
+//! Engine checks are done at runtime using [`Engine::current`], as the engine features are not
+//! visible to the crates depending on mlua.
+//!
+//! # Examples
+//!
+//! ```
+//! use mlua::testing::make_lua;
+//! use mlua::{assert_lua_eq, skip_if, Result};
+//!
+//! fn integer_division() -> Result<()> {
+//!     skip_if!(lua51, luajit);
+//!
+//!     let lua = make_lua();
+//!     assert_lua_eq!(&lua, "7 // 2", 3);
+//!     Ok(())
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! integer_division()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requires `feature = "testing"`
+//!
+//! [`skip_if!`]: crate::skip_if
+//! [`assert_lua_eq!`]: crate::assert_lua_eq
+
+use std::fmt::{self, Write};
+use std::string::String as StdString;
+
+use crate::error::Result;
+use crate::lua::Lua;
+use crate::table::{EqualsOptions, Table};
+use crate::value::{IntoLua, Value};
+
+#[cfg(not(feature = "luau"))]
+use crate::{lua::LuaOptions, stdlib::StdLib};
+
+/// Lua engine that mlua is compiled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Engine {
+    /// Lua 5.4
+    Lua54,
+    /// Lua 5.3
+    Lua53,
+    /// Lua 5.2
+    Lua52,
+    /// Lua 5.1
+    Lua51,
+    /// LuaJIT (including `luajit52` feature)
+    LuaJIT,
+    /// Roblox Luau
+    Luau,
+}
+
+impl Engine {
+    /// Returns the engine enabled by mlua features.
+    pub const fn current() -> Engine {
+        #[cfg(feature = "lua54")]
+        {
+            Engine::Lua54
+        }
+        #[cfg(feature = "lua53")]
+        {
+            Engine::Lua53
+        }
+        #[cfg(feature = "lua52")]
+        {
+            Engine::Lua52
+        }
+        #[cfg(feature = "lua51")]
+        {
+            Engine::Lua51
+        }
+        #[cfg(feature = "luajit")]
+        {
+            Engine::LuaJIT
+        }
+        #[cfg(feature = "luau")]
+        {
+            Engine::Luau
+        }
+    }
+
+    /// Returns the feature name of the engine, eg. `lua54` or `luau`.
+    pub const fn feature_name(self) -> &'static str {
+        match self {
+            Engine::Lua54 => "lua54",
+            Engine::Lua53 => "lua53",
+            Engine::Lua52 => "lua52",
+            Engine::Lua51 => "lua51",
+            Engine::LuaJIT => "luajit",
+            Engine::Luau => "luau",
+        }
+    }
+
+    /// Returns `true` if the engine is one of the engines named by their feature names.
+    ///
+    /// Unknown names are ignored.
+    pub fn is_any_of(self, names: &[&str]) -> bool {
+        names.iter().any(|&name| name == self.feature_name())
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Engine::Lua54 => "Lua 5.4",
+            Engine::Lua53 => "Lua 5.3",
+            Engine::Lua52 => "Lua 5.2",
+            Engine::Lua51 => "Lua 5.1",
+            Engine::LuaJIT => "LuaJIT",
+            Engine::Luau => "Luau",
+        };
+        write!(fmt, "{}", name)
+    }
+}
+
+/// Creates a new [`Lua`] instance with the safe subset of the standard libraries.
+pub fn make_lua() -> Lua {
+    Lua::new()
+}
+
+/// Creates a new [`Lua`] instance that can load precompiled chunks (bytecode).
+///
+/// Loading bytecode is unsafe in all engines except Luau (where bytecode is verified), so
+/// the instance is created in unsafe mode with the safe subset of the standard libraries.
+pub fn make_lua_with_bytecode() -> Lua {
+    #[cfg(feature = "luau")]
+    {
+        Lua::new()
+    }
+    #[cfg(not(feature = "luau"))]
+    unsafe {
+        Lua::unsafe_new_with(StdLib::ALL_SAFE, LuaOptions::default())
+    }
+}
+
+/// Value returned from a test function that has been skipped, see [`skip_if!`].
+///
+/// [`skip_if!`]: crate::skip_if
+pub trait Skipped {
+    /// Returns the value that marks the test as passed.
+    fn skipped() -> Self;
+}
+
+impl Skipped for () {
+    fn skipped() -> Self {}
+}
+
+impl<E> Skipped for std::result::Result<(), E> {
+    fn skipped() -> Self {
+        Ok(())
+    }
+}
+
+/// Skips the rest of the test function if mlua is compiled with any of the listed engines.
+///
+/// Engines are named by their feature names: `lua54`, `lua53`, `lua52`, `lua51`, `luajit`
+/// and `luau`. Works in test functions returning `()` or `Result<(), E>`.
+///
+/// ```
+/// # use mlua::skip_if;
+/// fn test() {
+///     skip_if!(luau);
+///     // Code that is not supported by Luau
+/// }
+/// # test();
+/// ```
+///
+/// Requires `feature = "testing"`
+#[macro_export]
+macro_rules! skip_if {
+    ($($engine:ident),+ $(,)?) => {
+        let engine = $crate::testing::Engine::current();
+        if engine.is_any_of(&[$(stringify!($engine)),+]) {
+            return $crate::testing::Skipped::skipped();
+        }
+    };
+}
+
+/// Evaluates a Lua expression and asserts that the result is equal to the expected value.
+///
+/// The expected value is converted to Lua and compared with the result using
+/// [`values_equal`]. On failure, the macro panics with a message that includes the expression,
+/// both values in Lua notation and the engine name.
+///
+/// ```
+/// # use mlua::{assert_lua_eq, Lua};
+/// let lua = Lua::new();
+/// assert_lua_eq!(&lua, "1 + 2", 3);
+/// assert_lua_eq!(&lua, "('a'):rep(3)", "aaa");
+/// assert_lua_eq!(&lua, "{1, 2, x = true}", lua.load("{1, 2, x = true}").eval::<mlua::Table>().unwrap());
+/// ```
+///
+/// Requires `feature = "testing"`
+///
+/// [`values_equal`]: crate::testing::values_equal
+#[macro_export]
+macro_rules! assert_lua_eq {
+    ($lua:expr, $expr:expr, $expected:expr $(,)?) => {
+        if let Err(msg) = $crate::testing::check_lua_eq($lua, $expr, $expected) {
+            panic!("{}", msg);
+        }
+    };
+}
+
+/// Evaluates a Lua expression and compares the result with the expected value.
+///
+/// Returns a human readable error message on mismatch (or if the evaluation fails).
+/// This is the function behind the [`assert_lua_eq!`] macro.
+///
+/// [`assert_lua_eq!`]: crate::assert_lua_eq
+pub fn check_lua_eq<'lua>(
+    lua: &'lua Lua,
+    expr: &str,
+    expected: impl IntoLua<'lua>,
+) -> std::result::Result<(), StdString> {
+    let engine = Engine::current();
+    let expected = expected
+        .into_lua(lua)
+        .map_err(|err| format!("[{}] cannot convert expected value to Lua: {}", engine, err))?;
+    let actual = (lua.load(expr).set_name("=assert_lua_eq").eval::<Value>())
+        .map_err(|err| format!("[{}] failed to evaluate `{}`: {}", engine, expr, err))?;
+
+    match values_equal(&actual, &expected) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!(
+            "[{}] assertion `{}` failed\n  actual: {}\nexpected: {}",
+            engine,
+            expr,
+            describe(&actual),
+            describe(&expected),
+        )),
+        Err(err) => Err(format!("[{}] failed to compare values: {}", engine, err)),
+    }
+}
+
+/// Compares two Lua values without invoking metamethods.
+///
+/// Tables are compared structurally using [`Table::deep_equals`], numbers are compared by value
+/// (`1 == 1.0`) and other values are compared using [`Value`] equality.
+pub fn values_equal(a: &Value, b: &Value) -> Result<bool> {
+    match (a, b) {
+        (Value::Table(a), Value::Table(b)) => a.deep_equals(b, &EqualsOptions::new()),
+        (Value::Number(a), Value::Number(b)) if a.is_nan() && b.is_nan() => Ok(true),
+        _ => Ok(a == b),
+    }
+}
+
+// Formats the value in Lua notation (tables are printed with sorted keys)
+fn describe(value: &Value) -> StdString {
+    let mut out = StdString::new();
+    let _ = describe_into(&mut out, value, 0);
+    out
+}
+
+fn describe_into(out: &mut StdString, value: &Value, depth: usize) -> fmt::Result {
+    match value {
+        Value::Nil => write!(out, "nil"),
+        Value::Boolean(b) => write!(out, "{}", b),
+        Value::Integer(i) => write!(out, "{}", i),
+        Value::Number(n) => write!(out, "{:?}", n),
+        Value::String(s) => write!(out, "{:?}", s),
+        Value::Table(t) if depth < 8 => describe_table(out, t, depth),
+        value => write!(out, "{}: {:?}", value.type_name(), value.to_pointer()),
+    }
+}
+
+fn describe_table(out: &mut StdString, table: &Table, depth: usize) -> fmt::Result {
+    let pairs = match table.sorted_pairs_by::<Value>(|a, b| a.sort_cmp(b)) {
+        Ok(pairs) => pairs,
+        Err(_) => return write!(out, "table: {:?}", table.to_pointer()),
+    };
+    write!(out, "{{")?;
+    let mut next_index = 1;
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ")?;
+        }
+        match key {
+            Value::Integer(k) if *k == next_index => next_index += 1,
+            Value::String(s) if is_identifier(s.as_bytes()) => {
+                write!(out, "{} = ", s.to_string_lossy())?
+            }
+            key => {
+                write!(out, "[")?;
+                describe_into(out, key, depth + 1)?;
+                write!(out, "] = ")?;
+            }
+        }
+        describe_into(out, value, depth + 1)?;
+    }
+    write!(out, "}}")
+}
+
+fn is_identifier(s: &[u8]) -> bool {
+    match s.first() {
+        Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {}
+        _ => return false,
+    }
+    s.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'_')
+}
@@ -1,24 +1,26 @@
 use std::cmp;
-use std::os::raw::c_int;
+use std::hash::{Hash, Hasher};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::string::String as StdString;
 
 use crate::error::{Error, Result};
 use crate::ffi;
-use crate::types::LuaRef;
-use crate::util::{check_stack, error_traceback_thread, pop_error, StackGuard};
-use crate::value::{FromLuaMulti, IntoLuaMulti};
-
-#[cfg(any(
-    feature = "lua54",
-    all(feature = "luajit", feature = "vendored"),
-    feature = "luau",
-))]
 use crate::function::Function;
+use crate::types::{Integer, LuaRef};
+use crate::util::{
+    check_call_args, check_stack, error_traceback_thread, pop_error, to_string, StackGuard,
+};
+use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, Value};
+
+#[cfg(feature = "lua54")]
+use crate::util::get_main_state;
 
 #[cfg(feature = "async")]
 use {
     crate::{
         lua::{Lua, ASYNC_POLL_PENDING},
-        value::{MultiValue, Value},
+        value::MultiValue,
     },
     futures_core::{future::Future, stream::Stream},
     std::{
@@ -47,6 +49,10 @@ pub enum ThreadStatus {
 #[derive(Clone, Debug)]
 pub struct Thread<'lua>(pub(crate) LuaRef<'lua>);
 
+/// Identifier of a value anchored to a [`Thread`] using [`Thread::anchor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnchorId(Integer);
+
 /// Thread (coroutine) representation as an async [`Future`] or [`Stream`].
 ///
 /// Requires `feature = "async"`
@@ -62,6 +68,22 @@ pub struct AsyncThread<'lua, R> {
     recycle: bool,
 }
 
+/// Stream of values yielded by a Lua thread (coroutine).
+///
+/// Unlike [`AsyncThread`] stream, the values returned from the thread function are not included
+/// into the stream; they can be retrieved using [`finish`] once the stream is exhausted.
+///
+/// Requires `feature = "async"`
+///
+/// [`finish`]: #method.finish
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct AsyncThreadStream<'lua, R> {
+    inner: AsyncThread<'lua, MultiValue<'lua>>,
+    ret: Option<MultiValue<'lua>>,
+    item: PhantomData<fn() -> R>,
+}
+
 impl<'lua> Thread<'lua> {
     /// Resumes execution of this thread.
     ///
@@ -78,6 +100,9 @@ impl<'lua> Thread<'lua> {
     /// If the thread calls `coroutine.yield`, returns the values passed to `yield`. If the thread
     /// `return`s values from its main function, returns those.
     ///
+    /// If the thread raises an error, the returned error message includes a traceback of the
+    /// thread stack (see [`Thread::traceback`]).
+    ///
     /// # Examples
     ///
     /// ```
@@ -109,11 +134,12 @@ impl<'lua> Thread<'lua> {
         A: IntoLuaMulti<'lua>,
         R: FromLuaMulti<'lua>,
     {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
 
         let mut args = args.into_lua_multi(lua)?;
-        let nargs = args.len() as c_int;
+        let nargs = check_call_args(args.len())?;
         let results = unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, cmp::max(nargs + 1, 3))?;
@@ -135,7 +161,18 @@ impl<'lua> Thread<'lua> {
 
             let ret = ffi::lua_resume(thread_state, state, nargs, &mut nresults as *mut c_int);
             if ret != ffi::LUA_OK && ret != ffi::LUA_YIELD {
-                check_stack(state, 3)?;
+                check_stack(state, 5)?;
+                check_stack(thread_state, 1)?;
+                // Keep the error object to be retrieved later using `pending_error`
+                lua.push_ref(&self.0);
+                ffi::lua_pushvalue(thread_state, -1);
+                ffi::lua_xmove(thread_state, state, 1);
+                protect_lua!(state, 2, 0, fn(state) {
+                    push_weak_keys_table(state, &THREAD_ERRORS_REGISTRY_KEY);
+                    ffi::lua_insert(state, -3);
+                    ffi::lua_rawset(state, -3);
+                })?;
+
                 protect_lua!(state, 0, 1, |state| error_traceback_thread(
                     state,
                     thread_state
@@ -143,6 +180,10 @@ impl<'lua> Thread<'lua> {
                 return Err(pop_error(state, ret));
             }
 
+            if let Err(err) = check_call_args(nresults as usize) {
+                ffi::lua_pop(thread_state, nresults);
+                return Err(err);
+            }
             let mut results = args; // Reuse MultiValue container
             check_stack(state, nresults + 2)?; // 2 is extra for `lua.pop_value()` below
             ffi::lua_xmove(thread_state, state, nresults);
@@ -157,6 +198,7 @@ impl<'lua> Thread<'lua> {
 
     /// Gets the status of the thread.
     pub fn status(&self) -> ThreadStatus {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         unsafe {
             let thread_state = ffi::lua_tothread(lua.ref_thread(), self.0.index);
@@ -172,35 +214,275 @@ impl<'lua> Thread<'lua> {
         }
     }
 
+    /// Returns the error object that stopped the thread.
+    ///
+    /// Returns `None` if the thread is not in the [`ThreadStatus::Error`] state. The error is
+    /// remembered when the thread is resumed from Rust (using [`Thread::resume`] or as
+    /// an [`AsyncThread`]), so it can be retrieved even if the resume result was discarded.
+    /// Errors of coroutines resumed from Lua (using `coroutine.resume`) are not tracked.
+    ///
+    /// Errors raised from Rust are returned as [`Value::Error`].
+    ///
+    /// [`AsyncThread`]: crate::AsyncThread
+    /// [`Value::Error`]: crate::Value::Error
+    pub fn pending_error(&self) -> Result<Option<Value<'lua>>> {
+        let _guard = self.0.lua.lock();
+        if self.status() != ThreadStatus::Error {
+            return Ok(None);
+        }
+
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+
+            let errors_key = &THREAD_ERRORS_REGISTRY_KEY as *const u8 as *const c_void;
+            if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, errors_key) != ffi::LUA_TTABLE {
+                return Ok(None);
+            }
+            lua.push_ref(&self.0);
+            if ffi::lua_rawget(state, -2) == ffi::LUA_TNIL {
+                return Ok(None);
+            }
+            Ok(Some(lua.pop_value()))
+        }
+    }
+
+    /// Generates a traceback of the thread stack, optionally prefixed with `msg`.
+    ///
+    /// The traceback is built against the thread's own stack, so it can be used to inspect
+    /// a suspended coroutine (at the point where it yielded) or a coroutine that failed:
+    /// the stack of a thread that raised an error is preserved by Lua, which makes it possible
+    /// to inspect it later, eg. for logging.
+    ///
+    /// Errors returned from [`Thread::resume`] already include this traceback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Thread};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let thread: Thread = lua.load(r#"
+    ///     coroutine.create(function()
+    ///         coroutine.yield()
+    ///     end)
+    /// "#).eval()?;
+    ///
+    /// thread.resume::<_, ()>(())?;
+    /// let traceback = thread.traceback(Some("suspended"))?;
+    /// assert!(traceback.starts_with("suspended\nstack traceback:"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn traceback(&self, msg: Option<&str>) -> Result<StdString> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, ffi::LUA_TRACEBACK_STACK + 1)?;
+
+            let thread_state = ffi::lua_tothread(lua.ref_thread(), self.0.index);
+            protect_lua!(state, 0, 1, |state| {
+                let msg = match msg {
+                    Some(msg) => {
+                        ffi::lua_pushlstring(state, msg.as_ptr() as *const c_char, msg.len());
+                        ffi::lua_tostring(state, -1)
+                    }
+                    None => ptr::null(),
+                };
+                ffi::luaL_traceback(state, thread_state, msg, 0);
+            })?;
+            Ok(to_string(state, -1))
+        }
+    }
+
+    /// Converts the thread to a generic C pointer.
+    ///
+    /// Different threads will give different pointers.
+    /// There is no way to convert the pointer back to its original value.
+    ///
+    /// Typically this function is used only for hashing and debug information.
+    #[inline]
+    pub fn to_pointer(&self) -> *const c_void {
+        let _guard = self.0.lua.lock();
+        let ref_thread = self.0.lua.ref_thread();
+        unsafe { ffi::lua_topointer(ref_thread, self.0.index) }
+    }
+
+    /// Anchors `value` to this thread, keeping it alive as long as the thread is alive.
+    ///
+    /// The values are stored in a table associated with the thread (in a registry table with
+    /// weak keys), so unlike [`Lua::create_registry_value`] they don't need to be released
+    /// individually: once the thread is not referenced anymore, all its anchored values are
+    /// collected together with it. This makes it cheap to keep Lua data alive while the
+    /// coroutine is suspended, eg. in a pending Rust future.
+    ///
+    /// The anchored value can be retrieved using [`Thread::anchored`] and released earlier
+    /// using [`Thread::unanchor`].
+    ///
+    /// # Note
+    ///
+    /// In Lua 5.1 and LuaJIT, values that reference the thread itself (eg. a closure that captures
+    /// it) keep the thread alive, as weak tables are not ephemerons there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Thread};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let thread: Thread = lua.load("coroutine.create(function() end)").eval()?;
+    /// let id = thread.anchor("payload")?;
+    /// assert_eq!(thread.anchored::<String>(id)?, "payload");
+    /// thread.unanchor(id)?;
+    /// assert_eq!(thread.anchored::<Option<String>>(id)?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Lua::create_registry_value`]: crate::Lua::create_registry_value
+    pub fn anchor<V: IntoLua<'lua>>(&self, value: V) -> Result<AnchorId> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        let value = value.into_lua(lua)?;
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 7)?;
+
+            lua.push_value(value)?;
+            lua.push_ref(&self.0);
+            let id = protect_lua!(state, 2, 0, |state| {
+                push_anchors_table(state, -1);
+                ffi::lua_rawgeti(state, -1, 0);
+                let id = ffi::lua_tointeger(state, -1) + 1;
+                ffi::lua_pop(state, 1);
+                ffi::lua_pushinteger(state, id);
+                ffi::lua_rawseti(state, -2, 0);
+                ffi::lua_pushvalue(state, -3);
+                ffi::lua_rawseti(state, -2, id);
+                id
+            })?;
+            Ok(AnchorId(id))
+        }
+    }
+
+    /// Returns the value anchored to this thread using [`Thread::anchor`].
+    ///
+    /// Returns `nil` (converted to `V`) if the value was released using [`Thread::unanchor`].
+    pub fn anchored<V: FromLua<'lua>>(&self, id: AnchorId) -> Result<V> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        let value = unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            match self.push_anchors(state) {
+                true => {
+                    ffi::lua_rawgeti(state, -1, id.0);
+                    lua.pop_value()
+                }
+                false => Value::Nil,
+            }
+        };
+        V::from_lua(value, lua)
+    }
+
+    /// Releases the value anchored to this thread using [`Thread::anchor`].
+    ///
+    /// Releasing the same value twice is a no-op.
+    pub fn unanchor(&self, id: AnchorId) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            if self.push_anchors(state) {
+                ffi::lua_pushnil(state);
+                protect_lua!(state, 2, 0, |state| ffi::lua_rawseti(state, -2, id.0))?;
+            }
+            Ok(())
+        }
+    }
+
+    // Pushes the anchors table of this thread onto the stack if it exists.
+    // Uses 3 stack spaces.
+    unsafe fn push_anchors(&self, state: *mut ffi::lua_State) -> bool {
+        let _guard = self.0.lua.lock();
+        let anchors_key = &THREAD_ANCHORS_REGISTRY_KEY as *const u8 as *const c_void;
+        if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, anchors_key) != ffi::LUA_TTABLE {
+            ffi::lua_pop(state, 1);
+            return false;
+        }
+        self.0.lua.push_ref(&self.0);
+        if ffi::lua_rawget(state, -2) != ffi::LUA_TTABLE {
+            ffi::lua_pop(state, 2);
+            return false;
+        }
+        ffi::lua_remove(state, -2);
+        true
+    }
+
     /// Resets a thread
     ///
     /// In [Lua 5.4]: cleans its call stack and closes all pending to-be-closed variables.
     /// Returns a error in case of either the original error that stopped the thread or errors
     /// in closing methods.
     ///
-    /// In [LuaJIT] and Luau: resets to the initial state of a newly created Lua thread.
-    /// Lua threads in arbitrary states (like yielded or errored) can be reset properly.
+    /// In [LuaJIT] (with `vendored` feature) and Luau: resets to the initial state of a newly
+    /// created Lua thread. Lua threads in arbitrary states (like yielded or errored) can be reset
+    /// properly.
+    ///
+    /// In other Lua versions: a finished (or not started) thread is reused, otherwise (if the
+    /// thread is suspended or failed) the handle is pointed to a newly created thread. Other handles
+    /// and references from Lua keep referring to the original thread in that case.
     ///
     /// Sets a Lua function for the thread afterwards.
     ///
-    /// Requires `feature = "lua54"` OR `feature = "luajit,vendored"` OR `feature = "luau"`
+    /// Returns an error if the thread is currently running (or resuming another thread).
     ///
     /// [Lua 5.4]: https://www.lua.org/manual/5.4/manual.html#lua_resetthread
     /// [LuaJIT]: https://github.com/openresty/luajit2#lua_resetthread
-    #[cfg(any(
-        feature = "lua54",
-        all(feature = "luajit", feature = "vendored"),
-        feature = "luau",
-    ))]
     pub fn reset(&self, func: Function<'lua>) -> Result<()> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
             let _sg = StackGuard::new(state);
-            check_stack(state, 2)?;
+            check_stack(state, 3)?;
 
             lua.push_ref(&self.0);
             let thread_state = ffi::lua_tothread(state, -1);
+            if thread_state == state || is_thread_active(thread_state) {
+                return Err(Error::RuntimeError("cannot reset a running thread".into()));
+            }
+
+            #[cfg(not(any(
+                feature = "lua54",
+                all(feature = "luajit", feature = "vendored"),
+                feature = "luau",
+            )))]
+            let thread_state = if ffi::lua_status(thread_state) == ffi::LUA_OK {
+                // The thread has no active frames, so it can be reused
+                ffi::lua_settop(thread_state, 0);
+                thread_state
+            } else {
+                let thread_state = if lua.unlikely_memory_error() {
+                    ffi::lua_newthread(state)
+                } else {
+                    protect_lua!(state, 0, 1, |state| ffi::lua_newthread(state))?
+                };
+                let ref_thread = lua.ref_thread();
+                ffi::lua_xmove(state, ref_thread, 1);
+                ffi::lua_replace(ref_thread, self.0.index);
+                thread_state
+            };
 
             #[cfg(feature = "lua54")]
             let status = ffi::lua_resetthread(thread_state);
@@ -227,6 +509,45 @@ impl<'lua> Thread<'lua> {
         }
     }
 
+    /// Closes the thread, running its pending to-be-closed variables.
+    ///
+    /// This is the equivalent of `coroutine.close`: the `__close` metamethods of variables
+    /// declared `<close>` in a suspended coroutine are called (in reverse order) and the thread
+    /// becomes dead ([`ThreadStatus::Unresumable`]). Unlike [`Thread::reset`], the thread is not
+    /// prepared to run a new function.
+    ///
+    /// Returns an error raised by a `__close` metamethod. If the thread had already failed,
+    /// its original error is returned (like `coroutine.close` does).
+    ///
+    /// Closing the main thread or a running thread is an error.
+    ///
+    /// Requires `feature = "lua54"`
+    #[cfg(feature = "lua54")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lua54")))]
+    pub fn close(&self) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 1)?;
+
+            let thread_state = ffi::lua_tothread(lua.ref_thread(), self.0.index);
+            if get_main_state(state) == Some(thread_state) {
+                return Err(Error::RuntimeError("cannot close the main thread".into()));
+            }
+            if thread_state == state || is_thread_active(thread_state) {
+                return Err(Error::RuntimeError("cannot close a running thread".into()));
+            }
+
+            let status = ffi::lua_resetthread(thread_state);
+            if status != ffi::LUA_OK {
+                return Err(pop_error(thread_state, status));
+            }
+            Ok(())
+        }
+    }
+
     /// Converts Thread to an AsyncThread which implements [`Future`] and [`Stream`] traits.
     ///
     /// `args` are passed as arguments to the thread function for first call.
@@ -288,6 +609,61 @@ impl<'lua> Thread<'lua> {
         }
     }
 
+    /// Converts Thread to a [`Stream`] of values yielded by the thread.
+    ///
+    /// `args` are passed as arguments to the thread function for first call.
+    /// Every `coroutine.yield(...)` produces a stream item, converted to `R`. The stream ends when
+    /// the thread function returns, the returned values are available using
+    /// [`AsyncThreadStream::finish`]. If the thread raises an error, the error is returned as
+    /// the last stream item.
+    ///
+    /// Requires `feature = "async"`
+    ///
+    /// [`Stream`]: futures_core::stream::Stream
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Thread};
+    /// use futures::stream::TryStreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let thread: Thread = lua.load(r#"
+    ///     coroutine.create(function (n)
+    ///         for i = 1, n do
+    ///             coroutine.yield(i)
+    ///         end
+    ///         return "done"
+    ///     end)
+    /// "#).eval()?;
+    ///
+    /// let mut stream = thread.into_stream::<_, i64>(3);
+    /// let mut items = Vec::new();
+    /// while let Some(i) = stream.try_next().await? {
+    ///     items.push(i);
+    /// }
+    ///
+    /// assert_eq!(items, vec![1, 2, 3]);
+    /// assert_eq!(stream.finish::<String>()?.as_deref(), Some("done"));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_stream<A, R>(self, args: A) -> AsyncThreadStream<'lua, R>
+    where
+        A: IntoLuaMulti<'lua>,
+        R: FromLuaMulti<'lua>,
+    {
+        AsyncThreadStream {
+            inner: self.into_async(args),
+            ret: None,
+            item: PhantomData,
+        }
+    }
+
     /// Enables sandbox mode on this thread.
     ///
     /// Under the hood replaces the global environment table with a new table,
@@ -323,6 +699,7 @@ impl<'lua> Thread<'lua> {
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     #[doc(hidden)]
     pub fn sandbox(&self) -> Result<()> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
@@ -337,12 +714,67 @@ impl<'lua> Thread<'lua> {
     }
 }
 
+static THREAD_ANCHORS_REGISTRY_KEY: u8 = 0;
+static THREAD_ERRORS_REGISTRY_KEY: u8 = 0;
+
+// Pushes the registry table with weak keys stored at `key`, creating it if missing.
+// Must be called in a protected context. Uses 3 stack spaces.
+unsafe fn push_weak_keys_table(state: *mut ffi::lua_State, key: &'static u8) {
+    let key = key as *const u8 as *const c_void;
+    if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, key) != ffi::LUA_TTABLE {
+        ffi::lua_pop(state, 1);
+        ffi::lua_createtable(state, 0, 0);
+        ffi::lua_createtable(state, 0, 1);
+        ffi::lua_pushstring(state, cstr!("k"));
+        ffi::lua_setfield(state, -2, cstr!("__mode"));
+        ffi::lua_setmetatable(state, -2);
+        ffi::lua_pushvalue(state, -1);
+        ffi::lua_rawsetp(state, ffi::LUA_REGISTRYINDEX, key);
+    }
+}
+
+// Returns `true` if the thread is running or resuming another thread (has active frames).
+unsafe fn is_thread_active(thread_state: *mut ffi::lua_State) -> bool {
+    if ffi::lua_status(thread_state) != ffi::LUA_OK {
+        return false;
+    }
+    let mut ar: ffi::lua_Debug = std::mem::zeroed();
+    #[cfg(not(feature = "luau"))]
+    return ffi::lua_getstack(thread_state, 0, &mut ar) != 0;
+    #[cfg(feature = "luau")]
+    return ffi::lua_getinfo(thread_state, 0, cstr!(""), &mut ar) != 0;
+}
+
+// Pushes the anchors table of the thread at `idx`, creating it if missing.
+// Must be called in a protected context. Uses 4 stack spaces.
+unsafe fn push_anchors_table(state: *mut ffi::lua_State, idx: c_int) {
+    let idx = ffi::lua_absindex(state, idx);
+    push_weak_keys_table(state, &THREAD_ANCHORS_REGISTRY_KEY);
+    ffi::lua_pushvalue(state, idx);
+    if ffi::lua_rawget(state, -2) != ffi::LUA_TTABLE {
+        ffi::lua_pop(state, 1);
+        ffi::lua_createtable(state, 0, 0);
+        ffi::lua_pushvalue(state, idx);
+        ffi::lua_pushvalue(state, -2);
+        ffi::lua_rawset(state, -4);
+    }
+    ffi::lua_remove(state, -2);
+}
+
 impl<'lua> PartialEq for Thread<'lua> {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
+impl<'lua> Eq for Thread<'lua> {}
+
+impl<'lua> Hash for Thread<'lua> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_pointer().hash(state);
+    }
+}
+
 #[cfg(feature = "async")]
 impl<'lua, R> AsyncThread<'lua, R> {
     #[inline]
@@ -359,6 +791,7 @@ impl<'lua, R> AsyncThread<'lua, R> {
 ))]
 impl<'lua, R> Drop for AsyncThread<'lua, R> {
     fn drop(&mut self) {
+        let _guard = self.thread.0.lua.lock();
         if self.recycle {
             unsafe {
                 let lua = self.thread.0.lua;
@@ -409,6 +842,48 @@ where
     }
 }
 
+#[cfg(feature = "async")]
+impl<'lua, R> AsyncThreadStream<'lua, R> {
+    /// Returns the values returned by the thread function, converted to `T`.
+    ///
+    /// Returns `None` if the stream is not exhausted yet, or if the thread raised an error.
+    /// The values can be taken only once.
+    pub fn finish<T: FromLuaMulti<'lua>>(&mut self) -> Result<Option<T>> {
+        let lua = self.inner.thread.0.lua;
+        match self.ret.take() {
+            Some(ret) => T::from_lua_multi(ret, lua).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'lua, R> Stream for AsyncThreadStream<'lua, R>
+where
+    R: FromLuaMulti<'lua>,
+{
+    type Item = Result<R>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let lua = this.inner.thread.0.lua;
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            // The thread has yielded
+            Poll::Ready(Some(Ok(ret))) if this.inner.thread.status() == ThreadStatus::Resumable => {
+                Poll::Ready(Some(R::from_lua_multi(ret, lua)))
+            }
+            // The thread function has returned
+            Poll::Ready(Some(Ok(ret))) => {
+                this.ret = Some(ret);
+                Poll::Ready(None)
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
 #[cfg(feature = "async")]
 impl<'lua, R> Future for AsyncThread<'lua, R>
 where
@@ -11,7 +11,8 @@ use std::ffi::CStr;
 #[cfg(feature = "async")]
 use futures_core::future::LocalBoxFuture;
 
-use crate::error::Result;
+use crate::chunk::ChunkEvent;
+use crate::error::{Error, Result};
 use crate::ffi;
 #[cfg(not(feature = "luau"))]
 use crate::hook::Debug;
@@ -20,8 +21,17 @@ use crate::util::{assert_stack, StackGuard};
 use crate::value::MultiValue;
 
 /// Type of Lua integer numbers.
+///
+/// This is `i64` for Lua 5.3 and 5.4, where it matches the integer subtype of Lua numbers.
+/// For Lua 5.1, 5.2 and LuaJIT it's a pointer-sized integer (`isize` width), and for Luau
+/// it's `i32`. These Lua versions represent all numbers as floats, so integers are converted
+/// to [`Number`] when passed to Lua. See [`Lua::numeric_model`] for details.
+///
+/// [`Lua::numeric_model`]: crate::Lua::numeric_model
 pub type Integer = ffi::lua_Integer;
 /// Type of Lua floating point numbers.
+///
+/// This is `f64` for all supported Lua versions.
 pub type Number = ffi::lua_Number;
 
 /// A "light" userdata value. Equivalent to an unmanaged raw pointer.
@@ -68,6 +78,24 @@ pub(crate) type InterruptCallback = Arc<dyn Fn() -> Result<VmState> + Send>;
 #[cfg(all(feature = "luau", not(feature = "send")))]
 pub(crate) type InterruptCallback = Arc<dyn Fn() -> Result<VmState>>;
 
+#[cfg(feature = "send")]
+pub(crate) type ChunkObserverCallback = Arc<dyn Fn(ChunkEvent) + Send>;
+
+#[cfg(not(feature = "send"))]
+pub(crate) type ChunkObserverCallback = Arc<dyn Fn(ChunkEvent)>;
+
+#[cfg(feature = "send")]
+pub(crate) type ErrorTranslatorCallback = Arc<dyn Fn(&Error) -> Option<String> + Send>;
+
+#[cfg(not(feature = "send"))]
+pub(crate) type ErrorTranslatorCallback = Arc<dyn Fn(&Error) -> Option<String>>;
+
+#[cfg(all(feature = "send", not(feature = "luau")))]
+pub(crate) type DropOffloaderCallback = Box<dyn Fn(Box<dyn FnOnce() + Send>) + Send>;
+
+#[cfg(all(not(feature = "send"), not(feature = "luau")))]
+pub(crate) type DropOffloaderCallback = Box<dyn Fn(Box<dyn FnOnce() + Send>)>;
+
 #[cfg(all(feature = "send", feature = "lua54"))]
 pub(crate) type WarnCallback = Box<dyn Fn(&Lua, &CStr, bool) -> Result<()> + Send>;
 
@@ -221,6 +249,7 @@ impl<'lua> Drop for LuaRef<'lua> {
 
 impl<'lua> PartialEq for LuaRef<'lua> {
     fn eq(&self, other: &Self) -> bool {
+        let _guard = self.lua.lock();
         let lua = self.lua;
         let state = lua.state();
         unsafe {
@@ -233,6 +262,50 @@ impl<'lua> PartialEq for LuaRef<'lua> {
     }
 }
 
+// A reentrant lock that serializes access to a Lua instance from different threads.
+//
+// The lock can be acquired multiple times by the same thread, eg. when a callback invoked under
+// the lock accesses another handle of the same Lua instance.
+#[cfg(feature = "send")]
+#[derive(Default)]
+pub(crate) struct ReentrantLock {
+    // Owner thread and the number of times the lock was acquired by it
+    state: Mutex<(Option<std::thread::ThreadId>, usize)>,
+    released: std::sync::Condvar,
+}
+
+#[cfg(feature = "send")]
+pub(crate) struct ReentrantLockGuard<'a>(&'a ReentrantLock);
+
+#[cfg(not(feature = "send"))]
+pub(crate) struct ReentrantLockGuard<'a>(pub(crate) std::marker::PhantomData<&'a ()>);
+
+#[cfg(feature = "send")]
+impl ReentrantLock {
+    pub(crate) fn lock(&self) -> ReentrantLockGuard<'_> {
+        let current = std::thread::current().id();
+        let mut state = mlua_expect!(self.state.lock(), "lua lock poisoned");
+        while matches!(state.0, Some(owner) if owner != current) {
+            state = mlua_expect!(self.released.wait(state), "lua lock poisoned");
+        }
+        state.0 = Some(current);
+        state.1 += 1;
+        ReentrantLockGuard(self)
+    }
+}
+
+#[cfg(feature = "send")]
+impl<'a> Drop for ReentrantLockGuard<'a> {
+    fn drop(&mut self) {
+        let mut state = mlua_expect!(self.0.state.lock(), "lua lock poisoned");
+        state.1 -= 1;
+        if state.1 == 0 {
+            state.0 = None;
+            self.0.released.notify_one();
+        }
+    }
+}
+
 #[cfg(feature = "unstable")]
 pub(crate) struct LuaOwnedRef {
     pub(crate) lua: Lua,
@@ -240,6 +313,11 @@ pub(crate) struct LuaOwnedRef {
     _non_send: std::marker::PhantomData<*const ()>,
 }
 
+// With `send` feature every access to the Lua state (including dropping and cloning the reference)
+// is done under the Lua lock, so the reference can be moved to another thread
+#[cfg(all(feature = "unstable", feature = "send"))]
+unsafe impl Send for LuaOwnedRef {}
+
 #[cfg(feature = "unstable")]
 impl fmt::Debug for LuaOwnedRef {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -268,14 +346,6 @@ impl Drop for LuaOwnedRef {
 #[cfg(feature = "unstable")]
 impl LuaOwnedRef {
     pub(crate) const fn new(lua: Lua, index: c_int) -> Self {
-        #[cfg(feature = "send")]
-        {
-            let _lua = lua;
-            let _index = index;
-            panic!("mlua must be compiled without \"send\" feature to use Owned types");
-        }
-
-        #[cfg(not(feature = "send"))]
         LuaOwnedRef {
             lua,
             index,
@@ -299,6 +369,10 @@ mod assertions {
     static_assertions::assert_impl_all!(RegistryKey: Send, Sync);
     static_assertions::assert_not_impl_any!(LuaRef: Send);
 
-    #[cfg(feature = "unstable")]
+    #[cfg(all(feature = "unstable", not(feature = "send")))]
     static_assertions::assert_not_impl_any!(LuaOwnedRef: Send);
+    #[cfg(all(feature = "unstable", feature = "send"))]
+    static_assertions::assert_impl_all!(LuaOwnedRef: Send);
+    #[cfg(feature = "unstable")]
+    static_assertions::assert_not_impl_any!(LuaOwnedRef: Sync);
 }
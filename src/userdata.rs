@@ -1,9 +1,12 @@
-use std::any::TypeId;
-use std::cell::{Ref, RefCell, RefMut};
+use std::any::{Any, TypeId};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::fmt;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::slice;
 use std::string::String as StdString;
 
 #[cfg(feature = "async")]
@@ -11,6 +14,7 @@ use std::future::Future;
 
 #[cfg(feature = "serialize")]
 use {
+    crate::serde::serialize_unsupported,
     serde::ser::{self, Serialize, Serializer},
     std::result::Result as StdResult,
 };
@@ -21,8 +25,10 @@ use crate::function::Function;
 use crate::lua::Lua;
 use crate::table::{Table, TablePairs};
 use crate::types::{Callback, LuaRef, MaybeSend};
-use crate::util::{check_stack, get_userdata, take_userdata, StackGuard};
-use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti};
+use crate::util::{
+    check_stack, get_userdata, ptr_to_cstr_bytes, short_type_name, take_userdata, StackGuard,
+};
+use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, Nil, Value};
 
 #[cfg(feature = "async")]
 use crate::types::AsyncCallback;
@@ -129,6 +135,15 @@ pub enum MetaMethod {
     #[cfg(any(feature = "luau", doc))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
     Iter,
+    /// The `__type` metafield.
+    ///
+    /// Used by the Luau `typeof` function to get type name of a userdata.
+    /// If not set, the Rust type name (without module path) is used.
+    ///
+    /// Requires `feature = "luau"`
+    #[cfg(any(feature = "luau", doc))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    Type,
     /// The `__close` metamethod.
     ///
     /// Executed when a variable, that marked as to-be-closed, goes out of scope.
@@ -209,6 +224,8 @@ impl MetaMethod {
             MetaMethod::IPairs => "__ipairs",
             #[cfg(feature = "luau")]
             MetaMethod::Iter => "__iter",
+            #[cfg(feature = "luau")]
+            MetaMethod::Type => "__type",
 
             #[cfg(feature = "lua54")]
             MetaMethod::Close => "__close",
@@ -231,9 +248,48 @@ impl AsRef<str> for MetaMethod {
     }
 }
 
+/// Position of the user-defined `__index` metamethod in the userdata key lookup order.
+///
+/// See [`UserDataMethods::set_index_fallback_priority`] for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IndexFallbackPriority {
+    /// Field getters and methods are looked up first, the user-defined `__index` receives only
+    /// the remaining keys (default).
+    #[default]
+    Last,
+    /// The user-defined `__index` is called first, field getters and methods are looked up only
+    /// if it returns `nil`.
+    First,
+}
+
 /// Method registry for [`UserData`] implementors.
 ///
+/// # Lookup order
+///
+/// When a key is read from a userdata (`ud.key` or `ud:key(...)`), it's looked up in the following
+/// order, the same way for every supported Lua version (including Luau method calls):
+///
+/// 1. Field getters (registered using [`UserDataFields`] `add_field_*_get` methods).
+/// 2. Methods and functions (registered using `add_method`, `add_function` and similar methods).
+/// 3. User-defined `__index` metamethod (or meta field), which receives every other key,
+///    including non-string keys, keys that look like metamethod names and names of
+///    [static functions](UserDataMethods::add_static_function).
+///
+/// The user-defined `__index` can be moved to the front of the lookup using
+/// [`set_index_fallback_priority`].
+///
+/// If no user-defined `__index` is set, reading an unknown key raises the
+/// "attempt to get an unknown field" error.
+///
+/// On Luau a user-defined `__namecall` metamethod takes over all `ud:method()` calls and
+/// bypasses this lookup.
+///
+/// Writes (`ud.key = value`) are handled the same way: field setters are checked first, then
+/// a user-defined `__newindex` metamethod.
+///
 /// [`UserData`]: crate::UserData
+/// [`UserDataFields`]: crate::UserDataFields
+/// [`set_index_fallback_priority`]: UserDataMethods::set_index_fallback_priority
 pub trait UserDataMethods<'lua, T: UserData> {
     /// Add a regular method which accepts a `&T` as the first parameter.
     ///
@@ -241,7 +297,8 @@ pub trait UserDataMethods<'lua, T: UserData> {
     /// accessed method. This allows them to be used with the expected `userdata:method()` syntax.
     ///
     /// If `add_meta_method` is used to set the `__index` metamethod, the `__index` metamethod will
-    /// be used as a fall-back if no regular method is found.
+    /// be used as a fall-back if no regular field or method is found.
+    /// See [lookup order](crate::UserDataMethods#lookup-order) for details.
     fn add_method<M, A, R>(&mut self, name: impl AsRef<str>, method: M)
     where
         M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
@@ -321,6 +378,28 @@ pub trait UserDataMethods<'lua, T: UserData> {
         FR: Future<Output = Result<R>> + 'lua,
         R: IntoLuaMulti<'lua>;
 
+    /// Add a static function which accepts generic arguments.
+    ///
+    /// Unlike [`add_function`], static functions are not reachable through instances of `T`
+    /// (looking them up behaves as if they don't exist). They are exposed only through the type
+    /// proxy created by [`Lua::create_userdata_proxy`] and the table returned by
+    /// [`Lua::userdata_statics`].
+    ///
+    /// [`add_function`]: #method.add_function
+    /// [`Lua::create_userdata_proxy`]: crate::Lua::create_userdata_proxy
+    /// [`Lua::userdata_statics`]: crate::Lua::userdata_statics
+    fn add_static_function<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        let callback: Callback<'lua, 'static> = Box::new(move |lua, args| {
+            function(lua, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
+        });
+        self.add_static_callback(name.as_ref().into(), callback);
+    }
+
     /// Add a metamethod which accepts a `&T` as the first parameter.
     ///
     /// # Note
@@ -328,7 +407,14 @@ pub trait UserDataMethods<'lua, T: UserData> {
     /// This can cause an error with certain binary metamethods that can trigger if only the right
     /// side has a metatable. To prevent this, use [`add_meta_function`].
     ///
+    /// The `__gc` metamethod is restricted, unless the Lua state was created using
+    /// [`Lua::unsafe_new`] (or [`Lua::unsafe_new_with`]) and Luau is not used.
+    /// In that case it's called once, when the userdata is collected, before the Rust value
+    /// is dropped. Errors returned from the `__gc` metamethod are ignored.
+    ///
     /// [`add_meta_function`]: #method.add_meta_function
+    /// [`Lua::unsafe_new`]: crate::Lua::unsafe_new
+    /// [`Lua::unsafe_new_with`]: crate::Lua::unsafe_new_with
     fn add_meta_method<M, A, R>(&mut self, name: impl AsRef<str>, method: M)
     where
         M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
@@ -405,6 +491,84 @@ pub trait UserDataMethods<'lua, T: UserData> {
         FR: Future<Output = Result<R>> + 'lua,
         R: IntoLuaMulti<'lua>;
 
+    /// Makes the userdata iterable from Lua using an iterator returned by `f`.
+    ///
+    /// On each iteration start `f` is called to create a fresh iterator over `(key, value)` pairs.
+    /// The iterator is exposed as the `__pairs` metamethod (used by the `pairs` function) in
+    /// Lua 5.2+, and as the `__iter` metamethod (used by generalized iteration
+    /// `for k, v in userdata do`) in Luau. Lua 5.1 and LuaJIT don't have such metamethods,
+    /// so an `iter` method is added instead, to be used as `for k, v in userdata:iter() do`.
+    ///
+    /// Errors converting keys or values to Lua are raised from the iterator function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// struct Inventory(Vec<(String, u32)>);
+    ///
+    /// impl UserData for Inventory {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_meta_pairs(|_, this| Ok(this.0.clone()));
+    ///     }
+    /// }
+    ///
+    /// # let lua = Lua::new();
+    /// let inventory = Inventory(vec![("apple".into(), 3), ("pear".into(), 5)]);
+    /// lua.globals().set("inventory", inventory)?;
+    /// # #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    /// lua.load(r#"
+    ///     for item, count in pairs(inventory) do
+    ///         print(item, count)
+    ///     end
+    /// "#).exec()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn add_meta_pairs<F, I, K, V>(&mut self, f: F)
+    where
+        F: Fn(&'lua Lua, &T) -> Result<I> + MaybeSend + 'static,
+        I: IntoIterator<Item = (K, V)>,
+        I::IntoIter: MaybeSend + 'static,
+        K: IntoLua<'lua> + 'static,
+        V: IntoLua<'lua> + 'static,
+    {
+        let iter_function = move |lua: &'lua Lua, this: &T, _: ()| {
+            let mut iter = f(lua, this)?.into_iter().fuse();
+            lua.create_function_mut(move |lua, ()| match iter.next() {
+                Some((k, v)) => Ok((k.into_lua(lua)?, v.into_lua(lua)?)),
+                None => Ok((Nil, Nil)),
+            })
+        };
+
+        #[cfg(any(
+            feature = "lua54",
+            feature = "lua53",
+            feature = "lua52",
+            feature = "luajit52",
+        ))]
+        self.add_meta_method(MetaMethod::Pairs, move |lua, this, ()| {
+            Ok((iter_function(lua, this, ())?, Nil, Nil))
+        });
+        #[cfg(feature = "luau")]
+        self.add_meta_method(MetaMethod::Iter, move |lua, this, ()| {
+            Ok((iter_function(lua, this, ())?, Nil, Nil))
+        });
+        #[cfg(all(any(feature = "lua51", feature = "luajit"), not(feature = "luajit52")))]
+        self.add_method("iter", iter_function);
+    }
+
+    /// Sets the position of the user-defined `__index` metamethod (or meta field) in the
+    /// [lookup order](crate::UserDataMethods#lookup-order).
+    ///
+    /// With [`IndexFallbackPriority::First`] the user-defined `__index` is called for every key,
+    /// and field getters and methods are used only for keys it returns `nil` for.
+    /// Has no effect if no `__index` is set.
+    ///
+    /// Default: [`IndexFallbackPriority::Last`]
+    fn set_index_fallback_priority(&mut self, priority: IndexFallbackPriority);
+
     //
     // Below are internal methods used in generated code
     //
@@ -416,6 +580,9 @@ pub trait UserDataMethods<'lua, T: UserData> {
     #[cfg(feature = "async")]
     fn add_async_callback(&mut self, _name: String, _callback: AsyncCallback<'lua, 'static>) {}
 
+    #[doc(hidden)]
+    fn add_static_callback(&mut self, _name: String, _callback: Callback<'lua, 'static>) {}
+
     #[doc(hidden)]
     fn add_meta_callback(&mut self, _name: String, _callback: Callback<'lua, 'static>) {}
 
@@ -435,6 +602,7 @@ pub trait UserDataFields<'lua, T: UserData> {
     ///
     /// If `add_meta_method` is used to set the `__index` metamethod, the `__index` metamethod will
     /// be used as a fall-back if no regular field or method are found.
+    /// See [lookup order](crate::UserDataMethods#lookup-order) for details.
     fn add_field_method_get<M, R>(&mut self, name: impl AsRef<str>, method: M)
     where
         M: Fn(&'lua Lua, &T) -> Result<R> + MaybeSend + 'static,
@@ -452,6 +620,26 @@ pub trait UserDataFields<'lua, T: UserData> {
         M: FnMut(&'lua Lua, &mut T, A) -> Result<()> + MaybeSend + 'static,
         A: FromLua<'lua>;
 
+    /// Add a read-only field getter as a method which accepts a `&T` as the parameter.
+    ///
+    /// This is the same as [`add_field_method_get`], but any attempt to assign the field raises
+    /// an error like `field 'x' is read-only on MyType` instead of falling through to the
+    /// `__newindex` metamethod.
+    ///
+    /// [`add_field_method_get`]: #method.add_field_method_get
+    fn add_field_method_get_readonly<M, R>(&mut self, name: impl AsRef<str>, method: M)
+    where
+        M: Fn(&'lua Lua, &T) -> Result<R> + MaybeSend + 'static,
+        R: IntoLua<'lua>,
+    {
+        let name = name.as_ref();
+        self.add_field_method_get(name, method);
+        let message = format!("field '{name}' is read-only on {}", short_type_name::<T>());
+        let setter: Callback<'lua, 'static> =
+            Box::new(move |_, _| Err(Error::RuntimeError(message.clone())));
+        self.add_field_setter(name.into(), setter);
+    }
+
     /// Add a regular field getter as a function which accepts a generic [`AnyUserData`] of type `T`
     /// argument.
     ///
@@ -615,11 +803,109 @@ impl<T> UserDataCell<T> {
 
     // Consumes this `UserDataCell`, returning the wrapped value.
     #[inline]
-    unsafe fn into_inner(self) -> T {
+    pub(crate) unsafe fn into_inner(self) -> T {
         self.0.into_inner().into_inner()
     }
 }
 
+// Calls the function with a reference to the value of userdata on top of the stack
+type BorrowAnyFn = unsafe fn(*mut ffi::lua_State, &mut dyn FnMut(&dyn Any)) -> Result<()>;
+
+// Type-erased functions to access the value of a registered userdata type
+#[derive(Clone, Copy)]
+pub(crate) struct UserDataAnyFns {
+    // Takes the value of userdata on top of the stack
+    take: unsafe fn(*mut ffi::lua_State) -> Result<Box<dyn Any>>,
+    borrow: BorrowAnyFn,
+    // Borrows the inline bytes that follow the value of userdata on top of the stack
+    bytes: unsafe fn(*mut ffi::lua_State) -> Result<Ref<'static, [u8]>>,
+    // Mutably borrows the inline bytes that follow the value of userdata on top of the stack
+    bytes_mut: unsafe fn(*mut ffi::lua_State) -> Result<RefMut<'static, [u8]>>,
+}
+
+impl UserDataAnyFns {
+    // Functions for userdata stored as `UserDataCell<T>`
+    pub(crate) fn new<T: 'static>() -> Self {
+        unsafe fn take<T: 'static>(state: *mut ffi::lua_State) -> Result<Box<dyn Any>> {
+            // Try to borrow userdata exclusively
+            let _ = (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow_mut()?;
+            Ok(Box::new(
+                take_userdata::<UserDataCell<T>>(state).into_inner(),
+            ))
+        }
+
+        unsafe fn borrow<T: 'static>(
+            state: *mut ffi::lua_State,
+            f: &mut dyn FnMut(&dyn Any),
+        ) -> Result<()> {
+            f(&*(*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow()?);
+            Ok(())
+        }
+
+        // Returns a pointer to the inline bytes and their length
+        unsafe fn bytes_ptr<T>(state: *mut ffi::lua_State) -> (*mut u8, usize) {
+            let size = mem::size_of::<UserDataCell<T>>();
+            let ud = get_userdata::<UserDataCell<T>>(state, -1) as *mut u8;
+            (ud.add(size), ffi::lua_rawlen(state, -1) - size)
+        }
+
+        unsafe fn bytes<T: 'static>(state: *mut ffi::lua_State) -> Result<Ref<'static, [u8]>> {
+            let (data, len) = bytes_ptr::<T>(state);
+            let r = (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow()?;
+            Ok(Ref::map(r, |_| slice::from_raw_parts(data, len)))
+        }
+
+        unsafe fn bytes_mut<T: 'static>(
+            state: *mut ffi::lua_State,
+        ) -> Result<RefMut<'static, [u8]>> {
+            let (data, len) = bytes_ptr::<T>(state);
+            let r = (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow_mut()?;
+            Ok(RefMut::map(r, |_| slice::from_raw_parts_mut(data, len)))
+        }
+
+        UserDataAnyFns {
+            take: take::<T>,
+            borrow: borrow::<T>,
+            bytes: bytes::<T>,
+            bytes_mut: bytes_mut::<T>,
+        }
+    }
+
+    // Functions for userdata stored as `Cell<T>` (`T` is `Copy`)
+    pub(crate) fn new_copy<T: 'static>() -> Self {
+        unsafe fn take<T: 'static>(state: *mut ffi::lua_State) -> Result<Box<dyn Any>> {
+            Ok(Box::new(take_userdata::<Cell<T>>(state).into_inner()))
+        }
+
+        unsafe fn borrow<T: 'static>(
+            state: *mut ffi::lua_State,
+            f: &mut dyn FnMut(&dyn Any),
+        ) -> Result<()> {
+            // The value is `Copy`, so pass a reference to a copy
+            let value =
+                ManuallyDrop::new(ptr::read((*get_userdata::<Cell<T>>(state, -1)).as_ptr()));
+            f(&*value);
+            Ok(())
+        }
+
+        // `Copy` userdata cannot be borrowed and never has inline bytes
+        unsafe fn bytes(_: *mut ffi::lua_State) -> Result<Ref<'static, [u8]>> {
+            Err(Error::UserDataCopyBorrowError)
+        }
+
+        unsafe fn bytes_mut(_: *mut ffi::lua_State) -> Result<RefMut<'static, [u8]>> {
+            Err(Error::UserDataCopyBorrowError)
+        }
+
+        UserDataAnyFns {
+            take: take::<T>,
+            borrow: borrow::<T>,
+            bytes,
+            bytes_mut,
+        }
+    }
+}
+
 pub(crate) enum UserDataWrapped<T> {
     Default(Box<T>),
     #[cfg(feature = "serialize")]
@@ -679,19 +965,6 @@ impl<T> DerefMut for UserDataWrapped<T> {
     }
 }
 
-#[cfg(feature = "serialize")]
-struct UserDataSerializeError;
-
-#[cfg(feature = "serialize")]
-impl Serialize for UserDataSerializeError {
-    fn serialize<S>(&self, _serializer: S) -> StdResult<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        Err(ser::Error::custom("cannot serialize <userdata>"))
-    }
-}
-
 /// Handle to an internal Lua userdata for any type that implements [`UserData`].
 ///
 /// Similar to `std::any::Any`, this provides an interface for dynamic type checking via the [`is`]
@@ -723,12 +996,54 @@ impl OwnedAnyUserData {
     }
 }
 
+/// An owned handle to a Lua userdata that can be sent to other threads.
+///
+/// Created by [`AnyUserData::into_send_owned`]. The handle does not keep the [`Lua`] instance
+/// alive: accessing the userdata after the instance was dropped returns [`Error::LuaDropped`].
+/// Cloning the handle is cheap and refers to the same userdata.
+///
+/// Access to the userdata is serialized with other users of the Lua instance using its lock,
+/// which is acquired by every [`Lua`] operation when the `send` feature is enabled.
+///
+/// Requires `feature = "send"`
+#[cfg(feature = "send")]
+#[cfg_attr(docsrs, doc(cfg(feature = "send")))]
+#[derive(Clone)]
+pub struct SendOwnedAnyUserData {
+    lua: crate::lua::WeakLua,
+    key: std::sync::Arc<crate::types::RegistryKey>,
+}
+
+#[cfg(feature = "send")]
+impl fmt::Debug for SendOwnedAnyUserData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SendOwnedAnyUserData({})", self.key.registry_id)
+    }
+}
+
+#[cfg(feature = "send")]
+impl SendOwnedAnyUserData {
+    /// Calls the function with the userdata while holding the lock of the Lua instance.
+    ///
+    /// Other threads using the same Lua instance are blocked until the function returns.
+    /// Returns [`Error::LuaDropped`] if the Lua instance was dropped.
+    ///
+    /// If this call temporarily holds the last reference to the instance, the Lua state is closed
+    /// on the calling thread.
+    pub fn with<R>(&self, f: impl FnOnce(AnyUserData) -> R) -> Result<R> {
+        let lua = self.lua.upgrade().ok_or(Error::LuaDropped)?;
+        let _guard = lua.lock();
+        let ud = lua.registry_value::<AnyUserData>(&self.key)?;
+        Ok(f(ud))
+    }
+}
+
 impl<'lua> AnyUserData<'lua> {
     /// Checks whether the type of this userdata is `T`.
-    pub fn is<T: UserData + 'static>(&self) -> bool {
+    pub fn is<T: 'static>(&self) -> bool {
         match self.inspect(|_: &UserDataCell<T>| Ok(())) {
-            Ok(()) => true,
-            Err(Error::UserDataTypeMismatch) => false,
+            Ok(()) | Err(Error::UserDataCopyBorrowError) => true,
+            Err(Error::UserDataTypeMismatchNamed { .. }) => false,
             Err(_) => unreachable!(),
         }
     }
@@ -738,9 +1053,12 @@ impl<'lua> AnyUserData<'lua> {
     /// # Errors
     ///
     /// Returns a `UserDataBorrowError` if the userdata is already mutably borrowed. Returns a
-    /// `UserDataTypeMismatch` if the userdata is not of type `T`.
+    /// `UserDataTypeMismatchNamed` if the userdata is not of type `T`. Returns a
+    /// `UserDataCopyBorrowError` if the userdata was created by [`Lua::create_copy_userdata`].
+    ///
+    /// [`Lua::create_copy_userdata`]: crate::Lua::create_copy_userdata
     #[inline]
-    pub fn borrow<T: UserData + 'static>(&self) -> Result<Ref<T>> {
+    pub fn borrow<T: 'static>(&self) -> Result<Ref<T>> {
         self.inspect(|cell| cell.try_borrow())
     }
 
@@ -749,31 +1067,231 @@ impl<'lua> AnyUserData<'lua> {
     /// # Errors
     ///
     /// Returns a `UserDataBorrowMutError` if the userdata cannot be mutably borrowed.
-    /// Returns a `UserDataTypeMismatch` if the userdata is not of type `T`.
+    /// Returns a `UserDataTypeMismatchNamed` if the userdata is not of type `T`. Returns a
+    /// `UserDataCopyBorrowError` if the userdata was created by [`Lua::create_copy_userdata`].
+    ///
+    /// [`Lua::create_copy_userdata`]: crate::Lua::create_copy_userdata
     #[inline]
-    pub fn borrow_mut<T: UserData + 'static>(&self) -> Result<RefMut<T>> {
+    pub fn borrow_mut<T: 'static>(&self) -> Result<RefMut<T>> {
         self.inspect(|cell| cell.try_borrow_mut())
     }
 
+    /// Borrow a part of this userdata immutably if it is of type `T`.
+    ///
+    /// Works like [`borrow`] followed by [`Ref::map`]: the returned guard points to the value
+    /// selected by `f` and keeps the whole userdata borrowed while it is alive, so it cannot be
+    /// mutated concurrently (eg. by a method call from Lua).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `UserDataBorrowError` if the userdata is already mutably borrowed. Returns a
+    /// `UserDataTypeMismatchNamed` if the userdata is not of type `T`.
+    ///
+    /// [`borrow`]: #method.borrow
+    #[inline]
+    pub fn borrow_map<T, U, F>(&self, f: F) -> Result<Ref<U>>
+    where
+        T: 'static,
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        self.inspect(|cell| cell.try_borrow().map(|r| Ref::map(r, f)))
+    }
+
+    /// Borrow a part of this userdata mutably if it is of type `T`.
+    ///
+    /// Works like [`borrow_mut`] followed by [`RefMut::map`]: the returned guard points to the
+    /// value selected by `f` and keeps the whole userdata exclusively borrowed while it is alive.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `UserDataBorrowMutError` if the userdata cannot be mutably borrowed.
+    /// Returns a `UserDataTypeMismatchNamed` if the userdata is not of type `T`.
+    ///
+    /// [`borrow_mut`]: #method.borrow_mut
+    #[inline]
+    pub fn borrow_mut_map<T, U, F>(&self, f: F) -> Result<RefMut<U>>
+    where
+        T: 'static,
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        self.inspect(|cell| cell.try_borrow_mut().map(|r| RefMut::map(r, f)))
+    }
+
+    /// Returns a copy of this userdata value if it is of type `T`.
+    ///
+    /// Works for userdata created by both [`Lua::create_userdata`] and
+    /// [`Lua::create_copy_userdata`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `UserDataBorrowError` if the userdata is already mutably borrowed. Returns a
+    /// `UserDataTypeMismatchNamed` if the userdata is not of type `T`.
+    ///
+    /// [`Lua::create_userdata`]: crate::Lua::create_userdata
+    /// [`Lua::create_copy_userdata`]: crate::Lua::create_copy_userdata
+    pub fn get_copy<T: Copy + 'static>(&self) -> Result<T> {
+        match self.inspect_copy(|cell: &Cell<T>| Ok(cell.get())) {
+            Err(Error::UserDataTypeMismatchNamed { .. }) => self.borrow::<T>().map(|ud| *ud),
+            res => res,
+        }
+    }
+
+    /// Returns description of methods and fields registered by the type of this userdata.
+    ///
+    /// See [`Lua::userdata_info`] for details.
+    ///
+    /// [`Lua::userdata_info`]: crate::Lua::userdata_info
+    pub fn info(&self) -> Result<UserDataInfo> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        unsafe {
+            let _sg = StackGuard::new(lua.state());
+            check_stack(lua.state(), 2)?;
+
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            type_id
+                .and_then(|type_id| lua.registered_userdata_info(type_id))
+                .ok_or_else(|| self.type_mismatch("mlua userdata"))
+        }
+    }
+
     /// Takes the value out of this userdata.
     /// Sets the special "destructed" metatable that prevents any further operations with this userdata.
     ///
     /// Keeps associated user values unchanged (they will be collected by Lua's GC).
-    pub fn take<T: UserData + 'static>(&self) -> Result<T> {
+    pub fn take<T: 'static>(&self) -> Result<T> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, 2)?;
 
-            let type_id = lua.push_userdata_ref(&self.0)?;
+            let type_id = self.push_typed_ref::<T>()?;
             match type_id {
                 Some(type_id) if type_id == TypeId::of::<T>() => {
                     // Try to borrow userdata exclusively
                     let _ = (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow_mut()?;
                     Ok(take_userdata::<UserDataCell<T>>(state).into_inner())
                 }
-                _ => Err(Error::UserDataTypeMismatch),
+                Some(type_id) if type_id == TypeId::of::<Cell<T>>() => {
+                    Ok(take_userdata::<Cell<T>>(state).into_inner())
+                }
+                _ => Err(self.type_mismatch(short_type_name::<T>())),
+            }
+        }
+    }
+
+    /// Takes the value out of this userdata, without knowing its type at compile time.
+    ///
+    /// Works for any userdata created with [`Lua::create_userdata`] (and similar functions).
+    /// The returned value can be downcast to the original type. For wrapped userdata types
+    /// (eg. `Arc<Mutex<T>>`) the wrapper is returned.
+    ///
+    /// Sets the special "destructed" metatable that prevents any further operations with this
+    /// userdata.
+    ///
+    /// Returns a `UserDataBorrowMutError` if the userdata is currently borrowed, or
+    /// `UserDataTypeMismatchNamed` if the userdata was not created by mlua (or created in a scope as a
+    /// non-static userdata).
+    ///
+    /// [`Lua::create_userdata`]: crate::Lua::create_userdata
+    pub fn take_any(&self) -> Result<Box<dyn Any>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            match type_id.and_then(|type_id| lua.registered_userdata_any_fns(type_id)) {
+                Some(fns) => (fns.take)(state),
+                None => Err(self.type_mismatch("mlua userdata")),
+            }
+        }
+    }
+
+    /// Calls the function with a reference to the value of this userdata, without knowing its
+    /// type at compile time.
+    ///
+    /// This is the borrowing counterpart of [`take_any`]. The userdata is immutably borrowed
+    /// while the function is running.
+    ///
+    /// Returns a `UserDataBorrowError` if the userdata is already mutably borrowed, or
+    /// `UserDataTypeMismatchNamed` if the userdata was not created by mlua.
+    ///
+    /// [`take_any`]: #method.take_any
+    pub fn borrow_any<R>(&self, f: impl FnOnce(&dyn Any) -> R) -> Result<R> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            let fns = type_id
+                .and_then(|type_id| lua.registered_userdata_any_fns(type_id))
+                .ok_or_else(|| self.type_mismatch("mlua userdata"))?;
+            let mut f = Some(f);
+            let mut result = None;
+            (fns.borrow)(state, &mut |value| result = f.take().map(|f| f(value)))?;
+            Ok(mlua_expect!(result, "borrow function was not called"))
+        }
+    }
+
+    /// Borrows the inline bytes of this userdata immutably.
+    ///
+    /// The bytes are allocated together with the userdata value by
+    /// [`Lua::create_userdata_with_bytes`]. For other userdata the returned slice is empty.
+    /// The userdata value is immutably borrowed while the returned guard is alive.
+    ///
+    /// Returns a `UserDataBorrowError` if the userdata is already mutably borrowed, or
+    /// `UserDataTypeMismatchNamed` if the userdata was not created by mlua. Returns a
+    /// `UserDataCopyBorrowError` for a `Copy` userdata.
+    ///
+    /// [`Lua::create_userdata_with_bytes`]: crate::Lua::create_userdata_with_bytes
+    pub fn bytes(&self) -> Result<Ref<[u8]>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            match type_id.and_then(|type_id| lua.registered_userdata_any_fns(type_id)) {
+                Some(fns) => (fns.bytes)(state),
+                None => Err(self.type_mismatch("mlua userdata")),
+            }
+        }
+    }
+
+    /// Borrows the inline bytes of this userdata mutably.
+    ///
+    /// See [`bytes`] for details. The userdata value is mutably borrowed while the returned
+    /// guard is alive.
+    ///
+    /// Returns a `UserDataBorrowMutError` if the userdata is already borrowed, or
+    /// `UserDataTypeMismatchNamed` if the userdata was not created by mlua. Returns a
+    /// `UserDataCopyBorrowError` for a `Copy` userdata.
+    ///
+    /// [`bytes`]: #method.bytes
+    pub fn bytes_mut(&self) -> Result<RefMut<[u8]>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            match type_id.and_then(|type_id| lua.registered_userdata_any_fns(type_id)) {
+                Some(fns) => (fns.bytes_mut)(state),
+                None => Err(self.type_mismatch("mlua userdata")),
             }
         }
     }
@@ -813,6 +1331,7 @@ impl<'lua> AnyUserData<'lua> {
     ///
     /// [`get_nth_user_value`]: #method.get_nth_user_value
     pub fn set_nth_user_value<V: IntoLua<'lua>>(&self, n: usize, v: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
         if n < 1 || n > u16::MAX as usize {
             return Err(Error::RuntimeError(
                 "user value index out of bounds".to_string(),
@@ -831,6 +1350,15 @@ impl<'lua> AnyUserData<'lua> {
             #[cfg(feature = "lua54")]
             if n < USER_VALUE_MAXSLOT {
                 ffi::lua_setiuservalue(state, -2, n as c_int);
+                // Remove weak value (if any) that was set previously
+                protect_lua!(state, 1, 0, |state| {
+                    if getuservalue_table(state, -1) == ffi::LUA_TTABLE
+                        && ffi::lua_rawgeti(state, -1, 0) == ffi::LUA_TTABLE
+                    {
+                        ffi::lua_pushnil(state);
+                        ffi::lua_rawseti(state, -2, n as ffi::lua_Integer);
+                    }
+                })?;
                 return Ok(());
             }
 
@@ -852,22 +1380,109 @@ impl<'lua> AnyUserData<'lua> {
                 ffi::lua_rawseti(state, -2, (n - USER_VALUE_MAXSLOT + 1) as ffi::lua_Integer);
                 #[cfg(not(feature = "lua54"))]
                 ffi::lua_rawseti(state, -2, n as ffi::lua_Integer);
+                // Remove weak value (if any) that was set previously
+                if ffi::lua_rawgeti(state, -1, 0) == ffi::LUA_TTABLE {
+                    ffi::lua_pushnil(state);
+                    ffi::lua_rawseti(state, -2, n as ffi::lua_Integer);
+                }
             })?;
 
             Ok(())
         }
     }
 
-    /// Returns an associated `n`th value set by [`set_nth_user_value`].
+    /// Sets an associated `n`th value to this `AnyUserData` without keeping it alive.
+    ///
+    /// Works like [`set_nth_user_value`], but the value is stored in a weak table (`__mode = "v"`),
+    /// so it does not prevent the referenced object from being garbage collected.
+    /// This makes it possible to have userdata referring to each other without creating
+    /// uncollectable cycles.
+    /// Once the value is collected, [`get_nth_user_value`] returns `Nil`.
+    ///
+    /// Weak values are always stored in the wrapping table, even for the first 7 elements in Lua 5.4.
+    /// Setting a weak value replaces a strong one with the same index and vice versa.
+    ///
+    /// [`set_nth_user_value`]: #method.set_nth_user_value
+    /// [`get_nth_user_value`]: #method.get_nth_user_value
+    pub fn set_weak_user_value<V: IntoLua<'lua>>(&self, n: usize, v: V) -> Result<()> {
+        let _guard = self.0.lua.lock();
+        if n < 1 || n > u16::MAX as usize {
+            return Err(Error::RuntimeError(
+                "user value index out of bounds".to_string(),
+            ));
+        }
+
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 6)?;
+
+            lua.push_userdata_ref(&self.0)?;
+            lua.push_value(v.into_lua(lua)?)?;
+
+            #[cfg(feature = "lua54")]
+            if n < USER_VALUE_MAXSLOT {
+                // Remove strong value from the fast slot
+                ffi::lua_pushnil(state);
+                ffi::lua_setiuservalue(state, -3, n as c_int);
+            }
+
+            protect_lua!(state, 2, 0, |state| {
+                if getuservalue_table(state, -2) != ffi::LUA_TTABLE {
+                    // Create a new table to use as uservalue
+                    ffi::lua_pop(state, 1);
+                    ffi::lua_newtable(state);
+                    ffi::lua_pushvalue(state, -1);
+
+                    #[cfg(feature = "lua54")]
+                    ffi::lua_setiuservalue(state, -4, USER_VALUE_MAXSLOT as c_int);
+                    #[cfg(not(feature = "lua54"))]
+                    ffi::lua_setuservalue(state, -4);
+                }
+                // Remove strong value from the uservalue table
+                #[cfg(feature = "lua54")]
+                if n >= USER_VALUE_MAXSLOT {
+                    ffi::lua_pushnil(state);
+                    ffi::lua_rawseti(state, -2, (n - USER_VALUE_MAXSLOT + 1) as ffi::lua_Integer);
+                }
+                #[cfg(not(feature = "lua54"))]
+                {
+                    ffi::lua_pushnil(state);
+                    ffi::lua_rawseti(state, -2, n as ffi::lua_Integer);
+                }
+                // Weak values are kept in a separate table stored at index 0 of the uservalue table
+                if ffi::lua_rawgeti(state, -1, 0) != ffi::LUA_TTABLE {
+                    ffi::lua_pop(state, 1);
+                    ffi::lua_newtable(state);
+                    ffi::lua_createtable(state, 0, 1);
+                    ffi::lua_pushliteral(state, "v");
+                    ffi::lua_setfield(state, -2, cstr!("__mode"));
+                    ffi::lua_setmetatable(state, -2);
+                    ffi::lua_pushvalue(state, -1);
+                    ffi::lua_rawseti(state, -3, 0);
+                }
+                ffi::lua_pushvalue(state, -3);
+                ffi::lua_rawseti(state, -2, n as ffi::lua_Integer);
+            })?;
+
+            Ok(())
+        }
+    }
+
+    /// Returns an associated `n`th value set by [`set_nth_user_value`] or [`set_weak_user_value`].
     ///
     /// `n` starts from 1 and can be up to 65535.
+    /// Weak values that have been garbage collected are returned as `Nil`.
     ///
     /// This is supported for all Lua versions.
     /// In Lua 5.4 first 7 elements are stored in a most efficient way.
     /// For other Lua versions this functionality is provided using a wrapping table.
     ///
     /// [`set_nth_user_value`]: #method.set_nth_user_value
+    /// [`set_weak_user_value`]: #method.set_weak_user_value
     pub fn get_nth_user_value<V: FromLua<'lua>>(&self, n: usize) -> Result<V> {
+        let _guard = self.0.lua.lock();
         if n < 1 || n > u16::MAX as usize {
             return Err(Error::RuntimeError(
                 "user value index out of bounds".to_string(),
@@ -884,8 +1499,10 @@ impl<'lua> AnyUserData<'lua> {
 
             #[cfg(feature = "lua54")]
             if n < USER_VALUE_MAXSLOT {
-                ffi::lua_getiuservalue(state, -1, n as c_int);
-                return V::from_lua(lua.pop_value(), lua);
+                if ffi::lua_getiuservalue(state, -1, n as c_int) != ffi::LUA_TNIL {
+                    return V::from_lua(lua.pop_value(), lua);
+                }
+                ffi::lua_pop(state, 1);
             }
 
             // Multiple (extra) user values are emulated by storing them in a table
@@ -895,9 +1512,21 @@ impl<'lua> AnyUserData<'lua> {
                     return;
                 }
                 #[cfg(feature = "lua54")]
-                ffi::lua_rawgeti(state, -1, (n - USER_VALUE_MAXSLOT + 1) as ffi::lua_Integer);
+                let t = if n >= USER_VALUE_MAXSLOT {
+                    ffi::lua_rawgeti(state, -1, (n - USER_VALUE_MAXSLOT + 1) as ffi::lua_Integer)
+                } else {
+                    ffi::lua_pushnil(state);
+                    ffi::LUA_TNIL
+                };
                 #[cfg(not(feature = "lua54"))]
-                ffi::lua_rawgeti(state, -1, n as ffi::lua_Integer);
+                let t = ffi::lua_rawgeti(state, -1, n as ffi::lua_Integer);
+                if t != ffi::LUA_TNIL {
+                    return;
+                }
+                // Try weak values
+                if ffi::lua_rawgeti(state, -2, 0) == ffi::LUA_TTABLE {
+                    ffi::lua_rawgeti(state, -1, n as ffi::lua_Integer);
+                }
             })?;
 
             V::from_lua(lua.pop_value(), lua)
@@ -913,6 +1542,7 @@ impl<'lua> AnyUserData<'lua> {
     where
         V: IntoLua<'lua>,
     {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let name = name.as_ref();
@@ -952,6 +1582,7 @@ impl<'lua> AnyUserData<'lua> {
     where
         V: FromLua<'lua>,
     {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let name = name.as_ref();
@@ -975,6 +1606,68 @@ impl<'lua> AnyUserData<'lua> {
         }
     }
 
+    /// Returns a function that calls the method `name` of this userdata with the userdata bound
+    /// as the first (`self`) argument.
+    ///
+    /// This is an equivalent of `function(...) return obj:name(...) end` in Lua.
+    /// The method is looked up once (invoking `__index` if needed), and an error is returned
+    /// if it does not exist or is not a function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// struct Counter(u32);
+    ///
+    /// impl UserData for Counter {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_method_mut("add", |_, this, n: u32| {
+    ///             this.0 += n;
+    ///             Ok(this.0)
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// let counter = lua.create_userdata(Counter(0))?;
+    /// let add = counter.bind_method("add")?;
+    /// assert_eq!(add.call::<_, u32>(5)?, 5);
+    /// assert_eq!(counter.borrow::<Counter>()?.0, 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind_method(&self, name: &str) -> Result<Function<'lua>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        let method = unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_userdata_ref(&self.0)?;
+            protect_lua!(state, 1, 1, |state| {
+                ffi::lua_pushlstring(state, name.as_ptr() as *const c_char, name.len());
+                ffi::lua_gettable(state, -2);
+            })?;
+
+            lua.pop_value()
+        };
+
+        match method {
+            Value::Function(method) => method.bind(self.clone()),
+            Value::Nil => Err(Error::RuntimeError(format!(
+                "cannot bind method '{}': method does not exist",
+                name
+            ))),
+            value => Err(Error::RuntimeError(format!(
+                "cannot bind method '{}': expected function, got {}",
+                name,
+                value.type_name()
+            ))),
+        }
+    }
+
     /// Returns a metatable of this `UserData`.
     ///
     /// Returned [`UserDataMetatable`] object wraps the original metatable and
@@ -989,6 +1682,7 @@ impl<'lua> AnyUserData<'lua> {
     }
 
     fn get_raw_metatable(&self) -> Result<Table<'lua>> {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
@@ -1001,6 +1695,43 @@ impl<'lua> AnyUserData<'lua> {
         }
     }
 
+    /// Returns the type name of this `UserData`.
+    ///
+    /// The type name is the `__name` metafield. For `T: UserData` it's set to the Rust type name
+    /// (without module path), unless overridden by a custom meta field.
+    ///
+    /// Returns `None` if the userdata does not have a name.
+    pub fn type_name(&self) -> Result<Option<StdString>> {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+
+            lua.push_ref(&self.0);
+            if ffi::luaL_getmetafield(state, -1, cstr!("__name")) != ffi::LUA_TSTRING {
+                return Ok(None);
+            }
+            let name = ptr_to_cstr_bytes(ffi::lua_tostring(state, -1));
+            Ok(name.map(|name| StdString::from_utf8_lossy(name).into_owned()))
+        }
+    }
+
+    /// Converts the userdata to a generic C pointer.
+    ///
+    /// The pointer is the address of the userdata block, so it remains stable for the lifetime
+    /// of the userdata and different userdata instances will give different pointers.
+    /// There is no way to convert the pointer back to its original value.
+    ///
+    /// Typically this function is used only for hashing and debug information.
+    #[inline]
+    pub fn to_pointer(&self) -> *const c_void {
+        let _guard = self.0.lua.lock();
+        let ref_thread = self.0.lua.ref_thread();
+        unsafe { ffi::lua_topointer(ref_thread, self.0.index) }
+    }
+
     #[cfg(feature = "unstable")]
     #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
     #[inline]
@@ -1008,30 +1739,93 @@ impl<'lua> AnyUserData<'lua> {
         OwnedAnyUserData(self.0.into_owned())
     }
 
-    pub(crate) fn equals<T: AsRef<Self>>(&self, other: T) -> Result<bool> {
+    /// Converts this userdata into an owned handle that can be sent to other threads.
+    ///
+    /// See [`SendOwnedAnyUserData`] for details.
+    ///
+    /// Requires `feature = "send"`
+    #[cfg(feature = "send")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "send")))]
+    pub fn into_send_owned(self) -> Result<SendOwnedAnyUserData> {
+        let lua = self.0.lua;
+        let _guard = lua.lock();
+        Ok(SendOwnedAnyUserData {
+            lua: lua.downgrade(),
+            key: std::sync::Arc::new(lua.create_registry_value(self)?),
+        })
+    }
+
+    /// Compares two userdata for equality, honoring the `__eq` metamethod.
+    ///
+    /// First the userdata are compared by reference (raw equality). If they are different objects,
+    /// their metatables are compared: userdata of the same type share a metatable, and its `__eq`
+    /// metamethod (if any) is invoked with the two userdata as arguments.
+    ///
+    /// If the metatables are different, the metamethod is selected following the rules of the
+    /// running Lua version (the same as for the `==` operator):
+    /// - Lua 5.3 and 5.4 check the first userdata for `__eq` and then the second one.
+    /// - Lua 5.1, 5.2, LuaJIT and Luau use the metamethod only if both userdata define the same
+    ///   `__eq` function.
+    ///
+    /// Returns `false` if no suitable `__eq` metamethod is found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mlua::{AnyUserData, Lua, MetaMethod, Result, UserData, UserDataMethods};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// struct Id(u32);
+    ///
+    /// impl UserData for Id {
+    ///     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+    ///         methods.add_meta_method(MetaMethod::Eq, |_, this, other: AnyUserData| {
+    ///             Ok(this.0 == other.borrow::<Id>()?.0)
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// let a = lua.create_userdata(Id(1))?;
+    /// let b = lua.create_userdata(Id(1))?;
+    /// assert!(a.equals(&b)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn equals<T: AsRef<Self>>(&self, other: T) -> Result<bool> {
         let other = other.as_ref();
         // Uses lua_rawequal() under the hood
         if self == other {
             return Ok(true);
         }
 
+        // Compare using __eq metamethod if exists
         let mt = self.get_raw_metatable()?;
-        if mt != other.get_raw_metatable()? {
-            return Ok(false);
+        let other_mt = other.get_raw_metatable()?;
+        let mut handler = mt.raw_get::<_, Option<Function>>("__eq")?;
+        if mt != other_mt {
+            let other_handler = other_mt.raw_get::<_, Option<Function>>("__eq")?;
+            // Lua 5.3+ checks the first operand and then the second one
+            #[cfg(any(feature = "lua54", feature = "lua53"))]
+            if handler.is_none() {
+                handler = other_handler;
+            }
+            // Older versions require the same metamethod for both operands
+            #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+            if handler != other_handler {
+                handler = None;
+            }
         }
 
-        if mt.contains_key("__eq")? {
-            return mt
-                .get::<_, Function>("__eq")?
-                .call((self.clone(), other.clone()));
+        match handler {
+            Some(handler) => handler.call((self.clone(), other.clone())),
+            None => Ok(false),
         }
-
-        Ok(false)
     }
 
     /// Returns true if this `AnyUserData` is serializable (eg. was created using `create_ser_userdata`).
     #[cfg(feature = "serialize")]
     pub(crate) fn is_serializable(&self) -> bool {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let is_serializable = || unsafe {
@@ -1039,7 +1833,10 @@ impl<'lua> AnyUserData<'lua> {
             check_stack(state, 2)?;
 
             // Userdata can be unregistered or destructed
-            lua.push_userdata_ref(&self.0)?;
+            let type_id = lua.push_userdata_ref(&self.0)?;
+            if lua.is_copy_userdata(type_id) {
+                return Result::Ok(false);
+            }
 
             let ud = &*get_userdata::<UserDataCell<()>>(state, -1);
             match &*ud.0.try_borrow().map_err(|_| Error::UserDataBorrowError)? {
@@ -1052,24 +1849,76 @@ impl<'lua> AnyUserData<'lua> {
 
     fn inspect<'a, T, F, R>(&'a self, func: F) -> Result<R>
     where
-        T: UserData + 'static,
+        T: 'static,
         F: FnOnce(&'a UserDataCell<T>) -> Result<R>,
     {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, 2)?;
 
-            let type_id = lua.push_userdata_ref(&self.0)?;
+            let type_id = self.push_typed_ref::<T>()?;
             match type_id {
                 Some(type_id) if type_id == TypeId::of::<T>() => {
                     func(&*get_userdata::<UserDataCell<T>>(state, -1))
                 }
-                _ => Err(Error::UserDataTypeMismatch),
+                Some(type_id) if type_id == TypeId::of::<Cell<T>>() => {
+                    Err(Error::UserDataCopyBorrowError)
+                }
+                _ => Err(self.type_mismatch(short_type_name::<T>())),
+            }
+        }
+    }
+
+    fn inspect_copy<'a, T, F, R>(&'a self, func: F) -> Result<R>
+    where
+        T: 'static,
+        F: FnOnce(&'a Cell<T>) -> Result<R>,
+    {
+        let _guard = self.0.lua.lock();
+        let lua = self.0.lua;
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 2)?;
+
+            let type_id = self.push_typed_ref::<T>()?;
+            match type_id {
+                Some(type_id) if type_id == TypeId::of::<Cell<T>>() => {
+                    func(&*get_userdata::<Cell<T>>(state, -1))
+                }
+                _ => Err(self.type_mismatch(short_type_name::<T>())),
             }
         }
     }
+
+    // Pushes the userdata onto the stack like `Lua::push_userdata_ref`, reporting type mismatches
+    // with `T` as the expected type.
+    // Uses 2 stack spaces, does not call checkstack.
+    pub(crate) unsafe fn push_typed_ref<T>(&self) -> Result<Option<TypeId>> {
+        self.0
+            .lua
+            .push_userdata_ref(&self.0)
+            .map_err(|err| match err {
+                Error::UserDataTypeMismatchNamed { actual, .. } => {
+                    Error::UserDataTypeMismatchNamed {
+                        expected: short_type_name::<T>(),
+                        actual,
+                    }
+                }
+                err => err,
+            })
+    }
+
+    // Returns an error for this userdata that is not of the `expected` type
+    pub(crate) fn type_mismatch(&self, expected: impl Into<StdString>) -> Error {
+        Error::UserDataTypeMismatchNamed {
+            expected: expected.into(),
+            actual: (self.type_name().ok().flatten()).unwrap_or_else(|| "userdata".to_string()),
+        }
+    }
 }
 
 impl<'lua> PartialEq for AnyUserData<'lua> {
@@ -1078,6 +1927,14 @@ impl<'lua> PartialEq for AnyUserData<'lua> {
     }
 }
 
+impl<'lua> Eq for AnyUserData<'lua> {}
+
+impl<'lua> Hash for AnyUserData<'lua> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_pointer().hash(state);
+    }
+}
+
 impl<'lua> AsRef<AnyUserData<'lua>> for AnyUserData<'lua> {
     #[inline]
     fn as_ref(&self) -> &Self {
@@ -1092,6 +1949,38 @@ unsafe fn getuservalue_table(state: *mut ffi::lua_State, idx: c_int) -> c_int {
     return ffi::lua_getuservalue(state, idx);
 }
 
+/// Description of methods and fields registered by a `UserData` type.
+///
+/// Returned by [`Lua::userdata_info`] and [`AnyUserData::info`]. The data is captured once, when
+/// the type metatable is created.
+///
+/// Names are listed in the order of registration. Methods are expected to be called using
+/// `obj:method()` syntax, functions using `obj.function()` syntax and fields are accessed
+/// using `obj.field` syntax.
+///
+/// [`Lua::userdata_info`]: crate::Lua::userdata_info
+/// [`AnyUserData::info`]: crate::AnyUserData::info
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UserDataInfo {
+    /// Regular methods (registered using `add_method` or `add_method_mut`).
+    pub methods: Vec<StdString>,
+    /// Async methods (registered using `add_async_method`).
+    pub async_methods: Vec<StdString>,
+    /// Functions (registered using `add_function` or `add_function_mut`).
+    pub functions: Vec<StdString>,
+    /// Async functions (registered using `add_async_function`).
+    pub async_functions: Vec<StdString>,
+    /// Readable fields (registered using `add_field_method_get` and similar methods).
+    pub field_getters: Vec<StdString>,
+    /// Writable fields (registered using `add_field_method_set` and similar methods).
+    pub field_setters: Vec<StdString>,
+    /// Meta methods, including async ones.
+    pub meta_methods: Vec<StdString>,
+    /// Meta fields (registered using `add_meta_field_with`).
+    pub meta_fields: Vec<StdString>,
+}
+
 /// Handle to a `UserData` metatable.
 #[derive(Clone, Debug)]
 pub struct UserDataMetatable<'lua>(pub(crate) Table<'lua>);
@@ -1172,19 +2061,26 @@ impl<'lua> Serialize for AnyUserData<'lua> {
     where
         S: Serializer,
     {
+        let _guard = self.0.lua.lock();
         let lua = self.0.lua;
         let state = lua.state();
         let data = unsafe {
             let _sg = StackGuard::new(state);
             check_stack(state, 3).map_err(ser::Error::custom)?;
 
-            lua.push_userdata_ref(&self.0).map_err(ser::Error::custom)?;
+            let type_id = lua.push_userdata_ref(&self.0).map_err(ser::Error::custom)?;
+            if lua.is_copy_userdata(type_id) {
+                return serialize_unsupported(&Value::UserData(self.clone()), serializer);
+            }
             let ud = &*get_userdata::<UserDataCell<()>>(state, -1);
             ud.0.try_borrow()
                 .map_err(|_| ser::Error::custom(Error::UserDataBorrowError))?
         };
         match &*data {
-            UserDataWrapped::Default(_) => UserDataSerializeError.serialize(serializer),
+            UserDataWrapped::Default(_) => {
+                drop(data);
+                serialize_unsupported(&Value::UserData(self.clone()), serializer)
+            }
             UserDataWrapped::Serializable(ser) => ser.serialize(serializer),
         }
     }
@@ -1196,6 +2092,11 @@ mod assertions {
 
     static_assertions::assert_not_impl_any!(AnyUserData: Send);
 
-    #[cfg(feature = "unstable")]
+    #[cfg(all(feature = "unstable", not(feature = "send")))]
     static_assertions::assert_not_impl_any!(OwnedAnyUserData: Send);
+    #[cfg(all(feature = "unstable", feature = "send"))]
+    static_assertions::assert_impl_all!(OwnedAnyUserData: Send);
+
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(SendOwnedAnyUserData: Send);
 }
@@ -1,6 +1,7 @@
 use std::any::TypeId;
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::marker::PhantomData;
+use std::ptr;
 use std::sync::{Arc, Mutex, RwLock};
 
 use crate::error::{Error, Result};
@@ -8,9 +9,10 @@ use crate::ffi;
 use crate::lua::Lua;
 use crate::types::{Callback, MaybeSend};
 use crate::userdata::{
-    AnyUserData, MetaMethod, UserData, UserDataCell, UserDataFields, UserDataMethods,
+    AnyUserData, IndexFallbackPriority, MetaMethod, UserData, UserDataCell, UserDataFields,
+    UserDataInfo, UserDataMethods,
 };
-use crate::util::{check_stack, get_userdata, StackGuard};
+use crate::util::{check_stack, get_userdata, short_type_name, StackGuard};
 use crate::value::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, Value};
 
 #[cfg(not(feature = "send"))]
@@ -27,9 +29,13 @@ pub(crate) struct StaticUserDataMethods<'lua, T: UserData + 'static> {
     pub(crate) methods: Vec<(String, Callback<'lua, 'static>)>,
     #[cfg(feature = "async")]
     pub(crate) async_methods: Vec<(String, AsyncCallback<'lua, 'static>)>,
+    pub(crate) static_functions: Vec<(String, Callback<'lua, 'static>)>,
     pub(crate) meta_methods: Vec<(String, Callback<'lua, 'static>)>,
     #[cfg(feature = "async")]
     pub(crate) async_meta_methods: Vec<(String, AsyncCallback<'lua, 'static>)>,
+    // Names of `methods` and `async_methods` that do not take `self`
+    pub(crate) function_names: Vec<String>,
+    pub(crate) index_priority: IndexFallbackPriority,
     _type: PhantomData<T>,
 }
 
@@ -39,9 +45,12 @@ impl<'lua, T: UserData + 'static> Default for StaticUserDataMethods<'lua, T> {
             methods: Vec::new(),
             #[cfg(feature = "async")]
             async_methods: Vec::new(),
+            static_functions: Vec::new(),
             meta_methods: Vec::new(),
             #[cfg(feature = "async")]
             async_meta_methods: Vec::new(),
+            function_names: Vec::new(),
+            index_priority: IndexFallbackPriority::default(),
             _type: PhantomData,
         }
     }
@@ -87,6 +96,7 @@ impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataMet
         A: FromLuaMulti<'lua>,
         R: IntoLuaMulti<'lua>,
     {
+        self.function_names.push(name.as_ref().into());
         self.methods
             .push((name.as_ref().into(), Self::box_function(function)));
     }
@@ -97,6 +107,7 @@ impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataMet
         A: FromLuaMulti<'lua>,
         R: IntoLuaMulti<'lua>,
     {
+        self.function_names.push(name.as_ref().into());
         self.methods
             .push((name.as_ref().into(), Self::box_function_mut(function)));
     }
@@ -109,10 +120,21 @@ impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataMet
         FR: Future<Output = Result<R>> + 'lua,
         R: IntoLuaMulti<'lua>,
     {
+        self.function_names.push(name.as_ref().into());
         self.async_methods
             .push((name.as_ref().into(), Self::box_async_function(function)));
     }
 
+    fn add_static_function<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        self.static_functions
+            .push((name.as_ref().into(), Self::box_function(function)));
+    }
+
     fn add_meta_method<M, A, R>(&mut self, name: impl AsRef<str>, method: M)
     where
         M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
@@ -178,6 +200,10 @@ impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataMet
             .push((name.as_ref().into(), Self::box_async_function(function)));
     }
 
+    fn set_index_fallback_priority(&mut self, priority: IndexFallbackPriority) {
+        self.index_priority = priority;
+    }
+
     // Below are internal methods used in generated code
 
     fn add_callback(&mut self, name: String, callback: Callback<'lua, 'static>) {
@@ -189,6 +215,10 @@ impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataMet
         self.async_methods.push((name, callback));
     }
 
+    fn add_static_callback(&mut self, name: String, callback: Callback<'lua, 'static>) {
+        self.static_functions.push((name, callback));
+    }
+
     fn add_meta_callback(&mut self, name: String, callback: Callback<'lua, 'static>) {
         self.meta_methods.push((name, callback));
     }
@@ -214,12 +244,16 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                     let _sg = StackGuard::new(state);
                     check_stack(state, 2)?;
 
-                    let type_id = lua.push_userdata_ref(&userdata.0)?;
+                    let type_id = userdata.push_typed_ref::<T>()?;
                     match type_id {
                         Some(id) if id == TypeId::of::<T>() => {
                             let ud = get_userdata_ref::<T>(state)?;
                             method(lua, &ud, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
                         }
+                        Some(id) if id == TypeId::of::<Cell<T>>() => {
+                            let ud = get_userdata_copy::<T>(state);
+                            method(lua, &ud, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
+                        }
                         #[cfg(not(feature = "send"))]
                         Some(id) if id == TypeId::of::<Rc<RefCell<T>>>() => {
                             let ud = get_userdata_ref::<Rc<RefCell<T>>>(state)?;
@@ -248,7 +282,7 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                             let ud = ud.try_read().ok_or(Error::UserDataBorrowError)?;
                             method(lua, &ud, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
                         }
-                        _ => Err(Error::UserDataTypeMismatch),
+                        _ => Err(userdata.type_mismatch(short_type_name::<T>())),
                     }
                 }
             } else {
@@ -279,12 +313,19 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                     let _sg = StackGuard::new(state);
                     check_stack(state, 2)?;
 
-                    let type_id = lua.push_userdata_ref(&userdata.0)?;
+                    let type_id = userdata.push_typed_ref::<T>()?;
                     match type_id {
                         Some(id) if id == TypeId::of::<T>() => {
                             let mut ud = get_userdata_mut::<T>(state)?;
                             method(lua, &mut ud, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
                         }
+                        Some(id) if id == TypeId::of::<Cell<T>>() => {
+                            let cell = get_userdata::<Cell<T>>(state, -1);
+                            let mut ud = read_userdata_copy(cell);
+                            let ret = method(lua, &mut ud, A::from_lua_multi(args, lua)?)?;
+                            write_userdata_copy(cell, ud);
+                            ret.into_lua_multi(lua)
+                        }
                         #[cfg(not(feature = "send"))]
                         Some(id) if id == TypeId::of::<Rc<RefCell<T>>>() => {
                             let ud = get_userdata_mut::<Rc<RefCell<T>>>(state)?;
@@ -317,7 +358,7 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                             let mut ud = ud.try_write().ok_or(Error::UserDataBorrowMutError)?;
                             method(lua, &mut ud, A::from_lua_multi(args, lua)?)?.into_lua_multi(lua)
                         }
-                        _ => Err(Error::UserDataTypeMismatch),
+                        _ => Err(userdata.type_mismatch(short_type_name::<T>())),
                     }
                 }
             } else {
@@ -348,12 +389,16 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                         let _sg = StackGuard::new(state);
                         check_stack(state, 2)?;
 
-                        let type_id = lua.push_userdata_ref(&userdata.0)?;
+                        let type_id = userdata.push_typed_ref::<T>()?;
                         match type_id {
                             Some(id) if id == TypeId::of::<T>() => {
                                 let ud = get_userdata_ref::<T>(state)?;
                                 Ok(method(lua, ud.clone(), A::from_lua_multi(args, lua)?))
                             }
+                            Some(id) if id == TypeId::of::<Cell<T>>() => {
+                                let ud = get_userdata_copy::<T>(state);
+                                Ok(method(lua, ud, A::from_lua_multi(args, lua)?))
+                            }
                             #[cfg(not(feature = "send"))]
                             Some(id) if id == TypeId::of::<Rc<RefCell<T>>>() => {
                                 let ud = get_userdata_ref::<Rc<RefCell<T>>>(state)?;
@@ -382,7 +427,7 @@ impl<'lua, T: UserData + 'static> StaticUserDataMethods<'lua, T> {
                                 let ud = ud.try_read().ok_or(Error::UserDataBorrowError)?;
                                 Ok(method(lua, ud.clone(), A::from_lua_multi(args, lua)?))
                             }
-                            _ => Err(Error::UserDataTypeMismatch),
+                            _ => Err(userdata.type_mismatch(short_type_name::<T>())),
                         }
                     }
                 } else {
@@ -545,6 +590,47 @@ impl<'lua, T: UserData + 'static> UserDataFields<'lua, T> for StaticUserDataFiel
     }
 }
 
+// Collects names of methods and fields registered by the UserData type `T`
+pub(crate) fn collect_userdata_info<'lua, T: UserData + 'static>(
+    fields: &StaticUserDataFields<'lua, T>,
+    methods: &StaticUserDataMethods<'lua, T>,
+) -> UserDataInfo {
+    let mut info = UserDataInfo::default();
+    let is_function = |name: &String| methods.function_names.contains(name);
+    for (name, _) in &methods.methods {
+        match is_function(name) {
+            true => info.functions.push(name.clone()),
+            false => info.methods.push(name.clone()),
+        }
+    }
+    #[cfg(feature = "async")]
+    for (name, _) in &methods.async_methods {
+        match is_function(name) {
+            true => info.async_functions.push(name.clone()),
+            false => info.async_methods.push(name.clone()),
+        }
+    }
+    info.meta_methods = methods
+        .meta_methods
+        .iter()
+        .map(|(k, _)| k.clone())
+        .collect();
+    #[cfg(feature = "async")]
+    (info.meta_methods).extend(methods.async_meta_methods.iter().map(|(k, _)| k.clone()));
+    info.field_getters = fields
+        .field_getters
+        .iter()
+        .map(|(k, _)| k.clone())
+        .collect();
+    info.field_setters = fields
+        .field_setters
+        .iter()
+        .map(|(k, _)| k.clone())
+        .collect();
+    info.meta_fields = fields.meta_fields.iter().map(|(k, _)| k.clone()).collect();
+    info
+}
+
 #[inline]
 unsafe fn get_userdata_ref<'a, T>(state: *mut ffi::lua_State) -> Result<Ref<'a, T>> {
     (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow()
@@ -555,6 +641,24 @@ unsafe fn get_userdata_mut<'a, T>(state: *mut ffi::lua_State) -> Result<RefMut<'
     (*get_userdata::<UserDataCell<T>>(state, -1)).try_borrow_mut()
 }
 
+// Userdata with `Cell<T>` storage is created only for `T: Copy` (by `Lua::create_copy_userdata`),
+// so it's safe to make bitwise copies of the value.
+
+#[inline]
+unsafe fn get_userdata_copy<T>(state: *mut ffi::lua_State) -> T {
+    read_userdata_copy(get_userdata::<Cell<T>>(state, -1))
+}
+
+#[inline]
+unsafe fn read_userdata_copy<T>(cell: *const Cell<T>) -> T {
+    ptr::read((*cell).as_ptr())
+}
+
+#[inline]
+unsafe fn write_userdata_copy<T>(cell: *const Cell<T>, value: T) {
+    ptr::write((*cell).as_ptr(), value)
+}
+
 macro_rules! lua_userdata_impl {
     ($type:ty) => {
         impl<T: UserData + 'static> UserData for $type {
@@ -579,6 +683,9 @@ macro_rules! lua_userdata_impl {
                 for (name, callback) in orig_methods.async_methods {
                     methods.add_async_callback(name, callback);
                 }
+                for (name, callback) in orig_methods.static_functions {
+                    methods.add_static_callback(name, callback);
+                }
                 for (meta, callback) in orig_methods.meta_methods {
                     methods.add_meta_callback(meta, callback);
                 }
@@ -604,3 +711,171 @@ lua_userdata_impl!(Arc<parking_lot::RwLock<T>>);
 pub(crate) struct UserDataProxy<T>(pub(crate) PhantomData<T>);
 
 lua_userdata_impl!(UserDataProxy<T>);
+
+// Collects only functions (that do not take `self`) of the UserData type `T`, including
+// static functions, and the `__call` metamethod, ignoring everything else.
+pub(crate) struct StaticUserDataFunctions<'lua, T: UserData + 'static>(
+    StaticUserDataMethods<'lua, T>,
+);
+
+impl<'lua, T: UserData + 'static> Default for StaticUserDataFunctions<'lua, T> {
+    fn default() -> StaticUserDataFunctions<'lua, T> {
+        StaticUserDataFunctions(StaticUserDataMethods::default())
+    }
+}
+
+impl<'lua, T: UserData + 'static> UserDataMethods<'lua, T> for StaticUserDataFunctions<'lua, T> {
+    fn add_method<M, A, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    fn add_method_mut<M, A, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        M: FnMut(&'lua Lua, &mut T, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    #[cfg(feature = "async")]
+    fn add_async_method<M, A, MR, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        T: Clone,
+        M: Fn(&'lua Lua, T, A) -> MR + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        MR: Future<Output = Result<R>> + 'lua,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    fn add_function<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        self.0.add_function(name, function);
+    }
+
+    fn add_function_mut<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: FnMut(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        self.0.add_function_mut(name, function);
+    }
+
+    #[cfg(feature = "async")]
+    fn add_async_function<F, A, FR, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> FR + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        FR: Future<Output = Result<R>> + 'lua,
+        R: IntoLuaMulti<'lua>,
+    {
+        self.0.add_async_function(name, function);
+    }
+
+    fn add_static_function<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        self.0.add_function(name, function);
+    }
+
+    fn add_meta_method<M, A, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        M: Fn(&'lua Lua, &T, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    fn add_meta_method_mut<M, A, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        M: FnMut(&'lua Lua, &mut T, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    #[cfg(all(feature = "async", not(any(feature = "lua51", feature = "luau"))))]
+    fn add_async_meta_method<M, A, MR, R>(&mut self, _name: impl AsRef<str>, _method: M)
+    where
+        T: Clone,
+        M: Fn(&'lua Lua, T, A) -> MR + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        MR: Future<Output = Result<R>> + 'lua,
+        R: IntoLuaMulti<'lua>,
+    {
+    }
+
+    fn add_meta_function<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        if name.as_ref() == MetaMethod::Call {
+            self.0.add_meta_function(name, function);
+        }
+    }
+
+    fn add_meta_function_mut<F, A, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: FnMut(&'lua Lua, A) -> Result<R> + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+    {
+        if name.as_ref() == MetaMethod::Call {
+            self.0.add_meta_function_mut(name, function);
+        }
+    }
+
+    #[cfg(all(feature = "async", not(any(feature = "lua51", feature = "luau"))))]
+    fn add_async_meta_function<F, A, FR, R>(&mut self, name: impl AsRef<str>, function: F)
+    where
+        F: Fn(&'lua Lua, A) -> FR + MaybeSend + 'static,
+        A: FromLuaMulti<'lua>,
+        FR: Future<Output = Result<R>> + 'lua,
+        R: IntoLuaMulti<'lua>,
+    {
+        if name.as_ref() == MetaMethod::Call {
+            self.0.add_async_meta_function(name, function);
+        }
+    }
+
+    fn set_index_fallback_priority(&mut self, _priority: IndexFallbackPriority) {}
+}
+
+// A special proxy object that exposes only functions and the `__call` metamethod of UserData
+pub(crate) struct UserDataClassProxy<T>(pub(crate) PhantomData<T>);
+
+impl<T: UserData + 'static> UserData for UserDataClassProxy<T> {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        let mut orig_functions = StaticUserDataFunctions::default();
+        T::add_methods(&mut orig_functions);
+        let orig_functions = orig_functions.0;
+        for (name, callback) in orig_functions.methods {
+            methods.add_callback(name, callback);
+        }
+        #[cfg(feature = "async")]
+        for (name, callback) in orig_functions.async_methods {
+            methods.add_async_callback(name, callback);
+        }
+        for (meta, callback) in orig_functions.meta_methods {
+            methods.add_meta_callback(meta, callback);
+        }
+        #[cfg(feature = "async")]
+        for (meta, callback) in orig_functions.async_meta_methods {
+            methods.add_async_meta_callback(meta, callback);
+        }
+    }
+}
@@ -1,17 +1,21 @@
 use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
 use std::ffi::CStr;
 use std::fmt::Write;
+use std::io;
 use std::mem::MaybeUninit;
 use std::os::raw::{c_char, c_int, c_void};
 use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::sync::Arc;
-use std::{mem, ptr, slice};
+use std::{mem, process, ptr, slice};
 
 use once_cell::sync::Lazy;
 use rustc_hash::FxHashMap;
 
 use crate::error::{Error, Result};
 use crate::ffi;
+use crate::lua::translate_error;
+use crate::userdata::IndexFallbackPriority;
 
 static METATABLE_CACHE: Lazy<FxHashMap<TypeId, u8>> = Lazy::new(|| {
     let mut map = FxHashMap::with_capacity_and_hasher(32, Default::default());
@@ -44,6 +48,25 @@ pub unsafe fn check_stack(state: *mut ffi::lua_State, amount: c_int) -> Result<(
     }
 }
 
+// Maximum number of values (function arguments or results) that can be pushed to the stack at once.
+// A few slots are reserved for internal use.
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+pub const MAX_CALL_ARGS: usize = (ffi::LUAI_MAXSTACK - 2 * ffi::LUA_MINSTACK) as usize;
+#[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+pub const MAX_CALL_ARGS: usize = (ffi::LUAI_MAXCSTACK - 2 * ffi::LUA_MINSTACK) as usize;
+
+// Checks that `count` values can be pushed to the stack before touching it.
+#[inline]
+pub fn check_call_args(count: usize) -> Result<c_int> {
+    if count > MAX_CALL_ARGS {
+        return Err(Error::TooManyArguments {
+            count,
+            limit: MAX_CALL_ARGS,
+        });
+    }
+    Ok(count as c_int)
+}
+
 pub struct StackGuard {
     state: *mut ffi::lua_State,
     top: c_int,
@@ -205,10 +228,13 @@ pub unsafe fn pop_error(state: *mut ffi::lua_State, err_code: c_int) -> Error {
             err.clone()
         }
         Some(WrappedFailure::Panic(panic)) => {
-            if let Some(p) = panic.take() {
-                resume_unwind(p);
-            } else {
-                Error::PreviouslyResumedPanic
+            // Pop the wrapped panic before resuming it, so the stack is balanced
+            // when `StackGuard`s are dropped during unwinding
+            let panic = panic.take();
+            ffi::lua_pop(state, 1);
+            match panic {
+                Some(p) => resume_unwind(p),
+                None => Error::PreviouslyResumedPanic,
             }
         }
         _ => {
@@ -255,6 +281,100 @@ pub unsafe fn push_string(state: *mut ffi::lua_State, s: &[u8], protect: bool) -
     }
 }
 
+// Pushes a string with the content read from `reader` until EOF.
+// The content is read directly into a Lua buffer (`luaL_Buffer`). On Lua 5.1 and LuaJIT it's read
+// in chunks that are pushed to the stack and concatenated.
+// IO errors are returned as `Error::RuntimeError`, panics in the reader are resumed.
+// Uses 3 stack spaces, does not call checkstack.
+pub unsafe fn push_string_from_reader<R: io::Read>(
+    state: *mut ffi::lua_State,
+    reader: R,
+) -> Result<()> {
+    // Number of bytes to read at once
+    const CHUNK_SIZE: usize = 16 * 1024;
+
+    // Reader errors and panics cannot cross Lua frames, they're kept here until the buffer is freed
+    let reader = RefCell::new(reader);
+    let failure = Cell::new(None);
+    let read = |buf: &mut [u8]| -> Option<usize> {
+        loop {
+            match catch_unwind(AssertUnwindSafe(|| reader.borrow_mut().read(buf))) {
+                Ok(Ok(n)) => return Some(n),
+                Ok(Err(err)) if err.kind() == io::ErrorKind::Interrupted => {}
+                Ok(Err(err)) => {
+                    let err = Error::RuntimeError(format!("cannot read string: {err}"));
+                    failure.set(Some(Ok(err)));
+                    return None;
+                }
+                Err(panic) => {
+                    failure.set(Some(Err(panic)));
+                    return None;
+                }
+            }
+        }
+    };
+
+    protect_lua!(state, 0, 1, |state| {
+        #[cfg(not(any(feature = "lua51", feature = "luajit")))]
+        {
+            let mut buffer = MaybeUninit::<ffi::luaL_Buffer>::uninit();
+            let b = buffer.as_mut_ptr();
+            let mut p = ffi::luaL_buffinitsize(state, b, CHUNK_SIZE);
+            loop {
+                // Lua does not initialize the buffer memory
+                ptr::write_bytes(p, 0, CHUNK_SIZE);
+                let n = match read(slice::from_raw_parts_mut(p as *mut u8, CHUNK_SIZE)) {
+                    Some(0) => break,
+                    Some(n) => n,
+                    None => return,
+                };
+                #[cfg(feature = "luau")]
+                {
+                    (*b).p = (*b).p.add(n);
+                }
+                #[cfg(not(feature = "luau"))]
+                ffi::luaL_addsize(b, n);
+                p = ffi::luaL_prepbuffsize(b, CHUNK_SIZE);
+            }
+            ffi::luaL_pushresult(b);
+        }
+
+        #[cfg(any(feature = "lua51", feature = "luajit"))]
+        {
+            let mut chunk = [0u8; CHUNK_SIZE];
+            let mut pieces = 0;
+            loop {
+                let n = match read(&mut chunk) {
+                    Some(0) => break,
+                    Some(n) => n,
+                    None => return,
+                };
+                ffi::luaL_checkstack(state, 1, ptr::null());
+                ffi::lua_pushlstring(state, chunk.as_ptr() as *const c_char, n);
+                pieces += 1;
+                // Keep pieces sorted by decreasing size (as `luaL_Buffer` does)
+                while pieces > 1 && ffi::lua_rawlen(state, -2) <= ffi::lua_rawlen(state, -1) {
+                    ffi::lua_concat(state, 2);
+                    pieces -= 1;
+                }
+            }
+            ffi::lua_concat(state, pieces);
+        }
+    })?;
+
+    match failure.take() {
+        Some(Ok(err)) => {
+            ffi::lua_pop(state, 1);
+            Err(err)
+        }
+        Some(Err(panic)) => {
+            ffi::lua_pop(state, 1);
+            resume_unwind(panic)
+        }
+        None => Ok(()),
+    }
+}
+
 // Uses 3 stack spaces, does not call checkstack.
 #[inline]
 pub unsafe fn push_table(
@@ -281,6 +401,22 @@ pub unsafe fn rawset_field(state: *mut ffi::lua_State, table: c_int, field: &str
     })
 }
 
+// Sets `field` of the table on top of the stack to the `value` string if the field is nil.
+// Uses 3 stack spaces, does not call checkstack.
+pub unsafe fn rawset_field_default(
+    state: *mut ffi::lua_State,
+    field: &str,
+    value: &str,
+) -> Result<()> {
+    push_string(state, field.as_bytes(), true)?;
+    if ffi::lua_rawget(state, -2) == ffi::LUA_TNIL {
+        push_string(state, value.as_bytes(), true)?;
+        rawset_field(state, -3, field)?;
+    }
+    ffi::lua_pop(state, 1);
+    Ok(())
+}
+
 // Internally uses 3 stack spaces, does not call checkstack.
 #[cfg(not(feature = "luau"))]
 #[inline]
@@ -301,7 +437,7 @@ pub unsafe fn push_userdata<T>(state: *mut ffi::lua_State, t: T, protect: bool)
 #[inline]
 pub unsafe fn push_userdata<T>(state: *mut ffi::lua_State, t: T, protect: bool) -> Result<()> {
     unsafe extern "C" fn destructor<T>(ud: *mut c_void) {
-        ptr::drop_in_place(ud as *mut T);
+        abort_on_panic(|| ptr::drop_in_place(ud as *mut T));
     }
 
     let size = mem::size_of::<T>();
@@ -337,6 +473,41 @@ pub unsafe fn push_userdata_uv<T>(
     Ok(())
 }
 
+// Pushes the userdata `t` followed by `len` zeroed bytes in the same allocation.
+// Internally uses 3 stack spaces, does not call checkstack.
+pub unsafe fn push_userdata_with_bytes<T>(
+    state: *mut ffi::lua_State,
+    t: T,
+    len: usize,
+    protect: bool,
+) -> Result<()> {
+    #[cfg(feature = "luau")]
+    unsafe extern "C" fn destructor<T>(ud: *mut c_void) {
+        abort_on_panic(|| ptr::drop_in_place(ud as *mut T));
+    }
+
+    let size = mem::size_of::<T>()
+        .checked_add(len)
+        .ok_or_else(|| Error::MemoryError("userdata size overflow".to_string()))?;
+    let new_userdata = |state| {
+        #[cfg(feature = "lua54")]
+        let ud = ffi::lua_newuserdatauv(state, size, crate::userdata::USER_VALUE_MAXSLOT as c_int);
+        #[cfg(feature = "luau")]
+        let ud = ffi::lua_newuserdatadtor(state, size, destructor::<T>);
+        #[cfg(not(any(feature = "lua54", feature = "luau")))]
+        let ud = ffi::lua_newuserdata(state, size);
+        ud as *mut T
+    };
+    let ud = if protect {
+        protect_lua!(state, 0, 1, new_userdata)?
+    } else {
+        new_userdata(state)
+    };
+    ptr::write(ud, t);
+    ptr::write_bytes((ud as *mut u8).add(mem::size_of::<T>()), 0, len);
+    Ok(())
+}
+
 #[inline]
 pub unsafe fn get_userdata<T>(state: *mut ffi::lua_State, index: c_int) -> *mut T {
     let ud = ffi::lua_touserdata(state, index) as *mut T;
@@ -405,7 +576,17 @@ pub unsafe fn get_gc_userdata<T: Any>(
     ud
 }
 
-unsafe extern "C" fn lua_error_impl(state: *mut ffi::lua_State) -> c_int {
+// Raises "attempt to <action> an unknown field '<key>'" error.
+// Keys of any type (not only strings) are formatted using `luaL_tolstring`.
+unsafe extern "C" fn lua_unknown_field_impl(state: *mut ffi::lua_State) -> c_int {
+    ffi::luaL_checkstack(state, 2, ptr::null());
+    ffi::luaL_tolstring(state, 2, ptr::null_mut());
+    ffi::lua_pushfstring(
+        state,
+        cstr!("attempt to %s an unknown field '%s'"),
+        ffi::lua_tostring(state, 1),
+        ffi::lua_tostring(state, -1),
+    );
     ffi::lua_error(state);
 }
 
@@ -426,8 +607,33 @@ unsafe fn init_userdata_metatable_index(state: *mut ffi::lua_State) -> Result<()
     // Create and cache `__index` helper
     let code = cstr!(
         r#"
-            local error, isfunction = ...
-            return function (__index, field_getters, methods)
+            local unknown_field, isfunction = ...
+            return function (__index, field_getters, methods, index_first)
+                if index_first and __index ~= nil then
+                    return function (self, key)
+                        local value
+                        if isfunction(__index) then
+                            value = __index(self, key)
+                        else
+                            value = __index[key]
+                        end
+                        if value ~= nil then
+                            return value
+                        end
+
+                        if field_getters ~= nil then
+                            local field_getter = field_getters[key]
+                            if field_getter ~= nil then
+                                return field_getter(self)
+                            end
+                        end
+
+                        if methods ~= nil then
+                            return methods[key]
+                        end
+                    end
+                end
+
                 return function (self, key)
                     if field_getters ~= nil then
                         local field_getter = field_getters[key]
@@ -446,7 +652,7 @@ unsafe fn init_userdata_metatable_index(state: *mut ffi::lua_State) -> Result<()
                     if isfunction(__index) then
                         return __index(self, key)
                     elseif __index == nil then
-                        error("attempt to get an unknown field '"..key.."'")
+                        unknown_field("get", key)
                     else
                         return __index[key]
                     end
@@ -460,7 +666,7 @@ unsafe fn init_userdata_metatable_index(state: *mut ffi::lua_State) -> Result<()
         if ret != ffi::LUA_OK {
             ffi::lua_error(state);
         }
-        ffi::lua_pushcfunction(state, lua_error_impl);
+        ffi::lua_pushcfunction(state, lua_unknown_field_impl);
         ffi::lua_pushcfunction(state, lua_isfunction_impl);
         ffi::lua_call(state, 2, 1);
 
@@ -470,6 +676,37 @@ unsafe fn init_userdata_metatable_index(state: *mut ffi::lua_State) -> Result<()
     })
 }
 
+unsafe fn init_userdata_metatable_index_error(state: *mut ffi::lua_State) -> Result<()> {
+    let index_error_key = &USERDATA_METATABLE_INDEX_ERROR as *const u8 as *const _;
+    if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, index_error_key) == ffi::LUA_TFUNCTION {
+        return Ok(());
+    }
+    ffi::lua_pop(state, 1);
+
+    // Create and cache `__index` fallback for the methods table
+    let code = cstr!(
+        r#"
+            local unknown_field = ...
+            return function (_, key)
+                unknown_field("get", key)
+            end
+    "#
+    );
+    let code_len = CStr::from_ptr(code).to_bytes().len();
+    protect_lua!(state, 0, 1, |state| {
+        let ret = ffi::luaL_loadbuffer(state, code, code_len, cstr!("__mlua_index_error"));
+        if ret != ffi::LUA_OK {
+            ffi::lua_error(state);
+        }
+        ffi::lua_pushcfunction(state, lua_unknown_field_impl);
+        ffi::lua_call(state, 1, 1);
+
+        // Store in the registry
+        ffi::lua_pushvalue(state, -1);
+        ffi::lua_rawsetp(state, ffi::LUA_REGISTRYINDEX, index_error_key);
+    })
+}
+
 pub unsafe fn init_userdata_metatable_newindex(state: *mut ffi::lua_State) -> Result<()> {
     let newindex_key = &USERDATA_METATABLE_NEWINDEX as *const u8 as *const _;
     if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, newindex_key) == ffi::LUA_TFUNCTION {
@@ -480,7 +717,7 @@ pub unsafe fn init_userdata_metatable_newindex(state: *mut ffi::lua_State) -> Re
     // Create and cache `__newindex` helper
     let code = cstr!(
         r#"
-            local error, isfunction = ...
+            local unknown_field, isfunction = ...
             return function (__newindex, field_setters)
                 return function (self, key, value)
                     if field_setters ~= nil then
@@ -494,7 +731,7 @@ pub unsafe fn init_userdata_metatable_newindex(state: *mut ffi::lua_State) -> Re
                     if isfunction(__newindex) then
                         __newindex(self, key, value)
                     elseif __newindex == nil then
-                        error("attempt to set an unknown field '"..key.."'")
+                        unknown_field("set", key)
                     else
                         __newindex[key] = value
                     end
@@ -508,7 +745,7 @@ pub unsafe fn init_userdata_metatable_newindex(state: *mut ffi::lua_State) -> Re
         if ret != ffi::LUA_OK {
             ffi::lua_error(state);
         }
-        ffi::lua_pushcfunction(state, lua_error_impl);
+        ffi::lua_pushcfunction(state, lua_unknown_field_impl);
         ffi::lua_pushcfunction(state, lua_isfunction_impl);
         ffi::lua_call(state, 2, 1);
 
@@ -523,18 +760,40 @@ pub unsafe fn init_userdata_metatable_newindex(state: *mut ffi::lua_State) -> Re
 // to it for the given type and a `__metatable` entry to protect the table from script access.
 // The function also, if given a `field_getters` or `methods` tables, will create an `__index` metamethod
 // (capturing previous one) to lookup in `field_getters` first, then `methods` and falling back to the
-// captured `__index` if no matches found.
+// captured `__index` if no matches found (or calling the captured `__index` first, depending on
+// `index_priority`).
+// If there are no `field_getters` and no previous `__index`, the `methods` table is installed as
+// `__index` directly to avoid calling the generated closure on every lookup.
 // The same is also applicable for `__newindex` metamethod and `field_setters` table.
-// Internally uses 9 stack spaces and does not call checkstack.
+// Internally uses 10 stack spaces and does not call checkstack.
 pub unsafe fn init_userdata_metatable<T>(
     state: *mut ffi::lua_State,
     metatable: c_int,
     field_getters: Option<c_int>,
     field_setters: Option<c_int>,
     methods: Option<c_int>,
+    index_priority: IndexFallbackPriority,
 ) -> Result<()> {
     ffi::lua_pushvalue(state, metatable);
 
+    if let (None, Some(methods)) = (field_getters, methods) {
+        push_string(state, b"__index", true)?;
+        if ffi::lua_rawget(state, -2) == ffi::LUA_TNIL {
+            ffi::lua_pop(state, 1);
+
+            // Unknown keys are reported as errors, the same way as the generated `__index` does
+            push_table(state, 0, 1, true)?;
+            init_userdata_metatable_index_error(state)?;
+            rawset_field(state, -2, "__index")?;
+            ffi::lua_setmetatable(state, methods);
+
+            ffi::lua_pushvalue(state, methods);
+            rawset_field(state, -2, "__index")?;
+            return init_userdata_metatable_rest::<T>(state, field_setters);
+        }
+        ffi::lua_pop(state, 1);
+    }
+
     if field_getters.is_some() || methods.is_some() {
         // Push `__index` generator function
         init_userdata_metatable_index(state)?;
@@ -550,9 +809,11 @@ pub unsafe fn init_userdata_metatable<T>(
                         ffi::lua_pushnil(state);
                     }
                 }
+                let index_first = index_priority == IndexFallbackPriority::First;
+                ffi::lua_pushboolean(state, index_first as c_int);
 
                 // Generate `__index`
-                protect_lua!(state, 4, 1, fn(state) ffi::lua_call(state, 3, 1))?;
+                protect_lua!(state, 5, 1, fn(state) ffi::lua_call(state, 4, 1))?;
             }
             _ => mlua_panic!("improper __index type {}", index_type),
         }
@@ -560,6 +821,15 @@ pub unsafe fn init_userdata_metatable<T>(
         rawset_field(state, -2, "__index")?;
     }
 
+    init_userdata_metatable_rest::<T>(state, field_setters)
+}
+
+// Finishes `init_userdata_metatable` by setting `__newindex`, `__gc` and `__metatable` fields.
+// Expects the metatable on top of the stack and pops it.
+unsafe fn init_userdata_metatable_rest<T>(
+    state: *mut ffi::lua_State,
+    field_setters: Option<c_int>,
+) -> Result<()> {
     if let Some(field_setters) = field_setters {
         // Push `__newindex` generator function
         init_userdata_metatable_newindex(state)?;
@@ -596,10 +866,87 @@ pub unsafe fn init_userdata_metatable<T>(
 pub unsafe extern "C" fn userdata_destructor<T>(state: *mut ffi::lua_State) -> c_int {
     // It's probably NOT a good idea to catch Rust panics in finalizer
     // Lua 5.4 ignores it, other versions generates `LUA_ERRGCMM` without calling message handler
-    take_userdata::<T>(state);
+    abort_on_panic(|| drop(take_userdata::<T>(state)));
+    0
+}
+
+// Calls user-defined `__gc` metamethod (stored as the first upvalue) and then drops the userdata.
+// A panic in the metamethod aborts the process, same as a panic in the Rust destructor.
+// Errors are reported once the userdata is dropped: Lua 5.4 emits a warning (as it does for
+// any failing finalizer), other versions propagate the error (`LUA_ERRGCMM` on Lua 5.2/5.3).
+#[cfg(not(feature = "luau"))]
+pub unsafe extern "C" fn userdata_destructor_chained<T>(state: *mut ffi::lua_State) -> c_int {
+    userdata_destructor_chained_with(state, |state| drop(take_userdata::<T>(state)))
+}
+
+// Same as `userdata_destructor_chained` but the value is destructed by `destruct` function,
+// that receives the userdata on top of the stack and must pop it.
+#[cfg(not(feature = "luau"))]
+pub unsafe fn userdata_destructor_chained_with(
+    state: *mut ffi::lua_State,
+    destruct: unsafe fn(*mut ffi::lua_State),
+) -> c_int {
+    ffi::luaL_checkstack(state, 5, ptr::null());
+    ffi::lua_pushvalue(state, ffi::lua_upvalueindex(1));
+    ffi::lua_pushvalue(state, 1);
+    let failed = ffi::lua_pcall(state, 1, 0, 0) != ffi::LUA_OK;
+    if failed {
+        if let Some(WrappedFailure::Panic(_)) =
+            get_gc_userdata::<WrappedFailure>(state, -1, ptr::null()).as_ref()
+        {
+            process::abort();
+        }
+    } else {
+        ffi::lua_pushnil(state);
+    }
+    ffi::lua_settop(state, 2);
+
+    // The metamethod could take the value out of the userdata
+    ffi::lua_getmetatable(state, 1);
+    get_destructed_userdata_metatable(state);
+    let destructed = ffi::lua_rawequal(state, -1, -2) != 0;
+    ffi::lua_pop(state, 2);
+    if !destructed {
+        ffi::lua_pushvalue(state, 1);
+        abort_on_panic(|| destruct(state));
+    }
+
+    if failed {
+        // Lua 5.2+ reports only string errors raised by finalizers
+        #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+        if let Some(WrappedFailure::Error(err)) =
+            get_gc_userdata::<WrappedFailure>(state, -1, ptr::null()).as_ref()
+        {
+            let msg = err.to_string();
+            ffi::lua_pushlstring(state, msg.as_ptr() as *const c_char, msg.len());
+        }
+        #[cfg(feature = "lua54")]
+        {
+            if ffi::lua_type(state, -1) != ffi::LUA_TSTRING {
+                ffi::lua_pushstring(state, cstr!("error object is not a string"));
+            }
+            ffi::lua_pushstring(state, cstr!("error in __gc ("));
+            ffi::lua_insert(state, -2);
+            ffi::lua_pushstring(state, cstr!(")"));
+            ffi::lua_concat(state, 3);
+            ffi::lua_warning(state, ffi::lua_tostring(state, -1), 0);
+        }
+        #[cfg(not(feature = "lua54"))]
+        ffi::lua_error(state);
+    }
     0
 }
 
+// Calls the given function and aborts the process if it panics.
+// Must be used in `extern "C"` functions that cannot return a Lua error (eg. destructors),
+// as unwinding into C frames is undefined behavior.
+pub fn abort_on_panic<F: FnOnce() -> R, R>(f: F) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => r,
+        Err(_) => process::abort(),
+    }
+}
+
 // In the context of a lua callback, this will call the given function and if the given function
 // returns an error, *or if the given function panics*, this will result in a call to `lua_error` (a
 // longjmp). The error or panic is wrapped in such a way that when calling `pop_error` back on
@@ -687,6 +1034,63 @@ pub unsafe extern "C" fn error_traceback(state: *mut ffi::lua_State) -> c_int {
     1
 }
 
+// A variant of `error_traceback` that also attaches traceback to Rust errors.
+// Errors raised from Rust callbacks already carry a traceback (as `CallbackError`), any other
+// wrapped error (eg. rethrown by Lua code) is wrapped into `CallbackError` with the current traceback.
+pub unsafe extern "C" fn error_traceback_full(state: *mut ffi::lua_State) -> c_int {
+    if ffi::lua_checkstack(state, 2) == 0 {
+        return 1;
+    }
+
+    match get_gc_userdata::<WrappedFailure>(state, -1, ptr::null()).as_ref() {
+        None => error_traceback(state),
+        Some(WrappedFailure::Error(err)) if !matches!(err, Error::CallbackError { .. }) => {
+            if ffi::lua_checkstack(state, ffi::LUA_TRACEBACK_STACK + 1) == 0 {
+                return 1;
+            }
+            let cause = Arc::new(err.clone());
+            let wrapped_error = WrappedFailure::new_userdata(state);
+            ffi::luaL_traceback(state, state, ptr::null(), 0);
+            let traceback = to_string(state, -1);
+            ffi::lua_pop(state, 1);
+            ptr::write(
+                wrapped_error,
+                WrappedFailure::Error(Error::CallbackError { traceback, cause }),
+            );
+            get_gc_metatable::<WrappedFailure>(state);
+            ffi::lua_setmetatable(state, -2);
+            1
+        }
+        Some(_) => 1,
+    }
+}
+
+// Pushes `error_traceback` function to the stack.
+// In Lua 5.1/LuaJIT/Luau C functions are always closures, so the function is cached
+// in the registry to avoid allocating a new one every time.
+// Uses 2 stack spaces, does not call checkstack.
+pub(crate) unsafe fn push_error_traceback(state: *mut ffi::lua_State) -> Result<()> {
+    #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+    {
+        let key = &ERROR_TRACEBACK_KEY as *const u8 as *const c_void;
+        if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, key) == ffi::LUA_TFUNCTION {
+            return Ok(());
+        }
+        ffi::lua_pop(state, 1);
+        protect_lua!(state, 0, 1, fn(state) {
+            ffi::lua_pushcfunction(state, error_traceback);
+            ffi::lua_pushvalue(state, -1);
+            ffi::lua_rawsetp(state, ffi::LUA_REGISTRYINDEX, &ERROR_TRACEBACK_KEY as *const u8 as *const c_void);
+        })
+    }
+
+    #[cfg(not(any(feature = "lua51", feature = "luajit", feature = "luau")))]
+    {
+        ffi::lua_pushcfunction(state, error_traceback);
+        Ok(())
+    }
+}
+
 // A variant of `error_traceback` that can safely inspect another (yielded) thread stack
 pub unsafe fn error_traceback_thread(state: *mut ffi::lua_State, thread: *mut ffi::lua_State) {
     // Move error object to the main thread to safely call `__tostring` metamethod if present
@@ -849,6 +1253,11 @@ pub unsafe fn init_error_registry(state: *mut ffi::lua_State) -> Result<()> {
 
             let err_buf = match get_gc_userdata::<WrappedFailure>(state, -1, ptr::null()).as_ref() {
                 Some(WrappedFailure::Error(error)) => {
+                    if let Some(msg) = translate_error(state, error) {
+                        push_string(state, msg.as_bytes(), true)?;
+                        return Ok(1);
+                    }
+
                     let err_buf_key = &ERROR_PRINT_BUFFER_KEY as *const u8 as *const c_void;
                     ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, err_buf_key);
                     let err_buf = ffi::lua_touserdata(state, -1) as *mut String;
@@ -880,7 +1289,10 @@ pub unsafe fn init_error_registry(state: *mut ffi::lua_State) -> Result<()> {
                 Some(WrappedFailure::Panic(None)) => Err(Error::PreviouslyResumedPanic),
                 _ => {
                     // I'm not sure whether this is possible to trigger without bugs in mlua?
-                    Err(Error::UserDataTypeMismatch)
+                    Err(Error::UserDataTypeMismatchNamed {
+                        expected: "WrappedFailure".to_string(),
+                        actual: "userdata".to_string(),
+                    })
                 }
             }?;
 
@@ -985,7 +1397,7 @@ impl WrappedFailure {
         #[cfg(feature = "luau")]
         let ud = {
             unsafe extern "C" fn destructor(p: *mut c_void) {
-                ptr::drop_in_place(p as *mut WrappedFailure);
+                abort_on_panic(|| ptr::drop_in_place(p as *mut WrappedFailure));
             }
             ffi::lua_newuserdatadtor(state, size, destructor) as *mut Self
         };
@@ -1042,6 +1454,26 @@ pub(crate) unsafe fn get_destructed_userdata_metatable(state: *mut ffi::lua_Stat
     ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, key);
 }
 
+// Returns the type name of `T` without module paths (e.g. `Arc<Mutex<MyType>>`)
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let full_name = std::any::type_name::<T>();
+    let mut name = String::with_capacity(full_name.len());
+    let mut path_start = 0;
+    let mut chars = full_name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            name.truncate(path_start);
+            continue;
+        }
+        name.push(c);
+        if !(c.is_alphanumeric() || c == '_') {
+            path_start = name.len();
+        }
+    }
+    name
+}
+
 pub(crate) unsafe fn ptr_to_cstr_bytes<'a>(input: *const c_char) -> Option<&'a [u8]> {
     if input.is_null() {
         return None;
@@ -1051,5 +1483,8 @@ pub(crate) unsafe fn ptr_to_cstr_bytes<'a>(input: *const c_char) -> Option<&'a [
 
 static DESTRUCTED_USERDATA_METATABLE: u8 = 0;
 static ERROR_PRINT_BUFFER_KEY: u8 = 0;
+#[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+static ERROR_TRACEBACK_KEY: u8 = 0;
 static USERDATA_METATABLE_INDEX: u8 = 0;
+static USERDATA_METATABLE_INDEX_ERROR: u8 = 0;
 static USERDATA_METATABLE_NEWINDEX: u8 = 0;
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::iter::{self, FromIterator};
 use std::ops::Index;
 use std::os::raw::c_void;
@@ -5,7 +6,7 @@ use std::{ptr, slice, str, vec};
 
 #[cfg(feature = "serialize")]
 use {
-    serde::ser::{self, Serialize, Serializer},
+    serde::ser::{Serialize, Serializer},
     std::convert::TryInto,
     std::result::Result as StdResult,
 };
@@ -61,6 +62,45 @@ pub enum Value<'lua> {
 pub use self::Value::Nil;
 
 impl<'lua> Value<'lua> {
+    /// Normalizes a number value to the representation used by the active Lua version.
+    ///
+    /// Returns the value that Lua gives back after storing this value, so normalized values
+    /// are equal if and only if they are the same table key in Lua:
+    ///
+    /// - In Lua 5.3 and 5.4, floats with an exact integer representation are converted to
+    ///   integers (like Lua does for table keys), and integers are kept as is.
+    /// - In other Lua versions (and Luau) all numbers are floats. Integers are converted to
+    ///   floats (possibly losing precision), and integral floats are returned as integers
+    ///   if they fit into [`Integer`].
+    ///
+    /// Non-number values are returned unchanged.
+    ///
+    /// [`Integer`]: crate::Integer
+    pub fn normalize_for_engine(self) -> Self {
+        #[cfg(any(feature = "lua54", feature = "lua53"))]
+        match self {
+            Value::Number(n) => match num_traits::cast::<_, Integer>(n) {
+                Some(i) if i as Number == n => Value::Integer(i),
+                _ => Value::Number(n),
+            },
+            value => value,
+        }
+
+        #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+        {
+            // Must match conversion of numbers in `Lua::pop_value`
+            let normalize = |n: Number| match num_traits::cast(n) {
+                Some(i) if (n - (i as Number)).abs() < Number::EPSILON => Value::Integer(i),
+                _ => Value::Number(n),
+            };
+            match self {
+                Value::Integer(i) => normalize(i as Number),
+                Value::Number(n) => normalize(n),
+                value => value,
+            }
+        }
+    }
+
     pub const fn type_name(&self) -> &'static str {
         match *self {
             Value::Nil => "nil",
@@ -89,6 +129,12 @@ impl<'lua> Value<'lua> {
     /// The first value is checked first. If that value does not define a metamethod
     /// for `__eq`, then mlua will check the second value.
     /// Then mlua calls the metamethod with the two values as arguments, if found.
+    /// The metamethod is never invoked for values of different types (eg. a table and a userdata).
+    ///
+    /// See [`Table::equals`] and [`AnyUserData::equals`] for details.
+    ///
+    /// [`Table::equals`]: crate::Table::equals
+    /// [`AnyUserData::equals`]: crate::AnyUserData::equals
     pub fn equals<T: AsRef<Self>>(&self, other: T) -> Result<bool> {
         match (self, other.as_ref()) {
             (Value::Table(a), Value::Table(b)) => a.equals(b),
@@ -97,6 +143,63 @@ impl<'lua> Value<'lua> {
         }
     }
 
+    /// Compares two values for sorting, eg. table keys of different types.
+    ///
+    /// Defines a total order where numbers (integers and floats compared by value, NaN last)
+    /// go before strings (compared lexicographically by bytes), followed by booleans (`false`
+    /// first) and other values ordered by type name and pointer.
+    ///
+    /// See [`Table::sorted_pairs_by`] for an example.
+    ///
+    /// [`Table::sorted_pairs_by`]: crate::Table::sorted_pairs_by
+    pub fn sort_cmp(&self, other: &Self) -> Ordering {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Integer(_) | Value::Number(_) => 0,
+                Value::String(_) => 1,
+                Value::Boolean(_) => 2,
+                _ => 3,
+            }
+        }
+
+        fn cmp_numbers(a: Number, b: Number) -> Ordering {
+            match a.partial_cmp(&b) {
+                Some(ord) => ord,
+                None => a.is_nan().cmp(&b.is_nan()),
+            }
+        }
+
+        // Compares an integer with a float exactly, without converting the integer (like Lua)
+        fn cmp_int_number(i: Integer, n: Number) -> Ordering {
+            // `Integer::MIN` is a power of two, so the bound is exact
+            let limit = -(Integer::MIN as Number);
+            if n.is_nan() || n >= limit {
+                return Ordering::Less;
+            }
+            if n < -limit {
+                return Ordering::Greater;
+            }
+            // `n` is in the integer range, so its floor is converted exactly
+            let floor = n.floor();
+            match i.cmp(&(floor as Integer)) {
+                Ordering::Equal if n > floor => Ordering::Less,
+                ord => ord,
+            }
+        }
+
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Number(b)) => cmp_int_number(*a, *b),
+            (Value::Number(a), Value::Integer(b)) => cmp_int_number(*b, *a).reverse(),
+            (Value::Number(a), Value::Number(b)) => cmp_numbers(*a, *b),
+            (Value::String(a), Value::String(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (a, b) => (rank(a).cmp(&rank(b)))
+                .then_with(|| a.type_name().cmp(b.type_name()))
+                .then_with(|| a.to_pointer().cmp(&b.to_pointer())),
+        }
+    }
+
     /// Converts the value to a generic C pointer.
     ///
     /// The value can be a userdata, a table, a thread, a string, or a function; otherwise it returns NULL.
@@ -114,6 +217,7 @@ impl<'lua> Value<'lua> {
                 Value::Function(Function(r))
                 | Value::Thread(Thread(r))
                 | Value::UserData(AnyUserData(r)) => {
+                    let _guard = r.lua.lock();
                     ffi::lua_topointer(r.lua.ref_thread(), r.index)
                 }
                 _ => ptr::null(),
@@ -172,8 +276,7 @@ impl<'lua> Serialize for Value<'lua> {
             Value::UserData(ud) => ud.serialize(serializer),
             Value::LightUserData(ud) if ud.0.is_null() => serializer.serialize_none(),
             Value::Error(_) | Value::LightUserData(_) | Value::Function(_) | Value::Thread(_) => {
-                let msg = format!("cannot serialize <{}>", self.type_name());
-                Err(ser::Error::custom(msg))
+                crate::serde::serialize_unsupported(self, serializer)
             }
         }
     }
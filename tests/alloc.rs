@@ -0,0 +1,91 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use mlua::{Function, Lua, MultiValue, Result};
+
+// Counts allocations made by the current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Returns the number of allocations made by the current thread while running `f`
+fn count_allocations(f: impl FnOnce() -> Result<()>) -> Result<usize> {
+    let allocations = ALLOCATIONS.with(|n| n.get());
+    f()?;
+    Ok(ALLOCATIONS.with(|n| n.get()) - allocations)
+}
+
+// LuaJIT can allocate a few times while compiling traces
+fn assert_allocation_free(allocations: usize) {
+    if cfg!(feature = "luajit") {
+        assert!(allocations < 100, "{} allocations", allocations);
+    } else {
+        assert_eq!(allocations, 0);
+    }
+}
+
+#[test]
+fn test_function_call_into() -> Result<()> {
+    let lua = Lua::new();
+
+    let func: Function = lua
+        .load(
+            r#"
+            local t, s = {}, "constant"
+            return function(i)
+                return i, s, t, i * 2
+            end
+        "#,
+        )
+        .eval()?;
+
+    let mut out = MultiValue::new();
+    func.call_into(0, &mut out)?;
+    let allocations = count_allocations(|| {
+        for i in 0..10_000 {
+            func.call_into(i, &mut out)?;
+        }
+        Ok(())
+    })?;
+    assert_allocation_free(allocations);
+
+    Ok(())
+}
+
+#[test]
+fn test_function_call0() -> Result<()> {
+    let lua = Lua::new();
+
+    let empty: Function = lua.load("function() end").eval()?;
+    empty.call0()?;
+    let allocations = count_allocations(|| {
+        for _ in 0..10_000 {
+            empty.call0()?;
+        }
+        Ok(())
+    })?;
+    assert_allocation_free(allocations);
+
+    Ok(())
+}
@@ -41,6 +41,27 @@ async fn test_async_function_wrap() -> Result<()> {
     let res: String = lua.load(r#"f("hello")"#).eval_async().await?;
     assert_eq!(res, "hello");
 
+    lua.load(r#"res = f("world")"#).exec_async().await?;
+    assert_eq!(lua.globals().get::<_, String>("res")?, "world");
+
+    Ok(())
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test]
+async fn test_async_owned_function() -> Result<()> {
+    let lua = Lua::new();
+
+    let sleep = lua
+        .create_async_function(move |_lua, n: u64| async move {
+            Delay::new(Duration::from_millis(n)).await;
+            Ok(format!("elapsed:{}ms", n))
+        })?
+        .into_owned();
+
+    let res: String = sleep.call_async(10).await?;
+    assert_eq!(res, "elapsed:10ms");
+
     Ok(())
 }
 
@@ -250,6 +271,83 @@ async fn test_async_thread_stream() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_async_thread_into_stream() -> Result<()> {
+    use futures_util::stream::StreamExt;
+
+    let lua = Lua::new();
+
+    let generator = lua
+        .load(
+            r#"
+            function(n)
+                for i = 1, n do
+                    coroutine.yield(i)
+                end
+            end
+            "#,
+        )
+        .eval::<Function>()?;
+
+    // Consumer that awaits between items
+    let mut stream = lua
+        .create_thread(generator.clone())?
+        .into_stream::<_, i64>(10);
+    let mut items = Vec::new();
+    while let Some(item) = stream.next().await {
+        items.push(item?);
+        Delay::new(Duration::from_millis(1)).await;
+    }
+    assert_eq!(items, (1..=10).collect::<Vec<_>>());
+    assert!(stream.next().await.is_none());
+    assert_eq!(stream.finish::<Option<i64>>()?, Some(None));
+    assert_eq!(stream.finish::<()>()?, None);
+
+    // Return values are not part of the stream
+    let thread = lua.create_thread(
+        lua.load("function() coroutine.yield(1) return 2, 3 end")
+            .eval()?,
+    )?;
+    let mut stream = thread.into_stream::<_, i64>(());
+    assert_eq!(stream.next().await.transpose()?, Some(1));
+    assert_eq!(stream.finish::<(i64, i64)>()?, None);
+    assert_eq!(stream.next().await.transpose()?, None);
+    assert_eq!(stream.finish::<(i64, i64)>()?, Some((2, 3)));
+
+    // Errors end the stream
+    let thread = lua.create_thread(
+        lua.load(r#"function() coroutine.yield(1) coroutine.yield(2) error("boom") end"#)
+            .eval()?,
+    )?;
+    let items = thread.into_stream::<_, i64>(()).collect::<Vec<_>>().await;
+    assert_eq!(items.len(), 3);
+    assert_eq!(*items[0].as_ref().unwrap(), 1);
+    assert_eq!(*items[1].as_ref().unwrap(), 2);
+    match &items[2] {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("boom")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    // Rust async functions can be awaited inside the thread
+    let sleep = lua.create_async_function(|_, n: u64| async move {
+        Delay::new(Duration::from_millis(n)).await;
+        Ok(n)
+    })?;
+    lua.globals().set("sleep", sleep)?;
+    let thread = lua.create_thread(
+        lua.load("function() for i = 1, 3 do coroutine.yield(sleep(i)) end end")
+            .eval()?,
+    )?;
+    let items = (thread.into_stream::<_, u64>(()))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(items, vec![1, 2, 3]);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_async_thread() -> Result<()> {
     let lua = Lua::new();
@@ -581,3 +679,65 @@ async fn test_async_scope_userdata() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_async_promise() -> Result<()> {
+    let lua = Lua::new();
+
+    // Starts a host operation that completes after `delay` ms (or fails if `value` is nil)
+    let start = lua.create_function(|lua, (delay, value): (u64, Option<i64>)| {
+        let (resolver, promise) = lua.create_promise::<i64>()?;
+        tokio::spawn(async move {
+            Delay::new(Duration::from_millis(delay)).await;
+            match value {
+                Some(value) => resolver.resolve(value),
+                None => resolver.reject(Error::RuntimeError("operation failed".into())),
+            }
+        });
+        Ok(promise)
+    })?;
+    lua.globals().set("start", start)?;
+
+    let (a, b) = lua
+        .load(
+            r#"
+            local a = start(100, 1)
+            local b = start(10, 2)
+            local c = start(20, nil)
+            assert(not a:is_ready())
+
+            local ok, err = pcall(a.get, a)
+            assert(not ok and tostring(err):find("promise is not resolved yet"))
+
+            -- `a` completes last
+            local a_value = a:await()
+            assert(b:is_ready() and c:is_ready())
+            local b_value = b:await()
+            assert(b:get() == b_value)
+
+            ok, err = pcall(c.await, c)
+            assert(not ok and tostring(err):find("operation failed"))
+            ok, err = pcall(c.get, c)
+            assert(not ok and tostring(err):find("operation failed"))
+
+            return a_value, b_value
+        "#,
+        )
+        .eval_async::<(i64, i64)>()
+        .await?;
+    assert_eq!((a, b), (1, 2));
+
+    // Dropping the resolver rejects the promise
+    let (resolver, promise) = lua.create_promise::<i64>()?;
+    drop(resolver);
+    lua.globals().set("promise", promise)?;
+    match lua.load("promise:await()").exec_async().await {
+        Err(Error::CallbackError { ref cause, .. }) => match cause.as_ref() {
+            Error::RuntimeError(msg) => assert!(msg.contains("dropped")),
+            e => panic!("expected RuntimeError, got {:?}", e),
+        },
+        r => panic!("expected CallbackError, got {:?}", r),
+    }
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use std::fs;
 use std::io;
+use std::mem;
+use std::sync::{Arc, Mutex};
 
-use mlua::{Error, Lua, Result};
+use mlua::{ChunkEvent, ChunkMode, Error, Lua, Result};
 
 #[test]
 fn test_chunk_path() -> Result<()> {
@@ -52,3 +54,211 @@ fn test_chunk_macro() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_chunk_observer() -> Result<()> {
+    let lua = Lua::new();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events2 = events.clone();
+    lua.set_chunk_observer(false, move |event| events2.lock().unwrap().push(event));
+
+    lua.load("x = 1").set_name("first").exec()?;
+    assert!(lua.load("error('boom')").set_name("second").exec().is_err());
+
+    let events = mem::take(&mut *events.lock().unwrap());
+    assert_eq!(events.len(), 4);
+    match &events[0] {
+        ChunkEvent::Loaded {
+            name,
+            bytes_len: 5,
+            mode: ChunkMode::Text,
+        } if name == "first" => {}
+        event => panic!("unexpected event: {event:?}"),
+    }
+    match &events[1] {
+        ChunkEvent::Executed { name, ok: true, .. } if name == "first" => {}
+        event => panic!("unexpected event: {event:?}"),
+    }
+    match &events[2] {
+        ChunkEvent::Loaded { name, .. } if name == "second" => {}
+        event => panic!("unexpected event: {event:?}"),
+    }
+    match &events[3] {
+        ChunkEvent::Executed {
+            name, ok: false, ..
+        } if name == "second" => {}
+        event => panic!("unexpected event: {event:?}"),
+    }
+
+    // Verbose observer also reports created functions
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events2 = events.clone();
+    lua.set_chunk_observer(true, move |event| events2.lock().unwrap().push(event));
+    let func = lua.load("return 1 + 1").set_name("third").into_function()?;
+    assert_eq!(func.call::<_, i32>(())?, 2);
+    assert_eq!(
+        events.lock().unwrap()[1],
+        ChunkEvent::FunctionCreated {
+            chunk_name: "third".into(),
+            line: 0,
+        }
+    );
+
+    // Only the main chunk function is reported, not the functions defined inside it
+    events.lock().unwrap().clear();
+    lua.load(
+        r#"
+        local function f()
+            return function() end
+        end
+
+        function g() end
+    "#,
+    )
+    .set_name("fourth")
+    .exec()?;
+    let lines = (events.lock().unwrap().iter())
+        .filter_map(|event| match event {
+            ChunkEvent::FunctionCreated { chunk_name, line } if chunk_name == "fourth" => {
+                Some(*line)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![0]);
+
+    lua.remove_chunk_observer();
+    events.lock().unwrap().clear();
+    lua.load("x = 2").exec()?;
+    assert!(events.lock().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_bytecode_inspect() -> Result<()> {
+    use mlua::bytecode::{self, BytecodeKind, Endianness};
+
+    #[cfg(not(feature = "luau"))]
+    let data = {
+        let lua = unsafe { Lua::unsafe_new() };
+        let func = lua.load("return 1 + 1").into_function()?;
+        func.dump(true)
+    };
+    #[cfg(feature = "luau")]
+    let data = mlua::Compiler::new().compile("return 1 + 1");
+
+    let info = bytecode::inspect(&data)?;
+    #[cfg(feature = "lua54")]
+    {
+        assert_eq!(info.kind, BytecodeKind::Lua);
+        assert_eq!(info.version, 0x54);
+        assert_eq!(info.integer_size, Some(8));
+        assert_eq!(info.int_size, None);
+    }
+    #[cfg(feature = "lua51")]
+    {
+        assert_eq!(info.kind, BytecodeKind::Lua);
+        assert_eq!(info.version, 0x51);
+        assert_eq!(info.size_t_size, Some(std::mem::size_of::<usize>() as u8));
+    }
+    #[cfg(feature = "luajit")]
+    assert_eq!(info.kind, BytecodeKind::LuaJIT);
+    #[cfg(feature = "luau")]
+    assert_eq!(info.kind, BytecodeKind::Luau);
+    #[cfg(target_endian = "little")]
+    assert_eq!(info.endianness, Endianness::Little);
+    #[cfg(not(any(feature = "luau", feature = "luajit")))]
+    assert_eq!(info.number_size, Some(8));
+
+    // Header of Lua 5.1 bytecode produced on a big-endian 32-bit host
+    let info = bytecode::inspect(b"\x1bLua\x51\x00\x00\x04\x04\x04\x08\x00")?;
+    assert_eq!(info.kind, BytecodeKind::Lua);
+    assert_eq!(info.endianness, Endianness::Big);
+    assert_eq!(info.size_t_size, Some(4));
+    assert_eq!(
+        info.to_string(),
+        "Lua 5.1 bytecode (format 0, big-endian, int 4 bytes, size_t 4 bytes, \
+         Instruction 4 bytes, lua_Number 8 bytes)"
+    );
+
+    assert!(bytecode::inspect(b"\x1bLua\x54\x00\x19\x93").is_err());
+    assert!(bytecode::inspect(b"return 1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_bytecode_mismatch() -> Result<()> {
+    let lua = unsafe { Lua::unsafe_new() };
+
+    #[cfg(not(feature = "luau"))]
+    let data = lua.load("return 1 + 1").into_function()?.dump(true);
+    #[cfg(feature = "luau")]
+    let data = mlua::Compiler::new().compile("return 1 + 1");
+
+    assert_eq!(lua.load(&data).eval::<i32>()?, 2);
+
+    let check_mismatch = |data: &[u8], found: &str| match lua.load(data).exec() {
+        Err(Error::BytecodeMismatch {
+            found: ref found2, ..
+        }) => assert!(found2.contains(found), "{}", found2),
+        r => panic!("expected BytecodeMismatch, got {:?}", r),
+    };
+
+    // Truncated header
+    #[cfg(not(any(feature = "luajit", feature = "luau")))]
+    check_mismatch(&data[..6], "invalid header (truncated header)");
+    #[cfg(feature = "luajit")]
+    check_mismatch(&data[..4], "invalid header (truncated header)");
+
+    // Different version
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    {
+        let mut data2 = b"\x1bLua\x51\x00\x01\x04\x08\x04\x08\x00".to_vec();
+        data2.extend_from_slice(&data[12..]);
+        check_mismatch(&data2, "Lua 5.1 bytecode");
+    }
+    #[cfg(feature = "lua51")]
+    {
+        let mut data2 = data.clone();
+        data2[4] = 0x50;
+        check_mismatch(&data2, "unsupported Lua version");
+    }
+    #[cfg(feature = "luajit")]
+    {
+        let mut data2 = data.clone();
+        data2[3] = 3;
+        check_mismatch(&data2, "LuaJIT bytecode v3");
+    }
+    #[cfg(feature = "luau")]
+    {
+        let mut data2 = data.clone();
+        data2[0] = 2;
+        check_mismatch(&data2, "Luau bytecode v2");
+    }
+
+    // Bytecode from a different configuration (32-bit numbers)
+    #[cfg(not(any(feature = "luajit", feature = "luau")))]
+    {
+        let mut data3 = data.clone();
+        let number_size_pos = if cfg!(feature = "lua54") {
+            14
+        } else if cfg!(feature = "lua53") {
+            16
+        } else {
+            10
+        };
+        data3[number_size_pos] = 4;
+        check_mismatch(&data3, "lua_Number 4 bytes");
+    }
+
+    // Chunks loaded as text are not checked
+    match lua.load(&data).set_mode(ChunkMode::Text).exec() {
+        Err(Error::SyntaxError { .. }) => {}
+        r => panic!("expected SyntaxError, got {:?}", r),
+    }
+
+    Ok(())
+}
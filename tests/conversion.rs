@@ -138,3 +138,182 @@ fn test_conv_array() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_conv_nonzero() -> Result<()> {
+    use std::num::{NonZeroI64, NonZeroU32};
+
+    let lua = Lua::new();
+
+    let n = NonZeroU32::new(42).unwrap();
+    lua.globals().set("n", n)?;
+    assert_eq!(lua.globals().get::<_, NonZeroU32>("n")?, n);
+    assert_eq!(lua.load("-5").eval::<NonZeroI64>()?.get(), -5);
+
+    match lua.load("0").eval::<NonZeroU32>() {
+        Err(Error::FromLuaConversionError { to, message, .. }) => {
+            assert_eq!(to, "NonZeroU32");
+            assert_eq!(message.as_deref(), Some("expected non-zero integer"));
+        }
+        r => panic!("expected FromLuaConversionError, got {r:?}"),
+    }
+    assert!(lua.load("-1").eval::<NonZeroU32>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_conv_via_try_from() -> Result<()> {
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq)]
+    struct UserId(u64);
+
+    impl TryFrom<u64> for UserId {
+        type Error = String;
+
+        fn try_from(id: u64) -> std::result::Result<Self, Self::Error> {
+            if id == 0 {
+                return Err("user id cannot be zero".to_string());
+            }
+            Ok(UserId(id))
+        }
+    }
+
+    impl From<UserId> for u64 {
+        fn from(id: UserId) -> u64 {
+            id.0
+        }
+    }
+
+    mlua::impl_from_lua_via_try_from!(UserId, u64);
+
+    let lua = Lua::new();
+
+    let next_id = lua.create_function(|_, id: UserId| Ok(UserId(id.0 + 1)))?;
+    lua.globals().set("next_id", next_id)?;
+    assert_eq!(lua.load("next_id(41)").eval::<UserId>()?, UserId(42));
+    lua.globals().set("id", UserId(7))?;
+    assert_eq!(lua.load("id").eval::<u64>()?, 7);
+
+    match lua.load("next_id(0)").exec() {
+        Err(Error::CallbackError { cause, .. }) => match cause.as_ref() {
+            Error::FromLuaConversionError { message, .. } => {
+                assert_eq!(message.as_deref(), Some("user id cannot be zero"));
+            }
+            err => panic!("expected FromLuaConversionError, got {err:?}"),
+        },
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_report() -> Result<()> {
+    let lua = Lua::new();
+
+    let value = lua
+        .load("{ a = {1, 2}, b = {3, 'x', 4}, c = 'y', d = {5, true} }")
+        .eval()?;
+    let report = lua.convert_report::<HashMap<String, Vec<u32>>>(value);
+    let mut issues = report
+        .issues()
+        .iter()
+        .map(|issue| (issue.path.clone(), issue.found.clone()))
+        .collect::<Vec<_>>();
+    issues.sort();
+    assert_eq!(
+        issues,
+        vec![
+            ("b[2]".to_string(), "string".to_string()),
+            ("c".to_string(), "string".to_string()),
+            ("d[2]".to_string(), "boolean".to_string()),
+        ]
+    );
+
+    let value = lua.load("{ a = {1, 2} }").eval()?;
+    let map = lua
+        .convert_report::<BTreeMap<String, Option<Vec<u32>>>>(value)
+        .into_result()
+        .unwrap();
+    assert_eq!(map, btreemap! { "a".to_string() => Some(vec![1, 2]) });
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn test_conv_enum() -> Result<()> {
+    #[derive(Debug, Clone, Copy, PartialEq, mlua::LuaEnum)]
+    #[allow(clippy::upper_case_acronyms)]
+    enum Color {
+        RED,
+        GREEN,
+        #[lua(value = 10)]
+        BLUE,
+        #[lua(name = "DEFAULT", value = "default")]
+        Default,
+    }
+
+    let lua = Lua::new();
+
+    let colors = lua.create_enum_table::<Color>()?;
+    assert!(colors.is_frozen());
+    lua.globals().set("Color", colors.clone())?;
+    assert_eq!(lua.load("Color.RED").eval::<i64>()?, 0);
+    assert_eq!(lua.load("Color.GREEN").eval::<i64>()?, 1);
+    assert_eq!(lua.load("Color.BLUE").eval::<i64>()?, 10);
+    assert_eq!(lua.load("Color.DEFAULT").eval::<String>()?, "default");
+    assert!(lua.load("Color.RED = 5").exec().is_err());
+    assert!(lua.load("Color.PURPLE = 5").exec().is_err());
+    assert!(colors.set("RED", 5).is_err());
+    assert_eq!(lua.load("Color.RED").eval::<i64>()?, 0);
+    assert_eq!(colors.get::<_, i64>("BLUE")?, 10);
+    #[cfg(not(feature = "luau"))]
+    assert!(!lua.load("getmetatable(Color)").eval::<bool>()?);
+    #[cfg(any(
+        feature = "lua54",
+        feature = "lua53",
+        feature = "lua52",
+        feature = "luau"
+    ))]
+    {
+        let count = lua
+            .load("local n = 0; for _ in pairs(Color) do n = n + 1 end; return n")
+            .eval::<i64>()?;
+        assert_eq!(count, 4);
+    }
+
+    let check = lua.create_function(|_, color: Color| Ok(format!("{:?}", color)))?;
+    lua.globals().set("check", check)?;
+    assert_eq!(lua.load("check(Color.RED)").eval::<String>()?, "RED");
+    assert_eq!(lua.load("check('RED')").eval::<String>()?, "RED");
+    assert_eq!(lua.load("check(0)").eval::<String>()?, "RED");
+    assert_eq!(lua.load("check(10.0)").eval::<String>()?, "BLUE");
+    assert_eq!(lua.load("check('default')").eval::<String>()?, "Default");
+
+    match lua.load("check('PURPLE')").exec() {
+        Err(Error::CallbackError { cause, .. }) => match cause.as_ref() {
+            Error::FromLuaConversionError { to, message, .. } => {
+                assert_eq!(*to, "Color");
+                let message = message.as_deref().unwrap();
+                assert!(message.contains("\"PURPLE\""), "{}", message);
+                assert!(
+                    message.contains(
+                        "expected one of: RED (0), GREEN (1), BLUE (10), DEFAULT (\"default\")"
+                    ),
+                    "{}",
+                    message
+                );
+            }
+            err => panic!("expected FromLuaConversionError, got {:?}", err),
+        },
+        r => panic!("expected CallbackError, got {:?}", r),
+    }
+
+    let color = lua.create_function(|_, ()| Ok(Color::BLUE))?;
+    assert_eq!(color.call::<_, i64>(())?, 10);
+
+    Ok(())
+}
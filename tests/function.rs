@@ -1,4 +1,6 @@
-use mlua::{Function, Lua, Result, String};
+use std::collections::HashSet;
+
+use mlua::{Error, Function, Lua, MultiValue, Result, String, Value, Variadic};
 
 #[test]
 fn test_function() -> Result<()> {
@@ -20,6 +22,117 @@ fn test_function() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_function_call_with_traceback() -> Result<()> {
+    let lua = Lua::new();
+
+    let func: Function = lua
+        .load(
+            r#"
+            local function c(err) error(err) end
+            local function b(err) c(err) end
+            return function(err) b(err) end
+            "#,
+        )
+        .set_name("=chunk")
+        .eval()?;
+
+    match func.call_with_traceback::<_, ()>("custom") {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(msg.starts_with("chunk:2: custom"), "{}", msg);
+            for line in ["chunk:2:", "chunk:3:", "chunk:4:"] {
+                assert!(msg.contains(line), "`{}` not found in: {}", line, msg);
+            }
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    // Error object with `__tostring` metamethod
+    let err_obj = lua
+        .load(r#"setmetatable({}, {__tostring = function() return "error object" end})"#)
+        .eval::<Value>()?;
+    match func.call_with_traceback::<_, ()>(err_obj) {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(msg.starts_with("error object"), "{}", msg);
+            assert!(msg.contains("chunk:2:"), "{}", msg);
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    // Rust error rethrown from Lua gets a traceback too
+    match func.call::<_, ()>(Error::external("boom")) {
+        Err(Error::ExternalError(_)) => {}
+        r => panic!("expected ExternalError, got {:?}", r),
+    }
+    match func.call_with_traceback::<_, ()>(Error::external("boom")) {
+        Err(Error::CallbackError { traceback, cause }) => {
+            assert!(matches!(*cause, Error::ExternalError(_)));
+            assert!(traceback.contains("chunk:2:"), "{}", traceback);
+        }
+        r => panic!("expected CallbackError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_function_call_into() -> Result<()> {
+    let lua = Lua::new();
+
+    let func: Function = lua
+        .load(
+            r#"
+            local t, s = {}, "constant"
+            return function(i)
+                return i, s, t, i * 2
+            end
+        "#,
+        )
+        .eval()?;
+
+    let mut out = MultiValue::new();
+    for i in 0..10 {
+        func.call_into(i, &mut out)?;
+        let expected = func.call::<_, MultiValue>(i)?;
+        assert_eq!(out.len(), 4);
+        for (a, b) in out.iter().zip(expected.iter()) {
+            assert!(a.equals(b)?);
+        }
+    }
+    assert_eq!(out[0], Value::Integer(9));
+    assert_eq!(out[3], Value::Integer(18));
+
+    // Errors are propagated and the buffer is left empty
+    let err_func: Function = lua.load("function() error('boom') end").eval()?;
+    assert!(err_func.call_into((), &mut out).is_err());
+    assert!(out.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_function_call0() -> Result<()> {
+    let lua = Lua::new();
+
+    let func: Function = lua
+        .load("function(...) calls = (calls or 0) + 1; return select('#', ...) end")
+        .eval()?;
+    func.call0()?;
+    assert_eq!(lua.globals().get::<_, i64>("calls")?, 1);
+
+    // Errors are reported the same way as in `call`
+    let err_func: Function = lua.load("function() error('boom') end").eval()?;
+    match (err_func.call0(), err_func.call::<_, ()>(())) {
+        (Err(Error::RuntimeError(msg0)), Err(Error::RuntimeError(msg))) => {
+            assert!(msg0.contains("boom") && msg0.contains("stack traceback"));
+            assert_eq!(msg0, msg);
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_bind() -> Result<()> {
     let lua = Lua::new();
@@ -56,6 +169,45 @@ fn test_bind() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_bind_flatten() -> Result<()> {
+    let lua = Lua::new();
+
+    // Returns the current stack depth and the passed arguments
+    let depth = lua.create_function(|lua, args: MultiValue| {
+        let mut depth = 0;
+        while lua.inspect_stack(depth).is_some() {
+            depth += 1;
+        }
+        Ok((depth, args.len(), args))
+    })?;
+    let (base_depth, _, _): (usize, usize, MultiValue) = depth.call(())?;
+
+    let mut bound = depth.clone();
+    for i in 0..100 {
+        bound = bound.bind(i)?;
+    }
+    // Nil values are preserved
+    bound = bound.bind((Value::Nil, "last"))?;
+
+    let (bound_depth, nargs, args): (usize, usize, Variadic<Value>) = bound.call("extra")?;
+    assert!(bound_depth <= base_depth + 1);
+    assert_eq!(nargs, 103);
+    assert_eq!(args[0], Value::Integer(0));
+    assert_eq!(args[99], Value::Integer(99));
+    assert_eq!(args[100], Value::Nil);
+    assert_eq!(args[101], Value::String(lua.create_string("last")?));
+    assert_eq!(args[102], Value::String(lua.create_string("extra")?));
+
+    // Rebinding a bound function does not affect the original one
+    let (_, nargs, _): (usize, usize, Variadic<Value>) = depth.bind(1)?.bind(2)?.call(())?;
+    assert_eq!(nargs, 2);
+    let (_, nargs, _): (usize, usize, Variadic<Value>) = depth.bind(1)?.call(())?;
+    assert_eq!(nargs, 1);
+
+    Ok(())
+}
+
 #[test]
 fn test_rust_function() -> Result<()> {
     let lua = Lua::new();
@@ -99,6 +251,47 @@ fn test_c_function() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_c_closure() -> Result<()> {
+    use std::os::raw::c_int;
+
+    extern "C" {
+        fn lua_pushvalue(state: *mut mlua::lua_State, idx: c_int);
+    }
+
+    // `lua_upvalueindex(1)`
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    const UPVALUE_1: c_int = -1_001_000 - 1;
+    #[cfg(any(feature = "lua51", feature = "luajit", feature = "luau"))]
+    const UPVALUE_1: c_int = -10_002 - 1;
+
+    unsafe extern "C" fn first_upvalue(state: *mut mlua::lua_State) -> c_int {
+        lua_pushvalue(state, UPVALUE_1);
+        1
+    }
+
+    let lua = Lua::new();
+
+    let func = unsafe { lua.create_c_closure(first_upvalue, ("hello", 123))? };
+    assert_eq!(func.call::<_, String>(())?, "hello");
+    assert_eq!(func.info().num_upvalues, 2);
+
+    let table = lua.create_table()?;
+    let func = unsafe { lua.create_c_closure(first_upvalue, table.clone())? };
+    assert_eq!(func.call::<_, mlua::Table>(())?, table);
+
+    let func = unsafe { lua.create_c_closure(first_upvalue, Value::Nil)? };
+    assert_eq!(func.call::<_, Value>(())?, Value::Nil);
+
+    let too_many = Variadic::from_iter(0..1000);
+    match unsafe { lua.create_c_closure(first_upvalue, too_many) } {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("too many upvalues")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
 #[cfg(not(feature = "luau"))]
 #[test]
 fn test_dump() -> Result<()> {
@@ -114,6 +307,48 @@ fn test_dump() -> Result<()> {
     Ok(())
 }
 
+#[cfg(not(feature = "luau"))]
+#[test]
+fn test_dump_to() -> Result<()> {
+    use std::fs::{self, File};
+    use std::io::{self, Write};
+
+    let lua = unsafe { Lua::unsafe_new() };
+
+    let func = lua
+        .load(r#"function(a, b) return a * b + 1 end"#)
+        .eval::<Function>()?;
+
+    let path = std::env::temp_dir().join(format!("mlua_dump_{}.luac", std::process::id()));
+    let mut file = File::create(&path).map_err(Error::external)?;
+    let written = func.dump_to(&mut file, false)?;
+    drop(file);
+    let data = fs::read(&path).map_err(Error::external)?;
+    fs::remove_file(&path).map_err(Error::external)?;
+
+    assert_eq!(written, data.len() as u64);
+    assert_eq!(data, func.dump(false));
+    let func2 = lua.load(&data).into_function()?;
+    assert_eq!(func2.call::<_, i64>((6, 7))?, 43);
+
+    // Writer errors
+    struct FailingWriter;
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    match func.dump_to(&mut FailingWriter, true) {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("disk full")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_function_info() -> Result<()> {
     let lua = Lua::new();
@@ -165,6 +400,224 @@ fn test_function_info() -> Result<()> {
     assert_eq!(print_info.what, Some(b"C".to_vec()));
     assert_eq!(print_info.line_defined, -1);
 
+    let function4 = lua
+        .load("local x = {}; return function(a, b, ...) return x end")
+        .set_name("=source4")
+        .eval::<Function>()?;
+    let function4_info = function4.info();
+    assert_eq!(function4_info.short_src, Some(b"source4".to_vec()));
+    assert_eq!(function4_info.num_upvalues, 1);
+    #[cfg(any(feature = "lua51", feature = "luajit"))]
+    {
+        assert_eq!(function4_info.num_params, None);
+        assert_eq!(function4_info.is_vararg, None);
+    }
+    #[cfg(not(any(feature = "lua51", feature = "luajit")))]
+    {
+        assert_eq!(function4_info.num_params, Some(2));
+        assert_eq!(function4_info.is_vararg, Some(true));
+        assert_eq!(function2_info.num_params, Some(0));
+        assert_eq!(function2_info.is_vararg, Some(false));
+    }
+    assert_eq!(function2_info.num_upvalues, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_function_environment() -> Result<()> {
+    let lua = Lua::new();
+
+    lua.globals().set("name", "global")?;
+    let f = lua.load("return name").into_function()?;
+    assert_eq!(f.call::<_, String>(())?, "global");
+    assert_eq!(f.environment()?, Some(lua.globals()));
+
+    let env = lua.create_table()?;
+    env.set("name", "sandbox")?;
+    assert!(f.set_environment(env.clone())?);
+    assert_eq!(f.call::<_, String>(())?, "sandbox");
+    assert_eq!(f.environment()?, Some(env.clone()));
+
+    // C functions don't have an environment
+    let cf = lua.create_function(|_, ()| Ok(()))?;
+    assert_eq!(cf.environment()?, None);
+    assert!(!cf.set_environment(env)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_function_deep_clone() -> Result<()> {
+    let lua = Lua::new();
+
+    let counter = lua
+        .load(
+            r#"
+            local prefix = "count: "
+            local n = 0
+            return function()
+                n = n + 1
+                return prefix .. n
+            end
+        "#,
+        )
+        .eval::<Function>()?;
+    assert_eq!(counter.call::<_, String>(())?, "count: 1");
+
+    let clone = counter.deep_clone()?;
+    assert_ne!(clone, counter);
+    assert_eq!(counter.call::<_, String>(())?, "count: 2");
+    assert_eq!(counter.call::<_, String>(())?, "count: 3");
+    // Luau shares mutable upvalues between the clones
+    #[cfg(not(feature = "luau"))]
+    assert_eq!(clone.call::<_, String>(())?, "count: 2");
+
+    // Environment is preserved
+    let env = lua.create_table()?;
+    env.set("name", "env")?;
+    let f = lua
+        .load("return name")
+        .set_environment(env.clone())
+        .into_function()?;
+    let clone = f.deep_clone()?;
+    assert_eq!(clone.call::<_, String>(())?, "env");
+
+    let shared = f.deep_clone_with_shared_env()?;
+    let env2 = lua.create_table()?;
+    env2.set("name", "env2")?;
+    clone.set_environment(env2)?;
+    assert_eq!(clone.call::<_, String>(())?, "env2");
+    assert_eq!(f.call::<_, String>(())?, "env");
+    assert_eq!(shared.call::<_, String>(())?, "env");
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+    {
+        let env3 = lua.create_table()?;
+        env3.set("name", "env3")?;
+        shared.set_environment(env3)?;
+        assert_eq!(f.call::<_, String>(())?, "env3");
+    }
+
+    // C functions cannot be cloned
+    let cf = lua.create_function(|_, ()| Ok(()))?;
+    assert!(cf.deep_clone().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_function_pointer() -> Result<()> {
+    let lua = Lua::new();
+
+    let func1 = lua
+        .load("return function() return function() end end")
+        .eval::<Function>()?;
+    let func2 = func1.call::<_, Function>(())?;
+
+    assert_eq!(func1.to_pointer(), func1.clone().to_pointer());
+    assert_ne!(func1.to_pointer(), func2.to_pointer());
+
+    let thread = lua.create_thread(func1.clone())?;
+    assert_eq!(thread.to_pointer(), thread.clone().to_pointer());
+    assert_ne!(thread.to_pointer(), lua.current_thread().to_pointer());
+
+    // Functions and threads can be used as identity keys
+    #[allow(clippy::mutable_key_type)]
+    let funcs = HashSet::from([func1.clone(), func1.clone(), func2]);
+    assert_eq!(funcs.len(), 2);
+    assert!(funcs.contains(&func1));
+    #[allow(clippy::mutable_key_type)]
+    let threads = HashSet::from([thread.clone(), thread, lua.current_thread()]);
+    assert_eq!(threads.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_function_is_c_function() -> Result<()> {
+    let lua = Lua::new();
+
+    let closure = lua
+        .load("local x = 1; return function() return x end")
+        .eval::<Function>()?;
+    let print = lua.globals().get::<_, Function>("print")?;
+    let rust_func = lua.create_function(|_, ()| Ok(()))?;
+
+    assert!(!closure.is_c_function());
+    assert!(print.is_c_function());
+    assert!(rust_func.is_c_function());
+
+    assert_ne!(print.to_pointer(), rust_func.to_pointer());
+    assert_eq!(print.to_pointer(), print.clone().to_pointer());
+
+    #[cfg(not(feature = "luau"))]
+    {
+        assert!(!closure.dump(false).is_empty());
+        assert!(print.dump(false).is_empty());
+        let mut buf = Vec::new();
+        match rust_func.dump_to(&mut buf, false) {
+            Err(Error::RuntimeError(msg)) => assert_eq!(msg, "cannot dump a C function"),
+            r => panic!("expected RuntimeError, got {:?}", r),
+        }
+        assert!(buf.is_empty());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn test_owned_function() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    use mlua::OwnedFunction;
+
+    #[derive(Default)]
+    struct Handlers {
+        on_event: Option<OwnedFunction>,
+    }
+
+    let lua = Lua::new();
+
+    let handlers = Arc::new(Mutex::new(Handlers::default()));
+    let handlers2 = handlers.clone();
+    let register = lua.create_function(move |_, f: OwnedFunction| {
+        handlers2.lock().unwrap().on_event = Some(f);
+        Ok(())
+    })?;
+    lua.globals().set("register", register)?;
+
+    let handlers3 = handlers.clone();
+    let emit = lua.create_function(move |_, name: String| -> Result<std::string::String> {
+        let handlers = handlers3.lock().unwrap();
+        let on_event = handlers
+            .on_event
+            .as_ref()
+            .expect("handler is not registered");
+        on_event.call(name)
+    })?;
+    lua.globals().set("emit", emit)?;
+
+    lua.load(
+        r#"
+        register(function(name) return "handled " .. name end)
+        assert(emit("click") == "handled click")
+    "#,
+    )
+    .exec()?;
+
+    let handler = handlers.lock().unwrap().on_event.take().unwrap();
+    assert_eq!(handler.call::<_, String>("key")?, "handled key");
+    lua.globals().set("handler", handler.clone())?;
+    assert_eq!(lua.load("handler('x')").eval::<String>()?, "handled x");
+
+    // With `send` feature the handle can be called from another thread
+    #[cfg(feature = "send")]
+    {
+        let res = std::thread::spawn(move || handler.call::<_, std::string::String>("thread"));
+        assert_eq!(res.join().unwrap()?, "handled thread");
+    }
+
     Ok(())
 }
 
@@ -200,3 +653,40 @@ fn test_function_wrap() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_function_max_call_args() -> Result<()> {
+    let lua = Lua::new();
+    let limit = lua.max_call_args();
+
+    let count = lua.create_function(|_, args: Variadic<f64>| Ok(args.len()))?;
+    let args = Variadic::from_iter(std::iter::repeat_n(1.0, limit));
+    assert_eq!(count.call::<_, usize>(args)?, limit);
+
+    let args = Variadic::from_iter(std::iter::repeat_n(1.0, limit + 1));
+    match count.call::<_, usize>(args) {
+        Err(Error::TooManyArguments { count, limit: l }) => {
+            assert_eq!(count, limit + 1);
+            assert_eq!(l, limit);
+        }
+        r => panic!("expected TooManyArguments, got {r:?}"),
+    }
+
+    // Returning too many values from a Rust function
+    let unpack =
+        lua.create_function(|_, n: usize| Ok(Variadic::from_iter(std::iter::repeat_n(true, n))))?;
+    assert_eq!(unpack.call::<_, Variadic<bool>>(10)?.len(), 10);
+    match unpack.call::<_, ()>(limit + 1) {
+        Err(Error::CallbackError { ref cause, .. }) => match cause.as_ref() {
+            Error::TooManyArguments { .. } => {}
+            err => panic!("expected TooManyArguments, got {err:?}"),
+        },
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+
+    // The state is still usable
+    assert_eq!(count.call::<_, usize>((1.0, 2.0))?, 2);
+    assert_eq!(lua.load("return 1 + 1").eval::<i32>()?, 2);
+
+    Ok(())
+}
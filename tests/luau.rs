@@ -141,6 +141,30 @@ fn test_sandbox() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_safe_env() -> Result<()> {
+    let lua = Lua::new();
+
+    let env = lua.create_table()?;
+    env.set("math", lua.globals().get::<_, Table>("math")?)?;
+    env.set("scale", 2)?;
+
+    let f = lua
+        .load("return function(x) return math.floor(x * scale) end")
+        .set_safe_environment(env.clone())
+        .eval::<mlua::Function>()?;
+    assert_eq!(f.call::<_, i32>(1.7)?, 3);
+
+    // Builtins also resolve in a table marked directly
+    let env2 = lua.create_table()?;
+    env2.set("math", lua.globals().get::<_, Table>("math")?)?;
+    env2.set_safe_env(true);
+    let n: i32 = lua.load("math.floor(2.5)").set_environment(env2).eval()?;
+    assert_eq!(n, 2);
+
+    Ok(())
+}
+
 #[test]
 fn test_sandbox_threads() -> Result<()> {
     let lua = Lua::new();
@@ -33,6 +33,127 @@ fn test_memory_limit() -> Result<()> {
     Ok(())
 }
 
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+#[test]
+fn test_out_of_memory_policy() -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use mlua::OomPolicy;
+
+    let lua = Lua::new();
+    let garbage = lua
+        .load("for i = 1, 100000 do local t = {i, i, i} end")
+        .into_function()?;
+
+    // Make sure that the next GC cycle would not start before reaching the limit
+    lua.gc_collect()?;
+    lua.set_memory_limit(lua.used_memory() + 10000)?;
+
+    lua.on_out_of_memory(OomPolicy::Fail)?;
+    match garbage.call::<_, ()>(()) {
+        Err(Error::MemoryError(_)) => {}
+        something_else => panic!("did not trigger memory error: {:?}", something_else),
+    };
+
+    // The state must be usable after the memory error
+    lua.gc_collect()?;
+    lua.on_out_of_memory(OomPolicy::CollectAndRetryOnce)?;
+    garbage.call::<_, ()>(())?;
+
+    // Live data cannot be collected, so the callback is asked to raise the limit
+    let live = lua
+        .load("local t = {}; for i = 1, 10000 do t[i] = tostring(i) end")
+        .into_function()?;
+    match live.call::<_, ()>(()) {
+        Err(Error::MemoryError(_)) => {}
+        something_else => panic!("did not trigger memory error: {:?}", something_else),
+    };
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls2 = calls.clone();
+    lua.on_out_of_memory(OomPolicy::Callback(Box::new(move |_used, required| {
+        calls2.fetch_add(1, Ordering::Relaxed);
+        Some(required + 64 * 1024)
+    })))?;
+    live.call::<_, ()>(())?;
+    assert!(calls.load(Ordering::Relaxed) > 0);
+
+    lua.on_out_of_memory(OomPolicy::Callback(Box::new(|_, _| None)))?;
+    lua.gc_collect()?;
+    lua.set_memory_limit(lua.used_memory() + 10000)?;
+    match live.call::<_, ()>(()) {
+        Err(Error::MemoryError(_)) => {}
+        something_else => panic!("did not trigger memory error: {:?}", something_else),
+    };
+
+    Ok(())
+}
+
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+#[test]
+fn test_string_builder_memory() -> Result<()> {
+    let lua = Lua::new();
+    lua.install_string_builder()?;
+    let fill = lua
+        .load(
+            r#"
+            local buf = ...
+            for i = 1, 64 * 1024 do
+                buf:put("0123456789abcdef")
+            end
+            return buf
+        "#,
+        )
+        .into_function()?;
+    let buf = lua.load("strbuf.new()").eval::<mlua::AnyUserData>()?;
+
+    lua.gc_collect()?;
+    let used_memory = lua.used_memory();
+    fill.call::<_, ()>(buf.clone())?;
+    assert!(lua.used_memory() >= used_memory + 1024 * 1024);
+
+    // Growing the buffer beyond the memory limit fails
+    lua.set_memory_limit(lua.used_memory() + 100 * 1024)?;
+    match fill.call::<_, ()>(buf.clone()) {
+        Err(Error::CallbackError { ref cause, .. })
+            if matches!(cause.as_ref(), Error::MemoryError(_)) => {}
+        something_else => panic!("did not trigger memory error: {:?}", something_else),
+    };
+    lua.set_memory_limit(0)?;
+
+    // Memory is released when the buffer is collected
+    drop(buf);
+    lua.gc_collect()?;
+    assert!(lua.used_memory() < used_memory + 1024 * 1024);
+
+    Ok(())
+}
+
+#[cfg(any(feature = "lua54", feature = "lua53", feature = "lua52"))]
+#[test]
+fn test_string_from_reader_memory() -> Result<()> {
+    let lua = Lua::new();
+
+    lua.gc_collect()?;
+    let used_memory = lua.used_memory();
+    lua.set_memory_limit(used_memory + 1024 * 1024)?;
+
+    // Reading stops once the buffer exceeds the memory limit
+    match lua.create_string_from_reader(std::io::repeat(b'x')) {
+        Err(Error::MemoryError(_)) => {}
+        // Lua 5.3 reports failed buffer allocation as a regular error
+        #[cfg(feature = "lua53")]
+        Err(Error::RuntimeError(msg)) if msg.contains("not enough memory") => {}
+        r => panic!("did not trigger memory error: {:?}", r),
+    }
+    assert!(lua.used_memory() < used_memory + 1024 * 1024);
+
+    let s = lua.create_string_from_reader(std::io::Read::take(std::io::repeat(b'x'), 64 * 1024))?;
+    assert_eq!(s.as_bytes().len(), 64 * 1024);
+
+    Ok(())
+}
+
 #[test]
 fn test_gc_control() -> Result<()> {
     let lua = Lua::new();
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use mlua::{
     AnyUserData, Error, Function, Lua, MetaMethod, Result, String, UserData, UserDataFields,
-    UserDataMethods,
+    UserDataMethods, Value,
 };
 
 #[test]
@@ -81,6 +81,7 @@ fn test_scope_userdata_fields() -> Result<()> {
                 data.0.set(val);
                 Ok(())
             });
+            fields.add_meta_field_with("__kind", |_| Ok("scoped"));
         }
     }
 
@@ -102,6 +103,30 @@ fn test_scope_userdata_fields() -> Result<()> {
 
     assert_eq!(i.get(), 44);
 
+    // Fields must not be accessible after the scope ends
+    lua.scope(|scope| {
+        let ud = scope.create_nonstatic_userdata(MyUserData(&i))?;
+        assert_eq!(ud.get_metatable()?.get::<String>("__kind")?, "scoped");
+        lua.globals().set("ud", ud.clone())?;
+        lua.load("ud.val = ud.val + 1").exec()
+    })?;
+    assert_eq!(i.get(), 45);
+    match lua.load("return ud.val").eval::<Value>() {
+        Err(Error::CallbackError { ref cause, .. }) => match *cause.as_ref() {
+            Error::CallbackDestructed => {}
+            ref err => panic!("expected CallbackDestructed, got {:?}", err),
+        },
+        r => panic!("improper return for destructed userdata: {:?}", r),
+    };
+    match lua.load("ud.val = 1").exec() {
+        Err(Error::CallbackError { ref cause, .. }) => match *cause.as_ref() {
+            Error::CallbackDestructed => {}
+            ref err => panic!("expected CallbackDestructed, got {:?}", err),
+        },
+        r => panic!("improper return for destructed userdata: {:?}", r),
+    };
+    assert_eq!(i.get(), 45);
+
     Ok(())
 }
 
@@ -228,7 +253,7 @@ fn test_scope_userdata_mismatch() -> Result<()> {
         assert!(okay.call::<_, ()>((au.clone(), bu.clone())).is_ok());
         match bad.call::<_, ()>((au, bu)) {
             Err(Error::CallbackError { ref cause, .. }) => match *cause.as_ref() {
-                Error::UserDataTypeMismatch => {}
+                Error::UserDataTypeMismatchNamed { .. } => {}
                 ref other => panic!("wrong error type {:?}", other),
             },
             Err(other) => panic!("wrong error type {:?}", other),
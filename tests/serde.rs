@@ -3,9 +3,10 @@
 use std::collections::HashMap;
 use std::error::Error as StdError;
 
+use mlua::serde::{set_unsupported_policy, UnsupportedPolicy};
 use mlua::{
-    DeserializeOptions, Error, Lua, LuaSerdeExt, Result as LuaResult, SerializeOptions, UserData,
-    Value,
+    DeserializeOptions, Error, Lua, LuaSerdeExt, Result as LuaResult, SerializeOptions, Table,
+    UserData, UserDataMethods, Value,
 };
 use serde::{Deserialize, Serialize};
 
@@ -71,6 +72,65 @@ fn test_serialize() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[test]
+fn test_serialize_any_userdata() -> Result<(), Box<dyn StdError>> {
+    let lua = Lua::new();
+
+    // `serde_json::Value` is a foreign type that does not implement `UserData`
+    let json = serde_json::json!({"name": "test", "tags": ["a", "b"], "count": 3});
+    let ud = lua.create_ser_any_userdata(json.clone())?;
+    assert!(ud.is::<serde_json::Value>());
+    assert_eq!(*ud.borrow::<serde_json::Value>()?, json);
+    ud.borrow_mut::<serde_json::Value>()?["count"] = 4.into();
+
+    // Serialize the inner value and convert it back
+    let val = lua.to_value(&ud)?;
+    let roundtrip = lua.from_value::<serde_json::Value>(val)?;
+    assert_eq!(
+        roundtrip,
+        serde_json::json!({"name": "test", "tags": ["a", "b"], "count": 4})
+    );
+
+    // Userdata has a minimal metatable without methods
+    lua.globals().set("ud", ud.clone())?;
+    // `__name` metafield is ignored by `tostring` in Lua 5.1/5.2
+    #[cfg(any(feature = "lua54", feature = "lua53", feature = "luau"))]
+    {
+        let name = lua.load("tostring(ud)").eval::<String>()?;
+        assert!(name.starts_with("Value"), "unexpected name: {name}");
+    }
+    assert!(lua.load("return ud.name").exec().is_err());
+
+    // The value can be taken out
+    let json = ud.take::<serde_json::Value>()?;
+    assert_eq!(json["count"], 4);
+    match lua.to_value(&ud) {
+        Err(Error::SerializeError(err)) => assert!(err.contains("destructed")),
+        r => panic!("expected SerializeError, got {r:?}"),
+    }
+
+    // The minimal metatable does not replace the metatable of `UserData` types
+    #[derive(Serialize)]
+    struct Point(i32, i32);
+
+    impl UserData for Point {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("sum", |_, this, ()| Ok(this.0 + this.1));
+        }
+    }
+
+    let any_ud = lua.create_ser_any_userdata(Point(1, 2))?;
+    let ud = lua.create_userdata(Point(3, 4))?;
+    lua.globals().set("any_ud", any_ud.clone())?;
+    lua.globals().set("ud", ud)?;
+    assert_eq!(lua.load("ud:sum()").eval::<i32>()?, 7);
+    assert!(lua.load("any_ud:sum()").exec().is_err());
+    assert_eq!(any_ud.borrow::<Point>()?.0, 1);
+    assert_eq!(serde_json::to_value(&any_ud)?, serde_json::json!([1, 2]));
+
+    Ok(())
+}
+
 #[test]
 fn test_serialize_in_scope() -> LuaResult<()> {
     #[derive(Serialize, Clone)]
@@ -115,6 +175,48 @@ fn test_serialize_in_scope() -> LuaResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_serialize_table() -> Result<(), Box<dyn StdError>> {
+    let lua = Lua::new();
+
+    let table = lua
+        .load(
+            r#"
+        local t = {
+            arr = {1, 2, {x = 3}},
+            map = {a = {}, b = "c"},
+        }
+        for i = 1, 1000 do t["k" .. i] = i end
+        return t
+    "#,
+        )
+        .eval::<Table>()?;
+
+    let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&table)?)?;
+    assert_eq!(json["arr"], serde_json::json!([1, 2, {"x": 3}]));
+    assert_eq!(json["map"], serde_json::json!({"a": {}, "b": "c"}));
+    assert_eq!(json["k1000"], 1000);
+    assert_eq!(json.as_object().unwrap().len(), 1002);
+
+    // Serializer errors in the middle of traversal
+    let nested = lua.create_table()?;
+    nested.set("f", lua.create_function(|_, ()| Ok(()))?)?;
+    table.set("nested", nested.clone())?;
+    assert!(serde_json::to_string(&table).is_err());
+    let arr = lua.create_sequence_from([Value::Table(nested.clone())])?;
+    assert!(serde_json::to_string(&arr).is_err());
+    nested.set("f", Value::Nil)?;
+    assert!(serde_json::to_string(&table).is_ok());
+    assert_eq!(serde_json::to_string(&arr)?, "[{}]");
+
+    // Recursive tables
+    nested.set("parent", table.clone())?;
+    let err = serde_json::to_string(&table).unwrap_err();
+    assert!(err.to_string().contains("recursive table detected"));
+
+    Ok(())
+}
+
 #[test]
 fn test_serialize_failure() -> Result<(), Box<dyn StdError>> {
     #[derive(Serialize)]
@@ -145,6 +247,62 @@ fn test_serialize_failure() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[test]
+fn test_serialize_unsupported_policy() -> Result<(), Box<dyn StdError>> {
+    struct MyUserData;
+
+    impl UserData for MyUserData {}
+
+    let lua = Lua::new();
+
+    let ud = lua.create_userdata(MyUserData)?;
+    let func = lua.create_function(|_, ()| Ok(()))?;
+    let table = lua.create_table()?;
+    table.set("name", "test")?;
+    table.set("func", func.clone())?;
+    table.set("ud", ud.clone())?;
+    table.set(
+        "list",
+        vec![Value::Integer(1), Value::Function(func.clone())],
+    )?;
+    let value = Value::Table(table);
+
+    // Default: error
+    let err = serde_json::to_string(&value).unwrap_err().to_string();
+    assert!(err.starts_with("cannot serialize <"), "{}", err);
+
+    {
+        let _guard = set_unsupported_policy(UnsupportedPolicy::Skip);
+        let json = serde_json::to_value(&value)?;
+        assert_eq!(json, serde_json::json!({"name": "test", "list": [1]}));
+        assert_eq!(
+            serde_json::to_string(&Value::Function(func.clone()))?,
+            "null"
+        );
+    }
+
+    {
+        let _guard = set_unsupported_policy(UnsupportedPolicy::Placeholder);
+        let json = serde_json::to_value(&value)?;
+        let func_str = format!("<function {:?}>", func.to_pointer());
+        let ud_str = format!("<userdata MyUserData {:?}>", ud.to_pointer());
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "test",
+                "func": func_str,
+                "ud": ud_str,
+                "list": [1, func_str],
+            })
+        );
+    }
+
+    // Previous policy is restored
+    assert!(serde_json::to_string(&Value::UserData(ud)).is_err());
+
+    Ok(())
+}
+
 #[cfg(feature = "luau")]
 #[test]
 fn test_serialize_vector() -> Result<(), Box<dyn StdError>> {
@@ -376,6 +534,151 @@ fn test_from_value_struct() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[test]
+fn test_from_table() -> Result<(), Box<dyn StdError>> {
+    let lua = Lua::new();
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Graphics {
+        shadows: bool,
+        resolution: (u32, u32),
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+        graphics: Graphics,
+        mods: Vec<String>,
+    }
+
+    let table: Table = lua
+        .load(
+            r#"
+            {
+                name = "game",
+                graphics = { shadows = true, resolution = {1920, 1080} },
+                mods = {"a", "b"},
+            }
+        "#,
+        )
+        .eval()?;
+    let config = Config::deserialize(&table)?;
+    assert_eq!(
+        Config {
+            name: "game".into(),
+            graphics: Graphics {
+                shadows: true,
+                resolution: (1920, 1080),
+            },
+            mods: vec!["a".into(), "b".into()],
+        },
+        config
+    );
+
+    // Sequence visit
+    let table: Table = lua.load(r#"{1, 2, 3}"#).eval()?;
+    assert_eq!(Vec::<i32>::deserialize(&table)?, vec![1, 2, 3]);
+
+    // Errors must contain path to the offending key
+    let table: Table = lua
+        .load(
+            r#"
+            {
+                name = "game",
+                graphics = { shadows = "yes", resolution = {1920, 1080} },
+                mods = {},
+            }
+        "#,
+        )
+        .eval()?;
+    match Config::deserialize(&table) {
+        Err(Error::DeserializeError(err)) => {
+            assert!(err.starts_with("at graphics.shadows: "), "{}", err);
+            assert!(err.contains("expected a boolean"), "{}", err);
+        }
+        r => panic!("expected `DeserializeError` error, got {:?}", r),
+    }
+
+    let table: Table = lua
+        .load(r#"{ name = "game", graphics = { shadows = true, resolution = {1920} }, mods = {} }"#)
+        .eval()?;
+    match Config::deserialize(&table) {
+        Err(Error::DeserializeError(err)) => {
+            assert!(err.starts_with("at graphics.resolution: "), "{}", err);
+        }
+        r => panic!("expected `DeserializeError` error, got {:?}", r),
+    }
+
+    let table: Table = lua
+        .load(r#"{ name = "game", graphics = { shadows = true, resolution = {1, 2} }, mods = {"a", 1} }"#)
+        .eval()?;
+    match Config::deserialize(&table) {
+        Err(Error::DeserializeError(err)) => assert!(err.starts_with("at mods[2]: "), "{}", err),
+        r => panic!("expected `DeserializeError` error, got {:?}", r),
+    }
+
+    // Errors swallowed by a visitor must not hide the path of later errors
+    #[allow(unused)]
+    struct Lenient(Vec<u32>);
+
+    impl<'de> Deserialize<'de> for Lenient {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct LenientVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for LenientVisitor {
+                type Value = Lenient;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a sequence")
+                }
+
+                fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> Result<Lenient, A::Error> {
+                    let mut items = Vec::new();
+                    loop {
+                        match seq.next_element::<u32>() {
+                            Ok(Some(item)) => items.push(item),
+                            Ok(None) => break,
+                            Err(_) => continue,
+                        }
+                    }
+                    Ok(Lenient(items))
+                }
+            }
+
+            deserializer.deserialize_seq(LenientVisitor)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[allow(unused)]
+    struct Settings {
+        ids: Lenient,
+        enabled: bool,
+    }
+
+    let table: Table = lua
+        .load(r#"{ ids = {1, "two", 3}, enabled = "yes" }"#)
+        .eval()?;
+    match Settings::deserialize(&table) {
+        Err(Error::DeserializeError(err)) => assert!(err.starts_with("at enabled: "), "{}", err),
+        r => panic!("expected `DeserializeError` error, got {:?}", r.map(|_| ())),
+    }
+
+    // Plain `from_value` errors are not annotated
+    let value = lua
+        .load(r#"{ name = "game", graphics = {}, mods = {} }"#)
+        .eval()?;
+    match lua.from_value::<Config>(value) {
+        Err(Error::DeserializeError(err)) => assert!(!err.starts_with("at "), "{}", err),
+        r => panic!("expected `DeserializeError` error, got {:?}", r),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_from_value_newtype_struct() -> Result<(), Box<dyn StdError>> {
     let lua = Lua::new();
@@ -514,6 +817,19 @@ fn test_from_value_with_options() -> Result<(), Box<dyn StdError>> {
     let globals: Globals = lua.from_value_with(Value::Table(lua.globals()), options)?;
     assert_eq!(globals.hello, "world");
 
+    // Deny unknown struct fields
+    let value: Value = lua.load(r#"{hello = "world", extra = 1}"#).eval()?;
+    let globals: Globals = lua.from_value(value.clone())?;
+    assert_eq!(globals.hello, "world");
+    let options = DeserializeOptions::new().deny_unknown_fields(true);
+    match lua.from_value_with::<Globals>(value, options) {
+        Ok(v) => panic!("expected deserialization error, got {:?}", v),
+        Err(Error::DeserializeError(err)) => {
+            assert!(err.contains("unknown field `extra`"))
+        }
+        Err(err) => panic!("expected `DeserializeError` error, got {:?}", err),
+    };
+
     Ok(())
 }
 
@@ -575,3 +891,165 @@ fn test_from_value_userdata() -> Result<(), Box<dyn StdError>> {
 
     Ok(())
 }
+
+#[test]
+fn test_from_value_report() -> Result<(), Box<dyn StdError>> {
+    #[derive(Debug, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        #[serde(default)]
+        tls: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+        timeout: Option<f64>,
+    }
+
+    let lua = Lua::new();
+
+    let value = lua
+        .load(
+            r#"{
+                name = "app",
+                servers = {
+                    { host = "a.example", port = 80 },
+                    { host = "b.example", port = "eighty" },
+                    { port = 443, colour = "red" },
+                },
+            }"#,
+        )
+        .eval()?;
+    let options = DeserializeOptions::new().deny_unknown_fields(true);
+    let report = lua.from_value_report_with::<Config>(value, options);
+    assert!(!report.is_ok());
+    let mut issues = report
+        .issues()
+        .iter()
+        .map(|issue| (issue.path.as_str(), issue.message.as_str()))
+        .collect::<Vec<_>>();
+    issues.sort();
+    assert_eq!(issues.len(), 3, "{issues:?}");
+    assert_eq!(issues[0], ("servers[2].port", "expected u16, got string"));
+    assert!(issues[1].0 == "servers[3].colour" && issues[1].1.starts_with("unknown field"));
+    assert_eq!(issues[2], ("servers[3].host", "missing field `host`"));
+
+    // Valid value
+    let value = lua
+        .load(r#"{ name = "app", servers = { { host = "a", port = 1, tls = true } } }"#)
+        .eval()?;
+    let config = lua
+        .from_value_report::<Config>(value)
+        .into_result()
+        .unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.servers[0].host, "a");
+    assert_eq!(config.servers[0].port, 1);
+    assert!(config.servers[0].tls);
+    assert_eq!(config.timeout, None);
+
+    // Root value of a wrong type
+    let report = lua.from_value_report::<Config>(Value::Boolean(true));
+    assert_eq!(report.issues().len(), 1);
+    assert_eq!(report.issues()[0].path, "");
+    assert_eq!(report.issues()[0].found, "boolean");
+
+    // Deserialization options are honored
+    let value: Value = lua
+        .load(r#"{ name = "app", servers = {}, f = function() end }"#)
+        .eval()?;
+    let report = lua.from_value_report::<Config>(value.clone());
+    assert_eq!(report.issues().len(), 1);
+    assert_eq!(report.issues()[0].path, "f");
+    let options = DeserializeOptions::new().deny_unsupported_types(false);
+    let report = lua.from_value_report_with::<Config>(value, options);
+    assert!(report.is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_bytes_roundtrip() -> Result<(), Box<dyn StdError>> {
+    // Mirrors `serde_bytes`, but does not accept strings
+    mod bytes {
+        use std::fmt;
+
+        use serde::de::{self, SeqAccess, Visitor};
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(data)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            struct BytesVisitor;
+
+            impl<'de> Visitor<'de> for BytesVisitor {
+                type Value = Vec<u8>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("byte array")
+                }
+
+                fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                    Ok(v.to_vec())
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                    let mut data = Vec::new();
+                    while let Some(b) = seq.next_element()? {
+                        data.push(b);
+                    }
+                    Ok(data)
+                }
+            }
+
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Blob {
+        name: String,
+        #[serde(with = "bytes")]
+        data: Vec<u8>,
+    }
+
+    let lua = Lua::new();
+
+    let blob = Blob {
+        name: "binary".into(),
+        data: b"\x00\xff\xfe\x80 bytes".to_vec(),
+    };
+    let value = lua.to_value(&blob)?;
+    lua.globals().set("blob", value.clone())?;
+    lua.load(
+        r#"
+        assert(type(blob.data) == "string")
+        assert(#blob.data == 10 and blob.data:byte(2) == 255)
+    "#,
+    )
+    .exec()?;
+    assert_eq!(lua.from_value::<Blob>(value.clone())?, blob);
+
+    // Valid UTF-8 strings are deserialized as bytes too
+    let text_value = lua.load(r#"{name = "text", data = "plain text"}"#).eval()?;
+    let text_blob = lua.from_value::<Blob>(text_value)?;
+    assert_eq!(text_blob.data, b"plain text");
+
+    // Invalid UTF-8 strings are serialized as arrays of bytes by serde_json
+    let json = serde_json::to_string(&value)?;
+    let json_value: serde_json::Value = serde_json::from_str(&json)?;
+    assert_eq!(
+        json_value["data"],
+        serde_json::json!([0, 255, 254, 128, 32, 98, 121, 116, 101, 115])
+    );
+    assert_eq!(serde_json::from_str::<Blob>(&json)?, blob);
+
+    Ok(())
+}
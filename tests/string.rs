@@ -84,6 +84,38 @@ fn test_string_hash() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_string_ord() -> Result<()> {
+    use std::collections::BTreeSet;
+
+    let lua = Lua::new();
+
+    #[allow(clippy::mutable_key_type)]
+    let set: BTreeSet<String> = lua
+        .load(r#"{"b", "B", "a", "A", "\255", "\0", "ab", "", 10, "10"}"#)
+        .eval()?;
+    let sorted = set.iter().map(|s| s.as_bytes()).collect::<Vec<_>>();
+    let expected: Vec<&[u8]> = vec![b"", b"\0", b"10", b"A", b"B", b"a", b"ab", b"b", b"\xff"];
+    assert_eq!(sorted, expected);
+
+    // Order matches Lua `<` operator
+    let strings = set.iter().cloned().collect::<Vec<_>>();
+    let lua_sorted: Vec<String> = lua
+        .load("local t = {...}; table.sort(t); return t")
+        .call(mlua::Variadic::from_iter(strings.iter().rev().cloned()))?;
+    assert_eq!(lua_sorted, strings);
+
+    // Comparisons with Rust strings and bytes
+    let s = lua.create_string("hello")?;
+    assert!(s < "world");
+    assert!(s > "hell");
+    assert!(s <= b"hello"[..]);
+    assert!(s > lua.create_string("Hello")?);
+    assert_eq!(s.partial_cmp("hello"), Some(std::cmp::Ordering::Equal));
+
+    Ok(())
+}
+
 #[test]
 fn test_string_debug() -> Result<()> {
     let lua = Lua::new();
@@ -96,5 +128,212 @@ fn test_string_debug() -> Result<()> {
     let s = lua.create_string(b"hello\0world\r\n\t\xF0\x90\x80")?;
     assert_eq!(format!("{s:?}"), r#"b"hello\0world\r\n\t\xf0\x90\x80""#);
 
+    // Truncated using precision
+    let s = lua.create_string("hello, world")?;
+    assert_eq!(format!("{s:.5?}"), r#""hello"..."#);
+    assert_eq!(format!("{s:.12?}"), r#""hello, world""#);
+    assert_eq!(format!("{s:.0?}"), r#"""..."#);
+    let s = lua.create_string("привет")?;
+    assert_eq!(format!("{s:.5?}"), r#""пр"..."#);
+    let s = lua.create_string(b"a\0b\xffcdef")?;
+    assert_eq!(format!("{s:.4?}"), r#"b"a\0b\xff"..."#);
+    assert_eq!(format!("{s:?}"), r#"b"a\0b\xffcdef""#);
+
+    Ok(())
+}
+
+#[test]
+fn test_string_lossy() -> Result<()> {
+    let lua = Lua::new();
+
+    // Valid utf8 is borrowed
+    let s = lua.create_string("привет")?;
+    assert!(s.is_utf8());
+    assert!(matches!(s.to_string_lossy(), Cow::Borrowed("привет")));
+    assert_eq!(format!("{s}"), "привет");
+    assert_eq!(format!("[{s:>8}]"), "[  привет]");
+
+    // Invalid bytes are replaced
+    let s = lua.create_string(b"hello\xff world\xF0\x90\x80")?;
+    assert!(!s.is_utf8());
+    assert!(matches!(s.to_string_lossy(), Cow::Owned(_)));
+    assert_eq!(s.to_string_lossy(), "hello\u{fffd} world\u{fffd}");
+    assert_eq!(s.to_string(), "hello\u{fffd} world\u{fffd}");
+
+    // Embedded nuls are kept
+    let s = lua.create_string(b"a\0b")?;
+    assert!(s.is_utf8());
+    assert_eq!(s.to_string_lossy(), "a\0b");
+    assert_eq!(format!("{s}"), "a\0b");
+
+    Ok(())
+}
+
+#[test]
+fn test_string_sub() -> Result<()> {
+    let lua = Lua::new();
+
+    let s = lua.create_string("hello, world")?;
+    assert_eq!(s.sub(..)?, "hello, world");
+    assert_eq!(s.sub(0..12)?, "hello, world");
+    assert_eq!(s.sub(7..)?, "world");
+    assert_eq!(s.sub(..5)?, "hello");
+    assert_eq!(s.sub(..=4)?, "hello");
+    assert_eq!(s.sub(5..7)?, ", ");
+
+    // Empty ranges
+    assert_eq!(s.sub(3..3)?, "");
+    #[allow(clippy::reversed_empty_ranges)]
+    let r = s.sub(7..3)?;
+    assert_eq!(r, "");
+
+    // Out of range bounds are clamped
+    assert_eq!(s.sub(10..100)?, "ld");
+    assert_eq!(s.sub(100..)?, "");
+    assert_eq!(s.sub(..usize::MAX)?, "hello, world");
+
+    // Indices are byte offsets
+    let s = lua.create_string("привет")?;
+    assert_eq!(s.sub(2..6)?, "ри");
+    assert_eq!(s.sub(1..3)?, b"\xbf\xd1");
+    let s = lua.create_string(b"\x00\xff\x00abc")?;
+    assert_eq!(s.sub(1..4)?, b"\xff\x00a");
+
+    Ok(())
+}
+
+#[test]
+fn test_string_interned() -> Result<()> {
+    let lua = Lua::new();
+
+    let get = lua.intern("get")?;
+    let set = lua.intern("set")?;
+    assert_eq!(get.len(), 3);
+    assert!(!get.is_empty());
+
+    let s: String = lua.load(r#"("GET"):lower()"#).eval()?;
+    assert!(s.eq_interned(&get));
+    assert!(!s.eq_interned(&set));
+    assert!(!lua.create_string("ge")?.eq_interned(&get));
+    assert_eq!(lua.registry_value::<String>(get.registry_key())?, "get");
+
+    // Long strings
+    let long = "x".repeat(100);
+    let long_interned = lua.intern(&long)?;
+    let s: String = lua.load(r#"("x"):rep(100)"#).eval()?;
+    assert!(s.eq_interned(&long_interned));
+    let s: String = lua.load(r#"("x"):rep(99) .. "y""#).eval()?;
+    assert!(!s.eq_interned(&long_interned));
+
+    // Interned strings from other Lua states never match
+    let lua2 = Lua::new();
+    assert!(!lua2.create_string("get")?.eq_interned(&get));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_from_reader() -> Result<()> {
+    use std::io;
+
+    // Produces `len` bytes in chunks of 4 KB
+    struct Chunked {
+        pos: usize,
+        len: usize,
+    }
+
+    impl io::Read for Chunked {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(4096).min(self.len - self.pos);
+            for (i, b) in buf[..n].iter_mut().enumerate() {
+                *b = ((self.pos + i) % 251) as u8;
+            }
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    let lua = Lua::new();
+
+    let len = 10 * 1024 * 1024;
+    let s = lua.create_string_from_reader(Chunked { pos: 0, len })?;
+    let expected_sum = (0..len).step_by(997).map(|i| i % 251).sum::<usize>();
+    let (lua_len, lua_sum) = lua
+        .load(
+            r#"
+            local s = ...
+            local sum = 0
+            for i = 1, #s, 997 do
+                sum = sum + s:byte(i)
+            end
+            return #s, sum
+        "#,
+        )
+        .call::<_, (usize, usize)>(s)?;
+    assert_eq!(lua_len, len);
+    assert_eq!(lua_sum, expected_sum);
+
+    // Empty reader
+    let s = lua.create_string_from_reader(io::empty())?;
+    assert_eq!(s, "");
+
+    // IO errors are converted to runtime errors
+    struct Failing;
+
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken pipe"))
+        }
+    }
+
+    match lua.create_string_from_reader(Failing) {
+        Err(mlua::Error::RuntimeError(msg)) => assert!(msg.contains("broken pipe")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn test_owned_string() -> Result<()> {
+    use std::collections::HashMap;
+
+    use mlua::OwnedString;
+
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Key(OwnedString);
+
+    let lua = Lua::new();
+
+    let mut counters = HashMap::new();
+    for word in ["hello", "world", "hello"] {
+        let s = lua.create_string(word)?.into_owned();
+        *counters.entry(Key(s)).or_insert(0) += 1;
+    }
+    let hello = lua.create_string("hello")?.into_owned();
+    assert_eq!(counters[&Key(hello)], 2);
+    assert_eq!(counters.len(), 2);
+
+    let s = lua.load(r#""test\255""#).eval::<OwnedString>()?;
+    assert_eq!(s.as_bytes(), b"test\xff");
+    assert_eq!(s.as_bytes_with_nul(), b"test\xff\0");
+    assert!(s.to_str().is_err());
+    assert_eq!(s.to_string_lossy(), "test\u{fffd}");
+    assert_eq!(s.to_ref(), b"test\xff");
+    assert_eq!(format!("{:?}", s), r#"b"test\xff""#);
+    assert!(!s.is_utf8());
+    assert_eq!(format!("{}", s), "test\u{fffd}");
+
+    // Owned strings keep the Lua state alive
+    let s = lua.create_string("persistent")?.into_owned();
+    drop(lua);
+    assert_eq!(s.to_str()?, "persistent");
+
+    let lua = Lua::new();
+    lua.globals()
+        .set("s", lua.create_string("value")?.into_owned())?;
+    lua.load(r#"assert(s == "value")"#).exec()?;
+
     Ok(())
 }
@@ -1,4 +1,9 @@
-use mlua::{Error, Lua, Nil, Result, Table, TableExt, Value};
+use std::collections::HashMap;
+
+use mlua::{
+    AnyUserData, CloneOptions, EqualsOptions, Error, Integer, IntoLua, Lua, MetaMethod, Nil,
+    Result, Table, TableExt, UserData, UserDataMethods, Value, WeakMode,
+};
 
 #[test]
 fn test_set_get() -> Result<()> {
@@ -152,6 +157,70 @@ fn test_table_push_pop() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_table_extend_swap_remove() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua.create_sequence_from(vec![1, 2])?;
+    table.reserve(3, 0)?;
+    assert_eq!(table.raw_len(), 2);
+    table.extend(3..=5)?;
+    table.extend(Vec::<i64>::new())?;
+    assert_eq!(table.raw_len(), 5);
+    assert_eq!(
+        table
+            .clone()
+            .raw_sequence_values::<i64>()
+            .collect::<Result<Vec<_>>>()?,
+        vec![1, 2, 3, 4, 5]
+    );
+
+    // Reserved array slots are allocated in place, appending to them does not reallocate
+    let table2 = lua.create_table()?;
+    table2.raw_set(10, "ten")?;
+    table2.reserve(1000, 0)?;
+    assert_eq!(table2.raw_get::<_, String>(10)?, "ten");
+    assert_eq!(table2.clone().pairs::<Value, Value>().count(), 1);
+    table2.raw_set(10, Nil)?;
+    let used_memory = lua.used_memory();
+    table2.extend(1..=1000)?;
+    assert_eq!(lua.used_memory(), used_memory);
+
+    // Conversion errors keep the values appended before
+    struct Item(Integer);
+    impl<'lua> IntoLua<'lua> for Item {
+        fn into_lua(self, _: &'lua Lua) -> Result<Value<'lua>> {
+            match self.0 {
+                n if n < 0 => Err(Error::RuntimeError("bad".into())),
+                n => Ok(Value::Integer(n)),
+            }
+        }
+    }
+    let res = table.extend(vec![Item(6), Item(-1), Item(7)]);
+    assert!(matches!(res, Err(Error::RuntimeError(msg)) if msg == "bad"));
+    assert_eq!(table.raw_len(), 6);
+
+    assert_eq!(table.swap_remove::<i64>(2)?, 2);
+    assert_eq!(table.swap_remove::<i64>(5)?, 5);
+    assert_eq!(
+        table
+            .clone()
+            .raw_sequence_values::<i64>()
+            .collect::<Result<Vec<_>>>()?,
+        vec![1, 6, 3, 4]
+    );
+
+    match table.swap_remove::<Value>(5) {
+        Err(Error::RuntimeError(msg)) => {
+            assert_eq!(msg, "index 5 out of bounds (table length is 4)")
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert!(table.swap_remove::<Value>(0).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_table_clear() -> Result<()> {
     let lua = Lua::new();
@@ -175,7 +244,12 @@ fn test_table_clear() -> Result<()> {
     t.set("b", "2")?;
     t.clear()?;
     assert_eq!(t.len()?, 0);
-    assert_eq!(t.pairs::<Value, Value>().count(), 0);
+    assert_eq!(t.clone().pairs::<Value, Value>().count(), 0);
+
+    // Clearing must not leak stack slots
+    for _ in 0..1_100_000 {
+        t.clear()?;
+    }
 
     // Test table with metamethods
     let t2 = lua
@@ -199,6 +273,125 @@ fn test_table_clear() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_table_for_each() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua
+        .load("{1, 2, 3, [5] = 5, a = 10, b = 20}")
+        .eval::<Table>()?;
+
+    let mut sum = 0;
+    let mut count = 0;
+    table.for_each(|_: Value, v: i64| {
+        sum += v;
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!((sum, count), (41, 6));
+
+    // Error from the closure stops iteration
+    let mut count = 0;
+    let res = table.for_each(|_: Value, _: Value| {
+        count += 1;
+        Err(Error::RuntimeError("stop".into()))
+    });
+    assert!(matches!(res, Err(Error::RuntimeError(msg)) if msg == "stop"));
+    assert_eq!(count, 1);
+
+    // Conversion error is propagated
+    let res = table.for_each(|_: Table, _: i64| Ok(()));
+    assert!(matches!(res, Err(Error::FromLuaConversionError { .. })));
+
+    // Removing existing fields during traversal is allowed
+    table.for_each(|k: Value, _: Value| table.raw_set(k, Nil))?;
+    assert_eq!(table.clone().pairs::<Value, Value>().count(), 0);
+
+    // Sequence values, `__index` is not invoked
+    let table = lua
+        .load("setmetatable({1, 2, 3, [5] = 5}, {__index = function() return 0 end})")
+        .eval::<Table>()?;
+    let mut values = Vec::new();
+    table.for_each_value(|v: i64| {
+        values.push(v);
+        Ok(())
+    })?;
+    assert_eq!(values, vec![1, 2, 3]);
+
+    let res = table.for_each_value(|v: i64| match v {
+        2 => Err(Error::RuntimeError("stop".into())),
+        _ => Ok(()),
+    });
+    assert!(matches!(res, Err(Error::RuntimeError(msg)) if msg == "stop"));
+
+    Ok(())
+}
+
+#[test]
+fn test_table_raw_and_meta_pairs() -> Result<()> {
+    let lua = Lua::new();
+
+    // Metatable lies about the table contents
+    let table = lua
+        .load(
+            r#"
+            local fake = {x = 10, y = 20}
+            local function iter() return next, fake, nil end
+            return setmetatable({1, 2, a = 3}, {
+                __pairs = iter,
+                __iter = iter,
+                __index = function(_, k)
+                    if type(k) == "number" and k <= 4 then return k * 10 end
+                end,
+            })
+        "#,
+        )
+        .eval::<Table>()?;
+
+    let mut raw = table
+        .clone()
+        .pairs::<String, i64>()
+        .collect::<Result<Vec<_>>>()?;
+    raw.sort();
+    assert_eq!(raw, vec![("1".into(), 1), ("2".into(), 2), ("a".into(), 3)]);
+
+    let mut meta = table
+        .clone()
+        .pairs_metamethod::<String, i64>()
+        .collect::<Result<Vec<_>>>()?;
+    meta.sort();
+    #[cfg(any(
+        feature = "lua54",
+        feature = "lua53",
+        feature = "lua52",
+        feature = "luajit52",
+        feature = "luau"
+    ))]
+    assert_eq!(meta, vec![("x".into(), 10), ("y".into(), 20)]);
+    #[cfg(all(any(feature = "lua51", feature = "luajit"), not(feature = "luajit52")))]
+    assert_eq!(
+        meta,
+        vec![("1".into(), 1), ("2".into(), 2), ("a".into(), 3)]
+    );
+
+    // Errors are propagated and stop the iteration
+    let bad = lua
+        .load("setmetatable({}, {__pairs = function() error('boom') end, __iter = function() error('boom') end})")
+        .eval::<Table>()?;
+    let mut iter = bad.pairs_metamethod::<Value, Value>();
+    #[cfg(not(any(feature = "lua51", feature = "luajit")))]
+    assert!(matches!(iter.next(), Some(Err(_))));
+    assert!(iter.next().is_none());
+
+    // Sequence values: `raw_sequence_values` stops at the first raw nil
+    let raw = table.clone().raw_sequence_values::<i64>();
+    assert_eq!(raw.collect::<Result<Vec<_>>>()?, vec![1, 2]);
+    let meta = table.sequence_values::<i64>();
+    assert_eq!(meta.collect::<Result<Vec<_>>>()?, vec![1, 2, 30, 40]);
+
+    Ok(())
+}
+
 #[test]
 fn test_table_sequence_from() -> Result<()> {
     let lua = Lua::new();
@@ -392,3 +585,886 @@ fn test_table_call() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_table_deep_clone() -> Result<()> {
+    let lua = Lua::new();
+
+    let t = lua
+        .load(
+            r#"
+        local t = { a = { b = { c = 1 } }, list = { 1, 2, 3 }, f = print }
+        t.self = t
+        t.a.parent = t
+        return t
+    "#,
+        )
+        .eval::<Table>()?;
+
+    let copy = t.deep_clone()?;
+    assert_ne!(copy, t);
+    let copy_a: Table = copy.get("a")?;
+    assert_ne!(copy_a, t.get::<_, Table>("a")?);
+    assert_eq!(copy.get::<_, Table>("self")?, copy);
+    assert_eq!(copy_a.get::<_, Table>("parent")?, copy);
+    assert_eq!(copy.get::<_, Value>("f")?, t.get::<_, Value>("f")?);
+    assert_eq!(copy.get::<_, Vec<i32>>("list")?, vec![1, 2, 3]);
+
+    // Mutating the copy does not affect the original
+    copy_a.get::<_, Table>("b")?.set("c", 2)?;
+    lua.globals().set("t", t)?;
+    assert_eq!(lua.load("t.a.b.c").eval::<i32>()?, 1);
+
+    // Metatables
+    let t = lua
+        .load("setmetatable({ x = 1 }, { __index = { y = 2 } })")
+        .eval::<Table>()?;
+    let shared = t.deep_clone()?;
+    assert_eq!(shared.get_metatable(), t.get_metatable());
+    assert_eq!(shared.get::<_, i32>("y")?, 2);
+    let copied = t.deep_clone_with_options(CloneOptions::new().copy_metatables(true))?;
+    let mt = copied.get_metatable().unwrap();
+    assert_ne!(Some(mt.clone()), t.get_metatable());
+    assert_ne!(
+        mt.get::<_, Table>("__index")?,
+        t.get_metatable().unwrap().get::<_, Table>("__index")?
+    );
+    assert_eq!(copied.get::<_, i32>("y")?, 2);
+
+    // Depth limit
+    let t = lua.load("{ a = { b = { c = {} } } }").eval::<Table>()?;
+    assert!(t
+        .deep_clone_with_options(CloneOptions::new().max_depth(3))
+        .is_ok());
+    match t.deep_clone_with_options(CloneOptions::new().max_depth(2)) {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("maximum table depth (2)")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_table_deep_equals() -> Result<()> {
+    let lua = Lua::new();
+    let opts = EqualsOptions::new();
+
+    let load = |code: &str| lua.load(code).eval::<Table>();
+
+    // Nested tables
+    let a = load("{ 1, 2, { x = 'y', list = { true, false } }, key = 1.5 }")?;
+    let b = load("{ 1.0, 2, { x = 'y', list = { true, false } }, key = 1.5 }")?;
+    assert!(a.deep_equals(&b, &opts)?);
+    assert!(b.deep_equals(&a, &opts)?);
+    let c = load("{ 1, 2, { x = 'y', list = { true } }, key = 1.5 }")?;
+    assert!(!a.deep_equals(&c, &opts)?);
+    assert!(!c.deep_equals(&a, &opts)?);
+    let d = load("{ 1, 2, { x = 'y', list = { true, false } }, key = 1.5, extra = 0 }")?;
+    assert!(!a.deep_equals(&d, &opts)?);
+    assert!(!d.deep_equals(&a, &opts)?);
+
+    // NaN values are equal to each other
+    let a = load("{ x = 0/0 }")?;
+    let b = load("{ x = 0/0 }")?;
+    assert!(a.deep_equals(&b, &opts)?);
+    assert!(a.deep_equals(&a, &opts)?);
+
+    // Cycles
+    let a = load("local t = { n = 1, child = {} }; t.child.parent = t; t.self = t; return t")?;
+    let b = load("local t = { n = 1, child = {} }; t.child.parent = t; t.self = t; return t")?;
+    assert!(a.deep_equals(&b, &opts)?);
+    let c = load("local t = { n = 2, child = {} }; t.child.parent = t; t.self = t; return t")?;
+    assert!(!a.deep_equals(&c, &opts)?);
+
+    // Metatables
+    let a = load("setmetatable({ x = 1 }, { __index = { y = 1 } })")?;
+    let b = load("setmetatable({ x = 1 }, { __index = { y = 2 } })")?;
+    let c = load("{ x = 1 }")?;
+    assert!(a.deep_equals(&b, &opts)?);
+    assert!(a.deep_equals(&c, &opts)?);
+    let opts_mt = EqualsOptions::new().compare_metatables(true);
+    assert!(!a.deep_equals(&b, &opts_mt)?);
+    assert!(!a.deep_equals(&c, &opts_mt)?);
+    assert!(a.deep_equals(
+        &load("setmetatable({ x = 1 }, { __index = { y = 1 } })")?,
+        &opts_mt
+    )?);
+
+    // Userdata
+    struct Id(u32);
+    impl UserData for Id {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_method(MetaMethod::Eq, |_, this, other: AnyUserData| {
+                Ok(this.0 == other.borrow::<Id>()?.0)
+            });
+        }
+    }
+    let a = lua.create_table_from([("id", lua.create_userdata(Id(1))?)])?;
+    let b = lua.create_table_from([("id", lua.create_userdata(Id(1))?)])?;
+    assert!(a.deep_equals(&b, &opts)?);
+    assert!(!a.deep_equals(&b, &EqualsOptions::new().userdata_eq(false))?);
+
+    // Depth limit
+    let a = load("{ a = { b = { c = {} } } }")?;
+    let b = load("{ a = { b = { c = {} } } }")?;
+    assert!(a.deep_equals(&b, &EqualsOptions::new().max_depth(3))?);
+    match a.deep_equals(&b, &EqualsOptions::new().max_depth(2)) {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("maximum table depth (2)")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "unstable", not(feature = "send")))]
+#[test]
+fn test_owned_table() -> Result<()> {
+    use std::rc::Rc;
+
+    use mlua::OwnedTable;
+
+    struct Config {
+        settings: OwnedTable,
+    }
+
+    let lua = Lua::new();
+
+    let config = Rc::new(Config {
+        settings: lua.create_table()?.into_owned(),
+    });
+    config.settings.set("volume", 5)?;
+    config.settings.push("first")?;
+    lua.globals().set("settings", config.settings.clone())?;
+
+    let config2 = config.clone();
+    let bump = lua.create_function(move |_, ()| {
+        let volume: i32 = config2.settings.get("volume")?;
+        config2.settings.set("volume", volume + 1)?;
+        config2.settings.push("second")
+    })?;
+    lua.globals().set("bump", bump)?;
+
+    lua.load("bump()").exec()?;
+    lua.load(
+        r#"
+        assert(settings.volume == 6)
+        assert(#settings == 2 and settings[2] == "second")
+        settings.muted = true
+    "#,
+    )
+    .exec()?;
+
+    assert_eq!(config.settings.len()?, 2);
+    assert_eq!(config.settings.raw_len(), 2);
+    assert!(!config.settings.is_empty());
+    assert!(config.settings.contains_key("muted")?);
+    assert_eq!(config.settings.raw_get::<_, i32>("volume")?, 6);
+    config.settings.raw_set("volume", 0)?;
+    assert_eq!(config.settings.pop::<String>()?, "second");
+    // The iterator keeps its own handle to the table and yields owned values
+    let pairs = config.settings.clone().pairs::<String, bool>();
+    let mut keys = pairs
+        .map(|kv| kv.map(|(k, _)| k))
+        .collect::<Result<Vec<_>>>()?;
+    keys.sort();
+    assert_eq!(keys, vec!["1", "muted", "volume"]);
+    assert_eq!(lua.load("settings.volume").eval::<i32>()?, 0);
+
+    // Break the reference cycle between the callback and the Lua state
+    lua.globals().raw_remove("bump")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_table_macro() -> Result<()> {
+    let lua = Lua::new();
+
+    let handler = lua.create_function(|_, x: i32| Ok(x + 1))?;
+    let mut order = Vec::new();
+    let mut next = |name: &'static str| {
+        order.push(name);
+        name
+    };
+
+    let t = mlua::table!(&lua, {
+        name = next("name"),
+        pos = [1, 2, next("pos")],
+        [next("computed")] = { inner = true, [1 + 1] = next("inner") },
+        [10] = 1.5,
+        on_hit = handler.clone(),
+        empty = {},
+    })?;
+    assert_eq!(order, vec!["name", "pos", "computed", "inner"]);
+
+    let expected = lua.create_table()?;
+    expected.set("name", "name")?;
+    expected.set(
+        "pos",
+        lua.create_sequence_from([Value::Integer(1), Value::Integer(2)])?,
+    )?;
+    expected.get::<_, Table>("pos")?.push("pos")?;
+    let inner = lua.create_table()?;
+    inner.set("inner", true)?;
+    inner.set(2, "inner")?;
+    expected.set("computed", inner)?;
+    expected.set(10, 1.5)?;
+    expected.set("on_hit", handler)?;
+    expected.set("empty", lua.create_table()?)?;
+    assert!(t.deep_equals(&expected, &EqualsOptions::new())?);
+
+    let seq = mlua::table!(&lua, [1, "two", { three = 3 }, [4],])?;
+    assert_eq!(seq.raw_len(), 4);
+    assert_eq!(seq.get::<_, String>(2)?, "two");
+    assert_eq!(seq.get::<_, Table>(3)?.get::<_, i32>("three")?, 3);
+    assert_eq!(seq.get::<_, Table>(4)?.get::<_, i32>(1)?, 4);
+
+    // Errors short-circuit the evaluation
+    let mut evaluated = Vec::new();
+    let mut eval = |name: &'static str, fail: bool| -> Result<&'static str> {
+        evaluated.push(name);
+        if fail {
+            return Err(Error::RuntimeError(format!("{} failed", name)));
+        }
+        Ok(name)
+    };
+    let res = mlua::table!(&lua, {
+        a = eval("a", false)?,
+        b = { c = eval("c", true)? },
+        d = eval("d", false)?,
+    });
+    match res {
+        Err(Error::RuntimeError(msg)) => assert_eq!(msg, "c failed"),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert_eq!(evaluated, vec!["a", "c"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_concat() -> Result<()> {
+    let lua = Lua::new();
+
+    let t = lua.create_sequence_from((1..=10000).map(|i| format!("item{}", i)))?;
+    t.raw_set(5000, 5000)?;
+    t.raw_set(6000, 0.5)?;
+    let expected = lua
+        .load("return table.concat(...)")
+        .call::<_, mlua::String>((t.clone(), ", "))?;
+    assert_eq!(t.concat(", ", None)?, expected);
+    assert_eq!(t.concat("", Some((4999, 5001)))?, "item49995000item5001");
+    assert_eq!(t.concat("-", Some((3, 3)))?, "item3");
+    assert_eq!(t.concat("-", Some((3, 2)))?, "");
+
+    // Non-UTF8 data is preserved
+    let t =
+        lua.create_sequence_from([lua.create_string([0xff, 0xfe])?, lua.create_string([0x00])?])?;
+    assert_eq!(t.concat("|", None)?.as_bytes(), &[0xff, 0xfe, b'|', 0x00]);
+
+    // Invalid values
+    let t = lua.load("{ 'a', 'b', {}, 'd' }").eval::<Table>()?;
+    match t.concat(",", None) {
+        Err(Error::RuntimeError(msg)) => {
+            assert_eq!(msg, "invalid value (at index 3) in table for 'concat'")
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert_eq!(t.concat(",", Some((1, 2)))?, "a,b");
+    assert!(t.concat(",", Some((4, 5))).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_is_empty() -> Result<()> {
+    let lua = Lua::new();
+
+    let t = lua.create_table()?;
+    assert!(t.is_empty());
+    assert_eq!(t.raw_len(), 0);
+
+    // Array part only
+    let t = lua.create_sequence_from([1, 2, 3])?;
+    assert!(!t.is_empty());
+    assert_eq!(t.raw_len(), 3);
+
+    // Hash part only
+    let t = lua.create_table()?;
+    t.set("a", 1)?;
+    assert!(!t.is_empty());
+    assert_eq!(t.raw_len(), 0);
+    t.set("a", Nil)?;
+    assert!(t.is_empty());
+
+    // Only `__len` metamethod
+    let t: Table = lua
+        .load("setmetatable({}, { __len = function() return 5 end })")
+        .eval()?;
+    assert!(t.is_empty());
+    assert_eq!(t.raw_len(), 0);
+    #[cfg(any(
+        feature = "lua54",
+        feature = "lua53",
+        feature = "lua52",
+        feature = "luau"
+    ))]
+    assert_eq!(t.len()?, 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_count_entries() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua.create_table()?;
+    assert_eq!(table.count_entries()?, 0);
+
+    table.extend(1..=100)?;
+    for i in 0..50 {
+        table.raw_set(format!("key{i}"), i)?;
+    }
+    assert_eq!(table.raw_len(), 100);
+    assert_eq!(table.count_entries()?, 150);
+
+    let count = table.count_entries_where(|k, _| matches!(k, Value::String(_)))?;
+    assert_eq!(count, 50);
+    let count = table.count_entries_where(|_, v| matches!(v, Value::Integer(i) if i % 2 == 0))?;
+    assert_eq!(count, 75);
+
+    // Metamethods are not invoked
+    let proxy = lua
+        .load("setmetatable({a = 1}, {__index = table, __pairs = function() error('no') end})")
+        .eval::<Table>()?;
+    assert_eq!(proxy.count_entries()?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_path() -> Result<()> {
+    let lua = Lua::new();
+
+    let config: Table = lua
+        .load("{ window = { size = { width = 800 }, title = 'main' } }")
+        .eval()?;
+    assert_eq!(config.get_path::<u32>("window.size.width")?, 800);
+    assert_eq!(config.get_path::<Option<u32>>("window.size.height")?, None);
+    assert_eq!(config.get_path::<String>("window.title")?, "main");
+
+    match config.get_path::<u32>("window.title.len") {
+        Err(Error::RuntimeError(msg)) => {
+            assert_eq!(
+                msg,
+                "path 'window.title': 'title' is a string, expected table"
+            )
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    match config.get_path::<u32>("window.position.x") {
+        Err(Error::RuntimeError(msg)) => {
+            assert_eq!(
+                msg,
+                "path 'window.position': 'position' is nil, expected table"
+            )
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert!(config.get_path::<Value>("window..size").is_err());
+    assert!(config.get_path::<Value>("").is_err());
+
+    config.set_path("window.size.height", 600, false)?;
+    assert_eq!(config.get_path::<u32>("window.size.height")?, 600);
+    assert!(config.set_path("window.position.x", 10, false).is_err());
+    config.set_path("window.position.x", 10, true)?;
+    assert_eq!(config.get_path::<u32>("window.position.x")?, 10);
+    assert!(config.set_path("window.title.x", 10, true).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_get_or() -> Result<()> {
+    let lua = Lua::new();
+
+    let config: Table = lua
+        .load("{ retries = 3, timeout = 'soon', [1] = false, window = { width = 800 } }")
+        .eval()?;
+
+    // Missing value
+    assert_eq!(config.get_or("delay", 10)?, 10);
+    // Present value, `false` is not replaced by the default
+    assert_eq!(config.get_or("retries", 1)?, 3);
+    assert!(!config.get_or(1, true)?);
+    // Present value with the wrong type
+    match config.get_or("timeout", 30u32) {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert!(message.unwrap().starts_with("key 'timeout'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+    match config.get_or(1, 0) {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert!(message.unwrap().starts_with("key '[1]'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+
+    // Paths
+    assert_eq!(config.get_path_or("window.width", 640)?, 800);
+    assert_eq!(config.get_path_or("window.height", 480)?, 480);
+    assert_eq!(config.get_path_or("panel.height", 20)?, 20);
+    match config.get_path_or("window.width", Vec::<u32>::new()) {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert!(message.unwrap().starts_with("key 'window.width'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+    assert!(config.get_path_or("timeout.x", 0).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_get_with_depth() -> Result<()> {
+    let lua = Lua::new();
+
+    // obj -> proto1 -> proto2 -> proto3
+    let obj: Table = lua
+        .load(
+            r#"
+            local proto3 = { name = "base" }
+            local proto2 = setmetatable({}, { __index = proto3 })
+            local proto1 = setmetatable({ own = 1 }, { __index = proto2 })
+            return setmetatable({}, { __index = proto1 })
+        "#,
+        )
+        .eval()?;
+
+    assert_eq!(obj.get_with_depth::<_, String>("name", 3)?, "base");
+    match obj.get_with_depth::<_, String>("name", 2) {
+        Err(Error::MetaIndexDepthExceeded { max_depth: 2 }) => {}
+        r => panic!("expected MetaIndexDepthExceeded, got {:?}", r),
+    }
+    assert_eq!(obj.get_with_depth::<_, i32>("own", 1)?, 1);
+    assert!(obj.get_with_depth::<_, Option<i32>>("own", 0).is_err());
+    assert_eq!(obj.get_with_depth::<_, Option<i32>>("missing", 10)?, None);
+
+    // Function `__index` at the end of the chain
+    let obj: Table = lua
+        .load("setmetatable({}, { __index = function(_, k) return k .. '!' end })")
+        .eval()?;
+    assert_eq!(obj.get_with_depth::<_, String>("hi", 1)?, "hi!");
+    assert!(obj.get_with_depth::<_, String>("hi", 0).is_err());
+
+    // Looping chain
+    let obj: Table = lua
+        .load("local t = {}; t.__index = t; return setmetatable(t, t)")
+        .eval()?;
+    match obj.get_with_depth::<_, Value>("x", 1000) {
+        Err(Error::MetaIndexDepthExceeded { max_depth: 1000 }) => {}
+        r => panic!("expected MetaIndexDepthExceeded, got {:?}", r),
+    }
+    // Lua limits the chain length too
+    assert!(obj.get::<_, Value>("x").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_to_vec_hashmap() -> Result<()> {
+    let lua = Lua::new();
+
+    let t: Table = lua.load("{ 1, 2, 3, nil, 5, x = 'y' }").eval()?;
+    assert_eq!(t.to_vec::<i64>()?, vec![1, 2, 3]);
+    assert_eq!(lua.create_table()?.to_vec::<i64>()?, Vec::<i64>::new());
+
+    let t: Table = lua.load("{ a = 1, b = 2, [3] = 3 }").eval()?;
+    let map = t.to_hashmap::<String, i64>()?;
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["a"], 1);
+    assert_eq!(map["3"], 3);
+
+    // Conversion errors include the index/key
+    let t: Table = lua.load("{ 1, 2, 'three' }").eval()?;
+    match t.to_vec::<i64>() {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert!(message.unwrap().starts_with("key '[3]'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+    let t: Table = lua.load("{ a = 1, b = {} }").eval()?;
+    match t.to_hashmap::<String, i64>() {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert!(message.unwrap().starts_with("key 'b'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+    let t: Table = lua.load("{ a = 1, [true] = 2 }").eval()?;
+    match t.to_hashmap::<i64, i64>() {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            let message = message.unwrap();
+            assert!(message.starts_with("key 'a'") || message.starts_with("key '[<boolean>]'"))
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+
+    // `Vec` conversion still respects `__index` for tables with metatable
+    let t: Table = lua
+        .load("setmetatable({ 1 }, { __index = function(_, i) if i < 4 then return i end end })")
+        .eval()?;
+    assert_eq!(lua.unpack::<Vec<i64>>(Value::Table(t))?, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_pairs_from() -> Result<()> {
+    let lua = Lua::new();
+
+    let t = lua.create_table()?;
+    for i in 1..=10 {
+        t.set(format!("k{}", i), i)?;
+    }
+
+    // Iterate in two halves
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    for pair in t.clone().pairs_from::<String, i64>(None)?.take(5) {
+        let (key, value) = pair?;
+        seen.push(value);
+        cursor = Some(key);
+    }
+    assert_eq!(seen.len(), 5);
+    for pair in t.clone().pairs_from::<String, i64>(cursor)? {
+        seen.push(pair?.1);
+    }
+    seen.sort();
+    assert_eq!(seen, (1..=10).collect::<Vec<_>>());
+
+    // Unknown key
+    let mut pairs = t.pairs_from::<String, i64>(Some("missing".into()))?;
+    assert!(pairs.next().unwrap().is_err());
+    assert!(pairs.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_sequence_values_bytes() -> Result<()> {
+    let lua = Lua::new();
+
+    let t: Table = lua.load(r#"{ "a", "bc", "\0\255", nil, "d" }"#).eval()?;
+    let mut seen = Vec::new();
+    t.sequence_values_bytes(|i, bytes| {
+        seen.push((i, bytes.to_vec()));
+        Ok(())
+    })?;
+    assert_eq!(
+        seen,
+        vec![
+            (1, b"a".to_vec()),
+            (2, b"bc".to_vec()),
+            (3, b"\0\xff".to_vec())
+        ]
+    );
+
+    // Non-string values are rejected
+    let t: Table = lua.load(r#"{ "a", 2, "c" }"#).eval()?;
+    match t.sequence_values_bytes(|_, _| Ok(())) {
+        Err(Error::FromLuaConversionError { from, message, .. }) => {
+            assert_eq!(from, "integer");
+            assert!(message.unwrap().starts_with("key '[2]'"));
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+
+    // Closure errors stop the iteration
+    let mut count = 0;
+    let res = t.sequence_values_bytes(|_, _| {
+        count += 1;
+        Err(Error::RuntimeError("stop".into()))
+    });
+    assert!(matches!(res, Err(Error::RuntimeError(msg)) if msg == "stop"));
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_keys_values() -> Result<()> {
+    let lua = Lua::new();
+
+    // Values are functions and cannot be converted to `String`
+    let handlers: Table = lua
+        .load("{ on_start = function() end, on_stop = function() end }")
+        .eval()?;
+    let mut keys = handlers
+        .clone()
+        .keys::<String>()
+        .collect::<Result<Vec<_>>>()?;
+    keys.sort();
+    assert_eq!(keys, vec!["on_start", "on_stop"]);
+    assert!(handlers
+        .clone()
+        .pairs::<String, String>()
+        .any(|p| p.is_err()));
+
+    // Keys of mixed types are not converted
+    let t: Table = lua.load("{ 10, 20, x = 30, [true] = 40 }").eval()?;
+    let mut values = t.clone().values::<i64>().collect::<Result<Vec<_>>>()?;
+    values.sort();
+    assert_eq!(values, vec![10, 20, 30, 40]);
+
+    // Conversion errors are reported
+    assert!(t.keys::<i64>().any(|k| k.is_err()));
+    assert_eq!(lua.create_table()?.values::<Value>().count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_raw_get_multi() -> Result<()> {
+    let lua = Lua::new();
+
+    let pos: Table = lua
+        .load("setmetatable({ x = 1, y = 2, z = 3 }, { __index = function() return 0 end })")
+        .eval()?;
+    assert_eq!(pos.raw_get_multi::<i32, 3>(["x", "y", "z"])?, [1, 2, 3]);
+    assert_eq!(
+        pos.raw_get_multi::<Option<i32>, 2>(["z", "w"])?,
+        [Some(3), None]
+    );
+    assert_eq!(pos.raw_get_multi::<i32, 0>([])?, []);
+
+    match pos.raw_get_multi::<i32, 4>(["x", "y", "z", "w"]) {
+        Err(Error::FromLuaConversionError { from, message, .. }) => {
+            assert_eq!(from, "nil");
+            assert!(message.unwrap().starts_with("key 'w'"));
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_table_identity_hash() -> Result<()> {
+    let lua = Lua::new();
+
+    let t1 = lua.create_sequence_from([1, 2, 3])?;
+    let t2 = lua.create_sequence_from([1, 2, 3])?;
+    assert_eq!(t1.to_pointer(), t1.clone().to_pointer());
+    assert_ne!(t1.to_pointer(), t2.to_pointer());
+
+    // Tables with the same contents are different keys
+    #[allow(clippy::mutable_key_type)]
+    let mut cache = HashMap::new();
+    cache.insert(t1.clone(), "first");
+    cache.insert(t2.clone(), "second");
+    cache.insert(
+        lua.load("return ...").call::<_, Table>(t1.clone())?,
+        "updated",
+    );
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache[&t1], "updated");
+    assert_eq!(cache[&t2], "second");
+
+    Ok(())
+}
+
+#[test]
+fn test_table_replace_metatable() -> Result<()> {
+    let lua = Lua::new();
+
+    let t = lua.create_table()?;
+    let mt1 = lua.create_table()?;
+    let mt2 = lua.create_table()?;
+
+    // Swap and restore
+    assert_eq!(t.replace_metatable(Some(mt1.clone()))?, None);
+    assert_eq!(t.replace_metatable(Some(mt2.clone()))?, Some(mt1.clone()));
+    assert_eq!(t.get_metatable(), Some(mt2.clone()));
+    assert_eq!(t.replace_metatable(Some(mt1.clone()))?, Some(mt2));
+    assert_eq!(t.replace_metatable(None)?, Some(mt1.clone()));
+    assert_eq!(t.get_metatable(), None);
+
+    // Protected metatable
+    mt1.set("__metatable", "locked")?;
+    t.set_metatable(Some(mt1.clone()));
+    match t.replace_metatable(None) {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("protected")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert_eq!(t.get_metatable(), Some(mt1));
+
+    Ok(())
+}
+
+#[test]
+fn test_table_freeze() -> Result<()> {
+    let lua = Lua::new();
+
+    let t: Table = lua.load("{ a = 1, nested = { b = 2 }, 10, 20 }").eval()?;
+    assert!(!t.is_frozen());
+    t.freeze()?;
+    assert!(t.is_frozen());
+    lua.globals().set("t", t.clone())?;
+
+    // Existing entries are readable and iterable
+    assert_eq!(t.get::<_, i32>("a")?, 1);
+    assert_eq!(t.raw_get::<_, i32>("a")?, 1);
+    assert_eq!(lua.load("t.a + t[2]").eval::<i32>()?, 21);
+    assert_eq!(lua.load("#t").eval::<i32>()?, 2);
+    assert_eq!(t.raw_len(), 2);
+    let n = lua
+        .load("local n = 0; for _ in pairs(t) do n = n + 1 end; return n")
+        .eval::<i32>()?;
+    assert_eq!(n, 4);
+    assert_eq!(t.clone().pairs::<Value, Value>().count(), 4);
+    let sum = lua
+        .load("local sum = 0; for _, v in ipairs(t) do sum = sum + v end; return sum")
+        .eval::<i32>()?;
+    assert_eq!(sum, 30);
+
+    // Set from Lua
+    match lua.load("t.c = 3").exec() {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(msg.contains("attempt to modify a"), "{}", msg)
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert_eq!(t.get::<_, Option<i32>>("c")?, None);
+    // Existing keys are protected only in Luau
+    #[cfg(not(feature = "luau"))]
+    {
+        lua.load("t[1] = 0").exec()?;
+        assert_eq!(t.get::<_, i32>(1)?, 0);
+    }
+    #[cfg(feature = "luau")]
+    assert!(lua.load("t[1] = 0").exec().is_err());
+
+    // Set from Rust
+    match t.set("a", 2) {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(msg.contains("attempt to modify a"), "{}", msg)
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    assert!(t.push(30).is_err());
+    assert_eq!(t.get::<_, i32>("a")?, 1);
+
+    // Nested tables are not frozen
+    let nested: Table = t.get("nested")?;
+    assert!(!nested.is_frozen());
+    lua.load("t.nested.b = 3").exec()?;
+    assert_eq!(nested.get::<_, i32>("b")?, 3);
+
+    // Metatable is locked
+    #[cfg(not(feature = "luau"))]
+    assert!(lua.load("getmetatable(t) == false").eval::<bool>()?);
+    assert!(lua.load("setmetatable(t, {})").exec().is_err());
+
+    // Existing metatable is preserved
+    let (t, mt): (Table, Table) = lua
+        .load(
+            r#"
+            local mt = { __index = function(_, k) return k .. "!" end, __call = function() return 1 end }
+            return setmetatable({}, mt), mt
+        "#,
+        )
+        .eval()?;
+    t.freeze()?;
+    lua.globals().set("t2", t.clone())?;
+    lua.globals().set("mt", mt)?;
+    assert_eq!(t.get::<_, String>("x")?, "x!");
+    assert!(t.set("x", 1).is_err());
+    assert!(lua
+        .load("getmetatable(t2) == mt and t2() == 1")
+        .eval::<bool>()?);
+
+    // Freezing twice is a no-op
+    t.freeze()?;
+    assert!(t.is_frozen());
+
+    Ok(())
+}
+
+#[test]
+fn test_weak_table() -> Result<()> {
+    let lua = Lua::new();
+
+    assert_eq!(lua.create_table()?.weak_mode(), None);
+    for mode in [WeakMode::Keys, WeakMode::Values, WeakMode::Both] {
+        assert_eq!(lua.create_weak_table(mode)?.weak_mode(), Some(mode));
+    }
+    let t: Table = lua.load("setmetatable({}, { __mode = 'vk' })").eval()?;
+    assert_eq!(t.weak_mode(), Some(WeakMode::Both));
+
+    // Weak keys
+    let cache = lua.create_weak_table(WeakMode::Keys)?;
+    let key = lua.create_table()?;
+    let key2 = lua.create_table()?;
+    cache.raw_set(key.clone(), "value")?;
+    cache.raw_set(key2.clone(), "value2")?;
+    drop(key);
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert_eq!(cache.clone().pairs::<Value, Value>().count(), 1);
+    assert_eq!(cache.raw_get::<_, String>(key2)?, "value2");
+
+    // Weak values
+    let cache = lua.create_weak_table(WeakMode::Values)?;
+    cache.raw_set("a", lua.create_table()?)?;
+    cache.raw_set("b", "string values are not collected")?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert_eq!(cache.raw_get::<_, Value>("a")?, Nil);
+    assert_ne!(cache.raw_get::<_, Value>("b")?, Nil);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_sorted_pairs() -> Result<()> {
+    let lua = Lua::new();
+
+    let t: Table = lua.load("{ c = 3, a = 1, b = 2, aa = 0 }").eval()?;
+    let pairs = t.sorted_pairs::<String, i32>()?;
+    let keys = pairs.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>();
+    assert_eq!(keys, vec!["a", "aa", "b", "c"]);
+    assert_eq!(pairs[3].1, 3);
+
+    // Mixed integer and string keys
+    let t: Table = lua
+        .load("{ 'one', 'two', [10] = 'ten', [-1] = 'minus', [2.5] = 'half', b = 'b', a = 'a', ['10'] = 's10' }")
+        .eval()?;
+    let pairs = t.sorted_pairs_by::<String>(Value::sort_cmp)?;
+    let values = pairs.iter().map(|(_, v)| v.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec!["minus", "one", "two", "half", "ten", "s10", "a", "b"]
+    );
+
+    // Integers and floats are compared exactly
+    let big = Value::Integer(mlua::Integer::MAX);
+    let big_float = Value::Number(2f64.powi(63));
+    assert_eq!(big.sort_cmp(&big_float), std::cmp::Ordering::Less);
+    assert_eq!(big_float.sort_cmp(&big), std::cmp::Ordering::Greater);
+    let (two, half) = (Value::Integer(2), Value::Number(2.5));
+    assert_eq!(two.sort_cmp(&half), std::cmp::Ordering::Less);
+    assert_eq!(two.sort_cmp(&Value::Number(2.0)), std::cmp::Ordering::Equal);
+    assert_eq!(
+        Value::Integer(-3).sort_cmp(&Value::Number(-3.5)),
+        std::cmp::Ordering::Greater
+    );
+
+    // The table can be accessed while processing sorted pairs
+    for (key, _) in t.sorted_pairs_by::<Value>(Value::sort_cmp)? {
+        t.raw_set(key, Nil)?;
+    }
+    assert!(t.is_empty());
+
+    Ok(())
+}
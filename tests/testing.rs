@@ -0,0 +1,100 @@
+#![cfg(feature = "testing")]
+
+use mlua::testing::{check_lua_eq, make_lua, make_lua_with_bytecode, values_equal, Engine};
+use mlua::{assert_lua_eq, skip_if, Function, Lua, Result, Table, Value};
+
+#[test]
+fn test_engine() {
+    let engine = Engine::current();
+    assert!(engine.is_any_of(&["lua54", "lua53", "lua52", "lua51", "luajit", "luau"]));
+    assert!(engine.is_any_of(&[engine.feature_name()]));
+    assert!(!engine.is_any_of(&["unknown"]));
+
+    let lua = make_lua();
+    let version = lua.globals().get::<_, String>("_VERSION").unwrap();
+    match engine {
+        Engine::Lua54 => assert_eq!(version, "Lua 5.4"),
+        Engine::Lua53 => assert_eq!(version, "Lua 5.3"),
+        Engine::Lua52 => assert_eq!(version, "Lua 5.2"),
+        Engine::Lua51 | Engine::LuaJIT => assert_eq!(version, "Lua 5.1"),
+        Engine::Luau => assert!(version.starts_with("Luau")),
+    }
+}
+
+#[test]
+fn test_skip_if() -> Result<()> {
+    // Integer division operator is not available in Lua 5.1/5.2 and LuaJIT
+    skip_if!(lua51, lua52, luajit);
+
+    let lua = make_lua();
+    assert_lua_eq!(&lua, "7 // 2", 3);
+    Ok(())
+}
+
+#[test]
+fn test_skip_if_unit() {
+    skip_if!(lua54, lua53, lua52, lua51, luajit, luau);
+    unreachable!("all engines are skipped");
+}
+
+#[test]
+fn test_assert_lua_eq() -> Result<()> {
+    let lua = make_lua();
+
+    assert_lua_eq!(&lua, "1 + 2", 3);
+    assert_lua_eq!(&lua, "3 / 2", 1.5);
+    assert_lua_eq!(&lua, "2 ^ 2", 4);
+    assert_lua_eq!(&lua, "('ab'):rep(2)", "abab");
+    assert_lua_eq!(&lua, "nil", Value::Nil);
+    assert_lua_eq!(
+        &lua,
+        "{1, 2, {x = 'y'}}",
+        lua.load("{1, 2, {x = 'y'}}").eval::<Table>()?
+    );
+    assert_lua_eq!(&lua, "0/0", f64::NAN);
+
+    // Integer/float subtypes differ by engine, but compare equal
+    assert_lua_eq!(&lua, "math.floor(2.5)", 2.0);
+
+    let err = check_lua_eq(&lua, "{1, 2, a = 'b'}", lua.create_table()?).unwrap_err();
+    let engine = Engine::current();
+    assert!(err.starts_with(&format!(
+        "[{}] assertion `{{1, 2, a = 'b'}}` failed",
+        engine
+    )));
+    assert!(err.contains("actual: {1, 2, a = \"b\"}"), "{}", err);
+    assert!(err.contains("expected: {}"), "{}", err);
+
+    let err = check_lua_eq(&lua, "error('boom')", 1).unwrap_err();
+    assert!(err.contains("failed to evaluate"), "{}", err);
+
+    assert!(values_equal(&Value::Integer(1), &Value::Number(1.0))?);
+    assert!(!values_equal(&Value::Integer(1), &Value::Boolean(true))?);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "assertion `1 + 1` failed")]
+fn test_assert_lua_eq_panics() {
+    let lua = Lua::new();
+    assert_lua_eq!(&lua, "1 + 1", 3);
+}
+
+#[test]
+fn test_make_lua_with_bytecode() -> Result<()> {
+    let lua = make_lua_with_bytecode();
+
+    #[cfg(not(feature = "luau"))]
+    let bytecode = {
+        let func = lua.load("return ...").into_function()?;
+        func.dump(false)
+    };
+    #[cfg(feature = "luau")]
+    let bytecode = mlua::Compiler::new().compile("return ...");
+
+    let func: Function = lua.load(&bytecode).into_function()?;
+    assert_eq!(func.call::<_, i32>(5)?, 5);
+
+    Ok(())
+}
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::string::String as StdString;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::{error, f32, f64, fmt};
+use std::{env, error, f32, f64, fmt, process};
 
 use mlua::{
-    ChunkMode, Error, ExternalError, Function, Lua, LuaOptions, Nil, Result, StdLib, String, Table,
-    UserData, Value, Variadic,
+    ArithOp, ChunkMode, Defaulted, Error, ExternalError, Function, HostInfo, Integer, Lua,
+    LuaOptions, Nil, Number, Result, StdLib, String, Table, UserData, UserDataMethods,
+    ValidatedHandle, Value, Variadic,
 };
 
 #[cfg(not(feature = "luau"))]
@@ -115,6 +116,38 @@ fn test_exec() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_globals() -> Result<()> {
+    let lua = Lua::new();
+
+    let globals = lua.globals();
+    assert_eq!(globals.to_pointer(), lua.globals().to_pointer());
+    globals.set("x", 1)?;
+    assert_eq!(lua.globals().get::<_, i32>("x")?, 1);
+
+    let new_globals = lua.create_table()?;
+    new_globals.set("print", globals.get::<_, Value>("print")?)?;
+    new_globals.set("x", 2)?;
+    lua.set_globals(new_globals.clone())?;
+
+    assert_eq!(lua.globals().to_pointer(), new_globals.to_pointer());
+    assert_eq!(lua.globals().to_pointer(), lua.globals().to_pointer());
+    assert_eq!(lua.load("x").eval::<i32>()?, 2);
+    lua.load("y = x + 1").exec()?;
+    assert_eq!(new_globals.get::<_, i32>("y")?, 3);
+    assert_eq!(globals.get::<_, Option<i32>>("y")?, None);
+
+    // Callbacks see the new globals too
+    let f = lua.create_function(|lua, ()| lua.globals().get::<_, i32>("y"))?;
+    assert_eq!(f.call::<_, i32>(())?, 3);
+
+    // Restore the original globals
+    lua.set_globals(globals)?;
+    assert_eq!(lua.load("x").eval::<i32>()?, 1);
+
+    Ok(())
+}
+
 #[test]
 fn test_eval() -> Result<()> {
     let lua = Lua::new();
@@ -136,6 +169,58 @@ fn test_eval() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_eval_tuple_defaults() -> Result<()> {
+    let lua = Lua::new();
+
+    lua.load(
+        r#"
+        function f1() return 1 end
+        function f2() return 1, "two" end
+        function f3() return 1, "two", {3} end
+    "#,
+    )
+    .exec()?;
+
+    type WithOption<'lua> = (i32, Option<StdString>, Option<Table<'lua>>);
+    let (a, b, c) = lua.load("return f1()").eval::<WithOption>()?;
+    assert_eq!((a, b), (1, None));
+    assert!(c.is_none());
+    let (_, b, c) = lua.load("return f2()").eval::<WithOption>()?;
+    assert_eq!(b.as_deref(), Some("two"));
+    assert!(c.is_none());
+    let (_, _, c) = lua.load("return f3()").eval::<WithOption>()?;
+    assert_eq!(c.unwrap().raw_get::<_, i32>(1)?, 3);
+
+    type WithDefault = (i32, Defaulted<StdString>, Defaulted<Vec<i32>>);
+    let (a, Defaulted(b), Defaulted(c)) = lua.load("return f1()").eval::<WithDefault>()?;
+    assert_eq!((a, b.as_str(), c), (1, "", vec![]));
+    let (_, Defaulted(b), Defaulted(c)) = lua.load("return f2()").eval::<WithDefault>()?;
+    assert_eq!((b.as_str(), c), ("two", vec![]));
+    let (_, Defaulted(b), Defaulted(c)) = lua.load("return f3()").eval::<WithDefault>()?;
+    assert_eq!((b.as_str(), c), ("two", vec![3]));
+
+    // Missing values are reported with their position
+    match lua.load("return f2()").eval::<(i32, StdString, Table)>() {
+        Err(Error::FromLuaConversionError { to, message, .. }) => {
+            assert_eq!(to, "table");
+            assert_eq!(message.unwrap(), "missing value #3 (expected table)");
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+    match lua
+        .load("return f1()")
+        .eval::<(i32, StdString, Option<Table>)>()
+    {
+        Err(Error::FromLuaConversionError { message, .. }) => {
+            assert_eq!(message.unwrap(), "missing value #2 (expected String)");
+        }
+        r => panic!("expected FromLuaConversionError, got {:?}", r),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_load_mode() -> Result<()> {
     let lua = unsafe { Lua::unsafe_new() };
@@ -365,6 +450,114 @@ fn test_error() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_syntax_error_info() -> Result<()> {
+    let lua = Lua::new();
+
+    let err = lua
+        .load("local x = = 1")
+        .set_name("bad")
+        .exec()
+        .unwrap_err();
+    let info = err.syntax_error_info().expect("expected syntax error info");
+    assert_eq!(info.chunk_name, "bad");
+    assert_eq!(info.line, 1);
+    assert_eq!(info.column, None);
+    assert_eq!(info.token.as_deref(), Some("="));
+    assert!(!info.message.contains("bad"));
+    // The original message is kept intact
+    match err {
+        Error::SyntaxError { ref message, .. } => assert!(message.contains("bad")),
+        _ => unreachable!(),
+    }
+
+    let chunk = r#"
+        local a = 1
+        local b = 2
+        local function f()
+            return a + b
+        end
+        x = )
+        return f()
+    "#;
+    let err = lua.load(chunk).exec().unwrap_err();
+    let info = err.syntax_error_info().expect("expected syntax error info");
+    assert!(info.chunk_name.starts_with("tests/tests.rs:"));
+    assert_eq!(info.line, 7);
+    assert_eq!(info.token.as_deref(), Some(")"));
+
+    let err = lua.load("if true then").exec().unwrap_err();
+    let info = err.syntax_error_info().expect("expected syntax error info");
+    assert_eq!(info.line, 1);
+    assert_eq!(info.token.as_deref(), Some("<eof>"));
+
+    assert_eq!(Error::RuntimeError("x".into()).syntax_error_info(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_script_error_source() -> Result<()> {
+    let lua = Lua::new();
+
+    let chunk = r#"
+        local function raise(level)
+            error("boom", level)
+        end
+        raise(...)
+    "#;
+    let func = lua.load(chunk).set_name("script").into_function()?;
+
+    // Level 0: no location
+    let err = func.call::<_, ()>(0).unwrap_err();
+    assert_eq!(err.script_source(), None);
+    assert_eq!(err.script_message(), Some("boom"));
+    let expected = "runtime error: boom\nstack traceback:";
+    assert!(err.to_string().starts_with(expected));
+
+    // Level 1: position of the `error` call
+    let err = func.call::<_, ()>(1).unwrap_err();
+    assert_eq!(err.script_source(), Some(("script", 3)));
+    assert_eq!(err.script_message(), Some("boom"));
+    let expected = "runtime error: [string \"script\"]:3: boom\nstack traceback:";
+    assert!(err.to_string().starts_with(expected));
+
+    // Level 2: position of the `raise` call
+    let err = func.call::<_, ()>(2).unwrap_err();
+    assert_eq!(err.script_source(), Some(("script", 5)));
+    assert_eq!(err.script_message(), Some("boom"));
+    let expected = "runtime error: [string \"script\"]:5: boom\nstack traceback:";
+    assert!(err.to_string().starts_with(expected));
+
+    // Errors raised by the VM
+    let err = lua
+        .load("local t = nil; return t.x")
+        .set_name("=vm")
+        .exec()
+        .unwrap_err();
+    assert_eq!(err.script_source(), Some(("vm", 1)));
+
+    // Message that only looks like it has a location prefix
+    let err = lua.load(r#"error("a:1: b", 0)"#).exec().unwrap_err();
+    assert_eq!(err.script_source(), None);
+    assert_eq!(err.script_message(), Some("a:1: b"));
+    let err = lua
+        .load(r#"error("a:1: b")"#)
+        .set_name("=a:1: b")
+        .exec()
+        .unwrap_err();
+    assert_eq!(err.script_source(), Some(("a:1: b", 1)));
+    assert_eq!(err.script_message(), Some("a:1: b"));
+
+    // Non-string errors and other error kinds have no location
+    assert_eq!(Error::RuntimeError("x".into()).script_source(), None);
+    assert_eq!(Error::RuntimeError("x".into()).script_message(), Some("x"));
+    assert_eq!(Error::StackError.script_source(), None);
+    assert_eq!(Error::StackError.script_message(), None);
+
+    Ok(())
+}
+
 #[test]
 fn test_panic() -> Result<()> {
     fn make_lua(options: LuaOptions) -> Result<Lua> {
@@ -499,6 +692,94 @@ fn test_panic() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_panic_in_method() -> Result<()> {
+    struct Bomb;
+
+    impl UserData for Bomb {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("explode", |_, _, ()| -> Result<()> { panic!("boom") });
+        }
+    }
+
+    for catch_rust_panics in [true, false] {
+        let lua = Lua::new_with(
+            StdLib::ALL_SAFE,
+            LuaOptions::new().catch_rust_panics(catch_rust_panics),
+        )?;
+        lua.globals().set("bomb", Bomb)?;
+
+        let explode = lua
+            .load("function(...) local a, b = 1, 2; return a, b, bomb:explode(...) end")
+            .eval::<Function>()?;
+        for _ in 0..100 {
+            match catch_unwind(AssertUnwindSafe(|| explode.call::<_, Value>((1, 2, 3)))) {
+                Ok(r) => panic!("no panic was detected: {:?}", r),
+                Err(p) => assert_eq!(*p.downcast::<&str>().unwrap(), "boom"),
+            }
+            // The state must be still usable
+            assert_eq!(lua.load("return 1 + 2").eval::<i32>()?, 3);
+        }
+
+        let pcall_explode = lua.load(
+            r#"
+            local ok, err = pcall(bomb.explode, bomb)
+            assert(not ok)
+            return tostring(err)
+        "#,
+        );
+        if catch_rust_panics {
+            let err = pcall_explode.eval::<StdString>()?;
+            assert!(err.contains("boom"), "unexpected error: {err}");
+        } else {
+            match catch_unwind(AssertUnwindSafe(|| pcall_explode.eval::<StdString>())) {
+                Ok(r) => panic!("no panic was detected: {:?}", r),
+                Err(p) => assert_eq!(*p.downcast::<&str>().unwrap(), "boom"),
+            }
+        }
+        assert_eq!(lua.load("return 1 + 2").eval::<i32>()?, 3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_panic_in_destructor_aborts() {
+    struct Bomb;
+
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            panic!("boom");
+        }
+    }
+
+    impl UserData for Bomb {}
+
+    // Panics cannot be propagated out of the Lua garbage collector, so the process must abort.
+    // Run the panicking part in a subprocess to check that.
+    if env::var_os("MLUA_TEST_PANIC_IN_DESTRUCTOR").is_some() {
+        let lua = Lua::new();
+        lua.create_userdata(Bomb).unwrap();
+        lua.gc_collect().unwrap();
+        lua.gc_collect().unwrap();
+        return;
+    }
+
+    let status = process::Command::new(env::current_exe().unwrap())
+        .args(["test_panic_in_destructor_aborts", "--exact", "--nocapture"])
+        .env("MLUA_TEST_PANIC_IN_DESTRUCTOR", "1")
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(6)); // SIGABRT
+    }
+}
+
 #[test]
 fn test_result_conversions() -> Result<()> {
     let lua = Lua::new();
@@ -592,6 +873,56 @@ fn test_num_conversion() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_numeric_model() -> Result<()> {
+    let lua = Lua::new();
+
+    let model = lua.numeric_model();
+    assert_eq!(model.float_width, 64);
+    assert!(model.floor_division);
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    assert_eq!(model.integer_width, Some(64));
+    #[cfg(any(feature = "lua52", feature = "lua51", feature = "luau"))]
+    assert_eq!(model.integer_width, None);
+    #[cfg(all(feature = "luajit", target_arch = "x86_64"))]
+    assert_eq!(model.integer_width, None);
+
+    // Integral floats and integers are the same table keys
+    let table = lua.create_table()?;
+    table.set(3i64, "three")?;
+    assert_eq!(table.get::<_, StdString>(3.0f64)?, "three");
+    assert!(matches!(
+        Value::Number(3.0).normalize_for_engine(),
+        Value::Integer(3)
+    ));
+    assert!(matches!(
+        Value::Number(3.5).normalize_for_engine(),
+        Value::Number(n) if n == 3.5
+    ));
+
+    // 2^53 + 1 cannot be represented as a float
+    let big = (1i64 << 53) + 1;
+    let table = lua.create_table()?;
+    table.set(big, "integer")?;
+    table.set((1i64 << 53) as f64, "float")?;
+    let count = table.clone().pairs::<Value, Value>().count();
+    let normalized = lua.pack(big)?.normalize_for_engine();
+    if model.has_integer_subtype() {
+        assert_eq!(count, 2);
+        assert_eq!(table.get::<_, StdString>(big)?, "integer");
+        assert!(matches!(normalized, Value::Integer(_)));
+        assert_eq!(lua.unpack::<i64>(normalized)?, big);
+    } else {
+        assert_eq!(count, 1);
+        assert_eq!(table.get::<_, StdString>(big)?, "float");
+        assert_eq!(normalized, Value::Number((1i64 << 53) as f64));
+        let key = table.pairs::<Value, Value>().next().unwrap()?.0;
+        assert_eq!(key.normalize_for_engine(), normalized);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_pcall_xpcall() -> Result<()> {
     let lua = Lua::new();
@@ -880,6 +1211,68 @@ fn test_registry_value_reuse() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_registry_values_batch() -> Result<()> {
+    let lua = Lua::new();
+    lua.expire_registry_values();
+
+    let keys = lua.create_registry_values((0..10_000).map(|i| format!("value{i}")))?;
+    assert_eq!(keys.len(), 10_000);
+
+    // Resolve a (pseudo) random sample
+    let mut seed = 12345u32;
+    for _ in 0..100 {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        let i = (seed >> 8) as usize % keys.len();
+        assert_eq!(
+            lua.registry_value::<StdString>(&keys[i])?,
+            format!("value{i}")
+        );
+    }
+
+    // Keys are bound to the Lua instance as usual
+    assert!(lua.owns_registry_value(&keys[0]));
+    match Lua::new().registry_value::<StdString>(&keys[0]) {
+        Err(Error::MismatchedRegistryKey) => {}
+        r => panic!("expected MismatchedRegistryKey, got {:?}", r),
+    }
+
+    // Released slots are reused by the next batch, even without a size hint
+    let slots = keys
+        .iter()
+        .map(|key| format!("{key:?}"))
+        .collect::<HashSet<_>>();
+    drop(keys);
+    lua.expire_registry_values();
+    let values = (0..10_000).filter(|_| true).map(|i| format!("value{i}"));
+    let keys = lua.create_registry_values(values)?;
+    assert_eq!(lua.registry_value::<StdString>(&keys[9_999])?, "value9999");
+    let slots2 = keys
+        .iter()
+        .map(|key| format!("{key:?}"))
+        .collect::<HashSet<_>>();
+    assert_eq!(slots, slots2);
+    drop(keys);
+    lua.expire_registry_values();
+
+    // Nil values do not use registry slots
+    let keys = lua.create_registry_values(vec![Value::Nil, Value::Boolean(true)])?;
+    assert_eq!(lua.registry_value::<Value>(&keys[0])?, Value::Nil);
+    assert!(lua.registry_value::<bool>(&keys[1])?);
+    assert!(slots.contains(&format!("{:?}", keys[1])));
+    assert!(!slots.contains(&format!("{:?}", keys[0])));
+    drop(keys);
+
+    // Reserved slots are used by individually created keys
+    lua.reserve_registry_slots(10)?;
+    let key = lua.create_registry_value("value")?;
+    assert_eq!(lua.registry_value::<StdString>(&key)?, "value");
+    drop(key);
+    lua.expire_registry_values();
+
+    Ok(())
+}
+
 #[test]
 fn test_application_data() -> Result<()> {
     let lua = Lua::new();
@@ -1154,6 +1547,56 @@ fn test_jit_version() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg(all(feature = "luajit", target_os = "linux"))]
+fn test_jit_ffi_policy() -> Result<()> {
+    let lua = unsafe { Lua::unsafe_new_with(StdLib::ALL_SAFE | StdLib::FFI, LuaOptions::new()) };
+    lua.configure_jit_ffi(
+        mlua::FfiPolicy::new()
+            .allow_library("libm.so.6")
+            .symbol_filter(|name| name == "abs"),
+    )?;
+
+    lua.load("ffi.cdef[[ int abs(int); int getpid(void); double floor(double); ]]")
+        .exec()?;
+
+    // Allowed library and symbol
+    let floor = lua
+        .load("return ffi.load('libm.so.6').floor(2.5)")
+        .eval::<f64>()?;
+    assert_eq!(floor, 2.0);
+    assert_eq!(lua.load("ffi.C.abs(-3)").eval::<i32>()?, 3);
+
+    // Disallowed library and symbol
+    match lua.load("ffi.load('libz.so.1')").exec() {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(
+                msg.contains("ffi library 'libz.so.1' is not allowed"),
+                "{}",
+                msg
+            )
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+    match lua.load("require('ffi').C.getpid()").exec() {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(
+                msg.contains("ffi symbol 'getpid' is not allowed"),
+                "{}",
+                msg
+            )
+        }
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    // The policy requires the `ffi` library
+    assert!(Lua::new()
+        .configure_jit_ffi(mlua::FfiPolicy::new())
+        .is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_load_from_function() -> Result<()> {
     let lua = Lua::new();
@@ -1244,6 +1687,290 @@ fn test_multi_states() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_error_translator() -> Result<()> {
+    let lua = Lua::new();
+
+    lua.set_error_translator(|err| match err {
+        Error::CallbackError { cause, .. } => match cause.as_ref() {
+            Error::RuntimeError(msg) if msg == "item not found" => Some("objet introuvable".into()),
+            _ => None,
+        },
+        _ => None,
+    });
+
+    let find = lua.create_function(|_, name: StdString| -> Result<()> {
+        match name.as_str() {
+            "missing" => Err(Error::RuntimeError("item not found".into())),
+            _ => Err(Error::RuntimeError("other error".into())),
+        }
+    })?;
+    lua.globals().set("find", find)?;
+
+    // Scripts see translated message
+    lua.load(
+        r#"
+        local ok, err = pcall(find, "missing")
+        assert(not ok)
+        assert(tostring(err) == "objet introuvable", tostring(err))
+
+        -- Translator returns `None`, default formatting applies
+        local ok, err = pcall(find, "other")
+        assert(not ok)
+        assert(tostring(err):find("other error") ~= nil, tostring(err))
+    "#,
+    )
+    .exec()?;
+
+    // Rust side keeps the original error
+    match lua.load(r#"find("missing")"#).exec() {
+        Err(Error::CallbackError { cause, .. }) => match cause.as_ref() {
+            Error::RuntimeError(msg) => assert_eq!(msg, "item not found"),
+            err => panic!("expected RuntimeError, got {:?}", err),
+        },
+        r => panic!("expected CallbackError, got {:?}", r),
+    }
+
+    lua.remove_error_translator();
+    lua.load(
+        r#"
+        local ok, err = pcall(find, "missing")
+        assert(tostring(err):find("item not found") ~= nil, tostring(err))
+    "#,
+    )
+    .exec()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_registry() -> Result<()> {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Entity(u32);
+
+    let lua = Lua::new();
+    let entities = lua.create_handle_registry::<Entity>();
+    let entity_id = lua.create_function(|_, entity: ValidatedHandle<Entity>| Ok(entity.0))?;
+    lua.globals().set("entity_id", entity_id)?;
+
+    let handle = entities.register(Entity(7));
+    lua.globals().set("h", handle)?;
+    assert_eq!(lua.load("type(h)").eval::<StdString>()?, "userdata");
+    assert_eq!(lua.load("entity_id(h)").eval::<u32>()?, 7);
+    assert_eq!(entities.resolve(lua.globals().get("h")?)?, Entity(7));
+
+    // The same registry is returned for the same type
+    assert_eq!(lua.create_handle_registry::<Entity>().len(), 1);
+
+    assert_eq!(entities.invalidate(handle), Some(Entity(7)));
+    assert_eq!(entities.invalidate(handle), None);
+    match lua.load("entity_id(h)").exec() {
+        Err(Error::CallbackError { ref cause, .. }) => match cause.as_ref() {
+            Error::StaleHandle => {}
+            err => panic!("expected StaleHandle, got {err:?}"),
+        },
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+
+    // Slot is reused with a new generation
+    let handle2 = entities.register(Entity(8));
+    assert_eq!(handle2.index(), handle.index());
+    assert!(!entities.contains(handle));
+    assert!(matches!(entities.resolve(handle), Err(Error::StaleHandle)));
+    assert_eq!(entities.resolve(handle2)?, Entity(8));
+
+    entities.clear();
+    assert!(entities.is_empty());
+    assert!(matches!(entities.resolve(handle2), Err(Error::StaleHandle)));
+
+    Ok(())
+}
+
+#[test]
+fn test_host_info() -> Result<()> {
+    let lua = Lua::new();
+    assert!(lua.host_info().is_none());
+
+    let info = HostInfo::new("test-host", "0.1.0")
+        .sandbox_profile("strict")
+        .extra("http", true)
+        .extra("max_players", 16);
+    lua.install_host_info(info.clone())?;
+    assert_eq!(lua.host_info(), Some(info.clone()));
+
+    lua.globals().set("engine", info.engine())?;
+    lua.load(
+        r#"
+        assert(_HOST.name == "test-host")
+        assert(_HOST.version == "0.1.0")
+        assert(_HOST.engine == engine)
+        assert(type(_HOST.engine_version) == "string")
+        assert(type(_HOST.mlua_version) == "string")
+        assert(type(_HOST.features) == "table")
+        assert(_HOST.sandbox == "strict")
+        assert(_HOST.extra.http == true)
+        assert(_HOST.extra.max_players == 16)
+        assert(_HOST.extra.missing == nil)
+    "#,
+    )
+    .exec()?;
+
+    // The table is read-only
+    let host: Table = lua.globals().get("_HOST")?;
+    assert!(host.is_frozen());
+    assert!(host.get::<_, Table>("extra")?.is_frozen());
+    assert!(host.set("name", "other").is_err());
+    assert!(lua.load("_HOST.name = 'other'").exec().is_err());
+    assert!(lua.load("_HOST.extra.http = false").exec().is_err());
+    assert!(lua.load("_HOST.extra.new_flag = true").exec().is_err());
+    lua.load(r#"assert(_HOST.name == "test-host" and _HOST.extra.http == true)"#)
+        .exec()?;
+    #[cfg(any(
+        feature = "lua54",
+        feature = "lua53",
+        feature = "lua52",
+        feature = "luau"
+    ))]
+    lua.load("local n = 0; for _ in pairs(_HOST.extra) do n = n + 1 end; assert(n == 2)")
+        .exec()?;
+
+    // Custom global name
+    lua.install_host_info(HostInfo::new("test-host", "0.2.0").global_name("host"))?;
+    assert_eq!(lua.load("host.version").eval::<StdString>()?, "0.2.0");
+
+    Ok(())
+}
+
+#[test]
+fn test_string_builder() -> Result<()> {
+    let lua = Lua::new();
+    lua.install_string_builder()?;
+
+    lua.load(
+        r#"
+        local buf = strbuf.new(16)
+        for i = 1, 64 * 1024 do
+            buf:put("0123456789abcdef")
+        end
+        assert(#buf == 1024 * 1024)
+        local s = buf:tostring()
+        assert(#s == 1024 * 1024)
+        assert(s:sub(1, 20) == "0123456789abcdef0123")
+        assert(s:sub(-4) == "cdef")
+
+        assert(buf:reset() == buf)
+        assert(#buf == 0)
+        buf:put("a", 1, 2.5):putf("%d-%s", 42, "x")
+        assert(tostring(buf) == "a12.542-x")
+        assert(buf .. "!" == "a12.542-x!")
+        assert("<" .. buf == "<a12.542-x")
+
+        local other = strbuf.new():put("[", buf, "]")
+        assert(other:tostring() == "[a12.542-x]")
+    "#,
+    )
+    .exec()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_arithmetic() -> Result<()> {
+    use mlua::math::{fdiv, fmod, idiv, imod};
+
+    let lua = Lua::new();
+    let same = |a: Number, b: Number| a == b || (a.is_nan() && b.is_nan());
+
+    #[allow(unused_mut)]
+    let mut ints: Vec<Integer> = vec![7, -7, 3, -3, 1, -1, 0];
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    ints.push(Integer::MIN);
+    for &a in &ints {
+        for &b in &ints {
+            let vm_mod = lua.arithmetic(ArithOp::Mod, Value::Integer(a), Value::Integer(b));
+            match imod(a, b) {
+                Ok(m) => assert_eq!(lua.unpack::<Number>(vm_mod?)?, m as Number, "{a} % {b}"),
+                #[cfg(any(feature = "lua54", feature = "lua53"))]
+                Err(err) => assert!(vm_mod.unwrap_err().to_string().contains(&err.to_string())),
+                #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+                Err(_) => assert!(lua.unpack::<Number>(vm_mod?)?.is_nan(), "{a} % {b}"),
+            }
+
+            #[cfg(any(feature = "lua54", feature = "lua53"))]
+            {
+                let vm_idiv = lua.arithmetic(ArithOp::IDiv, Value::Integer(a), Value::Integer(b));
+                match idiv(a, b) {
+                    Ok(q) => assert_eq!(vm_idiv?, Value::Integer(q), "{a} // {b}"),
+                    Err(err) => {
+                        assert!(vm_idiv.unwrap_err().to_string().contains(&err.to_string()))
+                    }
+                }
+            }
+            // Without integer subtype floor division is performed on floats
+            #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+            {
+                let vm_div = lua.arithmetic(ArithOp::Div, Value::Integer(a), Value::Integer(b))?;
+                let vm_idiv = lua.unpack::<Number>(vm_div)?.floor();
+                match idiv(a, b) {
+                    Ok(q) => assert_eq!(vm_idiv, q as Number, "{a} // {b}"),
+                    Err(_) => assert!(!vm_idiv.is_finite(), "{a} // {b}"),
+                }
+            }
+        }
+    }
+
+    let floats: &[Number] = &[5.5, -5.5, 2.0, -2.0, 0.0, Number::INFINITY, Number::NAN];
+    for &a in floats {
+        for &b in floats {
+            let vm_mod = lua.arithmetic(ArithOp::Mod, Value::Number(a), Value::Number(b))?;
+            let vm_mod = lua.unpack::<Number>(vm_mod)?;
+            assert!(same(fmod(a, b), vm_mod), "{a} % {b}");
+            if ArithOp::IDiv.is_supported() {
+                let vm_div = lua.arithmetic(ArithOp::IDiv, Value::Number(a), Value::Number(b))?;
+                assert!(same(fdiv(a, b), lua.unpack(vm_div)?), "{a} // {b}");
+            }
+        }
+        // Mixed integer and float operands
+        for &i in &ints {
+            let vm_mod = lua.arithmetic(ArithOp::Mod, Value::Integer(i), Value::Number(a))?;
+            assert!(same(fmod(i as Number, a), lua.unpack(vm_mod)?), "{i} % {a}");
+        }
+    }
+
+    // Operations honor metamethods
+    let t = lua
+        .load(
+            r#"
+            setmetatable({}, {
+                __add = function(a, b) return "added" end,
+                __unm = function(a) return "neg" end,
+            })
+        "#,
+        )
+        .eval::<Table>()?;
+    let r = lua.arithmetic(ArithOp::Add, Value::Table(t.clone()), Value::Integer(1))?;
+    assert_eq!(lua.unpack::<StdString>(r)?, "added");
+    let r = lua.arithmetic(ArithOp::Unm, Value::Table(t), Nil)?;
+    assert_eq!(lua.unpack::<StdString>(r)?, "neg");
+    let r = lua.arithmetic(ArithOp::Unm, Value::Integer(5), Nil)?;
+    assert_eq!(lua.unpack::<Integer>(r)?, -5);
+    assert!(lua
+        .arithmetic(ArithOp::Add, Value::Boolean(true), Value::Integer(1))
+        .is_err());
+
+    assert_eq!(
+        ArithOp::BAnd.is_supported(),
+        cfg!(any(feature = "lua54", feature = "lua53"))
+    );
+    if !ArithOp::BAnd.is_supported() {
+        assert!(lua
+            .arithmetic(ArithOp::BAnd, Value::Integer(1), Value::Integer(1))
+            .is_err());
+    }
+
+    Ok(())
+}
+
 #[test]
 #[cfg(feature = "lua54")]
 fn test_warnings() -> Result<()> {
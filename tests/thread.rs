@@ -1,6 +1,6 @@
 use std::panic::catch_unwind;
 
-use mlua::{Error, Function, Lua, Result, Thread, ThreadStatus};
+use mlua::{Error, Function, Lua, Result, Thread, ThreadStatus, Value};
 
 #[test]
 fn test_thread() -> Result<()> {
@@ -94,11 +94,6 @@ fn test_thread() -> Result<()> {
 }
 
 #[test]
-#[cfg(any(
-    feature = "lua54",
-    all(feature = "luajit", feature = "vendored"),
-    feature = "luau",
-))]
 fn test_thread_reset() -> Result<()> {
     use mlua::{AnyUserData, UserData};
     use std::sync::Arc;
@@ -144,12 +139,289 @@ fn test_thread_reset() -> Result<()> {
         // assert!(thread.reset(func.clone()).is_ok());
         // assert_eq!(thread.status(), ThreadStatus::Resumable);
     }
-    #[cfg(any(feature = "lua54", feature = "luau"))]
+    #[cfg(not(feature = "luajit"))]
     {
         assert!(thread.reset(func.clone()).is_ok());
         assert_eq!(thread.status(), ThreadStatus::Resumable);
     }
 
+    // Suspended threads
+    let func: Function = lua
+        .load(r#"function(n) coroutine.yield(n) return n + 1 end"#)
+        .eval()?;
+    let thread = lua.create_thread(func.clone())?;
+    assert_eq!(thread.resume::<_, i32>(1)?, 1);
+    thread.reset(func.clone())?;
+    assert_eq!(thread.status(), ThreadStatus::Resumable);
+    assert_eq!(thread.resume::<_, i32>(10)?, 10);
+    assert_eq!(thread.resume::<_, i32>(())?, 11);
+
+    // Running threads cannot be reset
+    let thread = lua.create_thread(lua.create_function(|lua, ()| {
+        let thread = lua.current_thread();
+        let func = lua.create_function(|_, ()| Ok(()))?;
+        match thread.reset(func) {
+            Err(Error::RuntimeError(msg)) => assert!(msg.contains("cannot reset a running thread")),
+            r => panic!("expected RuntimeError, got {:?}", r),
+        }
+        Ok(())
+    })?)?;
+    thread.resume::<_, ()>(())?;
+    assert!(lua.current_thread().reset(func).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_reset_pooled() -> Result<()> {
+    let lua = Lua::new();
+
+    let func: Function = lua
+        .load(
+            r#"
+            function(n)
+                local x = coroutine.yield(n * 2)
+                return x + n
+            end
+        "#,
+        )
+        .eval()?;
+    let thread = lua.create_thread(func.clone())?;
+
+    for i in 0..1000 {
+        if i > 0 {
+            thread.reset(func.clone())?;
+        }
+        assert_eq!(thread.resume::<_, i32>(i)?, i * 2);
+        // Leave every third thread suspended
+        if i % 3 != 0 {
+            assert_eq!(thread.resume::<_, i32>(1)?, i + 1);
+            assert_eq!(thread.status(), ThreadStatus::Unresumable);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_pending_error() -> Result<()> {
+    let lua = Lua::new();
+
+    let thread = lua.create_thread(
+        lua.load(
+            r#"
+            function(x)
+                local function inner()
+                    error({ code = x })
+                end
+                coroutine.yield()
+                inner()
+            end
+            "#,
+        )
+        .set_name("failing")
+        .eval()?,
+    )?;
+
+    // Healthy thread
+    thread.resume::<_, ()>(42)?;
+    assert!(thread.pending_error()?.is_none());
+    let traceback = thread.traceback(None)?;
+    assert!(traceback.starts_with("stack traceback:"), "{}", traceback);
+
+    // Discard the resume result
+    let _ = thread.resume::<_, ()>(());
+    assert_eq!(thread.status(), ThreadStatus::Error);
+
+    match thread.pending_error()? {
+        Some(Value::Table(err)) => assert_eq!(err.get::<_, i32>("code")?, 42),
+        err => panic!("expected error table, got {:?}", err),
+    }
+    let traceback = thread.traceback(None)?;
+    assert!(traceback.starts_with("stack traceback:"), "{}", traceback);
+    assert!(traceback.contains("failing"), "{}", traceback);
+
+    // Errors raised from Rust
+    let thread = lua.create_thread(lua.create_function(|_, ()| -> Result<()> {
+        Err(Error::RuntimeError("rust error".into()))
+    })?)?;
+    assert!(thread.resume::<_, ()>(()).is_err());
+    match thread.pending_error()? {
+        Some(Value::Error(Error::CallbackError { cause, .. })) => {
+            assert!(matches!(&*cause, Error::RuntimeError(msg) if msg == "rust error"))
+        }
+        err => panic!("expected rust error, got {:?}", err),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_traceback() -> Result<()> {
+    let lua = Lua::new();
+
+    let func = lua
+        .load(
+            r#"
+            local function inner_frame()
+                error("boom")
+            end
+            local function outer_frame()
+                inner_frame()
+            end
+            return function()
+                coroutine.yield()
+                outer_frame()
+            end
+            "#,
+        )
+        .set_name("traceback")
+        .call::<_, Function>(())?;
+    let thread = lua.create_thread(func)?;
+
+    // Suspended thread
+    thread.resume::<_, ()>(())?;
+    let traceback = thread.traceback(Some("suspended"))?;
+    assert!(
+        traceback.starts_with("suspended\nstack traceback:"),
+        "{}",
+        traceback
+    );
+    assert!(traceback.contains("traceback"), "{}", traceback);
+
+    // Dead thread
+    match thread.resume::<_, ()>(()) {
+        Err(Error::RuntimeError(msg)) => {
+            assert!(msg.contains("boom"), "{}", msg);
+            assert!(msg.contains("inner_frame"), "{}", msg);
+            assert!(msg.contains("outer_frame"), "{}", msg);
+        }
+        res => panic!("expected runtime error, got {:?}", res),
+    }
+    assert_eq!(thread.status(), ThreadStatus::Error);
+    let traceback = thread.traceback(None)?;
+    assert!(traceback.starts_with("stack traceback:"), "{}", traceback);
+    assert!(traceback.contains("inner_frame"), "{}", traceback);
+    assert!(traceback.contains("outer_frame"), "{}", traceback);
+
+    Ok(())
+}
+
+#[cfg(feature = "lua54")]
+#[test]
+fn test_thread_close() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use mlua::{MetaMethod, UserData, UserDataMethods};
+
+    struct Guard(Arc<AtomicBool>);
+
+    impl UserData for Guard {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_method(MetaMethod::Close, |_, this, _: Value| {
+                this.0.store(true, Ordering::Relaxed);
+                Ok(())
+            });
+        }
+    }
+
+    let lua = Lua::new();
+
+    let closed = Arc::new(AtomicBool::new(false));
+    let thread = lua.create_thread(
+        lua.load(
+            r#"
+            function(guard)
+                local _ <close> = guard
+                coroutine.yield()
+            end
+            "#,
+        )
+        .eval()?,
+    )?;
+    thread.resume::<_, ()>(Guard(closed.clone()))?;
+    assert!(!closed.load(Ordering::Relaxed));
+    thread.close()?;
+    assert!(closed.load(Ordering::Relaxed));
+    assert_eq!(thread.status(), ThreadStatus::Unresumable);
+    assert!(matches!(
+        thread.resume::<_, ()>(()),
+        Err(Error::CoroutineInactive)
+    ));
+
+    // Errors raised by `__close` metamethods
+    let thread = lua.create_thread(
+        lua.load(
+            r#"
+            function()
+                local _ <close> = setmetatable({}, { __close = function() error("close error") end })
+                coroutine.yield()
+            end
+            "#,
+        )
+        .eval()?,
+    )?;
+    thread.resume::<_, ()>(())?;
+    match thread.close() {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("close error"), "{}", msg),
+        res => panic!("expected runtime error, got {:?}", res),
+    }
+    assert_eq!(thread.status(), ThreadStatus::Unresumable);
+
+    // Main and running threads cannot be closed
+    assert!(lua.current_thread().close().is_err());
+    let thread = lua.create_thread(lua.create_function(|lua, ()| {
+        match lua.current_thread().close() {
+            Err(Error::RuntimeError(msg)) => assert_eq!(msg, "cannot close a running thread"),
+            res => panic!("expected runtime error, got {:?}", res),
+        }
+        Ok(())
+    })?)?;
+    thread.resume::<_, ()>(())?;
+    thread.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_anchor() -> Result<()> {
+    let lua = Lua::new();
+
+    let thread = lua.create_thread(lua.load("return 1").into_function()?)?;
+    let id = thread.anchor("hello")?;
+    let id2 = thread.anchor(123)?;
+    assert_ne!(id, id2);
+    assert_eq!(thread.anchored::<String>(id)?, "hello");
+    thread.unanchor(id)?;
+    thread.unanchor(id)?;
+    assert_eq!(thread.anchored::<Option<String>>(id)?, None);
+    assert_eq!(thread.anchored::<i32>(id2)?, 123);
+
+    // Anchors are per thread
+    let thread2 = lua.create_thread(lua.load("return 2").into_function()?)?;
+    assert_eq!(thread2.anchored::<Option<i32>>(id2)?, None);
+    drop((thread, thread2));
+
+    // Anchored values are collected together with the thread
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    let baseline = lua.used_memory();
+
+    let thread = lua.create_thread(lua.load("return 3").into_function()?)?;
+    for i in 0..10000 {
+        thread.anchor(format!("anchored string #{}", i))?;
+    }
+    let peak = lua.used_memory();
+    assert!(peak > baseline + 10000 * 20);
+    drop(thread);
+
+    // A few cycles to let the string table shrink back
+    for _ in 0..4 {
+        lua.gc_collect()?;
+    }
+    assert!(lua.used_memory() < baseline + 16 * 1024);
+
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+use std::string::String as StdString;
 use std::sync::Arc;
 #[cfg(not(feature = "parking_lot"))]
 use std::sync::{Mutex, RwLock};
@@ -12,8 +13,9 @@ use std::{cell::RefCell, rc::Rc};
 use std::sync::atomic::{AtomicI64, Ordering};
 
 use mlua::{
-    AnyUserData, Error, ExternalError, FromLua, Function, Lua, MetaMethod, Nil, Result, String,
-    UserData, UserDataFields, UserDataMethods, Value,
+    AnyUserData, Error, ExternalError, FromLua, Function, IndexFallbackPriority, IntoLua, Lua,
+    MetaMethod, Nil, Result, String, Table, UserData, UserDataFields, UserDataInfo,
+    UserDataMethods, Value,
 };
 
 #[test]
@@ -250,6 +252,251 @@ fn test_metamethod_close() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_userdata_borrow_map() -> Result<()> {
+    struct Inventory {
+        items: Vec<StdString>,
+    }
+
+    impl UserData for Inventory {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method_mut("add", |_, this, item: StdString| {
+                this.items.push(item);
+                Ok(())
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let ud = lua.create_userdata(Inventory {
+        items: vec!["sword".into()],
+    })?;
+    lua.globals().set("inventory", ud.clone())?;
+
+    {
+        let items = ud.borrow_map(|inv: &Inventory| &inv.items)?;
+        assert_eq!(*items, vec!["sword".to_string()]);
+
+        // The userdata stays borrowed while the mapped guard is alive
+        match ud.borrow_mut::<Inventory>() {
+            Err(Error::UserDataBorrowMutError) => {}
+            r => panic!("expected UserDataBorrowMutError, got {:?}", r.map(|_| ())),
+        }
+        assert!(lua.load(r#"inventory:add("shield")"#).exec().is_err());
+        assert!(ud
+            .borrow_map(|inv: &Inventory| inv.items.as_slice())
+            .is_ok());
+    }
+
+    {
+        let mut items = ud.borrow_mut_map(|inv: &mut Inventory| &mut inv.items)?;
+        items.push("bow".into());
+        match ud.borrow_map(|inv: &Inventory| &inv.items) {
+            Err(Error::UserDataBorrowError) => {}
+            r => panic!("expected UserDataBorrowError, got {:?}", r.map(|_| ())),
+        }
+    }
+
+    lua.load(r#"inventory:add("shield")"#).exec()?;
+    assert_eq!(
+        ud.borrow::<Inventory>()?.items,
+        vec!["sword".to_string(), "bow".to_string(), "shield".to_string()]
+    );
+    struct Other(i32);
+    impl UserData for Other {}
+    assert!(matches!(
+        ud.borrow_map(|o: &Other| &o.0),
+        Err(Error::UserDataTypeMismatchNamed { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_userdata() -> Result<()> {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    impl UserData for Point {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("x", |_, this| Ok(this.x));
+            fields.add_field_method_set("x", |_, this, x| {
+                this.x = x;
+                Ok(())
+            });
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("sum", |_, this, ()| Ok(this.x + this.y));
+            methods.add_method_mut("shift", |_, this, (dx, dy): (i64, i64)| {
+                this.x += dx;
+                this.y += dy;
+                Ok(())
+            });
+            methods.add_method_mut("shift_fail", |_, this, ()| {
+                this.y = -1;
+                Err::<(), _>(Error::RuntimeError("shift failed".into()))
+            });
+            methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+                Ok(format!("({}, {})", this.x, this.y))
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let ud = lua.create_copy_userdata(Point { x: 1, y: 2 })?;
+    lua.globals().set("p", ud.clone())?;
+
+    assert!(ud.is::<Point>());
+    lua.load(
+        r#"
+        assert(p:sum() == 3)
+        p:shift(10, 20)
+        assert(p:sum() == 33)
+        p.x = 5
+        assert(p.x == 5)
+        assert(tostring(p) == "(5, 22)")
+        assert(not pcall(p.shift_fail, p))
+    "#,
+    )
+    .exec()?;
+    // Changes made before returning an error are discarded
+    assert_eq!(ud.get_copy::<Point>()?, Point { x: 5, y: 22 });
+
+    // Cannot borrow userdata with `Cell` storage
+    assert!(ud.is::<Point>());
+    assert!(matches!(
+        ud.borrow::<Point>(),
+        Err(Error::UserDataCopyBorrowError)
+    ));
+    assert!(matches!(
+        ud.borrow_mut::<Point>(),
+        Err(Error::UserDataCopyBorrowError)
+    ));
+
+    // Regular userdata of the same type still works
+    let ud2 = lua.create_userdata(Point { x: 3, y: 4 })?;
+    lua.globals().set("p2", ud2.clone())?;
+    lua.load("p2:shift(1, 1); assert(p2:sum() == 9)").exec()?;
+    assert_eq!(ud2.get_copy::<Point>()?, Point { x: 4, y: 5 });
+
+    assert_eq!(ud.take::<Point>()?, Point { x: 5, y: 22 });
+    assert!(lua.load("p:sum()").exec().is_err());
+    assert!(matches!(
+        ud.get_copy::<Point>(),
+        Err(Error::UserDataDestructed)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_equals() -> Result<()> {
+    struct Id(u32);
+
+    impl UserData for Id {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            // The operands can be swapped if only the right one is `Id`
+            methods.add_meta_function(
+                MetaMethod::Eq,
+                |_, (lhs, rhs): (AnyUserData, AnyUserData)| match (
+                    lhs.borrow::<Id>(),
+                    rhs.borrow::<Id>(),
+                ) {
+                    (Ok(lhs), Ok(rhs)) => Ok(lhs.0 == rhs.0),
+                    _ => Ok(false),
+                },
+            );
+        }
+    }
+
+    struct Other;
+
+    impl UserData for Other {}
+
+    let lua = Lua::new();
+
+    // Same type with custom `__eq`
+    let a = lua.create_userdata(Id(1))?;
+    let b = lua.create_userdata(Id(1))?;
+    let c = lua.create_userdata(Id(2))?;
+    assert!(a.equals(&a)?);
+    assert!(a.equals(&b)?);
+    assert!(!a.equals(&c)?);
+
+    // Different types, only one of them defines `__eq`
+    let other = lua.create_userdata(Other)?;
+    let other2 = lua.create_userdata(Other)?;
+    assert!(!a.equals(&other)?);
+    assert!(!other.equals(&a)?);
+    assert!(!other.equals(&other2)?);
+
+    // Only the right operand defines `__eq`
+    struct Wildcard;
+
+    impl UserData for Wildcard {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_function(MetaMethod::Eq, |_, ()| Ok(true));
+        }
+    }
+
+    // Lua 5.3+ uses `__eq` of the first operand and then of the second one,
+    // older versions require the same metamethod for both operands
+    let wildcard = lua.create_userdata(Wildcard)?;
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    {
+        assert!(other.equals(&wildcard)?);
+        assert!(wildcard.equals(&other)?);
+        assert!(!a.equals(&wildcard)?);
+        assert!(wildcard.equals(&a)?);
+    }
+    #[cfg(not(any(feature = "lua54", feature = "lua53")))]
+    {
+        assert!(!other.equals(&wildcard)?);
+        assert!(!wildcard.equals(&other)?);
+        assert!(!a.equals(&wildcard)?);
+        assert!(!wildcard.equals(&a)?);
+    }
+
+    // Different metatables sharing the same `__eq` function
+    struct Tagged;
+
+    impl UserData for Tagged {}
+
+    let tagged = lua.create_userdata(Tagged)?;
+    let always_eq_fn = lua.create_function(|_, ()| Ok(true))?;
+    (other.get_metatable()?).set(MetaMethod::Eq, always_eq_fn.clone())?;
+    (tagged.get_metatable()?).set(MetaMethod::Eq, always_eq_fn)?;
+    assert!(other.equals(&tagged)?);
+    assert!(tagged.equals(&other)?);
+
+    // The result is always the same as of the `==` operator
+    let lua_eq = lua
+        .load("local a, b = ...; return a == b")
+        .into_function()?;
+    let values = [&a, &b, &c, &other, &wildcard, &tagged];
+    for x in values {
+        for y in values {
+            let expected = lua_eq.call::<_, bool>((x.clone(), y.clone()))?;
+            assert_eq!(x.equals(y)?, expected);
+        }
+    }
+
+    // Userdata compared against a table
+    let always_eq = lua.create_table()?;
+    always_eq.set("__eq", lua.create_function(|_, ()| Ok(true))?)?;
+    let table = lua.create_table()?;
+    table.set_metatable(Some(always_eq));
+    assert!(!Value::UserData(a.clone()).equals(Value::Table(table.clone()))?);
+    assert!(!Value::Table(table).equals(Value::UserData(a.clone()))?);
+    assert!(Value::UserData(a).equals(Value::UserData(b))?);
+
+    Ok(())
+}
+
 #[test]
 fn test_gc_userdata() -> Result<()> {
     struct MyUserdata {
@@ -290,6 +537,114 @@ fn test_gc_userdata() -> Result<()> {
     Ok(())
 }
 
+#[cfg(not(feature = "luau"))]
+#[test]
+fn test_userdata_unsafe_gc() -> Result<()> {
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    struct Foreign(Arc<AtomicI64>);
+
+    impl UserData for Foreign {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_method("__gc", |_, this, ()| {
+                this.0.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            });
+        }
+    }
+
+    struct Resurrected;
+
+    impl UserData for Resurrected {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_function("__gc", |lua, ud: AnyUserData| {
+                lua.globals().set("resurrected", ud)
+            });
+        }
+    }
+
+    let calls = Arc::new(AtomicI64::new(0));
+
+    // Not allowed in safe mode
+    let lua = Lua::new();
+    match lua.create_userdata(Foreign(calls.clone())) {
+        Err(Error::MetaMethodRestricted(method)) => assert_eq!(method, "__gc"),
+        r => panic!("expected MetaMethodRestricted, got {:?}", r.map(|_| ())),
+    }
+    drop(lua);
+
+    let lua = unsafe { Lua::unsafe_new() };
+    lua.globals()
+        .set("ud", lua.create_userdata(Foreign(calls.clone()))?)?;
+    lua.load("ud = nil").exec()?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    // Rust value is dropped after the callback
+    assert_eq!(Arc::strong_count(&calls), 1);
+
+    // The userdata is destructed even if it was resurrected by `__gc`
+    lua.globals().set("ud", lua.create_userdata(Resurrected)?)?;
+    lua.load("ud = nil").exec()?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    let ud: AnyUserData = lua.globals().get("resurrected")?;
+    match ud.take::<Resurrected>() {
+        Err(Error::UserDataDestructed) => {}
+        r => panic!("expected UserDataDestructed, got {:?}", r.map(|_| ())),
+    }
+
+    // Errors from `__gc` are surfaced after the userdata is dropped
+    struct Failing;
+
+    impl UserData for Failing {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_method("__gc", |_, _, ()| -> Result<()> {
+                Err(Error::RuntimeError("gc failed".into()))
+            });
+        }
+    }
+
+    #[cfg(feature = "lua54")]
+    {
+        let warnings = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let warnings2 = warnings.clone();
+        lua.set_warning_function(move |_, msg, _| {
+            warnings2
+                .lock()
+                .unwrap()
+                .push(msg.to_string_lossy().into_owned());
+            Ok(())
+        });
+        lua.globals().set("ud", lua.create_userdata(Failing)?)?;
+        lua.load("ud = nil").exec()?;
+        lua.gc_collect()?;
+        lua.gc_collect()?;
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("error in __gc (callback error"));
+        assert!(warnings[0].contains("runtime error: gc failed"));
+    }
+    #[cfg(not(feature = "lua54"))]
+    {
+        lua.globals().set("ud", lua.create_userdata(Failing)?)?;
+        lua.load("ud = nil").exec()?;
+        match lua.gc_collect() {
+            #[cfg(any(feature = "lua53", feature = "lua52"))]
+            Err(Error::GarbageCollectorError(msg)) => assert!(msg.contains("gc failed")),
+            #[cfg(any(feature = "lua51", feature = "luajit"))]
+            Err(Error::CallbackError { cause, .. }) => match *cause {
+                Error::RuntimeError(ref msg) => assert_eq!(msg, "gc failed"),
+                ref err => panic!("expected RuntimeError, got {:?}", err),
+            },
+            r => panic!("expected __gc error, got {:?}", r),
+        }
+    }
+    assert_eq!(Arc::strong_count(&calls), 1);
+
+    Ok(())
+}
+
 #[test]
 fn test_userdata_take() -> Result<()> {
     #[derive(Debug)]
@@ -368,6 +723,230 @@ fn test_userdata_take() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_userdata_take_any() -> Result<()> {
+    #[derive(Debug, PartialEq)]
+    struct Plugin(StdString);
+
+    impl UserData for Plugin {}
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Point(i32, i32);
+
+    impl UserData for Point {}
+
+    let lua = Lua::new();
+
+    let ud = lua.create_userdata(Plugin("audio".into()))?;
+    let name = ud.borrow_any(|value| value.downcast_ref::<Plugin>().map(|p| p.0.clone()))?;
+    assert_eq!(name.as_deref(), Some("audio"));
+
+    // Cannot take a borrowed value
+    let borrow = ud.borrow::<Plugin>()?;
+    assert!(matches!(ud.take_any(), Err(Error::UserDataBorrowMutError)));
+    drop(borrow);
+
+    let value = ud.take_any()?;
+    assert_eq!(*value.downcast::<Plugin>().unwrap(), Plugin("audio".into()));
+    assert!(matches!(ud.take_any(), Err(Error::UserDataDestructed)));
+    assert!(matches!(
+        ud.borrow_any(|_| ()),
+        Err(Error::UserDataDestructed)
+    ));
+
+    // Copy userdata
+    let ud = lua.create_copy_userdata(Point(1, 2))?;
+    assert!(ud.borrow_any(|value| value.is::<Point>())?);
+    assert_eq!(*ud.take_any()?.downcast::<Point>().unwrap(), Point(1, 2));
+
+    // Wrapped userdata returns the wrapper
+    let ud = lua.create_userdata(Arc::new(Mutex::new(Plugin("video".into()))))?;
+    let value = ud.take_any()?;
+    assert!(value.is::<Arc<Mutex<Plugin>>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_with_bytes() -> Result<()> {
+    struct Header {
+        format: u32,
+    }
+
+    impl UserData for Header {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("format", |_, this, ()| Ok(this.format));
+        }
+    }
+
+    const LEN: usize = 1024 * 1024;
+
+    let lua = Lua::new();
+    lua.gc_collect()?;
+    let used_memory = lua.used_memory();
+
+    let ud = lua.create_userdata_with_bytes(Header { format: 7 }, LEN)?;
+    assert!(lua.used_memory() - used_memory >= LEN);
+
+    assert_eq!(ud.borrow::<Header>()?.format, 7);
+    assert_eq!(ud.bytes()?.len(), LEN);
+    assert!(ud.bytes()?.iter().all(|&b| b == 0));
+
+    ud.bytes_mut()?[..5].copy_from_slice(b"hello");
+    ud.bytes_mut()?[LEN - 1] = 0xff;
+    {
+        let bytes = ud.bytes()?;
+        assert_eq!(&bytes[..5], b"hello");
+        assert_eq!(bytes[LEN - 1], 0xff);
+        // The header is borrowed together with the bytes
+        assert!(matches!(ud.bytes_mut(), Err(Error::UserDataBorrowMutError)));
+        assert!(matches!(
+            ud.borrow_mut::<Header>(),
+            Err(Error::UserDataBorrowMutError)
+        ));
+    }
+
+    lua.globals().set("ud", ud.clone())?;
+    assert_eq!(lua.load("return ud:format()").eval::<u32>()?, 7);
+
+    // Regular userdata has no inline bytes
+    let ud2 = lua.create_userdata(Header { format: 1 })?;
+    assert!(ud2.bytes()?.is_empty());
+
+    // Take the header out, the bytes are freed by GC
+    assert_eq!(ud.take::<Header>()?.format, 7);
+    assert!(matches!(ud.bytes(), Err(Error::UserDataDestructed)));
+
+    drop((ud, ud2));
+    lua.globals().set("ud", Nil)?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert!(lua.used_memory() < used_memory + LEN);
+
+    Ok(())
+}
+
+#[cfg(feature = "send")]
+#[test]
+fn test_userdata_send_owned() -> Result<()> {
+    struct Counter(u32);
+
+    impl UserData for Counter {}
+
+    let lua = Lua::new();
+    let ud = lua.create_userdata(Counter(0))?.into_send_owned()?;
+
+    // The Lua instance keeps being used by the main thread while other threads access the userdata
+    let handles = (0..4)
+        .map(|_| {
+            let ud = ud.clone();
+            std::thread::spawn(move || -> Result<()> {
+                for _ in 0..100 {
+                    ud.with(|ud| ud.borrow_mut::<Counter>().map(|mut c| c.0 += 1))??;
+                }
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    for i in 0..100 {
+        let t = lua.create_table()?;
+        t.set("i", i)?;
+        assert_eq!(
+            lua.load("return ...")
+                .call::<_, Table>(t)?
+                .get::<_, i32>("i")?,
+            i
+        );
+    }
+    for handle in handles {
+        handle.join().unwrap()?;
+    }
+    let value = ud.with(|ud| ud.borrow::<Counter>().map(|c| c.0))??;
+    assert_eq!(value, 400);
+
+    drop(lua);
+    assert!(matches!(ud.with(|_| ()), Err(Error::LuaDropped)));
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_meta_pairs() -> Result<()> {
+    struct Inventory(Vec<(StdString, u32)>);
+
+    impl UserData for Inventory {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_pairs(|_, this| Ok(this.0.clone()));
+        }
+    }
+
+    struct BadValue;
+
+    impl<'lua> IntoLua<'lua> for BadValue {
+        fn into_lua(self, _: &'lua Lua) -> Result<Value<'lua>> {
+            Err(Error::RuntimeError("bad value".into()))
+        }
+    }
+
+    struct Broken;
+
+    impl UserData for Broken {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_pairs(|_, _| Ok(vec![(1, BadValue)]));
+        }
+    }
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+    globals.set(
+        "inventory",
+        Inventory(vec![("apple".into(), 3), ("pear".into(), 5)]),
+    )?;
+    globals.set("broken", Broken)?;
+
+    #[cfg(any(
+        feature = "lua54",
+        feature = "lua53",
+        feature = "lua52",
+        feature = "luajit52"
+    ))]
+    let iterate = "function(ud) return pairs(ud) end";
+    #[cfg(feature = "luau")]
+    let iterate = "function(ud) return ud end";
+    #[cfg(all(any(feature = "lua51", feature = "luajit"), not(feature = "luajit52")))]
+    let iterate = "function(ud) return ud:iter() end";
+    globals.set("iterate", lua.load(iterate).eval::<Function>()?)?;
+
+    let items = lua
+        .load(
+            r#"
+            local items = {}
+            for k, v in iterate(inventory) do
+                items[k] = v
+            end
+            -- Iterate again to check that a fresh iterator is created
+            local count = 0
+            for _ in iterate(inventory) do
+                count = count + 1
+            end
+            return items, count
+        "#,
+        )
+        .eval::<(Table, u32)>()?;
+    assert_eq!(items.0.get::<_, u32>("apple")?, 3);
+    assert_eq!(items.0.get::<_, u32>("pear")?, 5);
+    assert_eq!(items.1, 2);
+
+    match lua.load("for _ in iterate(broken) do end").exec() {
+        Err(Error::CallbackError { ref cause, .. }) => {
+            assert!(matches!(cause.as_ref(), Error::RuntimeError(msg) if msg == "bad value"))
+        }
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_userdata_destroy() -> Result<()> {
     struct MyUserdata(Arc<()>);
@@ -424,6 +1003,49 @@ fn test_user_values() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_weak_user_values() -> Result<()> {
+    struct MyUserData;
+
+    impl UserData for MyUserData {}
+
+    let lua = Lua::new();
+    let ud = lua.create_userdata(MyUserData)?;
+
+    for n in [1, 2, 10] {
+        let weak = lua.create_table()?;
+        weak.set("name", "weak")?;
+        let strong = lua.create_table()?;
+        strong.set("name", "strong")?;
+
+        ud.set_weak_user_value(n, weak)?;
+        ud.set_nth_user_value(n + 1, strong)?;
+        assert_eq!(
+            ud.get_nth_user_value::<Table>(n)?
+                .get::<_, String>("name")?,
+            "weak"
+        );
+
+        lua.gc_collect()?;
+        lua.gc_collect()?;
+        assert_eq!(ud.get_nth_user_value::<Value>(n)?, Value::Nil);
+        assert_eq!(
+            ud.get_nth_user_value::<Table>(n + 1)?
+                .get::<_, String>("name")?,
+            "strong"
+        );
+    }
+
+    // Strong and weak values replace each other
+    ud.set_nth_user_value(3, "strong")?;
+    ud.set_weak_user_value(3, "weak")?;
+    assert_eq!(ud.get_nth_user_value::<String>(3)?, "weak");
+    ud.set_nth_user_value(3, Value::Nil)?;
+    assert_eq!(ud.get_nth_user_value::<Value>(3)?, Value::Nil);
+
+    Ok(())
+}
+
 #[test]
 fn test_functions() -> Result<()> {
     struct MyUserData(i64);
@@ -531,44 +1153,432 @@ fn test_fields() -> Result<()> {
 }
 
 #[test]
-fn test_metatable() -> Result<()> {
-    #[derive(Copy, Clone)]
-    struct MyUserData(i64);
+fn test_readonly_fields() -> Result<()> {
+    struct MyUserData {
+        id: u32,
+        name: std::string::String,
+    }
 
     impl UserData for MyUserData {
         fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
-            fields.add_meta_field_with("__type_name", |_| Ok("MyUserData"));
-        }
-
-        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
-            methods.add_function("my_type_name", |_, data: AnyUserData| {
-                let metatable = data.get_metatable()?;
-                metatable.get::<String>("__type_name")
+            fields.add_field_method_get_readonly("id", |_, this| Ok(this.id));
+            fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+            fields.add_field_method_set("name", |_, this, name| {
+                this.name = name;
+                Ok(())
             });
         }
     }
 
     let lua = Lua::new();
     let globals = lua.globals();
-    globals.set("ud", MyUserData(7))?;
+    let name = "alice".to_string();
+    globals.set("ud", MyUserData { id: 1, name })?;
+
     lua.load(
         r#"
-        assert(ud:my_type_name() == "MyUserData")
+        assert(ud.id == 1)
+        ud.name = "bob"
+        assert(ud.name == "bob")
     "#,
     )
     .exec()?;
 
-    let ud: AnyUserData = globals.get("ud")?;
-    let metatable = ud.get_metatable()?;
-
-    match metatable.get::<Value>("__gc") {
-        Ok(_) => panic!("expected MetaMethodRestricted, got no error"),
-        Err(Error::MetaMethodRestricted(_)) => {}
-        Err(e) => panic!("expected MetaMethodRestricted, got {:?}", e),
+    match lua.load("ud.id = 2").exec() {
+        Err(Error::CallbackError { ref cause, .. }) => match cause.as_ref() {
+            Error::RuntimeError(msg) => assert_eq!(msg, "field 'id' is read-only on MyUserData"),
+            err => panic!("expected RuntimeError, got {err:?}"),
+        },
+        r => panic!("expected CallbackError, got {r:?}"),
     }
+    assert_eq!(lua.load("ud.id").eval::<u32>()?, 1);
 
-    match metatable.set(MetaMethod::Index, Nil) {
-        Ok(_) => panic!("expected MetaMethodRestricted, got no error"),
+    Ok(())
+}
+
+#[test]
+fn test_userdata_method_lookup() -> Result<()> {
+    // Methods only, looked up without the `__index` closure
+    struct MyUserData1(i64);
+
+    impl UserData for MyUserData1 {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("get", |_, this, ()| Ok(this.0));
+        }
+    }
+
+    // Field getters shadow methods with the same name
+    struct MyUserData2(i64);
+
+    impl UserData for MyUserData2 {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("get", |_, this| Ok(this.0 * 10));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("get", |_, this, ()| Ok(this.0));
+            methods.add_method("double", |_, this, ()| Ok(this.0 * 2));
+        }
+    }
+
+    // User-provided `__index` is used as a fallback
+    struct MyUserData3(i64);
+
+    impl UserData for MyUserData3 {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("get", |_, this, ()| Ok(this.0));
+            methods.add_meta_method(MetaMethod::Index, |_, _, key: String| {
+                Ok(format!("index:{}", key.to_str()?))
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+    globals.set("ud1", MyUserData1(1))?;
+    globals.set("ud2", MyUserData2(2))?;
+    globals.set("ud3", MyUserData3(3))?;
+
+    lua.load(
+        r#"
+        assert(ud1:get() == 1)
+        local ok, err = pcall(function() return ud1.unknown end)
+        assert(not ok and tostring(err):find("attempt to get an unknown field 'unknown'"))
+
+        assert(ud2.get == 20)
+        assert(ud2:double() == 4)
+
+        assert(ud3:get() == 3)
+        assert(ud3.unknown == "index:unknown")
+    "#,
+    )
+    .exec()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_index_precedence() -> Result<()> {
+    // Lookup order is: field getters -> methods -> user `__index`
+    struct Point(i64);
+
+    impl UserData for Point {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("x", |_, this| Ok(this.0));
+            // Field getters shadow methods with the same name
+            fields.add_field_method_get("shadowed", |_, _| Ok("field"));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("get", |_, this, ()| Ok(this.0 * 10));
+            methods.add_method("shadowed", |_, _, ()| Ok("method"));
+            methods.add_static_function("new", |_, x| Ok(Point(x)));
+            methods.add_meta_method(MetaMethod::Index, |lua, _, key: Value| {
+                Ok(format!(
+                    "default:{}",
+                    lua.coerce_string(key)?
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| "?".into())
+                ))
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    lua.globals().set("p", Point(7))?;
+    lua.load(
+        r#"
+        assert(p.x == 7)
+        assert(p:get() == 70)
+        assert(p.shadowed == "field")
+        assert(p.missing == "default:missing")
+        assert(p[1] == "default:1")
+        assert(p[true] == "default:?")
+        -- Static functions are not reachable through instances, the user `__index` is called
+        assert(p.new == "default:new")
+        -- Keys that look like internal or metamethod names are passed to the user `__index` too
+        for _, key in ipairs({"__index", "__newindex", "__gc", "__metatable", "__name", "__close", "__mlua_uservalues"}) do
+            assert(p[key] == "default:" .. key, key)
+        end
+    "#,
+    )
+    .exec()?;
+
+    // Without user `__index` unknown keys of any type raise an error
+    struct Plain;
+
+    impl UserData for Plain {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("x", |_, _| Ok(1));
+            fields.add_field_method_set("x", |_, _, _: i64| Ok(()));
+        }
+    }
+
+    struct MethodsOnly;
+
+    impl UserData for MethodsOnly {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("get", |_, _, ()| Ok(1));
+        }
+    }
+
+    lua.globals().set("plain", Plain)?;
+    lua.globals().set("methods_only", MethodsOnly)?;
+    lua.load(
+        r#"
+        local function check_error(f, msg)
+            local ok, err = pcall(f)
+            assert(not ok and tostring(err):find(msg, 1, true), tostring(err))
+        end
+        for _, ud in ipairs({plain, methods_only}) do
+            check_error(function() return ud.missing end, "attempt to get an unknown field 'missing'")
+            check_error(function() return ud[1] end, "attempt to get an unknown field '1'")
+            check_error(function() return ud[{}] end, "attempt to get an unknown field 'table: ")
+        end
+        check_error(function() plain[false] = 1 end, "attempt to set an unknown field 'false'")
+    "#,
+    )
+    .exec()?;
+
+    // User `__index` first, fields and methods are used when it returns `nil`
+    struct Overlay;
+
+    impl UserData for Overlay {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("x", |_, _| Ok("field"));
+            fields.add_field_method_get("y", |_, _| Ok("field"));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.set_index_fallback_priority(IndexFallbackPriority::First);
+            methods.add_method("get", |_, _, ()| Ok("method"));
+            methods.add_meta_method(MetaMethod::Index, |_, _, key: StdString| {
+                Ok(match key.as_str() {
+                    "x" => Some("overlay"),
+                    "missing" => Some("default"),
+                    _ => None,
+                })
+            });
+        }
+    }
+
+    lua.globals().set("overlay", Overlay)?;
+    lua.load(
+        r#"
+        assert(overlay.x == "overlay")
+        assert(overlay.y == "field")
+        assert(overlay:get() == "method")
+        assert(overlay.missing == "default")
+        assert(overlay.other == nil)
+    "#,
+    )
+    .exec()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_info() -> Result<()> {
+    struct Player;
+
+    impl UserData for Player {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("name", |_, _| Ok("player"));
+            fields.add_field_method_get("hp", |_, _| Ok(100));
+            fields.add_field_method_set("hp", |_, _, _: i64| Ok(()));
+            fields.add_meta_field_with("__type_name", |_| Ok("Player"));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("greet", |_, _, ()| Ok(()));
+            methods.add_method_mut("heal", |_, _, ()| Ok(()));
+            methods.add_function("new", |_, ()| Ok(Player));
+            methods.add_meta_method(MetaMethod::ToString, |_, _, ()| Ok("player"));
+            #[cfg(feature = "async")]
+            methods.add_async_function("load", |_, ()| async { Ok(Player) });
+        }
+    }
+
+    let lua = Lua::new();
+    let info = lua.userdata_info::<Player>()?;
+    assert_eq!(info.methods, ["greet", "heal"]);
+    assert_eq!(info.functions, ["new"]);
+    assert!(info.async_methods.is_empty());
+    #[cfg(feature = "async")]
+    assert_eq!(info.async_functions, ["load"]);
+    assert_eq!(info.field_getters, ["name", "hp"]);
+    assert_eq!(info.field_setters, ["hp"]);
+    assert_eq!(info.meta_methods, ["__tostring"]);
+    assert_eq!(info.meta_fields, ["__type_name"]);
+
+    let ud = lua.create_userdata(Player)?;
+    assert_eq!(ud.info()?, info);
+
+    struct Empty;
+    impl UserData for Empty {}
+    assert_eq!(lua.create_userdata(Empty)?.info()?, UserDataInfo::default());
+
+    Ok(())
+}
+
+#[cfg(feature = "luau")]
+#[test]
+fn test_userdata_luau_typeof() -> Result<()> {
+    struct Vector3;
+
+    impl UserData for Vector3 {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_meta_field_with(MetaMethod::Type, |_| Ok("Vector3"));
+        }
+    }
+
+    struct MyUserData;
+
+    impl UserData for MyUserData {}
+
+    let lua = Lua::new();
+    lua.globals().set("v", Vector3)?;
+    lua.globals().set("ud", MyUserData)?;
+    lua.load(
+        r#"
+        assert(typeof(v) == "Vector3")
+        assert(typeof(ud) == "MyUserData")
+        assert(type(v) == "userdata")
+    "#,
+    )
+    .exec()?;
+
+    lua.scope(|scope| {
+        let ud = scope.create_nonstatic_userdata(MyUserData)?;
+        assert_eq!(
+            lua.load("typeof(...)").call::<_, StdString>(ud)?,
+            "MyUserData"
+        );
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_type_name() -> Result<()> {
+    struct Player;
+
+    impl UserData for Player {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method("name", |_, _, ()| Ok("player"));
+        }
+    }
+
+    struct Enemy;
+
+    impl UserData for Enemy {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_meta_field_with("__name", |_| Ok("Foe"));
+        }
+    }
+
+    let lua = Lua::new();
+    let player = lua.create_userdata(Player)?;
+    let enemy = lua.create_userdata(Enemy)?;
+    assert_eq!(player.type_name()?.as_deref(), Some("Player"));
+    assert_eq!(enemy.type_name()?.as_deref(), Some("Foe"));
+
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    {
+        let s = lua
+            .load("return tostring(...)")
+            .call::<_, StdString>(player.clone())?;
+        assert!(s.starts_with("Player: "), "{s}");
+    }
+
+    let name: Function = lua.load("return (...).name").call(player.clone())?;
+    assert_eq!(name.call::<_, StdString>(player)?, "player");
+    match name.call::<_, ()>(enemy) {
+        Err(Error::CallbackError { ref cause, .. }) => match cause.as_ref() {
+            Error::UserDataTypeMismatchNamed { expected, actual } => {
+                assert_eq!(expected, "Player");
+                assert_eq!(actual, "Foe");
+            }
+            err => panic!("expected UserDataTypeMismatchNamed, got {err:?}"),
+        },
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+    match name.call::<_, ()>("player") {
+        Err(Error::CallbackError { ref cause, .. }) => assert!(
+            matches!(
+                cause.as_ref(),
+                Error::FromLuaConversionError {
+                    from: "string",
+                    to: "userdata",
+                    ..
+                }
+            ),
+            "{cause:?}"
+        ),
+        r => panic!("expected CallbackError, got {r:?}"),
+    }
+
+    // Typed access reports both type names
+    let enemy = lua.create_userdata(Enemy)?;
+    match enemy.borrow::<Player>() {
+        Err(Error::UserDataTypeMismatchNamed { expected, actual }) => {
+            assert_eq!(expected, "Player");
+            assert_eq!(actual, "Foe");
+        }
+        r => panic!(
+            "expected UserDataTypeMismatchNamed, got {:?}",
+            r.map(|_| ())
+        ),
+    }
+    assert!(matches!(
+        enemy.take::<Player>(),
+        Err(Error::UserDataTypeMismatchNamed { .. })
+    ));
+    assert!(!enemy.is::<Player>());
+
+    Ok(())
+}
+
+#[test]
+fn test_metatable() -> Result<()> {
+    #[derive(Copy, Clone)]
+    struct MyUserData(i64);
+
+    impl UserData for MyUserData {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_meta_field_with("__type_name", |_| Ok("MyUserData"));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_function("my_type_name", |_, data: AnyUserData| {
+                let metatable = data.get_metatable()?;
+                metatable.get::<String>("__type_name")
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+    globals.set("ud", MyUserData(7))?;
+    lua.load(
+        r#"
+        assert(ud:my_type_name() == "MyUserData")
+    "#,
+    )
+    .exec()?;
+
+    let ud: AnyUserData = globals.get("ud")?;
+    let metatable = ud.get_metatable()?;
+
+    match metatable.get::<Value>("__gc") {
+        Ok(_) => panic!("expected MetaMethodRestricted, got no error"),
+        Err(Error::MetaMethodRestricted(_)) => {}
+        Err(e) => panic!("expected MetaMethodRestricted, got {:?}", e),
+    }
+
+    match metatable.set(MetaMethod::Index, Nil) {
+        Ok(_) => panic!("expected MetaMethodRestricted, got no error"),
         Err(Error::MetaMethodRestricted(_)) => {}
         Err(e) => panic!("expected MetaMethodRestricted, got {:?}", e),
     }
@@ -578,7 +1588,11 @@ fn test_metatable() -> Result<()> {
         .map(|kv: Result<(_, Value)>| Ok(kv?.0))
         .collect::<Result<Vec<_>>>()?;
     methods.sort();
-    assert_eq!(methods, vec!["__index", "__type_name"]);
+    // `__name` (and `__type` in Luau) are set to the Rust type name by default
+    #[cfg(not(feature = "luau"))]
+    assert_eq!(methods, vec!["__index", "__name", "__type_name"]);
+    #[cfg(feature = "luau")]
+    assert_eq!(methods, vec!["__index", "__name", "__type", "__type_name"]);
 
     #[derive(Copy, Clone)]
     struct MyUserData2(i64);
@@ -710,3 +1724,258 @@ fn test_userdata_proxy() -> Result<()> {
     )
     .exec()
 }
+
+#[test]
+fn test_userdata_class_proxy() -> Result<()> {
+    struct Point(f64, f64);
+
+    impl UserData for Point {
+        fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+            fields.add_field_method_get("x", |_, this| Ok(this.0));
+        }
+
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_function("new", |_, (x, y)| Ok(Point(x, y)));
+            methods.add_function("origin", |_, ()| Ok(Point(0., 0.)));
+            methods.add_meta_function(MetaMethod::Call, |_, (_, x, y): (AnyUserData, _, _)| {
+                Ok(Point(x, y))
+            });
+
+            methods.add_method("length", |_, this, ()| {
+                Ok((this.0 * this.0 + this.1 * this.1).sqrt())
+            });
+            methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+                Ok(format!("Point({}, {})", this.0, this.1))
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+    globals.set("Point", lua.create_userdata_proxy::<Point>()?)?;
+
+    lua.load(
+        r#"
+        local p1 = Point(3, 4)
+        assert(p1:length() == 5)
+        assert(p1.x == 3)
+        assert(tostring(p1) == "Point(3, 4)")
+
+        local p2 = Point.new(6, 8)
+        assert(p2:length() == 10)
+        assert(Point.origin():length() == 0)
+
+        -- Instance methods and fields are not reachable through the proxy
+        assert(not pcall(function() return Point.length end))
+        assert(not pcall(function() return Point.x end))
+        assert(tostring(Point) ~= "Point(3, 4)")
+    "#,
+    )
+    .exec()
+}
+
+#[test]
+fn test_userdata_static_functions() -> Result<()> {
+    struct Counter(u32);
+
+    impl UserData for Counter {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_static_function("new", |_, n| Ok(Counter(n)));
+            methods.add_static_function("max", |_, (a, b): (AnyUserData, AnyUserData)| {
+                let (a, b) = (a.borrow::<Counter>()?.0, b.borrow::<Counter>()?.0);
+                Ok(Counter(a.max(b)))
+            });
+            methods.add_method("get", |_, this, ()| Ok(this.0));
+        }
+    }
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+    globals.set("Counter", lua.userdata_statics::<Counter>()?)?;
+    globals.set("CounterProxy", lua.create_userdata_proxy::<Counter>()?)?;
+    globals.set("counter", Counter(1))?;
+
+    lua.load(
+        r#"
+        local c = Counter.new(5)
+        assert(c:get() == 5)
+        assert(Counter.max(c, Counter.new(7)):get() == 7)
+        assert(CounterProxy.new(3):get() == 3)
+
+        -- Static functions are not reachable through instances
+        local ok, err = pcall(function() return counter.new end)
+        assert(not ok and tostring(err):find("unknown field 'new'"))
+        assert(not pcall(function() return counter.new(1) end))
+        assert(not pcall(function() return c.max end))
+    "#,
+    )
+    .exec()?;
+
+    // Static functions are kept by wrapped userdata types
+    let statics = lua.userdata_statics::<Arc<Mutex<Counter>>>()?;
+    assert!(statics.contains_key("new")?);
+    assert!(!statics.contains_key("get")?);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "luau"))]
+#[test]
+fn test_userdata_offload_drop() -> Result<()> {
+    use std::sync::{mpsc, Mutex};
+    use std::thread::{self, ThreadId};
+    use std::time::Duration;
+
+    struct Connection(Arc<Mutex<Option<ThreadId>>>);
+
+    impl UserData for Connection {}
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            // Simulate expensive drop
+            thread::sleep(Duration::from_millis(10));
+            *self.0.lock().unwrap() = Some(thread::current().id());
+        }
+    }
+
+    let (tx, rx) = mpsc::channel::<Box<dyn FnOnce() + Send>>();
+    let worker = thread::spawn(move || {
+        for job in rx {
+            job();
+        }
+    });
+    let worker_id = worker.thread().id();
+
+    let lua = Lua::new();
+    lua.set_drop_offloader(move |job| tx.send(job).unwrap());
+    lua.register_offload_drop::<Connection>()?;
+
+    let dropped1 = Arc::new(Mutex::new(None));
+    let dropped2 = Arc::new(Mutex::new(None));
+    lua.globals().set("conn1", Connection(dropped1.clone()))?;
+    lua.globals().set("conn2", Connection(dropped2.clone()))?;
+
+    lua.globals().set("conn1", Nil)?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+
+    // `conn2` is still alive and must be dropped on close
+    lua.close();
+    assert_eq!(*dropped1.lock().unwrap(), Some(worker_id));
+    assert_eq!(*dropped2.lock().unwrap(), Some(worker_id));
+
+    // User-defined `__gc` (unsafe states only) is called before offloading the drop
+    struct Foreign(Arc<Mutex<Vec<Option<ThreadId>>>>);
+
+    impl UserData for Foreign {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_meta_method("__gc", |_, this, ()| {
+                this.0.lock().unwrap().push(None);
+                Ok(())
+            });
+        }
+    }
+
+    impl Drop for Foreign {
+        fn drop(&mut self) {
+            self.0.lock().unwrap().push(Some(thread::current().id()));
+        }
+    }
+
+    let (tx, rx) = mpsc::channel::<Box<dyn FnOnce() + Send>>();
+    let worker2 = thread::spawn(move || {
+        for job in rx {
+            job();
+        }
+    });
+    let worker2_id = worker2.thread().id();
+
+    let lua = unsafe { Lua::unsafe_new() };
+    lua.set_drop_offloader(move |job| tx.send(job).unwrap());
+    let events = Arc::new(Mutex::new(Vec::new()));
+    lua.globals().set("foreign", Foreign(events.clone()))?;
+    lua.register_offload_drop::<Foreign>()?;
+    lua.globals().set("foreign", Nil)?;
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    lua.close();
+    assert_eq!(*events.lock().unwrap(), [None, Some(worker2_id)]);
+
+    worker.join().unwrap();
+    worker2.join().unwrap();
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_pointer() -> Result<()> {
+    use std::collections::HashMap;
+
+    struct MyUserData;
+
+    impl UserData for MyUserData {}
+
+    let lua = Lua::new();
+    let ud1 = lua.create_userdata(MyUserData)?;
+    let ud2 = lua.create_userdata(MyUserData)?;
+
+    assert_eq!(ud1.to_pointer(), ud1.clone().to_pointer());
+    assert_ne!(ud1.to_pointer(), ud2.to_pointer());
+
+    // The pointer must be stable when userdata passes through Lua
+    lua.globals().set("ud1", ud1.clone())?;
+    let ud1_copy: AnyUserData = lua.load("ud1").eval()?;
+    assert_eq!(ud1.to_pointer(), ud1_copy.to_pointer());
+
+    #[allow(clippy::mutable_key_type)]
+    let mut map = HashMap::new();
+    map.insert(ud1.clone(), "ud1");
+    map.insert(ud2.clone(), "ud2");
+    assert_eq!(map.get(&ud1_copy), Some(&"ud1"));
+    assert_eq!(map.get(&ud2), Some(&"ud2"));
+    assert_eq!(map.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_userdata_bind_method() -> Result<()> {
+    struct Counter(i64);
+
+    impl UserData for Counter {
+        fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+            methods.add_method_mut("add", |_, this, (a, b): (i64, Option<i64>)| {
+                this.0 += a + b.unwrap_or(0);
+                Ok(this.0)
+            });
+        }
+    }
+
+    let lua = Lua::new();
+    let counter = lua.create_userdata(Counter(0))?;
+
+    let add = counter.bind_method("add")?;
+    assert_eq!(add.call::<_, i64>(1)?, 1);
+    assert_eq!(add.call::<_, i64>((2, 3))?, 6);
+    assert_eq!(counter.borrow::<Counter>()?.0, 6);
+
+    // Missing methods must fail at bind time
+    match counter.bind_method("sub") {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("'sub'")),
+        r => panic!("expected RuntimeError, got {:?}", r),
+    }
+
+    lua.install_bind_helper()?;
+    lua.globals().set("counter", counter.clone())?;
+    lua.load(
+        r#"
+        local add = mlua.bind(counter, "add")
+        assert(add(10) == 16)
+        assert(not pcall(mlua.bind, counter, "sub"))
+    "#,
+    )
+    .exec()?;
+    assert_eq!(counter.borrow::<Counter>()?.0, 16);
+
+    Ok(())
+}